@@ -0,0 +1,20 @@
+fn magnitude(x: i32) -> i32 {
+    if x < 0 { -x } else { x }
+}
+
+fn distance(a: i32, b: i32) -> i32 {
+    let d: i32 = a - b;
+    if d >= 0 { d } else { -d }
+}
+
+fn scaled(v: f64) -> f64 {
+    2.0 * if 0.0 > v { -v } else { v }
+}
+
+fn widened(x: u8) -> i64 {
+    if (x as i64) < 0 { -(x as i64) } else { x as i64 }
+}
+
+fn unsigned(n: u32) -> u32 {
+    if n < 0 { 0 - n } else { n }
+}
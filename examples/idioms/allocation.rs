@@ -0,0 +1,39 @@
+extern "C" {
+    fn malloc(_: libc::c_ulong) -> *mut libc::c_void;
+    fn calloc(_: libc::c_ulong, _: libc::c_ulong) -> *mut libc::c_void;
+    fn free(_: *mut libc::c_void);
+}
+
+pub unsafe fn boxed_counter(start: libc::c_int) -> libc::c_int {
+    let mut counter: *mut libc::c_int =
+        malloc(::core::mem::size_of::<libc::c_int>() as libc::c_ulong) as *mut libc::c_int;
+    *counter = start;
+    *counter += 1;
+    let value: libc::c_int = *counter;
+    free(counter as *mut libc::c_void);
+    value
+}
+
+pub unsafe fn squares(n: libc::c_int) -> libc::c_int {
+    let mut values: *mut libc::c_int = malloc(
+        (n as libc::c_ulong).wrapping_mul(::core::mem::size_of::<libc::c_int>() as libc::c_ulong),
+    ) as *mut libc::c_int;
+    let mut i: libc::c_int = 0 as libc::c_int;
+    while i < n {
+        *values.offset(i as isize) = i * i;
+        i += 1;
+    }
+    let last: libc::c_int = *values.offset((n - 1 as libc::c_int) as isize);
+    free(values as *mut libc::c_void);
+    last
+}
+
+pub unsafe fn zeroed(n: libc::c_int) -> libc::c_int {
+    let counts: *mut libc::c_int = calloc(
+        n as libc::c_ulong,
+        ::core::mem::size_of::<libc::c_int>() as libc::c_ulong,
+    ) as *mut libc::c_int;
+    let first: libc::c_int = *counts;
+    free(counts as *mut libc::c_void);
+    first
+}
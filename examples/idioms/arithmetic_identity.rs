@@ -0,0 +1,40 @@
+pub fn offset(base: usize, i: usize) -> usize {
+    base + i * 1 + 0
+}
+
+pub fn flags(mode: u32) -> u32 {
+    (mode | 0) << 0
+}
+
+pub fn masked(bits: u64) -> u64 {
+    bits & !0
+}
+
+pub fn scaled(x: i32, y: i32) -> i32 {
+    (x - 0) / 1 * (y >> 0)
+}
+
+pub fn cleared(x: u8) -> u8 {
+    let high = x * 0;
+    high | (x & 0)
+}
+
+pub fn stride(x: i64) -> i64 {
+    0 + 1 * x
+}
+
+pub fn by_ref(x: &u32) -> u32 {
+    x + 0
+}
+
+pub fn inverted(x: u16) -> u16 {
+    !x | 0
+}
+
+pub fn unknown(v: &[u32]) -> u32 {
+    v[0] * 1
+}
+
+pub fn half(x: f64) -> f64 {
+    x + 0.0
+}
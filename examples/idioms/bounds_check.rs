@@ -0,0 +1,35 @@
+fn lookup(a: &[i32], i: i32) -> i32 {
+    if i < 0 || i >= a.len() as i32 {
+        return -1;
+    }
+    let x = a[i as usize];
+    x * 2
+}
+
+fn first_word(words: &[String], n: usize) -> usize {
+    if n >= words.len() {
+        return 0;
+    }
+    let word = &words[n];
+    word.len()
+}
+
+fn sum_at(a: &[u8], indices: &[i64]) -> u32 {
+    let mut total = 0;
+    for &i in indices {
+        if i < 0 || i >= a.len() as i64 {
+            continue;
+        }
+        let byte = a[i as usize];
+        total += byte as u32;
+    }
+    total
+}
+
+fn unchecked_sign(a: &[i32], i: i32) -> i32 {
+    if i >= a.len() as i32 {
+        return -1;
+    }
+    let x = a[i as usize];
+    x
+}
@@ -0,0 +1,43 @@
+#[derive(Default)]
+pub struct Node {
+    pub value: i32,
+    pub weight: f64,
+    pub next: Option<Box<Node>>,
+}
+
+#[derive(Default)]
+pub struct Config {
+    pub retries: u32,
+    pub verbose: bool,
+    pub name: String,
+}
+
+pub fn make_node(value: i32) -> Box<Node> {
+    let mut node = Box::new(Node::default());
+    node.value = value;
+    node.next = None;
+    node
+}
+
+pub fn make_config(retries: u32) -> Box<Config> {
+    let mut config: Box<Config> = Box::new(Default::default());
+    config.retries = retries;
+    config.verbose = true;
+    config.name = String::from("default");
+    config
+}
+
+pub fn relink(value: i32, next: Box<Node>) -> Box<Node> {
+    let mut node = Box::new(Node::default());
+    node.value = value;
+    node.weight = 1.0;
+    node.next = Some(next);
+    node.weight = node.weight * 2.0;
+    node
+}
+
+pub fn self_referential(value: i32) -> Box<Node> {
+    let mut node = Box::new(Node::default());
+    node.value = node.value + value;
+    node
+}
@@ -0,0 +1,56 @@
+pub fn sign(v: i32) -> i32 {
+    let s;
+    if v < 0 {
+        s = -1;
+    } else if v == 0 {
+        s = 0;
+    } else {
+        s = 1;
+    }
+    s
+}
+
+pub fn clamp_into(out: &mut [u8], i: usize, v: i32) {
+    if v > 255 {
+        out[i] = 255;
+    } else {
+        out[i] = v as u8;
+    }
+}
+
+pub fn pick(flag: bool, a: u32, b: u32) -> u32 {
+    let mut r: u32 = 0;
+    if flag {
+        r = a;
+    } else {
+        r = b;
+    }
+    r += 1;
+    r
+}
+
+pub fn increment_or_reset(flag: bool, mut n: u32) -> u32 {
+    let mut m = 0;
+    if flag {
+        n = n + 1;
+    } else {
+        n = 0;
+    }
+    if flag {
+        m = m + 1;
+    } else {
+        m = 2;
+    }
+    n + m
+}
+
+pub fn mixed(flag: bool) -> (u32, u32) {
+    let mut a = 0;
+    let mut b = 0;
+    if flag {
+        a = 1;
+    } else {
+        b = 1;
+    }
+    (a, b)
+}
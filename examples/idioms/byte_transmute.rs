@@ -0,0 +1,14 @@
+use std::mem::transmute;
+
+pub fn read_u32(bytes: [u8; 4]) -> u32 {
+    unsafe { transmute::<[u8; 4], u32>(bytes) }
+}
+
+pub fn read_f64(raw: [u8; 8]) -> f64 {
+    let value: f64 = unsafe { std::mem::transmute(raw) };
+    value
+}
+
+pub fn mismatched(bytes: [u8; 4]) -> u64 {
+    unsafe { transmute::<[u8; 4], u64>(bytes) }
+}
@@ -0,0 +1,28 @@
+use std::ffi::CStr;
+use std::os::raw::c_char;
+
+extern "C" {
+    fn strcpy(dst: *mut c_char, src: *const c_char) -> *mut c_char;
+    fn strcat(dst: *mut c_char, src: *const c_char) -> *mut c_char;
+    fn strlen(s: *const c_char) -> usize;
+}
+
+unsafe fn greeting() -> usize {
+    let mut buf: [c_char; 64] = [0; 64];
+    strcpy(buf.as_mut_ptr(), b"hello, \0" as *const u8 as *const c_char);
+    strcat(buf.as_mut_ptr(), b"world\0" as *const u8 as *const c_char);
+    let s = CStr::from_ptr(buf.as_ptr()).to_str().unwrap();
+    s.len()
+}
+
+unsafe fn measured() -> usize {
+    let mut name: [c_char; 16] = [0; 16];
+    strcpy(name.as_mut_ptr(), b"id\0".as_ptr() as *const c_char);
+    strlen(name.as_ptr())
+}
+
+unsafe fn raw_bytes() -> u8 {
+    let mut bytes: [u8; 4] = [0; 4];
+    bytes[0] = 1;
+    bytes[0]
+}
@@ -0,0 +1,56 @@
+const BASE: u32 = 16;
+
+#[allow(non_snake_case)]
+fn MAX() -> i32 {
+    100
+}
+
+#[inline]
+fn buffer_size() -> usize {
+    (BASE as usize) * 4
+}
+
+fn limit() -> i64 {
+    MAX() as i64 * 2 - 1
+}
+
+fn name() -> &'static str {
+    "device"
+}
+
+fn counter() -> u32 {
+    BASE
+}
+
+fn seed() -> u32 {
+    let s = 7;
+    s * 3
+}
+
+pub fn clamp(x: i32) -> i32 {
+    if x > MAX() {
+        MAX()
+    } else {
+        x
+    }
+}
+
+pub fn alloc() -> Vec<u8> {
+    Vec::with_capacity(buffer_size() + buffer_size())
+}
+
+pub fn within(x: i64) -> bool {
+    x < limit() && x > -limit()
+}
+
+pub fn label() -> String {
+    format!("{}-{}", name(), seed())
+}
+
+pub fn callbacks() -> [fn() -> u32; 2] {
+    [counter, seed]
+}
+
+pub fn total() -> u32 {
+    counter() + seed()
+}
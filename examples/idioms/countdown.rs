@@ -0,0 +1,20 @@
+pub unsafe fn countdown_sum(n: i32) -> i32 {
+    let mut sum: i32 = 0 as i32;
+    let mut i: i32 = 10 as i32;
+    while i > 0 {
+        sum += i;
+        i -= 1;
+        i;
+    }
+    let mut j: i32 = 10 as i32;
+    while j >= 2 {
+        sum += j * 2;
+        j = j - 1;
+    }
+    let mut k: i32 = 10 as i32;
+    while k > n {
+        k -= 1;
+        sum += k * 3;
+    }
+    sum
+}
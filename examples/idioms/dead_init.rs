@@ -0,0 +1,29 @@
+fn compute(seed: u32) -> u32 {
+    seed.wrapping_mul(2654435761)
+}
+
+pub fn hash_once(seed: u32) -> u32 {
+    let mut h: u32 = 0;
+    h = compute(seed);
+    h
+}
+
+pub fn hash_twice(seed: u32) -> u32 {
+    let mut h = 0;
+    h = compute(seed);
+    h ^= h >> 16;
+    h
+}
+
+pub fn collect(n: usize) -> Vec<usize> {
+    let mut v = Vec::new();
+    v = Vec::with_capacity(n);
+    v.push(n);
+    v
+}
+
+pub fn accumulate(seed: u32) -> u32 {
+    let mut total = 1;
+    total = total + compute(seed);
+    total
+}
@@ -0,0 +1,39 @@
+fn clamp_u8_a(v: i32) -> u8 {
+    if v < 0 {
+        0
+    } else if v > 255 {
+        255
+    } else {
+        v as u8
+    }
+}
+
+/// Generated for the green channel.
+fn clamp_u8_b(v: i32) -> u8 {
+    if v < 0 {
+        0
+    } else if v > 255 {
+        255
+    } else {
+        v as u8
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn clamp_u8_c(v: i32) -> u8 {
+    if v < 0 {
+        0
+    } else if v > 255 {
+        255
+    } else {
+        v as u8
+    }
+}
+
+fn pixel(r: i32, g: i32) -> [u8; 2] {
+    [clamp_u8_a(r), clamp_u8_b(g)]
+}
+
+fn channels(values: &[i32]) -> Vec<u8> {
+    values.iter().copied().map(clamp_u8_b).collect()
+}
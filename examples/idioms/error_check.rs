@@ -0,0 +1,36 @@
+use std::ffi::c_int;
+
+pub fn open_device(id: u32) -> Result<(), c_int> {
+    if id == 0 {
+        return Err(-1);
+    }
+    Ok(())
+}
+
+pub fn configure(id: u32, rate: u32) -> Result<(), c_int> {
+    if rate > id {
+        return Err(-2);
+    }
+    Ok(())
+}
+
+pub fn start(id: u32, rate: u32) -> Result<(), c_int> {
+    let rc = open_device(id);
+    if rc.is_err() {
+        return rc;
+    }
+    let status = configure(id, rate);
+    if let Err(e) = status {
+        return Err(e);
+    }
+    Ok(())
+}
+
+pub fn start_logged(id: u32) -> Result<(), c_int> {
+    let rc = open_device(id);
+    if rc.is_err() {
+        return rc;
+    }
+    println!("opened: {:?}", rc);
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use std::os::raw::{c_double, c_int, c_long};
+
+pub unsafe extern "C" fn widen(
+    a: c_int,
+    b: libc::c_short,
+    c: std::os::raw::c_ushort,
+    d: c_long,
+    e: libc::c_ulonglong,
+    f: ::std::os::raw::c_longlong,
+) -> c_double {
+    (a as c_double) + (b as libc::c_double) + (c as f64) + (d + e as c_long + f as c_long) as f64
+}
+
+pub unsafe extern "C" fn first_char(
+    s: *const libc::c_char,
+    t: *mut std::os::raw::c_uchar,
+    u: *const libc::c_schar,
+) -> libc::c_float {
+    let bytes: [libc::c_char; 2] = [*s, *u as libc::c_char];
+    *t = bytes.len() as libc::c_uchar;
+    ::core::mem::size_of::<std::os::raw::c_float>() as libc::c_float
+}
@@ -0,0 +1,27 @@
+const UNSET: i32 = -1;
+
+pub fn clear(a: &mut [u8]) {
+    for i in 0..a.len() {
+        a[i] = 0;
+    }
+}
+
+pub fn reset() -> [i32; 8] {
+    let mut marks: [i32; 8] = [0; 8];
+    for i in 0..8 {
+        marks[i] = UNSET;
+    }
+    marks
+}
+
+pub fn fill_sevens(a: &mut [i64]) {
+    for i in 0..a.len() {
+        a[i] = 7;
+    }
+}
+
+pub fn partial(a: &mut [u8], n: usize) {
+    for i in 0..n {
+        a[i] = 0;
+    }
+}
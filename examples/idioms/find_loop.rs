@@ -0,0 +1,48 @@
+pub fn contains_key(keys: &[u32], key: u32) -> bool {
+    let mut idx: i32 = -1;
+    for i in 0..keys.len() {
+        if keys[i] == key {
+            idx = i as i32;
+            break;
+        }
+    }
+    if idx == -1 {
+        return false;
+    }
+    true
+}
+
+pub fn index_of(names: &Vec<String>, name: &str) -> i32 {
+    let mut found = -1;
+    for i in 0..names.len() {
+        if names[i] == name {
+            found = i as i32;
+            break;
+        }
+    }
+    found
+}
+
+pub fn first_negative(values: &[i64], n: i32) -> i32 {
+    let mut pos: i32 = -1;
+    for i in 0..n {
+        if values[i as usize] < 0 {
+            pos = i;
+            break;
+        }
+    }
+    if pos >= 0 {
+        println!("negative at {}", pos);
+    }
+    pos
+}
+
+pub fn last_zero(values: &[u8]) -> i32 {
+    let mut idx: i32 = -1;
+    for i in 0..values.len() {
+        if values[i] == 0 {
+            idx = i as i32;
+        }
+    }
+    idx
+}
@@ -0,0 +1,57 @@
+pub fn contains_negative(values: &[i32]) -> bool {
+    let mut found = false;
+    for v in values {
+        if *v < 0 {
+            found = true;
+            break;
+        }
+    }
+    found
+}
+
+pub fn all_even(values: &Vec<u32>) -> bool {
+    let mut ok = true;
+    for &v in values.iter() {
+        if v % 2 != 0 {
+            ok = false;
+            break;
+        }
+    }
+    ok
+}
+
+pub fn any_divisor(n: u32) -> bool {
+    let mut composite: bool = false;
+    for d in 2..n {
+        if n % d == 0 {
+            composite = true;
+        }
+    }
+    composite
+}
+
+pub fn all_printable(s: &str, strict: bool) -> bool {
+    let mut printable = true;
+    for c in s.chars() {
+        if !c.is_ascii_graphic() {
+            printable = false;
+            break;
+        }
+    }
+    if strict && s.is_empty() {
+        printable = false;
+    }
+    printable
+}
+
+pub fn count_and_flag(values: &[i32]) -> (bool, i32) {
+    let mut found = false;
+    let mut count = 0;
+    for v in values {
+        if *v == 0 {
+            found = true;
+            count += 1;
+        }
+    }
+    (found, count)
+}
@@ -0,0 +1,14 @@
+pub unsafe fn flipped_sum(values: &[i32], n: i32) -> i32 {
+    let mut sum: i32 = 0 as i32;
+    let mut i: i32 = 0 as i32;
+    while n > i {
+        sum += values[i as usize];
+        i += 1;
+    }
+    let mut j: i32 = 1 as i32;
+    while n >= j {
+        sum += values[j as usize] * 2;
+        j += 1;
+    }
+    sum
+}
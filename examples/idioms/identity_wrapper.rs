@@ -0,0 +1,27 @@
+fn wrap(x: u32) -> u32 {
+    x
+}
+
+fn pass_through(p: *mut u8) -> *mut u8 {
+    return p;
+}
+
+pub fn exported_id(v: i64) -> i64 {
+    v
+}
+
+fn scaled(a: u32, b: u32) -> u32 {
+    wrap(a + b) * 2
+}
+
+unsafe fn store(buf: *mut u8) {
+    *pass_through(buf) = 1;
+}
+
+fn twice(v: i64) -> i64 {
+    exported_id(v) + exported_id(v)
+}
+
+fn apply(values: &[u32]) -> Vec<u32> {
+    values.iter().copied().map(wrap).collect()
+}
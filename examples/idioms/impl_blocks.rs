@@ -0,0 +1,54 @@
+pub struct Counter {
+    value: u32,
+}
+
+impl Counter {
+    pub fn new() -> Self {
+        Counter { value: 0 }
+    }
+}
+
+impl Default for Counter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Counter {
+    pub fn increment(&mut self) {
+        self.value += 1;
+    }
+
+    pub fn get(&self) -> u32 {
+        self.value
+    }
+}
+
+#[cfg(test)]
+impl Counter {
+    pub fn reset(&mut self) {
+        self.value = 0;
+    }
+}
+
+pub struct Wrapper<T> {
+    inner: T,
+}
+
+impl<T> Wrapper<T> {
+    pub fn new(inner: T) -> Self {
+        Wrapper { inner }
+    }
+}
+
+impl<T: Clone> Wrapper<T> {
+    pub fn cloned(&self) -> T {
+        self.inner.clone()
+    }
+}
+
+impl<T> Wrapper<T> {
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
@@ -0,0 +1,19 @@
+pub fn double_all(a: &mut [i32]) {
+    for i in 0..a.len() {
+        a[i] = a[i] * 2;
+    }
+}
+
+pub fn clamp_prefix(a: &mut [i32], n: usize) {
+    for i in 0..n {
+        if a[i] > 100 {
+            a[i] = 100;
+        }
+    }
+}
+
+pub fn weighted(a: &mut [i32], w: &[i32]) {
+    for i in 0..a.len() {
+        a[i] = a[i] * w[i];
+    }
+}
@@ -0,0 +1,18 @@
+fn add_one(x: i32) -> i32 {
+    x + 1
+}
+
+fn scaled(value: i32, factor: i32) -> i32 {
+    let product = value * factor;
+    product + 1
+}
+
+fn used_twice(x: i32) -> i32 {
+    x * x
+}
+
+pub fn compute(x: i32, values: &[i32]) -> i32 {
+    let a = add_one(x) * 2;
+    let b = scaled(values[0], a);
+    used_twice(a) + used_twice(b)
+}
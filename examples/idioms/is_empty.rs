@@ -0,0 +1,31 @@
+use std::collections::HashMap;
+
+fn no_items(items: &Vec<u32>) -> bool {
+    items.len() == 0
+}
+
+fn has_entries(table: &HashMap<u32, String>) -> bool {
+    table.len() > 0
+}
+
+fn nonblank(line: &str) -> bool {
+    0 != line.trim().len()
+}
+
+fn any_bytes(buf: &[u8], extra: [u8; 4]) -> bool {
+    buf.len() != 0 || extra.len() >= 1
+}
+
+struct Queue {
+    len: usize,
+}
+
+impl Queue {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+fn idle(queue: &Queue) -> bool {
+    queue.len() == 0
+}
@@ -0,0 +1,16 @@
+pub fn sum_twice(opt: Option<i32>) -> i32 {
+    let mut total = 0;
+    if opt.is_some() {
+        total += opt.unwrap();
+        total += opt.unwrap() * 2;
+    }
+    total
+}
+
+pub fn reset_before_use(mut opt: Option<i32>) -> i32 {
+    if opt.is_some() {
+        opt = None;
+        return opt.unwrap();
+    }
+    0
+}
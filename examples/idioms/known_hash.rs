@@ -0,0 +1,55 @@
+pub fn djb2(s: &[u8]) -> u32 {
+    let mut hash: u32 = 5381;
+    for &c in s {
+        hash = ((hash << 5).wrapping_add(hash)).wrapping_add(c as u32);
+    }
+    hash
+}
+
+pub fn djb2_times(s: &[u8]) -> u64 {
+    let mut hash: u64 = 5381;
+    let mut i = 0;
+    while i < s.len() {
+        hash = hash.wrapping_mul(33) ^ s[i] as u64;
+        i += 1;
+    }
+    hash
+}
+
+pub fn fnv1a(data: &[u8]) -> u32 {
+    let mut hash: u32 = 0x811c9dc5;
+    for &byte in data {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x01000193);
+    }
+    hash
+}
+
+pub fn fnv1_64(data: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for &byte in data {
+        hash = hash.wrapping_mul(0x100000001b3);
+        hash ^= byte as u64;
+    }
+    hash
+}
+
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xffffffff;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb88320 & mask);
+        }
+    }
+    !crc
+}
+
+pub fn sum(data: &[u8]) -> u32 {
+    let mut total: u32 = 5381;
+    for &byte in data {
+        total += byte as u32;
+    }
+    total
+}
@@ -0,0 +1,12 @@
+pub unsafe fn annotated_sum(values: &[i32], n: i32) -> i32 {
+    let mut sum: i32 = 0 as i32;
+    let mut i: i32 = 0 as i32;
+    #[allow(clippy::needless_range_loop)]
+    while i < n {
+        #[allow(clippy::cast_sign_loss)]
+        let index = i as usize;
+        sum += values[index];
+        i += 1;
+    }
+    sum
+}
@@ -0,0 +1,39 @@
+pub unsafe fn first_negative(values: &[i32], n: i32) -> i32 {
+    let mut i: i32 = 0 as i32;
+    while i < n {
+        if values[i as usize] < 0 {
+            return i;
+        }
+        i += 1;
+    }
+    -1
+}
+pub unsafe fn positive_sum(values: &[i32], n: i32) -> i32 {
+    let mut sum: i32 = 0 as i32;
+    let mut i: i32 = 0 as i32;
+    while i < n {
+        if values[i as usize] < 0 {
+            i += 1;
+            continue;
+        }
+        sum += values[i as usize];
+        i += 1;
+    }
+    sum
+}
+pub unsafe fn until_zero(values: &[i32], n: i32) -> i32 {
+    let mut sum: i32 = 0 as i32;
+    let mut i: i32 = 0 as i32;
+    'scan: while i < n {
+        let mut j: i32 = 0 as i32;
+        while j < n {
+            if values[(i * j) as usize] == 0 {
+                break 'scan;
+            }
+            sum += values[(i * j) as usize];
+            j += 1;
+        }
+        i += 1;
+    }
+    sum
+}
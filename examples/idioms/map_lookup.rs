@@ -0,0 +1,30 @@
+use std::collections::HashMap;
+
+fn count_of(counts: &HashMap<String, u32>, word: &str) -> u32 {
+    match counts.get(word) {
+        Some(n) => *n,
+        None => 0,
+    }
+}
+
+fn label_of(labels: &HashMap<u32, String>, id: u32) -> String {
+    match labels.get(&id) {
+        Some(label) => label.clone(),
+        None => String::from("unknown"),
+    }
+}
+
+fn weight_of(weights: &HashMap<u8, f64>, key: u8, fallback: f64) -> f64 {
+    match weights.get(&key) {
+        None => fallback,
+        Some(&w) => w,
+    }
+}
+
+fn required(counts: &HashMap<String, u32>, word: &str) -> Option<u32> {
+    let n = match counts.get(word) {
+        Some(n) => *n,
+        None => return None,
+    };
+    Some(n + 1)
+}
@@ -0,0 +1,14 @@
+use std::mem;
+use std::sync::{Mutex, MutexGuard};
+
+fn reset(counter: &mut u32) -> u32 {
+    let mut total: u64 = 10;
+    let _ = std::mem::replace(&mut total, 0);
+    mem::replace(&mut *counter, 0);
+    total as u32
+}
+
+fn rearm<'a>(lock: &'a Mutex<i32>, mut guard: MutexGuard<'a, i32>, other: &'a Mutex<i32>) {
+    let _ = mem::replace(&mut guard, other.lock().unwrap());
+    drop(lock);
+}
@@ -0,0 +1,25 @@
+pub unsafe fn grid_sum(rows: i32, cols: i32) -> i32 {
+    let mut total: i32 = 0 as i32;
+    let mut i: i32 = 0 as i32;
+    while i < rows {
+        let mut j: i32 = 0 as i32;
+        while j < cols {
+            total += i * cols + j;
+            j += 1;
+        }
+        i += 1;
+    }
+    total
+}
+
+pub unsafe fn nested_in_branch(n: i32, enabled: bool) -> i32 {
+    let mut count: i32 = 0 as i32;
+    if enabled {
+        let mut k: i32 = 0 as i32;
+        while k < n {
+            count += k;
+            k += 1;
+        }
+    }
+    count
+}
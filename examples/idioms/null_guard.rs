@@ -0,0 +1,19 @@
+pub unsafe fn set_flag(flag: *mut libc::c_int) {
+    if flag.is_null() {
+        return;
+    }
+    *flag = 1 as libc::c_int;
+}
+
+pub unsafe fn read_or(value: *const libc::c_int, fallback: libc::c_int) -> libc::c_int {
+    if value == ::core::ptr::null() {
+        return fallback;
+    }
+    *value
+}
+
+pub unsafe fn clear_if_set(slot: *mut libc::c_int) {
+    if !slot.is_null() {
+        *slot = 0 as libc::c_int;
+    }
+}
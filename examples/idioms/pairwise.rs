@@ -0,0 +1,32 @@
+fn total_rise(a: &[i32]) -> i32 {
+    let mut total = 0;
+    for i in 0..a.len() - 1 {
+        if a[i + 1] > a[i] {
+            total += a[i + 1] - a[i];
+        }
+    }
+    total
+}
+
+fn is_sorted(values: &[u64]) -> bool {
+    for i in 1..values.len() {
+        if values[i - 1] > values[i] {
+            return false;
+        }
+    }
+    true
+}
+
+fn smooth(a: &mut [i32]) {
+    for i in 0..a.len() - 1 {
+        a[i] = (a[i] + a[i + 1]) / 2;
+    }
+}
+
+fn skip_one(a: &[i32]) -> i32 {
+    let mut sum = 0;
+    for i in 0..a.len() - 1 {
+        sum += a[i] * a[i + 2];
+    }
+    sum
+}
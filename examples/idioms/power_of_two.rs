@@ -0,0 +1,15 @@
+pub fn is_pow2(x: u32) -> bool {
+    x != 0 && (x & (x - 1)) == 0
+}
+
+pub fn is_pow2_or_zero(n: usize) -> bool {
+    (n & (n - 1)) == 0
+}
+
+pub fn aligned(size: u64, align: u64) -> bool {
+    size % align == 0 && align != 0 && (align & (align - 1)) == 0
+}
+
+pub fn signed(x: i32) -> bool {
+    x != 0 && (x & (x - 1)) == 0
+}
@@ -0,0 +1,38 @@
+fn has_prefix(s: &[u8], prefix: &[u8]) -> bool {
+    for i in 0..prefix.len() {
+        if s[i] != prefix[i] {
+            return false;
+        }
+    }
+    true
+}
+
+fn has_suffix(s: &[u8], suffix: &[u8], n: usize) -> bool {
+    for i in 0..n {
+        if s[s.len() - n + i] != suffix[i] {
+            return false;
+        }
+    }
+    true
+}
+
+fn matches_magic(header: &[u8], magic: [u8; 4]) -> i32 {
+    let mut ok = 1;
+    for i in 0..magic.len() {
+        if magic[i] != header[i] {
+            ok = 0;
+            break;
+        }
+    }
+    ok
+}
+
+fn mismatch_count(a: &[u8], b: &[u8]) -> usize {
+    let mut count = 0;
+    for i in 0..b.len() {
+        if a[i] != b[i] {
+            count += 1;
+        }
+    }
+    count
+}
@@ -0,0 +1,30 @@
+extern "C" {
+    fn qsort(
+        __base: *mut libc::c_void,
+        __nmemb: libc::size_t,
+        __size: libc::size_t,
+        __compar: __compar_fn_t,
+    );
+}
+pub type __compar_fn_t = Option<
+    unsafe extern "C" fn(*const libc::c_void, *const libc::c_void) -> libc::c_int,
+>;
+unsafe extern "C" fn compare_ints(
+    mut a: *const libc::c_void,
+    mut b: *const libc::c_void,
+) -> libc::c_int {
+    return *(a as *const libc::c_int) - *(b as *const libc::c_int);
+}
+#[no_mangle]
+pub unsafe extern "C" fn sort_scores(mut n: libc::c_int) {
+    let mut scores: [libc::c_int; 16] = [0; 16];
+    qsort(
+        scores.as_mut_ptr() as *mut libc::c_void,
+        n as libc::size_t,
+        ::core::mem::size_of::<libc::c_int>() as libc::c_ulong,
+        Some(
+            compare_ints
+                as unsafe extern "C" fn(*const libc::c_void, *const libc::c_void) -> libc::c_int,
+        ),
+    );
+}
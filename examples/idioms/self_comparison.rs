@@ -0,0 +1,11 @@
+pub fn always(x: i32) -> bool {
+    x == x
+}
+
+pub fn never(flag: bool, n: u64) -> bool {
+    flag != flag || n < n
+}
+
+pub fn nan_check(value: f64) -> bool {
+    value != value
+}
@@ -0,0 +1,37 @@
+fn log(msg: &str) {
+    let _ = msg;
+}
+
+fn next_id() -> u32 {
+    7
+}
+
+pub fn scaled(x: i32) -> i32 {
+    let y = match x + 1 {
+        v => v,
+    };
+    y * 2
+}
+
+pub fn describe(kind: u8) -> &'static str {
+    match kind {
+        _ => {
+            log("kind ignored");
+            "any"
+        }
+    }
+}
+
+pub fn announce(kind: u8) {
+    match kind {
+        _ => log("announcing"),
+    }
+    log("done");
+}
+
+pub fn fresh() -> u32 {
+    let id = match next_id() {
+        _ => 0,
+    };
+    id
+}
@@ -0,0 +1,18 @@
+pub unsafe fn sum(values: *const i32, len: usize) -> i32 {
+    let mut total: i32 = 0 as i32;
+    let mut i: usize = 0 as usize;
+    while i < len {
+        let v: i32 = *values.add(i);
+        total += v;
+        i += 1;
+    }
+    total
+}
+
+pub unsafe fn fill(out: *mut i32, len: usize, v: i32) {
+    let mut i: usize = 0 as usize;
+    while i < len {
+        *out.add(i) = v;
+        i += 1;
+    }
+}
@@ -0,0 +1,38 @@
+static mut COUNTER: i32 = 0;
+static mut FLAGS: u32 = 0;
+static mut BUFFER_LEN: usize = 0;
+
+pub fn next_id() -> i32 {
+    unsafe {
+        COUNTER += 1;
+        COUNTER
+    }
+}
+
+pub fn reset() {
+    unsafe {
+        COUNTER = 0;
+    }
+}
+
+pub fn current() -> i32 {
+    unsafe { COUNTER }
+}
+
+pub fn set_flag(bit: u32) {
+    unsafe {
+        FLAGS = FLAGS | 1 << bit;
+    }
+}
+
+pub fn has_flags() -> bool {
+    let flags = unsafe { FLAGS };
+    flags != 0
+}
+
+pub fn grow(n: usize) {
+    unsafe {
+        let len = &mut BUFFER_LEN;
+        *len += n;
+    }
+}
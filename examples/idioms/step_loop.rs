@@ -0,0 +1,14 @@
+pub unsafe fn strided_sum(values: &[i32], n: i32) -> i32 {
+    let mut sum: i32 = 0 as i32;
+    let mut i: i32 = 0 as i32;
+    while i < n {
+        sum += values[i as usize];
+        i += 2;
+    }
+    let mut j: i32 = 1 as i32;
+    while j <= n {
+        sum += values[j as usize] * 2;
+        j = j + 3;
+    }
+    sum
+}
@@ -0,0 +1,18 @@
+fn sign(x: i32) -> i32 {
+    return if x < 0 { -1 } else { 1 };
+}
+
+fn name(kind: u8) -> &'static str {
+    return match kind {
+        0 => "zero",
+        1 => "one",
+        _ => "many",
+    };
+}
+
+fn early(x: i32) -> i32 {
+    if x == 0 {
+        return if x > 1 { 2 } else { 3 };
+    }
+    x
+}
@@ -0,0 +1,55 @@
+fn sum_first(a: &[i32], n: usize) -> i32 {
+    let mut total = 0;
+    let mut i = 0;
+    loop {
+        total += a[i];
+        i += 1;
+        if i >= n {
+            break;
+        }
+    }
+    total
+}
+
+fn sum_range() -> u32 {
+    let mut hits = 0;
+    let mut k = 2;
+    loop {
+        hits += k;
+        k = k + 1;
+        if k >= 8 {
+            break;
+        }
+    }
+    hits
+}
+
+fn from_start(start: u64, end: u64) -> u64 {
+    let mut acc = 0;
+    let mut j = start;
+    loop {
+        acc ^= j;
+        j += 1;
+        if end <= j {
+            break;
+        }
+    }
+    acc
+}
+
+fn skips(n: i32) -> i32 {
+    let mut odd = 0;
+    let mut i = 0;
+    loop {
+        if i % 2 == 0 {
+            i += 1;
+            continue;
+        }
+        odd += 1;
+        i += 1;
+        if i >= n {
+            break;
+        }
+    }
+    odd
+}
@@ -0,0 +1,16 @@
+pub unsafe fn store(out: *mut i32, value: i32) {
+    *out = value;
+}
+
+pub unsafe fn store_shared(shared: *mut i32, value: i32) {
+    let copy: *mut i32 = shared;
+    *shared = value;
+    *copy += 1;
+}
+
+pub unsafe fn bump_both(counter: *mut i32, step: i32) -> i32 {
+    let other: *mut i32 = counter;
+    *counter = *other + step;
+    *other *= step + 1;
+    *counter
+}
@@ -0,0 +1,23 @@
+fn reset(state: &mut Vec<u32>) {
+    state.clear();
+}
+
+fn count(state: &[u32]) -> usize {
+    state.len()
+}
+
+pub fn restart(state: &mut Vec<u32>) {
+    let _ = reset(state);
+    let _: () = reset(state);
+    let _ = println!("restarted");
+    let _ = count(state);
+    let _ = for x in state.iter_mut() {
+        *x = 0;
+    };
+    let _ = if state.is_empty() {
+        reset(state);
+    } else {
+        state.push(1);
+    };
+    let _ = state.pop();
+}
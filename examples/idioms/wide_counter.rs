@@ -0,0 +1,14 @@
+pub unsafe fn count_wide() -> u64 {
+    let mut total: u64 = 0 as u64;
+    let mut i: u64 = 0 as u64;
+    while i < 5_000_000_000u64 {
+        total += i & 1;
+        i += 1;
+    }
+    let mut j: u64 = 6_000_000_000 as u64;
+    while j > 5_000_000_000u64 {
+        total += j & 3;
+        j -= 1;
+    }
+    total
+}
@@ -8,15 +8,16 @@
     unused_mut
 )]
 #[no_mangle]
-pub unsafe fn insertion_sort(n: i32, p: *mut i32) {
+pub unsafe fn insertion_sort(p: &mut [i32]) {
+    let n: i32 = p.len() as i32;
     let mut i: i32 = 1 as i32;
     for i in 1..n {
-        let tmp: i32 = *p.offset(i as isize);
+        let tmp: i32 = p[i as usize];
         let mut j: i32 = i;
-        while j > 0 as i32 && *p.offset((j - 1 as i32) as isize) > tmp {
-            *p.offset(j as isize) = *p.offset((j - 1 as i32) as isize);
+        while j > 0 as i32 && p[(j - 1 as i32) as usize] > tmp {
+            p[j as usize] = p[(j - 1 as i32) as usize];
             j -= 1;
         }
-        *p.offset(j as isize) = tmp;
+        p[j as usize] = tmp;
     }
 }
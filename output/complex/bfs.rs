@@ -12,7 +12,7 @@ extern "C" {
     fn scanf(_: *const i8, _: ...) -> i32;
 }
 #[no_mangle]
-pub unsafe fn bfs(mut graph: *mut [i32; 100], mut numNodes: i32, mut startNode: i32) {
+pub unsafe fn bfs(mut graph: &[[i32; 100]], mut numNodes: i32, mut startNode: i32) {
     let mut visited: [i32; 100] = [
         0 as i32,
         0,
@@ -123,13 +123,14 @@ pub unsafe fn bfs(mut graph: *mut [i32; 100], mut numNodes: i32, mut startNode:
     queue[fresh0 as usize] = startNode;
     visited[startNode as usize] = 1 as i32;
     printf(b"BFS Traversal: \0" as *const u8 as *const i8);
-    for front in 0..0 {
+    while front < rear {
         let fresh1 = front;
+        front = front + 1;
         let mut currentNode: i32 = queue[fresh1 as usize];
         printf(b"%d \0" as *const u8 as *const i8, currentNode);
         let mut neighbor: i32 = 0 as i32;
         for neighbor in 0..numNodes {
-            if (*graph.offset(currentNode as isize))[neighbor as usize] != 0
+            if graph[currentNode as usize][neighbor as usize] != 0
                 && visited[neighbor as usize] == 0
             {
                 let fresh2 = rear;
@@ -162,7 +163,7 @@ unsafe fn main_0() -> i32 {
     }
     printf(b"Enter the starting node: \0" as *const u8 as *const i8);
     scanf(b"%d\0" as *const u8 as *const i8, &mut startNode as *mut i32);
-    bfs(graph.as_mut_ptr(), numNodes, startNode);
+    bfs(&graph, numNodes, startNode);
     return 0 as i32;
 }
 pub fn main() {
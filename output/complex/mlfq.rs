@@ -35,12 +35,12 @@ pub static mut queues: [Queue; 3] = [Queue {
     rear: 0,
 }; 3];
 #[no_mangle]
-pub unsafe fn initializeQueue(mut q: *mut Queue) {
+pub unsafe fn initializeQueue(mut q: &mut Queue) {
     (*q).front = -(1 as i32);
     (*q).rear = -(1 as i32);
 }
 #[no_mangle]
-pub unsafe fn isEmpty(mut q: *mut Queue) -> i32 {
+pub unsafe fn isEmpty(mut q: &Queue) -> i32 {
     return ((*q).front == -(1 as i32)) as i32;
 }
 #[no_mangle]
@@ -49,7 +49,7 @@ pub unsafe fn enqueue(mut q: *mut Queue, mut p: *mut Process) {
         printf(b"Error: Queue is full!\n\0" as *const u8 as *const i8);
         exit(1 as i32);
     }
-    if isEmpty(q) != 0 {
+    if isEmpty(&*q) != 0 {
         (*q).front = 0 as i32;
     }
     (*q).rear += 1;
@@ -57,7 +57,7 @@ pub unsafe fn enqueue(mut q: *mut Queue, mut p: *mut Process) {
 }
 #[no_mangle]
 pub unsafe fn dequeue(mut q: *mut Queue) -> *mut Process {
-    if isEmpty(q) != 0 {
+    if isEmpty(&*q) != 0 {
         return 0 as *mut Process;
     }
     let mut p: *mut Process = (*q).processes[(*q).front as usize];
@@ -71,7 +71,8 @@ pub unsafe fn dequeue(mut q: *mut Queue) -> *mut Process {
     return p;
 }
 #[no_mangle]
-pub unsafe fn initializeScheduler(mut processes: *mut Process, mut numProcesses: i32) {
+pub unsafe fn initializeScheduler(mut processes: &mut [Process]) {
+    let mut numProcesses: i32 = processes.len() as i32;
     let mut i: i32 = 0 as i32;
     while i < 3 as i32 {
         initializeQueue(&mut *queues.as_mut_ptr().offset(i as isize));
@@ -79,11 +80,11 @@ pub unsafe fn initializeScheduler(mut processes: *mut Process, mut numProcesses:
     }
     let mut i_0: i32 = 0 as i32;
     for i_0 in 0..numProcesses {
-        (*processes.offset(i_0 as isize)).priority = 0 as i32;
-        (*processes.offset(i_0 as isize)).isCompleted = 0 as i32;
+        processes[i_0 as usize].priority = 0 as i32;
+        processes[i_0 as usize].isCompleted = 0 as i32;
         enqueue(
             &mut *queues.as_mut_ptr().offset(0 as i32 as isize),
-            &mut *processes.offset(i_0 as isize),
+            &mut processes[i_0 as usize],
         );
     }
 }
@@ -94,7 +95,7 @@ pub unsafe fn runMLFQScheduler(mut processes: *mut Process, mut numProcesses: i3
         let mut allCompleted: i32 = 1 as i32;
         let mut i: i32 = 0 as i32;
         while i < 3 as i32 {
-            while isEmpty(&mut *queues.as_mut_ptr().offset(i as isize)) == 0 {
+            while isEmpty(&*queues.as_mut_ptr().offset(i as isize)) == 0 {
                 let mut currentProcess: *mut Process = dequeue(
                     &mut *queues.as_mut_ptr().offset(i as isize),
                 );
@@ -185,7 +186,7 @@ unsafe fn main_0() -> i32 {
         );
         processes[i as usize].id = i;
     }
-    initializeScheduler(processes.as_mut_ptr(), numProcesses);
+    initializeScheduler(&mut processes[..numProcesses.max(0) as usize]);
     runMLFQScheduler(processes.as_mut_ptr(), numProcesses);
     return 0 as i32;
 }
@@ -24,7 +24,7 @@ extern "C" {
     fn stat(__file: *const i8, __buf: *mut stat) -> i32;
 }
 pub type __uint8_t = u8;
-pub type __uint16_t = libc::c_ushort;
+pub type __uint16_t = u16;
 pub type __dev_t = u64;
 pub type __uid_t = u32;
 pub type __gid_t = u32;
@@ -60,8 +60,8 @@ pub struct _IO_FILE {
     pub _fileno: i32,
     pub _flags2: i32,
     pub _old_offset: __off_t,
-    pub _cur_column: libc::c_ushort,
-    pub _vtable_offset: libc::c_schar,
+    pub _cur_column: u16,
+    pub _vtable_offset: i8,
     pub _shortbuf: [i8; 1],
     pub _lock: *mut (),
     pub _offset: __off64_t,
@@ -18,32 +18,27 @@ pub struct User {
     pub permissions: u32,
 }
 #[no_mangle]
-pub unsafe fn checkPermissions(mut users: *mut User, mut numUsers: i32) {
+pub unsafe fn checkPermissions(mut users: &[User]) {
+    let mut numUsers: i32 = users.len() as i32;
     printf(b"Checking permissions for all users:\n\0" as *const u8 as *const i8);
     let mut i: i32 = 0 as i32;
     for i in 0..numUsers {
-        printf(
-            b"User ID: %d\n\0" as *const u8 as *const i8,
-            (*users.offset(i as isize)).id,
-        );
+        printf(b"User ID: %d\n\0" as *const u8 as *const i8, users[i as usize].id);
         printf(b"  Permissions: \0" as *const u8 as *const i8);
-        if (*users.offset(i as isize)).permissions & ((1 as i32) << 2 as i32) as u32 != 0
-        {
+        if users[i as usize].permissions & ((1 as i32) << 2 as i32) as u32 != 0 {
             printf(b"Read \0" as *const u8 as *const i8);
         }
-        if (*users.offset(i as isize)).permissions & ((1 as i32) << 1 as i32) as u32 != 0
-        {
+        if users[i as usize].permissions & ((1 as i32) << 1 as i32) as u32 != 0 {
             printf(b"Write \0" as *const u8 as *const i8);
         }
-        if (*users.offset(i as isize)).permissions & ((1 as i32) << 0 as i32) as u32 != 0
-        {
+        if users[i as usize].permissions & ((1 as i32) << 0 as i32) as u32 != 0 {
             printf(b"Execute \0" as *const u8 as *const i8);
         }
         printf(b"\n\0" as *const u8 as *const i8);
     }
 }
 #[no_mangle]
-pub unsafe fn modifyPermissions(mut user: *mut User) {
+pub unsafe fn modifyPermissions(mut user: &mut User) {
     printf(
         b"\nModify permissions for User ID %d:\n\0" as *const u8 as *const i8,
         (*user).id,
@@ -97,7 +92,7 @@ unsafe fn main_0() -> i32 {
             );
         }
     }
-    checkPermissions(users.as_mut_ptr(), numUsers);
+    checkPermissions(&users[..numUsers.max(0) as usize]);
     let mut targetUserID: i32 = 0;
     printf(b"\nEnter the User ID to modify permissions: \0" as *const u8 as *const i8);
     scanf(b"%d\0" as *const u8 as *const i8, &mut targetUserID as *mut i32);
@@ -113,7 +108,7 @@ unsafe fn main_0() -> i32 {
         &mut *users.as_mut_ptr().offset((targetUserID - 1 as i32) as isize),
     );
     printf(b"\nPermissions after modification:\n\0" as *const u8 as *const i8);
-    checkPermissions(users.as_mut_ptr(), numUsers);
+    checkPermissions(&users[..numUsers.max(0) as usize]);
     return 0 as i32;
 }
 pub fn main() {
@@ -0,0 +1,16 @@
+fn magnitude(x: i32) -> i32 {
+    x.abs()
+}
+fn distance(a: i32, b: i32) -> i32 {
+    let d: i32 = a - b;
+    d.abs()
+}
+fn scaled(v: f64) -> f64 {
+    2.0 * v.abs()
+}
+fn widened(x: u8) -> i64 {
+    (x as i64).abs()
+}
+fn unsigned(n: u32) -> u32 {
+    if n < 0 { 0 - n } else { n }
+}
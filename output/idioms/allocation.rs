@@ -0,0 +1,29 @@
+extern "C" {
+    fn malloc(_: u64) -> *mut ();
+    fn calloc(_: u64, _: u64) -> *mut ();
+    fn free(_: *mut ());
+}
+pub unsafe fn boxed_counter(start: i32) -> i32 {
+    let mut counter: Box<i32> = Box::new(i32::default());
+    *counter = start;
+    *counter += 1;
+    let value: i32 = *counter;
+    drop(counter);
+    value
+}
+pub unsafe fn squares(n: i32) -> i32 {
+    let mut values: Vec<i32> = vec![i32::default(); n as usize];
+    let mut i: i32 = 0 as i32;
+    for i in 0..n {
+        values[i as usize] = i * i;
+    }
+    let last: i32 = values[(n - 1 as i32) as usize];
+    drop(values);
+    last
+}
+pub unsafe fn zeroed(n: i32) -> i32 {
+    let counts: Vec<i32> = vec![i32::default(); n as usize];
+    let first: i32 = counts[0];
+    drop(counts);
+    first
+}
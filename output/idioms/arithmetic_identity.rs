@@ -0,0 +1,31 @@
+pub fn offset(base: usize, i: usize) -> usize {
+    base + i
+}
+pub fn flags(mode: u32) -> u32 {
+    mode
+}
+pub fn masked(bits: u64) -> u64 {
+    bits
+}
+pub fn scaled(x: i32, y: i32) -> i32 {
+    x * y
+}
+pub fn cleared(x: u8) -> u8 {
+    let high = 0u8;
+    high | 0u8
+}
+pub fn stride(x: i64) -> i64 {
+    x
+}
+pub fn by_ref(x: &u32) -> u32 {
+    x + 0
+}
+pub fn inverted(x: u16) -> u16 {
+    !x
+}
+pub fn unknown(v: &[u32]) -> u32 {
+    v[0] * 1
+}
+pub fn half(x: f64) -> f64 {
+    x + 0.0
+}
@@ -0,0 +1,29 @@
+fn lookup(a: &[i32], i: i32) -> i32 {
+    let Some(&x) = a.get(i as usize) else {
+        return -1;
+    };
+    x * 2
+}
+fn first_word(words: &[String], n: usize) -> usize {
+    let Some(word) = words.get(n) else {
+        return 0;
+    };
+    word.len()
+}
+fn sum_at(a: &[u8], indices: &[i64]) -> u32 {
+    let mut total = 0;
+    for &i in indices {
+        let Some(&byte) = a.get(i as usize) else {
+            continue;
+        };
+        total += byte as u32;
+    }
+    total
+}
+fn unchecked_sign(a: &[i32], i: i32) -> i32 {
+    if i >= a.len() as i32 {
+        return -1;
+    }
+    let x = a[i as usize];
+    x
+}
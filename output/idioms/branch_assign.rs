@@ -0,0 +1,28 @@
+pub fn sign(v: i32) -> i32 {
+    let s = if v < 0 { -1 } else if v == 0 { 0 } else { 1 };
+    s
+}
+pub fn clamp_into(out: &mut [u8], i: usize, v: i32) {
+    out[i] = if v > 255 { 255 } else { v as u8 };
+}
+pub fn pick(flag: bool, a: u32, b: u32) -> u32 {
+    let mut r: u32 = if flag { a } else { b };
+    r += 1;
+    r
+}
+pub fn increment_or_reset(flag: bool, mut n: u32) -> u32 {
+    let mut m = 0;
+    n = if flag { n + 1 } else { 0 };
+    m = if flag { m + 1 } else { 2 };
+    n + m
+}
+pub fn mixed(flag: bool) -> (u32, u32) {
+    let mut a = 0;
+    let mut b = 0;
+    if flag {
+        a = 1;
+    } else {
+        b = 1;
+    }
+    (a, b)
+}
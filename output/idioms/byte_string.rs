@@ -0,0 +1,41 @@
+pub fn from_slice(bytes: &[u8]) -> String {
+    let mut s = String::new();
+    for &b in bytes {
+        s.push(b as char);
+    }
+    s
+}
+pub fn from_vec(data: Vec<u8>) -> String {
+    let mut out = String::with_capacity(data.len());
+    for b in data.iter() {
+        out.push(char::from(*b));
+    }
+    out.push('!');
+    out
+}
+pub fn by_index(buf: &Vec<u8>) -> String {
+    let mut text = String::new();
+    for i in 0..buf.len() {
+        text.push(buf[i] as char);
+    }
+    text
+}
+pub fn uppercased(bytes: &[u8]) -> String {
+    let mut s = String::new();
+    for &b in bytes {
+        s.push(b.to_ascii_uppercase() as char);
+    }
+    s
+}
+pub fn skipping(bytes: &[u8]) -> String {
+    let mut s = String::new();
+    for i in 1..bytes.len() {
+        s.push(bytes[i] as char);
+    }
+    s
+}
+pub fn from_literal() -> String {
+    let bytes: &[u8] = b"ok";
+    let s = String::from_utf8_lossy(bytes).into_owned();
+    s
+}
@@ -0,0 +1,11 @@
+use std::mem::transmute;
+pub fn read_u32(bytes: [u8; 4]) -> u32 {
+    u32::from_ne_bytes(bytes)
+}
+pub fn read_f64(raw: [u8; 8]) -> f64 {
+    let value: f64 = f64::from_ne_bytes(raw);
+    value
+}
+pub fn mismatched(bytes: [u8; 4]) -> u64 {
+    unsafe { transmute::<[u8; 4], u64>(bytes) }
+}
@@ -0,0 +1,24 @@
+use std::ffi::CStr;
+use std::os::raw::c_char;
+extern "C" {
+    fn strcpy(dst: *mut i8, src: *const i8) -> *mut i8;
+    fn strcat(dst: *mut i8, src: *const i8) -> *mut i8;
+    fn strlen(s: *const i8) -> usize;
+}
+unsafe fn greeting() -> usize {
+    let mut buf: [i8; 64] = [0; 64];
+    strcpy(buf.as_mut_ptr(), b"hello, \0" as *const u8 as *const i8);
+    strcat(buf.as_mut_ptr(), b"world\0" as *const u8 as *const i8);
+    let s = CStr::from_ptr(buf.as_ptr()).to_str().unwrap();
+    s.len()
+}
+unsafe fn measured() -> usize {
+    let mut name: [i8; 16] = [0; 16];
+    strcpy(name.as_mut_ptr(), b"id\0".as_ptr() as *const i8);
+    strlen(name.as_ptr())
+}
+unsafe fn raw_bytes() -> u8 {
+    let mut bytes: [u8; 4] = [0; 4];
+    bytes[0] = 1;
+    bytes[0]
+}
@@ -0,0 +1,19 @@
+pub fn percent(x: i32) -> i32 {
+    x.clamp(0, 100)
+}
+pub fn volume(level: f64) -> f64 {
+    level.clamp(0.0, 1.0)
+}
+pub fn limit_in_place(mut speed: i64) -> i64 {
+    speed = speed.clamp(-10, 10);
+    speed
+}
+pub fn window(x: u32, lo: u32, hi: u32) -> u32 {
+    if x < lo { lo } else if x > hi { hi } else { x }
+}
+pub fn reversed(x: i32) -> i32 {
+    if x < 10 { 10 } else if x > 0 { 0 } else { x }
+}
+pub fn bounded(x: i32) -> i32 {
+    x.max(0).min(255)
+}
@@ -0,0 +1,32 @@
+const BASE: u32 = 16;
+const MAX: i32 = 100;
+const BUFFER_SIZE: usize = (BASE as usize) * 4;
+const LIMIT: i64 = MAX as i64 * 2 - 1;
+fn name() -> &'static str {
+    "device"
+}
+fn counter() -> u32 {
+    BASE
+}
+fn seed() -> u32 {
+    let s = 7;
+    s * 3
+}
+pub fn clamp(x: i32) -> i32 {
+    if x > MAX { MAX } else { x }
+}
+pub fn alloc() -> Vec<u8> {
+    Vec::with_capacity(BUFFER_SIZE + BUFFER_SIZE)
+}
+pub fn within(x: i64) -> bool {
+    x < LIMIT && x > -LIMIT
+}
+pub fn label() -> String {
+    format!("{}-{}", name(), seed())
+}
+pub fn callbacks() -> [fn() -> u32; 2] {
+    [counter, seed]
+}
+pub fn total() -> u32 {
+    counter() + seed()
+}
@@ -0,0 +1,16 @@
+pub unsafe fn countdown_sum(n: i32) -> i32 {
+    let mut sum: i32 = 0 as i32;
+    let mut i: i32 = 10 as i32;
+    for i in (1..=10i32).rev() {
+        sum += i;
+    }
+    let mut j: i32 = 10 as i32;
+    for j in (2..=10i32).rev() {
+        sum += j * 2;
+    }
+    let mut k: i32 = 10 as i32;
+    for k in (n..10).rev() {
+        sum += k * 3;
+    }
+    sum
+}
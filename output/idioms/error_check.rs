@@ -0,0 +1,26 @@
+use std::ffi::c_int;
+pub fn open_device(id: u32) -> Result<(), i32> {
+    if id == 0 {
+        return Err(-1);
+    }
+    Ok(())
+}
+pub fn configure(id: u32, rate: u32) -> Result<(), i32> {
+    if rate > id {
+        return Err(-2);
+    }
+    Ok(())
+}
+pub fn start(id: u32, rate: u32) -> Result<(), i32> {
+    open_device(id)?;
+    configure(id, rate)?;
+    Ok(())
+}
+pub fn start_logged(id: u32) -> Result<(), i32> {
+    let rc = open_device(id);
+    if rc.is_err() {
+        return rc;
+    }
+    println!("opened: {:?}", rc);
+    Ok(())
+}
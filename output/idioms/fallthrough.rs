@@ -0,0 +1,28 @@
+fn handle(code: u8) -> u8 {
+    code.wrapping_mul(3)
+}
+pub fn classify(c: u8) -> u32 {
+    match c {
+        b' ' | b'\t' | b'\n' => 1,
+        b'0'..=b'9' => 2,
+        _ => 0,
+    }
+}
+pub fn dispatch(op: u8) -> u8 {
+    match op {
+        1 | 2 => {
+            handle(op);
+            handle(1)
+        }
+        3 | 4 | 5 => handle(3),
+        x if x > 100 => handle(3),
+        n => handle(n),
+    }
+}
+pub fn keep_bindings(pair: (u8, u8)) -> u8 {
+    match pair {
+        (0, b) => b,
+        (b, 0) => b,
+        _ => 0,
+    }
+}
@@ -0,0 +1,9 @@
+use std::os::raw::{c_double, c_int, c_long};
+pub unsafe fn widen(a: i32, b: i16, c: u16, d: i64, e: u64, f: i64) -> f64 {
+    (a as f64) + (b as f64) + (c as f64) + (d + e as i64 + f as i64) as f64
+}
+pub unsafe fn first_char(s: &i8, t: &mut u8, u: &i8) -> f32 {
+    let bytes: [i8; 2] = [*s, *u as i8];
+    *t = bytes.len() as u8;
+    ::core::mem::size_of::<f32>() as f32
+}
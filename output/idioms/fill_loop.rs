@@ -0,0 +1,17 @@
+const UNSET: i32 = -1;
+pub fn clear(a: &mut [u8]) {
+    a.fill(0);
+}
+pub fn reset() -> [i32; 8] {
+    let mut marks: [i32; 8] = [0; 8];
+    marks.fill(UNSET);
+    marks
+}
+pub fn fill_sevens(a: &mut [i64]) {
+    a.fill(7);
+}
+pub fn partial(a: &mut [u8], n: usize) {
+    for x in a[..n].iter_mut() {
+        *x = 0;
+    }
+}
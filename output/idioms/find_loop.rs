@@ -0,0 +1,30 @@
+pub fn contains_key(keys: &[u32], key: u32) -> bool {
+    let idx = keys.iter().position(|&x| x == key);
+    if idx.is_none() {
+        return false;
+    }
+    true
+}
+pub fn index_of(names: &Vec<String>, name: &str) -> i32 {
+    let found = names.iter().position(|x| *x == name).map_or(-1, |i| i as i32);
+    found
+}
+pub fn first_negative(values: &[i64], n: i32) -> i32 {
+    let pos: i32 = values[..n.max(0) as usize]
+        .iter()
+        .position(|&x| x < 0)
+        .map_or(-1, |i| i as i32);
+    if pos >= 0 {
+        println!("negative at {}", pos);
+    }
+    pos
+}
+pub fn last_zero(values: &[u8]) -> i32 {
+    let mut idx: i32 = -1;
+    for i in 0..values.len() {
+        if values[i] == 0 {
+            idx = i as i32;
+        }
+    }
+    idx
+}
@@ -0,0 +1,30 @@
+pub fn contains_negative(values: &[i32]) -> bool {
+    let found = values.into_iter().any(|v| *v < 0);
+    found
+}
+pub fn all_even(values: &Vec<u32>) -> bool {
+    let ok = values.iter().all(|&v| v % 2 == 0);
+    ok
+}
+pub fn any_divisor(n: u32) -> bool {
+    let composite: bool = (2..n).any(|d| n % d == 0);
+    composite
+}
+pub fn all_printable(s: &str, strict: bool) -> bool {
+    let mut printable = s.chars().all(|c| c.is_ascii_graphic());
+    if strict && s.is_empty() {
+        printable = false;
+    }
+    printable
+}
+pub fn count_and_flag(values: &[i32]) -> (bool, i32) {
+    let mut found = false;
+    let mut count = 0;
+    for v in values {
+        if *v == 0 {
+            found = true;
+            count += 1;
+        }
+    }
+    (found, count)
+}
@@ -0,0 +1,46 @@
+fn process(x: &i32) {
+    let _ = x;
+}
+fn scale(x: &mut i32) -> i32 {
+    *x *= 2;
+    *x
+}
+pub fn visit(v: &Vec<i32>) {
+    for x in v {
+        process(x);
+    }
+}
+pub fn visit_twice(v: &[i32]) {
+    for x in v.iter().chain(v) {
+        process(x);
+    }
+}
+pub fn visit_all(v: Vec<i32>, out: &mut Vec<i32>) {
+    for x in &v {
+        out.push(*x);
+    }
+}
+pub fn double(v: &mut Vec<i32>) {
+    for x in &mut v[..] {
+        scale(x);
+    }
+}
+pub fn quadruple(v: &mut Vec<i32>) {
+    for x in v.iter_mut() {
+        scale(x);
+        scale(x);
+    }
+}
+pub fn count_up(n: i32, out: &mut Vec<i32>) {
+    for i in 0..n {
+        out.push(i);
+    }
+}
+pub fn until_negative(v: &[i32], out: &mut Vec<i32>) {
+    for x in v {
+        if *x < 0 {
+            break;
+        }
+        out.push(*x);
+    }
+}
@@ -0,0 +1,29 @@
+use std::ffi::c_void;
+extern "C" {
+    fn malloc(size: usize) -> *mut c_void;
+    fn free(ptr: *mut c_void);
+}
+pub struct Buffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+unsafe fn checksum(len: usize) -> u8 {
+    let mut b = Buffer {
+        data: malloc(len) as *mut u8,
+        len,
+    };
+    *b.data = 7;
+    let sum = *b.data;
+    unsafe {
+        let b_1: &mut Buffer = &mut b;
+        free((*b_1).data as *mut c_void);
+    };
+    sum
+}
+#[derive(Copy, Clone)]
+pub struct Node {
+    pub next: *mut Node,
+}
+unsafe fn node_free(n: &mut Node) {
+    free(n.next as *mut c_void);
+}
@@ -0,0 +1,30 @@
+fn both_positive(x: i32, y: i32) -> bool {
+    if x <= 0 || y <= 0 {
+        return false;
+    }
+    true
+}
+fn differ(a: u8, b: u8) -> u8 {
+    if a != b {
+        return 1;
+    }
+    0
+}
+fn clamp_below(x: i64, y: i64) -> i64 {
+    if x >= y {
+        return y;
+    }
+    x
+}
+fn flags(a: bool, b: bool) -> bool {
+    if !(a && b) {
+        return false;
+    }
+    a
+}
+fn ratio(p: f64, q: f64) -> f64 {
+    if !(p < q) {
+        return 0.0;
+    }
+    p / q
+}
@@ -0,0 +1,18 @@
+fn wrap(x: u32) -> u32 {
+    x
+}
+pub fn exported_id(v: i64) -> i64 {
+    v
+}
+fn scaled(a: u32, b: u32) -> u32 {
+    (a + b) * 2
+}
+unsafe fn store(buf: *mut u8) {
+    *buf = 1;
+}
+fn twice(v: i64) -> i64 {
+    v + v
+}
+fn apply(values: &[u32]) -> Vec<u32> {
+    values.iter().copied().map(wrap).collect()
+}
@@ -0,0 +1,17 @@
+pub fn double_all(a: &mut [i32]) {
+    for x in a.iter_mut() {
+        *x *= 2;
+    }
+}
+pub fn clamp_prefix(a: &mut [i32], n: usize) {
+    for x in a[..n].iter_mut() {
+        if *x > 100 {
+            *x = 100;
+        }
+    }
+}
+pub fn weighted(a: &mut [i32], w: &[i32]) {
+    for i in 0..a.len() {
+        a[i] = a[i] * w[i];
+    }
+}
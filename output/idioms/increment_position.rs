@@ -0,0 +1,40 @@
+pub unsafe fn trailing(values: &[i32], n: i32) -> i32 {
+    let mut sum: i32 = 0 as i32;
+    let mut i: i32 = 0 as i32;
+    for i in 0..n {
+        sum += values[i as usize];
+    }
+    sum
+}
+pub unsafe fn conditional(values: &[i32], n: i32) -> i32 {
+    let mut sum: i32 = 0 as i32;
+    let mut i: i32 = 0 as i32;
+    while i < n {
+        sum += values[i as usize];
+        if values[i as usize] < 0 {
+            i += 1;
+        }
+        i += 1;
+    }
+    sum
+}
+pub unsafe fn middle(values: &mut [i32], n: i32) {
+    let mut i: i32 = 0 as i32;
+    while i < n {
+        values[i as usize] = 0;
+        i += 1;
+        values[i as usize] = 1;
+    }
+}
+pub unsafe fn skips_step(values: &[i32], n: i32) -> i32 {
+    let mut sum: i32 = 0 as i32;
+    let mut i: i32 = 0 as i32;
+    while i < n {
+        if values[i as usize] == 0 {
+            continue;
+        }
+        sum += values[i as usize];
+        i += 1;
+    }
+    sum
+}
@@ -0,0 +1,13 @@
+fn used_twice(x: i32) -> i32 {
+    x * x
+}
+pub fn compute(x: i32, values: &[i32]) -> i32 {
+    let a = (x + 1) * 2;
+    let b = {
+        let value_1: i32 = values[0];
+        let factor_1: i32 = a;
+        let product = value_1 * factor_1;
+        product + 1
+    };
+    used_twice(a) + used_twice(b)
+}
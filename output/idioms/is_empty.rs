@@ -0,0 +1,24 @@
+use std::collections::HashMap;
+fn no_items(items: &Vec<u32>) -> bool {
+    items.is_empty()
+}
+fn has_entries(table: &HashMap<u32, String>) -> bool {
+    !table.is_empty()
+}
+fn nonblank(line: &str) -> bool {
+    !line.trim().is_empty()
+}
+fn any_bytes(buf: &[u8], extra: [u8; 4]) -> bool {
+    !buf.is_empty() || !extra.is_empty()
+}
+struct Queue {
+    len: usize,
+}
+impl Queue {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+fn idle(queue: &Queue) -> bool {
+    queue.len() == 0
+}
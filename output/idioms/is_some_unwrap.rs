@@ -0,0 +1,15 @@
+pub fn sum_twice(opt: Option<i32>) -> i32 {
+    let mut total = 0;
+    if let Some(opt_value) = opt {
+        total += opt_value;
+        total += opt_value * 2;
+    }
+    total
+}
+pub fn reset_before_use(mut opt: Option<i32>) -> i32 {
+    if opt.is_some() {
+        opt = None;
+        return opt.unwrap();
+    }
+    0
+}
@@ -0,0 +1,11 @@
+pub unsafe fn annotated_sum(values: &[i32], n: i32) -> i32 {
+    let mut sum: i32 = 0 as i32;
+    let mut i: i32 = 0 as i32;
+    #[allow(clippy::needless_range_loop)]
+    for i in 0..n {
+        #[allow(clippy::cast_sign_loss)]
+        let index = i as usize;
+        sum += values[index];
+    }
+    sum
+}
@@ -0,0 +1,17 @@
+use std::collections::HashMap;
+fn count_of(counts: &HashMap<String, u32>, word: &str) -> u32 {
+    counts.get(word).copied().unwrap_or(0)
+}
+fn label_of(labels: &HashMap<u32, String>, id: u32) -> String {
+    labels.get(&id).cloned().unwrap_or_else(|| String::from("unknown"))
+}
+fn weight_of(weights: &HashMap<u8, f64>, key: u8, fallback: f64) -> f64 {
+    weights.get(&key).copied().unwrap_or(fallback)
+}
+fn required(counts: &HashMap<String, u32>, word: &str) -> Option<u32> {
+    let n = match counts.get(word) {
+        Some(n) => *n,
+        None => return None,
+    };
+    Some(n + 1)
+}
@@ -0,0 +1,21 @@
+pub unsafe fn grid_sum(rows: i32, cols: i32) -> i32 {
+    let mut total: i32 = 0 as i32;
+    let mut i: i32 = 0 as i32;
+    for i in 0..rows {
+        let mut j: i32 = 0 as i32;
+        for j in 0..cols {
+            total += i * cols + j;
+        }
+    }
+    total
+}
+pub unsafe fn nested_in_branch(n: i32, enabled: bool) -> i32 {
+    let mut count: i32 = 0 as i32;
+    if enabled {
+        let mut k: i32 = 0 as i32;
+        for k in 0..n {
+            count += k;
+        }
+    }
+    count
+}
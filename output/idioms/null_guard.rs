@@ -0,0 +1,17 @@
+pub unsafe fn set_flag(flag: Option<&mut i32>) {
+    let Some(flag) = flag else {
+        return;
+    };
+    *flag = 1 as i32;
+}
+pub unsafe fn read_or(value: Option<&i32>, fallback: i32) -> i32 {
+    let Some(value) = value else {
+        return fallback;
+    };
+    *value
+}
+pub unsafe fn clear_if_set(slot: *mut i32) {
+    if !slot.is_null() {
+        *slot = 0 as i32;
+    }
+}
@@ -0,0 +1,29 @@
+fn total_rise(a: &[i32]) -> i32 {
+    let mut total = 0;
+    for w in a.windows(2) {
+        if w[1] > w[0] {
+            total += w[1] - w[0];
+        }
+    }
+    total
+}
+fn is_sorted(values: &[u64]) -> bool {
+    for w in values.windows(2) {
+        if w[0] > w[1] {
+            return false;
+        }
+    }
+    true
+}
+fn smooth(a: &mut [i32]) {
+    for i in 0..a.len() - 1 {
+        a[i] = (a[i] + a[i + 1]) / 2;
+    }
+}
+fn skip_one(a: &[i32]) -> i32 {
+    let mut sum = 0;
+    for i in 0..a.len() - 1 {
+        sum += a[i] * a[i + 2];
+    }
+    sum
+}
@@ -0,0 +1,12 @@
+pub fn is_pow2(x: u32) -> bool {
+    x.is_power_of_two()
+}
+pub fn is_pow2_or_zero(n: usize) -> bool {
+    n == 0 || n.is_power_of_two()
+}
+pub fn aligned(size: u64, align: u64) -> bool {
+    size % align == 0 && align.is_power_of_two()
+}
+pub fn signed(x: i32) -> bool {
+    x != 0 && (x & (x - 1)) == 0
+}
@@ -0,0 +1,22 @@
+fn has_prefix(s: &[u8], prefix: &[u8]) -> bool {
+    s.starts_with(prefix)
+}
+fn has_suffix(s: &[u8], suffix: &[u8], n: usize) -> bool {
+    s.ends_with(&suffix[..n])
+}
+fn matches_magic(header: &[u8], magic: [u8; 4]) -> i32 {
+    let mut ok = 1;
+    if !header.starts_with(&magic) {
+        ok = 0;
+    }
+    ok
+}
+fn mismatch_count(a: &[u8], b: &[u8]) -> usize {
+    let mut count = 0;
+    for i in 0..b.len() {
+        if a[i] != b[i] {
+            count += 1;
+        }
+    }
+    count
+}
@@ -0,0 +1,23 @@
+extern "C" {
+    fn qsort(
+        __base: *mut (),
+        __nmemb: libc::size_t,
+        __size: libc::size_t,
+        __compar: __compar_fn_t,
+    );
+}
+pub type __compar_fn_t = Option<unsafe extern "C" fn(*const (), *const ()) -> i32>;
+unsafe fn compare_ints(mut a: *const (), mut b: *const ()) -> i32 {
+    return *(a as *const i32) - *(b as *const i32);
+}
+#[no_mangle]
+pub unsafe fn sort_scores(mut n: i32) {
+    let mut scores: [i32; 16] = [0; 16];
+    scores[..n as usize]
+        .sort_by(|a, b| {
+            unsafe {
+                compare_ints(a as *const i32 as *const _, b as *const i32 as *const _)
+            }
+                .cmp(&0)
+        });
+}
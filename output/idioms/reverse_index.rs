@@ -0,0 +1,31 @@
+pub fn digits_to_number(digits: &[u64]) -> u64 {
+    let mut total = 0;
+    for &x in digits.iter().rev() {
+        total = total * 10 + x;
+    }
+    total
+}
+pub fn last_words(words: &Vec<String>, n: i32) -> Vec<String> {
+    let mut out = Vec::new();
+    for x in words[..n.max(0) as usize].iter().rev() {
+        out.push((*x).clone());
+    }
+    out
+}
+pub fn reverse_in_place(a: &mut [i32]) {
+    let n = a.len();
+    for i in 0..n / 2 {
+        let j = n - 1 - i;
+        a.swap(i, j);
+    }
+}
+pub fn mirrored_index_used(a: &[i32]) -> usize {
+    let mut last = 0;
+    for i in 0..a.len() {
+        let j = a.len() - 1 - i;
+        if a[j] > 0 {
+            last = j;
+        }
+    }
+    last
+}
@@ -0,0 +1,9 @@
+pub fn always(x: i32) -> bool {
+    true
+}
+pub fn never(flag: bool, n: u64) -> bool {
+    false || false
+}
+pub fn nan_check(value: f64) -> bool {
+    value != value
+}
@@ -0,0 +1,22 @@
+fn log(msg: &str) {
+    let _ = msg;
+}
+const NEXT_ID: u32 = 7;
+pub fn scaled(x: i32) -> i32 {
+    let y = x + 1;
+    y * 2
+}
+pub fn describe(kind: u8) -> &'static str {
+    {
+        log("kind ignored");
+        "any"
+    }
+}
+pub fn announce(kind: u8) {
+    log("announcing");
+    log("done");
+}
+pub fn fresh() -> u32 {
+    let id = 0;
+    id
+}
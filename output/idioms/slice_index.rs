@@ -0,0 +1,17 @@
+pub unsafe fn sum(values: &[i32]) -> i32 {
+    let len: usize = values.len();
+    let mut total: i32 = 0 as i32;
+    let mut i: usize = 0 as usize;
+    for i in 0..len {
+        let v: i32 = values[i];
+        total += v;
+    }
+    total
+}
+pub unsafe fn fill(out: &mut [i32], v: i32) {
+    let len: usize = out.len();
+    let mut i: usize = 0 as usize;
+    for x in out[..len].iter_mut() {
+        *x = v;
+    }
+}
@@ -0,0 +1,26 @@
+static COUNTER: std::sync::atomic::AtomicI32 = std::sync::atomic::AtomicI32::new(0);
+static FLAGS: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+static mut BUFFER_LEN: usize = 0;
+pub fn next_id() -> i32 {
+    COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    COUNTER.load(std::sync::atomic::Ordering::Relaxed)
+}
+pub fn reset() {
+    COUNTER.store(0, std::sync::atomic::Ordering::Relaxed);
+}
+pub fn current() -> i32 {
+    COUNTER.load(std::sync::atomic::Ordering::Relaxed)
+}
+pub fn set_flag(bit: u32) {
+    FLAGS.fetch_or(1 << bit, std::sync::atomic::Ordering::Relaxed);
+}
+pub fn has_flags() -> bool {
+    let flags = FLAGS.load(std::sync::atomic::Ordering::Relaxed);
+    flags != 0
+}
+pub fn grow(n: usize) {
+    unsafe {
+        let len = &mut BUFFER_LEN;
+        *len += n;
+    }
+}
@@ -0,0 +1,12 @@
+pub unsafe fn strided_sum(values: &[i32], n: i32) -> i32 {
+    let mut sum: i32 = 0 as i32;
+    let mut i: i32 = 0 as i32;
+    for i in (0..n).step_by(2) {
+        sum += values[i as usize];
+    }
+    let mut j: i32 = 1 as i32;
+    for j in (1..=n).step_by(3) {
+        sum += values[j as usize] * 2;
+    }
+    sum
+}
@@ -0,0 +1,15 @@
+pub fn stars(n: usize) -> String {
+    let mut buf = String::new();
+    for _ in 0..n {
+        buf.push('*');
+    }
+    buf.push('\n');
+    buf
+}
+pub fn copy_bytes(bytes: &[u8]) -> String {
+    let mut buf: Vec<u8> = Vec::with_capacity(bytes.len());
+    for b in bytes {
+        buf.push(*b);
+    }
+    String::from_utf8(buf).unwrap()
+}
@@ -0,0 +1,37 @@
+fn sum_first(a: &[i32], n: usize) -> i32 {
+    let mut total = 0;
+    for i in 0..n.max(1) {
+        total += a[i];
+    }
+    total
+}
+fn sum_range() -> u32 {
+    let mut hits = 0;
+    for k in 2..8 {
+        hits += k;
+    }
+    hits
+}
+fn from_start(start: u64, end: u64) -> u64 {
+    let mut acc = 0;
+    for j in start..(start + 1).max(end) {
+        acc ^= j;
+    }
+    acc
+}
+fn skips(n: i32) -> i32 {
+    let mut odd = 0;
+    let mut i = 0;
+    loop {
+        if i % 2 == 0 {
+            i += 1;
+            continue;
+        }
+        odd += 1;
+        i += 1;
+        if i >= n {
+            break;
+        }
+    }
+    odd
+}
@@ -0,0 +1,14 @@
+pub unsafe fn store(out: &mut i32, value: i32) {
+    *out = value;
+}
+pub unsafe fn store_shared(shared: &std::cell::Cell<i32>, value: i32) {
+    let copy: &std::cell::Cell<i32> = shared;
+    shared.set(value);
+    copy.set(copy.get() + 1);
+}
+pub unsafe fn bump_both(counter: &std::cell::Cell<i32>, step: i32) -> i32 {
+    let other: &std::cell::Cell<i32> = counter;
+    counter.set(other.get() + step);
+    other.set(other.get() * (step + 1));
+    counter.get()
+}
@@ -0,0 +1,18 @@
+fn reset(state: &mut Vec<u32>) {
+    state.clear();
+}
+pub fn restart(state: &mut Vec<u32>) {
+    reset(state);
+    reset(state);
+    println!("restarted");
+    let _ = state.len();
+    for x in state.iter_mut() {
+        *x = 0;
+    }
+    if state.is_empty() {
+        reset(state);
+    } else {
+        state.push(1);
+    }
+    let _ = state.pop();
+}
@@ -0,0 +1,12 @@
+pub unsafe fn count_wide() -> u64 {
+    let mut total: u64 = 0 as u64;
+    let mut i: u64 = 0 as u64;
+    for i in 0..5_000_000_000u64 {
+        total += i & 1;
+    }
+    let mut j: u64 = 6_000_000_000 as u64;
+    for j in (5000000001u64..=6_000_000_000).rev() {
+        total += j & 3;
+    }
+    total
+}
@@ -1,7 +1,39 @@
 #![crate_type = "lib"]
 #![crate_name = "monadicast"]
+//! Transformations of the unidiomatic, and possibly unsafe, code constructs that
+//! C-to-Rust code generators emit into idiomatic Rust.
+//!
+//! [transform_str] is the entry point for running the default pipeline on source
+//! code. [MonadicAst] applies individual passes, and [Pipeline] composes them in
+//! any order.
 
 mod monad;
 mod passes;
 
-pub use monad::MonadicAst;
+pub use monad::{
+    format_parse_error, Config, Diagnostic, Event, FfiConfig, MonadicAst, Pass, PassConfig,
+    PassError, PassName, Pipeline, Stats, UnknownPassName, CONFIG_FILE_NAME,
+    DEFAULT_FIXPOINT_ITERATIONS, DEFAULT_MAX_INLINED_STATEMENTS,
+};
+pub use passes::promote_static_counters::AtomicOrdering;
+pub use passes::replace_byte_transmutes::Endianness;
+
+/// Parses the given Rust source code, applies every pass of the default pipeline
+/// and returns the formatted result, with the default [PassConfig].
+///
+/// ```
+/// let source = "pub unsafe extern \"C\" fn add_zero(x: libc::c_int) -> libc::c_int { x + 0 }";
+/// let output = monadicast::transform_str(source).unwrap();
+/// assert_eq!(output, "pub unsafe fn add_zero(x: i32) -> i32 {\n    x\n}\n");
+/// ```
+pub fn transform_str(source: &str) -> Result<String, PassError> {
+    transform_str_with(source, &PassConfig::default())
+}
+
+/// Like [transform_str], but with the given options for the passes.
+pub fn transform_str_with(source: &str, config: &PassConfig) -> Result<String, PassError> {
+    let monad = MonadicAst::new(source)?
+        .with_config(config.clone())
+        .apply_all(PassName::ALL)?;
+    Ok(monad.result_formatted()?)
+}
@@ -1,49 +1,681 @@
-use monadicast::MonadicAst;
+use monadicast::{
+    format_parse_error, Config, Event, MonadicAst, PassConfig, PassError, PassName, Stats,
+    UnknownPassName, CONFIG_FILE_NAME,
+};
+use serde::Serialize;
 use std::env;
-use std::error::Error;
 use std::fs;
-use std::path::Path;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
-fn main() -> Result<(), Box<dyn Error>> {
+/// Command line options.
+struct Options {
+    /// The file or directory to transform, or [STDIN] for standard input.
+    input: PathBuf,
+    /// Directory the transformed files are written under.
+    out: PathBuf,
+    /// Whether to only report the files that would change, writing nothing.
+    check: bool,
+    /// Whether to print a unified diff of each transformed file.
+    diff: bool,
+    /// Where to write the JSON report of the rewrites made, if anywhere.
+    report: Option<PathBuf>,
+    /// The configuration file given with `--config`, rather than the one found in
+    /// the input directory.
+    config: Option<PathBuf>,
+    /// The passes selected with `--passes`, and those skipped with `--skip`, which
+    /// override the configuration file's.
+    selected: Option<Vec<PassName>>,
+    skipped: Option<Vec<PassName>>,
+    /// The passes to apply, in pipeline order, once [Options::configure]d.
+    passes: Vec<PassName>,
+    /// The options of the passes, from the configuration file.
+    pass_config: PassConfig,
+    /// If set, how many times the passes are applied at most, until the output
+    /// stops changing.
+    fixpoint_iterations: Option<usize>,
+    /// How many times `-v` was given: once logs the passes' debug messages, and
+    /// twice their trace messages as well.
+    verbosity: usize,
+}
+
+const USAGE: &str = "<input-file-or-directory|-> [--out <dir>] [--check] [--diff] \
+                     [--passes <pass,...>] [--skip <pass,...>] [--fixpoint <iterations>] \
+                     [--config <file>] [--report <file.json>] [-v|--verbose]...";
+
+/// The input naming standard input, whose source is transformed to standard output.
+const STDIN: &str = "-";
+
+/// Parses a comma-separated list of pass names.
+fn parse_passes(list: &str) -> Result<Vec<PassName>, String> {
+    list.split(',')
+        .map(|name| {
+            name.trim()
+                .parse()
+                .map_err(|error: UnknownPassName| error.to_string())
+        })
+        .collect()
+}
+
+/// Parses the command line, described by [USAGE], returning a message on a
+/// malformed one.
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut input = None;
+    let mut out = PathBuf::from("output");
+    let mut check = false;
+    let mut diff = false;
+    let mut report = None;
+    let mut config = None;
+    let mut selected: Option<Vec<PassName>> = None;
+    let mut skipped: Option<Vec<PassName>> = None;
+    let mut fixpoint_iterations = None;
+    let mut verbosity = 0;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("`{arg}` expects a value"));
+        match arg.as_str() {
+            "--out" => out = PathBuf::from(value()?),
+            "--check" => check = true,
+            "--diff" => diff = true,
+            "--report" => report = Some(PathBuf::from(value()?)),
+            "--passes" => selected = Some(parse_passes(value()?)?),
+            "--skip" => skipped
+                .get_or_insert_with(Vec::new)
+                .extend(parse_passes(value()?)?),
+            "--fixpoint" => {
+                let iterations = value()?;
+                fixpoint_iterations = Some(iterations.parse().map_err(|_| {
+                    format!("`--fixpoint` expects a number of iterations, not `{iterations}`")
+                })?)
+            }
+            "--config" => config = Some(PathBuf::from(value()?)),
+            "-v" | "--verbose" => verbosity += 1,
+            "-vv" => verbosity += 2,
+            STDIN | "--stdin" if input.is_none() => input = Some(PathBuf::from(STDIN)),
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`")),
+            _ if input.is_none() => input = Some(PathBuf::from(arg)),
+            _ => return Err(format!("unexpected argument `{arg}`")),
+        }
+    }
+    let input = input.ok_or("missing input file or directory")?;
+    if input == Path::new(STDIN) && (check || diff) {
+        return Err("`--check` and `--diff` need input files, not standard input".to_string());
+    }
+    Ok(Options {
+        input,
+        out,
+        check,
+        diff,
+        report,
+        config,
+        selected,
+        skipped,
+        passes: Vec::new(),
+        pass_config: PassConfig::default(),
+        fixpoint_iterations,
+        verbosity,
+    })
+}
+
+impl Options {
+    /// Returns the configuration file to read: the one given with `--config`, or the
+    /// `monadicast.toml` file of the input directory, or of the current directory
+    /// for standard input, if there is one. Returns a message if it could not be read
+    /// or parsed.
+    fn read_config(&self) -> Result<Option<Config>, String> {
+        if let Some(path) = &self.config {
+            return Config::read(path).map(Some);
+        }
+        let directory = if self.input == Path::new(STDIN) {
+            Path::new(".")
+        } else if self.input.is_dir() {
+            self.input.as_path()
+        } else {
+            match self.input.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            }
+        };
+        Config::find(directory)
+    }
+
+    /// Sets the passes to apply and their options from the configuration, where not
+    /// given on the command line.
+    fn configure(&mut self, config: Config) {
+        let selected = self.selected.as_ref().or(config.passes.as_ref());
+        let skipped = self.skipped.as_ref().unwrap_or(&config.skip);
+        // Selected passes still run in pipeline order.
+        self.passes = PassName::ALL
+            .iter()
+            .copied()
+            .filter(|pass| selected.is_none_or(|selected| selected.contains(pass)))
+            .filter(|pass| !skipped.contains(pass))
+            .collect();
+        self.pass_config = config.pass_config();
+        self.fixpoint_iterations = self.fixpoint_iterations.or(config.fixpoint_iterations);
+    }
+}
+
+/// Returns the path of `input_path`, found under the input `root`, relative to
+/// `root`, or just its file name if `root` is the file itself.
+fn relative_path<'a>(root: &Path, input_path: &'a Path) -> &'a Path {
+    match input_path.strip_prefix(root) {
+        Ok(relative_path) if !relative_path.as_os_str().is_empty() => relative_path,
+        _ => input_path
+            .file_name()
+            .map_or(input_path, |file_name| Path::new(file_name)),
+    }
+}
+
+/// Returns where the transformed `input_path`, found under the input `root`, is
+/// written: its relative path under `out`.
+fn output_path(root: &Path, input_path: &Path, out: &Path) -> PathBuf {
+    out.join(relative_path(root, input_path))
+}
+
+/// Lines of unchanged context around each change in a diff.
+const DIFF_CONTEXT: usize = 3;
+
+/// Returns a unified diff turning `original` into `transformed`, with a header
+/// naming `path`, or an empty string if they are the same.
+fn unified_diff(original: &str, transformed: &str, path: &Path) -> String {
+    let original: Vec<&str> = original.lines().collect();
+    let transformed: Vec<&str> = transformed.lines().collect();
+    let lines = diff::slice(&original, &transformed);
+    // The number of original and transformed lines before each line of the diff.
+    let mut before = Vec::with_capacity(lines.len() + 1);
+    let (mut old_line, mut new_line) = (0, 0);
+    for line in &lines {
+        before.push((old_line, new_line));
+        match line {
+            diff::Result::Left(_) => old_line += 1,
+            diff::Result::Both(..) => (old_line, new_line) = (old_line + 1, new_line + 1),
+            diff::Result::Right(_) => new_line += 1,
+        }
+    }
+    before.push((old_line, new_line));
+
+    // Changes closer than twice the context share a hunk.
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        if matches!(line, diff::Result::Both(..)) {
+            continue;
+        }
+        let start = index.saturating_sub(DIFF_CONTEXT);
+        let end = (index + DIFF_CONTEXT + 1).min(lines.len());
+        match hunks.last_mut() {
+            Some((_, hunk_end)) if start <= *hunk_end => *hunk_end = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+    if hunks.is_empty() {
+        return String::new();
+    }
+
+    let mut output = format!("--- a/{0}\n+++ b/{0}\n", path.display());
+    // An empty range is numbered by the line before it.
+    let range = |first: usize, last: usize| {
+        let start = if last == first { first } else { first + 1 };
+        format!("{},{}", start, last - first)
+    };
+    for (start, end) in hunks {
+        let ((old_first, new_first), (old_last, new_last)) = (before[start], before[end]);
+        output.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            range(old_first, old_last),
+            range(new_first, new_last)
+        ));
+        for line in &lines[start..end] {
+            let (prefix, text) = match line {
+                diff::Result::Left(text) => ('-', text),
+                diff::Result::Both(text, _) => (' ', text),
+                diff::Result::Right(text) => ('+', text),
+            };
+            output.push(prefix);
+            output.push_str(text);
+            output.push('\n');
+        }
+    }
+    output
+}
+
+/// Applies the passes to the AST in order, returning the first that fails along
+/// with its error.
+fn apply_passes(
+    mut monad: MonadicAst,
+    passes: &[PassName],
+) -> Result<MonadicAst, (PassName, PassError)> {
+    for &pass in passes {
+        monad = monad.apply(pass).map_err(|error| (pass, error))?;
+    }
+    Ok(monad)
+}
+
+/// Describes the failure of a pass.
+fn pass_failed((pass, error): (PassName, PassError)) -> String {
+    format!("pass `{pass}` failed: {error}")
+}
+
+/// How the passes are run on each source: [run_passes], unless a test fakes a
+/// failing pass.
+type RunPasses = fn(MonadicAst, &Options) -> Result<MonadicAst, String>;
+
+/// Applies the passes to the AST, once or up to a fixpoint as configured by
+/// `options`, returning a message naming the pass that failed if one did.
+fn run_passes(monad: MonadicAst, options: &Options) -> Result<MonadicAst, String> {
+    match options.fixpoint_iterations {
+        None => apply_passes(monad, &options.passes).map_err(pass_failed),
+        Some(max_iterations) => {
+            let mut monad = monad;
+            monad
+                .run_to_fixpoint_with(&options.passes, max_iterations)
+                .map_err(pass_failed)?;
+            Ok(monad)
+        }
+    }
+}
+
+/// Returns whether the path names a Rust source file.
+fn is_rust_source(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "rs")
+}
+
+/// The files found under the input.
+#[derive(Default)]
+struct InputFiles {
+    /// The Rust source files to transform.
+    sources: Vec<PathBuf>,
+    /// How many other files were found.
+    skipped: usize,
+    /// The entries that could not be read while walking the input directory.
+    errors: Vec<String>,
+}
+
+/// Returns the files to transform: the input itself if it is a `.rs` file, or
+/// every `.rs` file under it if it is a directory.
+fn input_files(input: &Path) -> Result<InputFiles, String> {
+    if input.is_file() {
+        if is_rust_source(input) {
+            return Ok(InputFiles {
+                sources: vec![input.to_path_buf()],
+                ..Default::default()
+            });
+        }
+        return Err(format!("`{}` is not a Rust source file", input.display()));
+    }
+    if !input.is_dir() {
+        return Err(format!("`{}` is not a file or directory", input.display()));
+    }
+    let mut files = InputFiles::default();
+    for entry in WalkDir::new(input) {
+        match entry {
+            Ok(entry) if entry.file_type().is_file() && is_rust_source(entry.path()) => {
+                files.sources.push(entry.into_path())
+            }
+            // The configuration file of the input directory
+            Ok(entry) if entry.depth() == 1 && entry.file_name() == CONFIG_FILE_NAME => {}
+            Ok(entry) if entry.file_type().is_file() => files.skipped += 1,
+            Ok(_) => {}
+            Err(error) => files.errors.push(error.to_string()),
+        }
+    }
+    Ok(files)
+}
+
+/// The rewrites made in each transformed file, written by `--report`.
+#[derive(Default, Serialize)]
+struct Report {
+    files: Vec<FileReport>,
+}
+
+#[derive(Serialize)]
+struct FileReport {
+    path: PathBuf,
+    events: Vec<Event>,
+}
+
+impl Report {
+    /// Writes the report to `path` as JSON, returning a message naming it if it
+    /// could not be.
+    fn write(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|error| format!("{}: {error}", path.display()))?;
+        fs::write(path, json + "\n").map_err(located(path))
+    }
+}
+
+/// Returns a function prefixing the message of an I/O error on `path` with it.
+fn located(path: &Path) -> impl Fn(io::Error) -> String + '_ {
+    move |error| format!("{}: {error}", path.display())
+}
+
+/// Applies the passes to the source read from `source_name` with `run`, adding the
+/// rewrites made to `stats` and `report` and printing the passes' diagnostics.
+/// Returns the source as printed before the passes and after, or a message
+/// starting with `source_name` if it could not be parsed or transformed.
+fn transform(
+    content: &str,
+    source_name: &Path,
+    options: &Options,
+    run: RunPasses,
+    stats: &mut Stats,
+    report: &mut Report,
+) -> Result<(String, String), String> {
+    let source = source_name.display();
+    let parsed = MonadicAst::new(content)
+        .map_err(|error| format_parse_error(source_name, &error))?
+        .with_config(options.pass_config.clone());
+    // The input as printed, so that formatting alone is no change
+    let original = parsed.result();
+    let monad = run(parsed, options).map_err(|message| format!("{source}: {message}"))?;
+    *stats += monad.stats();
+    report.files.push(FileReport {
+        path: source_name.to_path_buf(),
+        events: monad.events().to_vec(),
+    });
+    for diagnostic in monad.diagnostics() {
+        eprintln!("note: {source}: {diagnostic}");
+    }
+    Ok((original, formatted(&monad, source_name)?))
+}
+
+/// Returns the source `monad` prints, or a message starting with `source_name` if
+/// it does not parse back, in which case it is not written.
+fn formatted(monad: &MonadicAst, source_name: &Path) -> Result<String, String> {
+    monad
+        .result_formatted()
+        .map_err(|error| format!("{}: output does not parse: {error}", source_name.display()))
+}
+
+/// Transforms the source read from `input` and writes it to `output`, as the
+/// filter `monadicast -` does with standard input and output, adding the rewrites
+/// made to `report`. Returns a message starting with `<stdin>` if it failed.
+fn process_stdin(
+    input: &mut impl Read,
+    output: &mut impl Write,
+    options: &Options,
+    run: RunPasses,
+    report: &mut Report,
+) -> Result<(), String> {
+    let source_name = Path::new("<stdin>");
+    let mut content = String::new();
+    input
+        .read_to_string(&mut content)
+        .map_err(located(source_name))?;
+    let (_, transformed) = transform(
+        &content,
+        source_name,
+        options,
+        run,
+        &mut Stats::default(),
+        report,
+    )?;
+    output
+        .write_all(transformed.as_bytes())
+        .map_err(located(source_name))
+}
+
+/// Transforms the file at `input_path`, found under the input `root`, adding the
+/// rewrites made to `stats` and `report`. Returns whether the output differs from the input, or
+/// a message starting with the path of the file that could not be read, parsed,
+/// transformed or written.
+fn process_file(
+    input_path: &Path,
+    root: &Path,
+    options: &Options,
+    run: RunPasses,
+    stats: &mut Stats,
+    report: &mut Report,
+) -> Result<bool, String> {
+    let content = fs::read_to_string(input_path).map_err(located(input_path))?;
+    let (original, output) = transform(&content, input_path, options, run, stats, report)?;
+    let changed = output != original;
+
+    if options.diff {
+        print!(
+            "{}",
+            unified_diff(&original, &output, relative_path(root, input_path))
+        );
+    }
+    if options.check {
+        if changed {
+            println!("{}", relative_path(root, input_path).display());
+        }
+        return Ok(changed);
+    }
+
+    let output_path = output_path(root, input_path, &options.out);
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent).map_err(located(parent))?;
+    }
+    fs::write(&output_path, output).map_err(located(&output_path))?;
+    println!("Processed: {}", input_path.display());
+    Ok(changed)
+}
+
+/// Writes the report to the `--report` file, if one was given.
+fn write_report(report: &Report, options: &Options) -> Result<(), String> {
+    match &options.report {
+        Some(path) => report.write(path),
+        None => Ok(()),
+    }
+}
+
+fn main() {
     let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        eprintln!("Usage: {} <input-directory>", args[0]);
-        std::process::exit(1);
+    let mut options = match parse_args(&args[1..]) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("error: {message}");
+            eprintln!("Usage: {} {}", args[0], USAGE);
+            std::process::exit(1);
+        }
+    };
+    match options.read_config() {
+        Ok(config) => options.configure(config.unwrap_or_default()),
+        Err(message) => {
+            eprintln!("error: {message}");
+            std::process::exit(1);
+        }
     }
 
-    let input_dir = Path::new(&args[1]);
-    if !input_dir.exists() || !input_dir.is_dir() {
-        eprintln!("The specified input path is not a valid directory.");
-        std::process::exit(1);
+    let level = match options.verbosity {
+        0 => log::LevelFilter::Warn,
+        1 => log::LevelFilter::Debug,
+        _ => log::LevelFilter::Trace,
+    };
+    env_logger::Builder::new()
+        .filter_module("monadicast", level)
+        .init();
+
+    let input = options.input.as_path();
+    let mut report = Report::default();
+    if input == Path::new(STDIN) {
+        let processed = process_stdin(
+            &mut io::stdin(),
+            &mut io::stdout(),
+            &options,
+            run_passes,
+            &mut report,
+        )
+        .and_then(|()| write_report(&report, &options));
+        if let Err(message) = processed {
+            eprintln!("error: {message}");
+            std::process::exit(1);
+        }
+        return;
+    }
+    let files = match input_files(input) {
+        Ok(files) => files,
+        Err(message) => {
+            eprintln!("error: {message}");
+            std::process::exit(1);
+        }
+    };
+    for error in &files.errors {
+        eprintln!("warning: {error}");
     }
 
-    for entry in WalkDir::new(input_dir) {
-        let entry = entry?;
-        match entry.file_type().is_file() {
-            true => {
-                let input_path = entry.path();
-
-                let content = fs::read_to_string(&input_path)?;
-                let output = MonadicAst::new(&content)?
-                    .convert_ffi_types()
-                    .replace_raw_pointers()
-                    .replace_while_loop()
-                    .remove_useless_identifier_expressions()
-                    .result();
-
-                let relative_path = input_path.strip_prefix("examples")?;
-                let output_path = Path::new("output").join(relative_path);
-
-                if let Some(parent) = output_path.parent() {
-                    fs::create_dir_all(parent)?;
-                }
-                fs::write(&output_path, output)?;
-                println!("Processed: {}", input_path.display());
+    let (mut processed, mut changed, mut errored) = (0, 0, files.errors.len());
+    let mut stats = Stats::default();
+    for input_path in &files.sources {
+        match process_file(
+            input_path,
+            input,
+            &options,
+            run_passes,
+            &mut stats,
+            &mut report,
+        ) {
+            Ok(file_changed) => {
+                processed += 1;
+                changed += usize::from(file_changed);
+            }
+            Err(message) => {
+                eprintln!("error: {message}");
+                errored += 1;
             }
-            false => {}
         }
     }
-    println!("Successfully processed all files in the directory.");
-    Ok(())
+    if let Err(message) = write_report(&report, &options) {
+        eprintln!("error: {message}");
+        errored += 1;
+    }
+    eprintln!("Total: {stats}.");
+    eprintln!(
+        "{} file(s) processed, {} skipped, {} errored.",
+        processed, files.skipped, errored
+    );
+    if options.check && changed > 0 {
+        eprintln!("{changed} file(s) would be transformed.");
+    }
+    if errored > 0 || (options.check && changed > 0) {
+        std::process::exit(1);
+    }
+    if !options.check {
+        println!("Successfully processed all files.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_paths_are_relative_to_the_input() {
+        let out = Path::new("out");
+        for (root, input_path, expected) in [
+            // A file given as the input is written by its name.
+            ("src/lib.rs", "src/lib.rs", "out/lib.rs"),
+            ("src", "src/lib.rs", "out/lib.rs"),
+            ("src/", "src/lib.rs", "out/lib.rs"),
+            ("src", "src/passes/utils.rs", "out/passes/utils.rs"),
+            ("src/", "src/passes/utils.rs", "out/passes/utils.rs"),
+        ] {
+            assert_eq!(
+                output_path(Path::new(root), Path::new(input_path), out),
+                Path::new(expected),
+                "{input_path} under {root}"
+            );
+        }
+    }
+
+    #[test]
+    fn output_paths_stay_under_an_outside_out_directory() {
+        let (root, input_path) = (Path::new("project/src"), Path::new("project/src/a/b.rs"));
+        assert_eq!(relative_path(root, input_path), Path::new("a/b.rs"));
+        for out in ["/tmp/out", "../out", "project/src/out"] {
+            assert_eq!(
+                output_path(root, input_path, Path::new(out)),
+                Path::new(out).join("a/b.rs")
+            );
+        }
+    }
+
+    #[test]
+    fn reports_output_that_does_not_parse() {
+        let ast: syn::File = syn::parse_quote!(
+            fn f() {}
+        );
+        let mut monad = MonadicAst::from(ast);
+        assert_eq!(
+            formatted(&monad, Path::new("a.rs")),
+            Ok("fn f() {}\n".to_string())
+        );
+        // A pass building an invalid node, here a statement missing its semicolon.
+        let syn::Item::Fn(item_fn) = &mut monad.ast.items[0] else {
+            unreachable!();
+        };
+        item_fn.block.stmts = vec![
+            syn::Stmt::Expr(syn::parse_quote!(a), None),
+            syn::Stmt::Expr(syn::parse_quote!(b), None),
+        ];
+        let message = formatted(&monad, Path::new("a.rs")).unwrap_err();
+        assert!(
+            message.starts_with("a.rs: output does not parse: "),
+            "{message}"
+        );
+    }
+
+    /// Fails as a pass finding code it cannot handle would.
+    fn unsupported(_: MonadicAst, _: &Options) -> Result<MonadicAst, String> {
+        let error = PassError::Unsupported("a construct".to_string());
+        Err(pass_failed((PassName::ReplaceRawPointers, error)))
+    }
+
+    #[test]
+    fn failing_passes_are_reported_and_nothing_is_written() {
+        let root = env::temp_dir().join(format!("monadicast-unsupported-{}", std::process::id()));
+        let (input, out) = (root.join("input"), root.join("output"));
+        fs::create_dir_all(&input).unwrap();
+        fs::write(input.join("a.rs"), "fn f() {}\n").unwrap();
+        for fixpoint in [&[][..], &["--fixpoint", "2"]] {
+            let mut args = vec![
+                input.display().to_string(),
+                "--out".to_string(),
+                out.display().to_string(),
+            ];
+            args.extend(fixpoint.iter().map(|arg| arg.to_string()));
+            let mut options = parse_args(&args).unwrap();
+            options.configure(Config::default());
+
+            let processed = process_file(
+                &input.join("a.rs"),
+                &input,
+                &options,
+                unsupported,
+                &mut Stats::default(),
+                &mut Report::default(),
+            );
+
+            let message = processed.unwrap_err();
+            assert!(
+                message.ends_with(
+                    "a.rs: pass `replace_raw_pointers` failed: unsupported: a construct"
+                ),
+                "{fixpoint:?}: {message}"
+            );
+            assert!(!out.join("a.rs").exists(), "{fixpoint:?}");
+        }
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn unchanged_files_have_no_diff() {
+        let source = "fn f() {}\nfn g() {}\n";
+        assert_eq!(unified_diff(source, source, Path::new("a.rs")), "");
+    }
+
+    #[test]
+    fn changed_lines_are_diffed_with_context() {
+        // The two changes are close enough to share a hunk.
+        let original = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+        let transformed = "1\n2\n3\n4\nfive\n6\n7\n8\n9\n10\n11\n";
+        assert_eq!(
+            unified_diff(original, transformed, Path::new("src/a.rs")),
+            "--- a/src/a.rs\n+++ b/src/a.rs\n\
+             @@ -2,9 +2,10 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n 9\n 10\n+11\n"
+        );
+    }
 }
@@ -1,7 +1,58 @@
+use crate::monad::config::PassConfig;
+use crate::monad::diagnostic::Diagnostic;
+use crate::monad::error::PassError;
+use crate::monad::event::Event;
+use crate::monad::pipeline::Pipeline;
+use crate::monad::stats::Stats;
+use crate::passes::annotate_spin_loops::SpinLoopAnnotate;
 use crate::passes::convert_ffi_types::TypeReplacer;
+use crate::passes::deduplicate_functions::DeduplicateFunctions;
+use crate::passes::elide_identity_wrappers::IdentityWrapperElision;
+use crate::passes::elide_single_arm_matches::SingleArmMatchElision;
+use crate::passes::flatten_tail_returns::IfExprReturnFlatten;
+use crate::passes::fold_arithmetic_identities::ArithmeticIdentityFold;
+use crate::passes::fold_self_comparisons::SelfComparisonFold;
+use crate::passes::generate_drop_impls::FreeFnToDrop;
+use crate::passes::inline_single_call_fns::InlineSingleCallFn;
+use crate::passes::introduce_abs::AbsIntro;
+use crate::passes::introduce_clamp::ClampIntro;
+use crate::passes::introduce_is_empty::IsEmptyIntro;
+use crate::passes::introduce_is_power_of_two::PowerOfTwoIntro;
+use crate::passes::merge_fallthrough_arms::FallthroughToOrPattern;
+use crate::passes::merge_impl_blocks::MergeImplBlocks;
+use crate::passes::normalize_guard_polarity::NormalizeGuardPolarity;
+use crate::passes::promote_static_counters::{AtomicOrdering, StaticMutToAtomic};
+use crate::passes::recognize_known_algorithms::RecognizeKnownAlgorithms;
 use crate::passes::remove_dangling_identifiers::IdentifierExpressionRemover;
+use crate::passes::remove_dead_inits::RemoveDeadInit;
+use crate::passes::remove_redundant_unsafe::RedundantUnsafeRemover;
+use crate::passes::remove_unit_let_bindings::RemoveUnitLetBinding;
+use crate::passes::replace_allocations::AllocationReplacer;
+use crate::passes::replace_bounds_checks::BoundsCheckToGet;
+use crate::passes::replace_box_field_inits::BoxFieldInitToLiteral;
+use crate::passes::replace_branch_assignments::BranchAssignToCondExpr;
+use crate::passes::replace_byte_string_loops::ByteLoopToFromUtf8;
+use crate::passes::replace_byte_transmutes::{Endianness, TransmuteBytesToFromBytes};
+use crate::passes::replace_char_buffers::CharBufferToString;
+use crate::passes::replace_const_fns::ConstFnToConst;
+use crate::passes::replace_error_checks::PropagateViaTry;
+use crate::passes::replace_fill_loops::ZeroLoopToFill;
+use crate::passes::replace_find_loops::FindLoopToPosition;
+use crate::passes::replace_flag_loops::FlagLoopToAnyAll;
+use crate::passes::replace_for_each_loops::ForToForEach;
+use crate::passes::replace_in_place_updates::InPlaceUpdateToIterMut;
+use crate::passes::replace_is_some_unwrap::IsSomeUnwrapToIfLet;
+use crate::passes::replace_map_lookups::MapLookupDefault;
+use crate::passes::replace_mem_replace::ReplaceToAssign;
+use crate::passes::replace_memcpy_calls::MemcpyToSliceOp;
+use crate::passes::replace_pairwise_loops::PairwiseLoopToWindows;
+use crate::passes::replace_prefix_loops::PrefixLoopToStartsWith;
+use crate::passes::replace_qsort::QsortToSort;
 use crate::passes::replace_raw_pointers::RawPointerSanitizer;
+use crate::passes::replace_reverse_index_loops::ReverseIndexToRevIter;
+use crate::passes::replace_trailing_break_loops::TrailingBreakCounterToFor;
 use crate::passes::replace_while_loop::WhileLoopReplacer;
+use crate::passes::simplify_string_building::StringBuildSimplify;
 use syn::{parse_file, Error, File};
 
 /// A monadic type wrapping a [syn::File] abstract syntax tree (AST) whose monadic
@@ -9,15 +60,30 @@ use syn::{parse_file, Error, File};
 ///
 /// (See https://en.wikipedia.org/wiki/Monad_(functional_programming) for more
 /// background on monads.)
+#[derive(Clone)]
 pub struct MonadicAst {
     pub ast: File,
+    /// Notes accumulated by the passes applied so far.
+    pub diagnostics: Vec<Diagnostic>,
+    /// Options for the passes applied to the AST.
+    pub config: PassConfig,
+    /// Counts of the rewrites made by the passes applied so far.
+    pub stats: Stats,
+    /// The rewrites made by the passes applied so far, where they fired.
+    pub events: Vec<Event>,
 }
 
 impl MonadicAst {
     /// Returns a monadic value wrapping the constructed AST of the given Rust code.
     pub fn new(file_content: &str) -> Result<Self, Error> {
         let ast = parse_file(file_content)?;
-        Ok(Self { ast })
+        Ok(Self::from(ast))
+    }
+
+    /// Sets the options read by the passes applied after this call.
+    pub fn with_config(mut self, config: PassConfig) -> Self {
+        self.config = config;
+        self
     }
 
     /// Returns the monad's held AST value, consuming the monad.
@@ -25,31 +91,371 @@ impl MonadicAst {
         self.ast
     }
 
+    /// Returns the diagnostics emitted by the passes applied so far.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Returns the counts of the rewrites made by the passes applied so far.
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Returns the rewrites made by the passes applied so far, in the order they
+    /// were made.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
     /// Returns a formatted string representation of the monad's held AST.
     pub fn result(&self) -> String {
         prettyplease::unparse(&self.ast)
     }
 
+    /// Returns a formatted string representation of the monad's held AST, or an
+    /// error if it does not parse back as Rust code, e.g. because a pass built an
+    /// invalid node.
+    pub fn result_formatted(&self) -> Result<String, Error> {
+        let output = prettyplease::unparse(&self.ast);
+        parse_file(&output)?;
+        Ok(output)
+    }
+
     /// Replaces C foreign function interface (FFI) types in the AST with their Rust
     /// equivalents, e.g. libc::c_int -> i32.
-    pub fn convert_ffi_types(self) -> Self {
-        TypeReplacer::new().bind(self)
+    pub fn convert_ffi_types(self) -> Result<Self, PassError> {
+        Pipeline::new().add(TypeReplacer::new()).run(self)
     }
 
     /// Identifies declared raw pointers and replaces them with their safe Rust type
     /// equivalent determined via static analysis on their access patterns or usages.
-    pub fn replace_raw_pointers(self) -> Self {
-        RawPointerSanitizer::default().bind(self)
+    pub fn replace_raw_pointers(self) -> Result<Self, PassError> {
+        Pipeline::new()
+            .add(RawPointerSanitizer::default())
+            .run(self)
     }
 
     /// Identifies un-idiomatic while loop and replaces them with their safe Rust for-loop
     /// equivalent determined via static analysis on their accesses and usages.
-    pub fn replace_while_loop(self) -> Self {
-        WhileLoopReplacer::default().bind(self)
+    pub fn replace_while_loop(self) -> Result<Self, PassError> {
+        Pipeline::new().add(WhileLoopReplacer::default()).run(self)
+    }
+
+    pub fn remove_useless_identifier_expressions(self) -> Result<Self, PassError> {
+        Pipeline::new().add(IdentifierExpressionRemover).run(self)
+    }
+
+    /// Replaces `Vec<u8>` buffers built byte-by-byte and then interpreted as a string
+    /// with a `String` built via `String::push`, when the pushed bytes are ASCII.
+    pub fn simplify_string_building(self) -> Result<Self, PassError> {
+        Pipeline::new()
+            .add(StringBuildSimplify::default())
+            .run(self)
+    }
+
+    /// Replaces `opt.unwrap()` calls guarded by an `opt.is_some()` check with a single
+    /// `if let Some(..) = opt` binding.
+    pub fn replace_is_some_unwrap(self) -> Result<Self, PassError> {
+        Pipeline::new()
+            .add(IsSomeUnwrapToIfLet::default())
+            .run(self)
+    }
+
+    /// Inlines small private functions called from exactly one site in the file, and
+    /// removes their definitions.
+    pub fn inline_single_call_fns(self) -> Result<Self, PassError> {
+        Pipeline::new().add(InlineSingleCallFn::default()).run(self)
+    }
+
+    /// Replaces `x != 0 && (x & (x - 1)) == 0` power-of-two tests on unsigned integers
+    /// with `x.is_power_of_two()`.
+    pub fn introduce_is_power_of_two(self) -> Result<Self, PassError> {
+        Pipeline::new().add(PowerOfTwoIntro::default()).run(self)
+    }
+
+    /// Replaces index loops which only update each element of one slice in place with
+    /// `iter_mut()` loops.
+    pub fn replace_in_place_updates(self) -> Result<Self, PassError> {
+        Pipeline::new()
+            .add(InPlaceUpdateToIterMut::default())
+            .run(self)
+    }
+
+    /// Replaces transmutes of `[u8; N]` arrays into primitive numbers with the safe
+    /// `from_ne_bytes` constructors.
+    pub fn replace_byte_transmutes(self) -> Result<Self, PassError> {
+        Pipeline::new()
+            .add(TransmuteBytesToFromBytes::default())
+            .run(self)
+    }
+
+    /// Like [MonadicAst::replace_byte_transmutes], but assumes the given byte order
+    /// for the transmuted bytes, e.g. `from_le_bytes` for little-endian data.
+    pub fn replace_byte_transmutes_with(self, endianness: Endianness) -> Result<Self, PassError> {
+        Pipeline::new()
+            .add(TransmuteBytesToFromBytes::new(endianness))
+            .run(self)
+    }
+
+    /// Folds comparisons of a side-effect-free integer, `bool` or `char` expression with
+    /// itself, e.g. `x == x` into `true`.
+    pub fn fold_self_comparisons(self) -> Result<Self, PassError> {
+        Pipeline::new().add(SelfComparisonFold::default()).run(self)
+    }
+
+    /// Replaces `qsort` calls over the elements of a slice or array with `sort_by`,
+    /// adapting the C comparator to return an `Ordering`.
+    pub fn replace_qsort(self) -> Result<Self, PassError> {
+        Pipeline::new().add(QsortToSort::default()).run(self)
+    }
+
+    /// Replaces loops assigning the same constant to every element of a slice or array
+    /// with a call to `fill`.
+    pub fn replace_fill_loops(self) -> Result<Self, PassError> {
+        Pipeline::new().add(ZeroLoopToFill::default()).run(self)
+    }
+
+    /// Identifies `[c_char; N]` buffers used only as string storage. They are
+    /// reported, and rewritten into `String`s only if `PassConfig::rewrite_char_buffers`
+    /// is set.
+    pub fn replace_char_buffers(self) -> Result<Self, PassError> {
+        Pipeline::new().add(CharBufferToString::default()).run(self)
+    }
+
+    /// Simplifies negated conditions such as `!(x > 0)` into their positive form.
+    pub fn normalize_guard_polarity(self) -> Result<Self, PassError> {
+        Pipeline::new()
+            .add(NormalizeGuardPolarity::default())
+            .run(self)
+    }
+
+    /// Replaces explicit bounds checks guarding an indexing with a `let`-`else` over
+    /// `get`.
+    pub fn replace_bounds_checks(self) -> Result<Self, PassError> {
+        Pipeline::new().add(BoundsCheckToGet::default()).run(self)
+    }
+
+    /// Adds a `spin_loop` hint to empty `while` loops which busy-wait on a variable.
+    pub fn annotate_spin_loops(self) -> Result<Self, PassError> {
+        Pipeline::new().add(SpinLoopAnnotate::default()).run(self)
+    }
+
+    /// Replaces counter loops which test for their exit at the end of the body with `for`
+    /// loops, keeping the first iteration the body always runs.
+    pub fn replace_trailing_break_loops(self) -> Result<Self, PassError> {
+        Pipeline::new().add(TrailingBreakCounterToFor).run(self)
+    }
+
+    /// Replaces calls of functions returning their sole parameter unchanged with their
+    /// argument, and removes such functions once they are unused.
+    pub fn elide_identity_wrappers(self) -> Result<Self, PassError> {
+        Pipeline::new()
+            .add(IdentityWrapperElision::default())
+            .run(self)
+    }
+
+    /// Replaces `mem::replace` calls whose result is discarded with an assignment, when
+    /// the replaced value has no drop glue.
+    pub fn replace_mem_replace(self) -> Result<Self, PassError> {
+        Pipeline::new().add(ReplaceToAssign::default()).run(self)
+    }
+
+    /// Replaces a `match` over a `get` lookup falling back to a default with
+    /// `copied()`/`cloned()` and `unwrap_or`.
+    pub fn replace_map_lookups(self) -> Result<Self, PassError> {
+        Pipeline::new().add(MapLookupDefault).run(self)
+    }
+
+    /// Turns a `return` of an `if` or `match` expression ending a function into the
+    /// function's tail expression.
+    pub fn flatten_tail_returns(self) -> Result<Self, PassError> {
+        Pipeline::new().add(IfExprReturnFlatten).run(self)
+    }
+
+    /// Replaces emptiness tests such as `v.len() == 0` with `is_empty`.
+    pub fn introduce_is_empty(self) -> Result<Self, PassError> {
+        Pipeline::new().add(IsEmptyIntro::default()).run(self)
+    }
+
+    /// Replaces conditional negations computing an absolute value with `abs`.
+    pub fn introduce_abs(self) -> Result<Self, PassError> {
+        Pipeline::new().add(AbsIntro::default()).run(self)
+    }
+
+    /// Replaces index loops reading pairs of adjacent elements of a slice with loops over
+    /// `windows(2)`.
+    pub fn replace_pairwise_loops(self) -> Result<Self, PassError> {
+        Pipeline::new()
+            .add(PairwiseLoopToWindows::default())
+            .run(self)
+    }
+
+    /// Merges functions which are identical apart from their names.
+    pub fn deduplicate_functions(self) -> Result<Self, PassError> {
+        Pipeline::new()
+            .add(DeduplicateFunctions::default())
+            .run(self)
+    }
+
+    /// Replaces loops comparing a slice element by element with a prefix or suffix with
+    /// `starts_with` or `ends_with`.
+    pub fn replace_prefix_loops(self) -> Result<Self, PassError> {
+        Pipeline::new()
+            .add(PrefixLoopToStartsWith::default())
+            .run(self)
+    }
+
+    /// Identifies structs owning a pointer released by a `free_*` function. They are
+    /// reported, and given an `impl Drop` only if `PassConfig::generate_drop_impls` is set.
+    pub fn generate_drop_impls(self) -> Result<Self, PassError> {
+        Pipeline::new().add(FreeFnToDrop::default()).run(self)
+    }
+
+    /// Replaces an `if`/`else` assigning the same place in every branch with a single
+    /// assignment of a conditional expression, or merges it into the preceding `let`.
+    pub fn replace_branch_assignments(self) -> Result<Self, PassError> {
+        Pipeline::new().add(BranchAssignToCondExpr).run(self)
+    }
+
+    /// Replaces loops setting a flag when an element matches a condition with `any`, or
+    /// with `all` when the flag starts out set and is cleared.
+    pub fn replace_flag_loops(self) -> Result<Self, PassError> {
+        Pipeline::new().add(FlagLoopToAnyAll).run(self)
+    }
+
+    /// Replaces loops recording the index of the first matching element with
+    /// `position`, turning the `-1` sentinel into `None` where only it is tested for.
+    pub fn replace_find_loops(self) -> Result<Self, PassError> {
+        Pipeline::new().add(FindLoopToPosition::default()).run(self)
+    }
+
+    /// Replaces loops reading a slice backwards through the index `n - 1 - i` with a loop
+    /// over `iter().rev()`.
+    pub fn replace_reverse_index_loops(self) -> Result<Self, PassError> {
+        Pipeline::new()
+            .add(ReverseIndexToRevIter::default())
+            .run(self)
+    }
+
+    /// Removes a declaration's initial value when it is overwritten by the next statement,
+    /// moving the assigned value into the declaration.
+    pub fn remove_dead_inits(self) -> Result<Self, PassError> {
+        Pipeline::new().add(RemoveDeadInit).run(self)
+    }
+
+    /// Removes `match` expressions whose single arm returns its binding unchanged, or
+    /// ignores a scrutinee free of side effects.
+    pub fn elide_single_arm_matches(self) -> Result<Self, PassError> {
+        Pipeline::new().add(SingleArmMatchElision).run(self)
+    }
+
+    /// Promotes `static mut` integers which are only read, assigned and updated in place
+    /// into atomics accessed with `Ordering::Relaxed`.
+    pub fn promote_static_counters(self) -> Result<Self, PassError> {
+        Pipeline::new().add(StaticMutToAtomic::default()).run(self)
+    }
+
+    /// Like [MonadicAst::promote_static_counters], but accesses the atomics with the
+    /// given memory ordering.
+    pub fn promote_static_counters_with(self, ordering: AtomicOrdering) -> Result<Self, PassError> {
+        Pipeline::new()
+            .add(StaticMutToAtomic::new(ordering))
+            .run(self)
+    }
+
+    /// Replaces checks returning the error of a `Result`-returning call with the `?`
+    /// operator.
+    pub fn replace_error_checks(self) -> Result<Self, PassError> {
+        Pipeline::new().add(PropagateViaTry::default()).run(self)
+    }
+
+    /// Folds field assignments following `Box::new(T::default())` into a struct literal
+    /// with a `..Default::default()` rest.
+    pub fn replace_box_field_inits(self) -> Result<Self, PassError> {
+        Pipeline::new()
+            .add(BoxFieldInitToLiteral::default())
+            .run(self)
+    }
+
+    /// Rewrites `for` loops whose body is a single call into `for_each`, if
+    /// `PassConfig::rewrite_for_each_loops` is set.
+    pub fn replace_for_each_loops(self) -> Result<Self, PassError> {
+        Pipeline::new().add(ForToForEach::default()).run(self)
+    }
+
+    /// Removes integer operations leaving their operand unchanged, such as `x + 0` and
+    /// `x * 1`, and folds `x * 0` and `x & 0` to `0`.
+    pub fn fold_arithmetic_identities(self) -> Result<Self, PassError> {
+        Pipeline::new()
+            .add(ArithmeticIdentityFold::default())
+            .run(self)
+    }
+
+    /// Replaces private functions without parameters returning a constant expression
+    /// with `const` items, and their calls with the constant.
+    pub fn replace_const_fns(self) -> Result<Self, PassError> {
+        Pipeline::new().add(ConstFnToConst::default()).run(self)
+    }
+
+    /// Merges the inherent `impl` blocks of the same type, with the same generics and
+    /// attributes, into the first of them.
+    pub fn merge_impl_blocks(self) -> Result<Self, PassError> {
+        Pipeline::new().add(MergeImplBlocks).run(self)
+    }
+
+    /// Replaces conditionals limiting a value to a range with literal bounds with
+    /// `clamp`, and reports those with other bounds.
+    pub fn introduce_clamp(self) -> Result<Self, PassError> {
+        Pipeline::new().add(ClampIntro::default()).run(self)
+    }
+
+    /// Reports functions which likely implement a well-known hash or checksum, such as
+    /// FNV-1a or djb2, by their characteristic constants.
+    pub fn recognize_known_algorithms(self) -> Result<Self, PassError> {
+        Pipeline::new()
+            .add(RecognizeKnownAlgorithms::default())
+            .run(self)
+    }
+
+    /// Replaces loops pushing every byte of an ASCII buffer to a new `String` with
+    /// `String::from_utf8_lossy`.
+    pub fn replace_byte_string_loops(self) -> Result<Self, PassError> {
+        Pipeline::new().add(ByteLoopToFromUtf8::default()).run(self)
+    }
+
+    /// Removes `let _ =` from statements whose value is known to be `()`, keeping the
+    /// expression for its side effects.
+    pub fn remove_unit_let_bindings(self) -> Result<Self, PassError> {
+        Pipeline::new()
+            .add(RemoveUnitLetBinding::default())
+            .run(self)
+    }
+
+    /// Merges consecutive `match` arms with identical bodies and patterns binding no
+    /// variables into one arm with an or-pattern.
+    pub fn merge_fallthrough_arms(self) -> Result<Self, PassError> {
+        Pipeline::new().add(FallthroughToOrPattern).run(self)
+    }
+
+    /// Replaces pointers allocated by `malloc` or `calloc` with `Box`es and `Vec`s,
+    /// and their `free` with `drop`.
+    pub fn replace_allocations(self) -> Result<Self, PassError> {
+        Pipeline::new().add(AllocationReplacer).run(self)
+    }
+
+    /// Unwraps the `unsafe` blocks performing no unsafe operation, e.g. once their raw
+    /// pointers were replaced with references, and reports the `unsafe fn`s whose
+    /// `unsafe` qualifier could be dropped.
+    pub fn remove_redundant_unsafe(self) -> Result<Self, PassError> {
+        Pipeline::new()
+            .add(RedundantUnsafeRemover::default())
+            .run(self)
     }
 
-    pub fn remove_useless_identifier_expressions(self) -> Self {
-        IdentifierExpressionRemover::default().bind(self)
+    /// Replaces `memcpy`, `memmove` and `memset` calls between slices and arrays with
+    /// `copy_from_slice`, `clone_from_slice` and `fill`.
+    pub fn replace_memcpy_calls(self) -> Result<Self, PassError> {
+        Pipeline::new().add(MemcpyToSliceOp::default()).run(self)
     }
 }
 
@@ -59,7 +465,13 @@ impl From<File> for MonadicAst {
     /// Receives a `syn::File` AST and wraps it into a monadic value, where `M`  is
     /// a `MonadicAst` and `a` is a `syn::File` abstract syntax trees.
     fn from(ast: File) -> Self {
-        Self { ast }
+        Self {
+            ast,
+            diagnostics: Vec::new(),
+            config: PassConfig::default(),
+            stats: Stats::default(),
+            events: Vec::new(),
+        }
     }
 }
 
@@ -70,5 +482,5 @@ pub trait Pass {
     /// Receives a monadic AST wrapper `M a` and returns the result `M b` of applying the
     /// `bind()` method on the unwrapped AST `a`, where `M` is a `MonadicAst` and `a`,`b`
     /// are `syn::File` abstract syntax trees.
-    fn bind(&mut self, monad: MonadicAst) -> MonadicAst;
+    fn bind(&mut self, monad: MonadicAst) -> Result<MonadicAst, PassError>;
 }
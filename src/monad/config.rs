@@ -0,0 +1,169 @@
+use crate::monad::pass_name::PassName;
+use serde::de::{Deserialize, Deserializer, Error as _};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Options for passes whose rewrites are opt-in or tunable, carried by the monad
+/// alongside the AST so that each pass reads them when it is bound.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PassConfig {
+    /// Rewrite `[c_char; N]` buffers used as string storage into `String`s, rather
+    /// than only reporting them.
+    pub rewrite_char_buffers: bool,
+    /// Report busy-wait loops as candidates for a synchronization primitive, besides
+    /// hinting them with `spin_loop`.
+    pub report_spin_loops: bool,
+    /// Move the body of a struct's `free_*` function into an `impl Drop`, rather
+    /// than only reporting it.
+    pub generate_drop_impls: bool,
+    /// Rewrite `for` loops whose body is a single call into `for_each`, rather than
+    /// keeping the loop.
+    pub rewrite_for_each_loops: bool,
+    /// Rewrite `x.max(lo).min(hi)` into `x.clamp(lo, hi)`, which differs for a NaN
+    /// float `x`.
+    pub rewrite_max_min_clamps: bool,
+    /// Functions whose bodies have more statements than this are never inlined at
+    /// their single call site.
+    pub max_inlined_statements: usize,
+    /// Types mapped by the FFI type conversion besides the C types it knows, e.g.
+    /// `my_size_t` to `usize`, keyed by their path as spelled in the source. They
+    /// take precedence over the built-in mappings.
+    pub ffi_types: BTreeMap<String, String>,
+}
+
+/// How many statements the body of a function inlined at its single call site may
+/// have by default.
+pub const DEFAULT_MAX_INLINED_STATEMENTS: usize = 3;
+
+impl Default for PassConfig {
+    fn default() -> Self {
+        Self {
+            rewrite_char_buffers: false,
+            report_spin_loops: false,
+            generate_drop_impls: false,
+            rewrite_for_each_loops: false,
+            rewrite_max_min_clamps: false,
+            max_inlined_statements: DEFAULT_MAX_INLINED_STATEMENTS,
+            ffi_types: BTreeMap::new(),
+        }
+    }
+}
+
+/// The name of the configuration file looked up in the input directory.
+pub const CONFIG_FILE_NAME: &str = "monadicast.toml";
+
+/// The contents of a [CONFIG_FILE_NAME] file, configuring a run of the command
+/// line tool so that it is repeatable:
+///
+/// ```toml
+/// passes = ["convert_ffi_types", "replace_raw_pointers"]
+/// skip = ["replace_raw_pointers"]
+/// fixpoint_iterations = 5
+/// rewrite_char_buffers = true
+/// max_inlined_statements = 5
+///
+/// [ffi.types]
+/// my_size_t = "usize"
+/// ```
+///
+/// Every key is optional, and unknown keys are rejected.
+///
+/// ```
+/// use monadicast::{Config, PassName};
+///
+/// let config = Config::from_toml("skip = [\"replace_qsort\"]\n[ffi.types]\nmy_size_t = \"usize\"").unwrap();
+/// assert_eq!(config.skip, [PassName::ReplaceQsort]);
+/// assert_eq!(config.pass_config().ffi_types["my_size_t"], "usize");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// The passes to apply, still in pipeline order, or all of them if unset.
+    pub passes: Option<Vec<PassName>>,
+    /// The passes not to apply.
+    pub skip: Vec<PassName>,
+    /// If set, the passes are applied repeatedly until the output stops changing,
+    /// at most this many times, rather than once.
+    pub fixpoint_iterations: Option<usize>,
+    /// See [PassConfig::rewrite_char_buffers].
+    pub rewrite_char_buffers: bool,
+    /// See [PassConfig::report_spin_loops].
+    pub report_spin_loops: bool,
+    /// See [PassConfig::generate_drop_impls].
+    pub generate_drop_impls: bool,
+    /// See [PassConfig::rewrite_for_each_loops].
+    pub rewrite_for_each_loops: bool,
+    /// See [PassConfig::rewrite_max_min_clamps].
+    pub rewrite_max_min_clamps: bool,
+    /// See [PassConfig::max_inlined_statements].
+    pub max_inlined_statements: Option<usize>,
+    /// Options of the FFI type conversion.
+    pub ffi: FfiConfig,
+}
+
+/// The `[ffi]` table of a [Config].
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FfiConfig {
+    /// The `[ffi.types]` table, see [PassConfig::ffi_types].
+    pub types: BTreeMap<String, String>,
+}
+
+impl Config {
+    /// Parses the TOML contents of a configuration file, failing on a malformed one,
+    /// an unknown pass name or a mapped type that is not a Rust type.
+    pub fn from_toml(source: &str) -> Result<Self, toml::de::Error> {
+        let config: Self = toml::from_str(source)?;
+        for (from, to) in &config.ffi.types {
+            if syn::parse_str::<syn::Type>(to).is_err() {
+                return Err(toml::de::Error::custom(format!(
+                    "`{to}`, which `{from}` is mapped to, is not a Rust type"
+                )));
+            }
+        }
+        Ok(config)
+    }
+
+    /// Reads the configuration file at `path`. Returns a message naming the file if
+    /// it could not be read or parsed.
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let source =
+            fs::read_to_string(path).map_err(|error| format!("{}: {error}", path.display()))?;
+        Self::from_toml(&source).map_err(|error| format!("{}: {error}", path.display()))
+    }
+
+    /// Reads the [CONFIG_FILE_NAME] file in `directory`, if there is one, as
+    /// [Config::read] does.
+    pub fn find(directory: &Path) -> Result<Option<Self>, String> {
+        let path = directory.join(CONFIG_FILE_NAME);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        Self::read(&path).map(Some)
+    }
+
+    /// Returns the options of the passes set by the configuration.
+    pub fn pass_config(&self) -> PassConfig {
+        PassConfig {
+            rewrite_char_buffers: self.rewrite_char_buffers,
+            report_spin_loops: self.report_spin_loops,
+            generate_drop_impls: self.generate_drop_impls,
+            rewrite_for_each_loops: self.rewrite_for_each_loops,
+            rewrite_max_min_clamps: self.rewrite_max_min_clamps,
+            max_inlined_statements: self
+                .max_inlined_statements
+                .unwrap_or(DEFAULT_MAX_INLINED_STATEMENTS),
+            ffi_types: self.ffi.types.clone(),
+        }
+    }
+}
+
+/// Pass names are deserialized from their snake case names, as on the command line.
+impl<'de> Deserialize<'de> for PassName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,33 @@
+use std::fmt;
+use syn::Ident;
+
+/// A note emitted by a pass about code it recognized but could not (or chose not
+/// to) rewrite, so a human can follow up on it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The name of the pass that emitted the note.
+    pub pass: &'static str,
+    /// The name of the function the note is about, if any.
+    pub function: Option<String>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn new(pass: &'static str, function: Option<&Ident>, message: impl Into<String>) -> Self {
+        Self {
+            pass,
+            function: function.map(Ident::to_string),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] ", self.pass)?;
+        if let Some(function) = &self.function {
+            write!(f, "in `fn {}`: ", function)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
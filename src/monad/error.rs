@@ -0,0 +1,60 @@
+use std::fmt;
+use std::path::Path;
+
+/// The reason a pass could not be applied to an AST.
+#[derive(Debug)]
+pub enum PassError {
+    /// The AST holds code the pass recognized but cannot handle.
+    Unsupported(String),
+    /// Code built or read by the pass failed to parse.
+    ParseFailure(syn::Error),
+}
+
+impl fmt::Display for PassError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PassError::Unsupported(message) => write!(f, "unsupported: {message}"),
+            PassError::ParseFailure(error) => write!(f, "parse failure: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for PassError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PassError::Unsupported(_) => None,
+            PassError::ParseFailure(error) => Some(error),
+        }
+    }
+}
+
+impl From<syn::Error> for PassError {
+    fn from(error: syn::Error) -> Self {
+        PassError::ParseFailure(error)
+    }
+}
+
+/// Formats a failure to parse the source read from `source_name` as
+/// `<source_name>:<line>:<column>: <message>`, with the 1-based position at which
+/// the error's span starts.
+///
+/// ```
+/// use monadicast::{format_parse_error, MonadicAst};
+/// use std::path::Path;
+///
+/// let error = MonadicAst::new("fn f() {\n    let x = 1\n}").err().unwrap();
+/// assert_eq!(
+///     format_parse_error(Path::new("f.rs"), &error),
+///     "f.rs:3:1: expected `;`"
+/// );
+/// ```
+pub fn format_parse_error(source_name: &Path, error: &syn::Error) -> String {
+    let start = error.span().start();
+    format!(
+        "{}:{}:{}: {}",
+        source_name.display(),
+        start.line,
+        start.column + 1,
+        error
+    )
+}
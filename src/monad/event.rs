@@ -0,0 +1,54 @@
+use proc_macro2::Span;
+use serde::Serialize;
+use syn::{parse_quote, File, Type};
+
+/// A rewrite made by a pass, recorded where it fired so that tooling can track the
+/// progress of a migration.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Event {
+    /// What the rewrite was, e.g. `while_to_for` or `ptr_to_ref`.
+    pub kind: &'static str,
+    /// The 1-based line and column in the source at which the rewritten code
+    /// started.
+    pub line: usize,
+    pub column: usize,
+    /// For a rewritten type, the type before and after the rewrite.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+}
+
+impl Event {
+    /// Returns a `kind` of rewrite of the code starting at `span`.
+    pub fn new(kind: &'static str, span: Span) -> Self {
+        let start = span.start();
+        Self {
+            kind,
+            line: start.line,
+            column: start.column + 1,
+            before: None,
+            after: None,
+        }
+    }
+
+    /// Returns the event with the type it rewrote, from `before` to `after`.
+    pub fn with_types(self, before: &Type, after: &Type) -> Self {
+        Self {
+            before: Some(type_string(before)),
+            after: Some(type_string(after)),
+            ..self
+        }
+    }
+}
+
+/// Returns the type as formatted in source code, e.g. `*mut i32`.
+fn type_string(ty: &Type) -> String {
+    let file: File = parse_quote!(type T = #ty;);
+    let printed = prettyplease::unparse(&file);
+    printed
+        .trim()
+        .trim_start_matches("type T = ")
+        .trim_end_matches(';')
+        .to_string()
+}
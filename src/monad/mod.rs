@@ -1,2 +1,16 @@
 pub mod ast;
-pub use ast::MonadicAst;
+pub mod config;
+pub mod diagnostic;
+pub mod error;
+pub mod event;
+pub mod pass_name;
+pub mod pipeline;
+pub mod stats;
+pub use ast::{MonadicAst, Pass};
+pub use config::{Config, FfiConfig, PassConfig, CONFIG_FILE_NAME, DEFAULT_MAX_INLINED_STATEMENTS};
+pub use diagnostic::Diagnostic;
+pub use error::{format_parse_error, PassError};
+pub use event::Event;
+pub use pass_name::{PassName, UnknownPassName, DEFAULT_FIXPOINT_ITERATIONS};
+pub use pipeline::Pipeline;
+pub use stats::Stats;
@@ -0,0 +1,179 @@
+use crate::monad::ast::MonadicAst;
+use crate::monad::diagnostic::Diagnostic;
+use crate::monad::error::PassError;
+use crate::monad::pipeline::Pipeline;
+use std::fmt;
+use std::str::FromStr;
+
+/// Declares [PassName] with a variant per pass, in the order the default pipeline
+/// applies them, named after the [MonadicAst] method applying the pass.
+macro_rules! pass_names {
+    ($($variant:ident => $method:ident,)*) => {
+        /// A pass of the default pipeline, for choosing which passes to apply.
+        #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+        pub enum PassName {
+            $($variant,)*
+        }
+
+        impl PassName {
+            /// Every pass, in the order the default pipeline applies them.
+            pub const ALL: &'static [PassName] = &[$(PassName::$variant,)*];
+
+            /// Returns the name of the pass, that of the method applying it.
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(PassName::$variant => stringify!($method),)*
+                }
+            }
+        }
+
+        impl MonadicAst {
+            /// Applies the given pass to the AST.
+            pub fn apply(self, pass: PassName) -> Result<Self, PassError> {
+                match pass {
+                    $(PassName::$variant => self.$method(),)*
+                }
+            }
+        }
+    };
+}
+
+pass_names! {
+    ConvertFfiTypes => convert_ffi_types,
+    ReplaceAllocations => replace_allocations,
+    ReplaceRawPointers => replace_raw_pointers,
+    ReplaceWhileLoop => replace_while_loop,
+    RemoveUselessIdentifierExpressions => remove_useless_identifier_expressions,
+    RemoveUnitLetBindings => remove_unit_let_bindings,
+    FoldArithmeticIdentities => fold_arithmetic_identities,
+    SimplifyStringBuilding => simplify_string_building,
+    ReplaceByteStringLoops => replace_byte_string_loops,
+    ReplaceIsSomeUnwrap => replace_is_some_unwrap,
+    RecognizeKnownAlgorithms => recognize_known_algorithms,
+    DeduplicateFunctions => deduplicate_functions,
+    GenerateDropImpls => generate_drop_impls,
+    ReplaceConstFns => replace_const_fns,
+    InlineSingleCallFns => inline_single_call_fns,
+    IntroduceIsPowerOfTwo => introduce_is_power_of_two,
+    ReplaceFillLoops => replace_fill_loops,
+    ReplaceMemcpyCalls => replace_memcpy_calls,
+    ReplaceInPlaceUpdates => replace_in_place_updates,
+    ReplaceByteTransmutes => replace_byte_transmutes,
+    FoldSelfComparisons => fold_self_comparisons,
+    ReplaceQsort => replace_qsort,
+    ReplaceCharBuffers => replace_char_buffers,
+    ReplaceFlagLoops => replace_flag_loops,
+    NormalizeGuardPolarity => normalize_guard_polarity,
+    ReplaceBoundsChecks => replace_bounds_checks,
+    AnnotateSpinLoops => annotate_spin_loops,
+    ReplaceTrailingBreakLoops => replace_trailing_break_loops,
+    ElideIdentityWrappers => elide_identity_wrappers,
+    ReplaceMemReplace => replace_mem_replace,
+    ReplaceMapLookups => replace_map_lookups,
+    FlattenTailReturns => flatten_tail_returns,
+    IntroduceIsEmpty => introduce_is_empty,
+    IntroduceAbs => introduce_abs,
+    IntroduceClamp => introduce_clamp,
+    ReplacePairwiseLoops => replace_pairwise_loops,
+    ReplacePrefixLoops => replace_prefix_loops,
+    ReplaceBranchAssignments => replace_branch_assignments,
+    ReplaceFindLoops => replace_find_loops,
+    ReplaceReverseIndexLoops => replace_reverse_index_loops,
+    RemoveDeadInits => remove_dead_inits,
+    ElideSingleArmMatches => elide_single_arm_matches,
+    PromoteStaticCounters => promote_static_counters,
+    ReplaceErrorChecks => replace_error_checks,
+    ReplaceBoxFieldInits => replace_box_field_inits,
+    ReplaceForEachLoops => replace_for_each_loops,
+    MergeImplBlocks => merge_impl_blocks,
+    MergeFallthroughArms => merge_fallthrough_arms,
+    RemoveRedundantUnsafe => remove_redundant_unsafe,
+}
+
+/// How many times [MonadicAst::run_to_fixpoint] applies the passes at most.
+pub const DEFAULT_FIXPOINT_ITERATIONS: usize = 10;
+
+impl MonadicAst {
+    /// Applies the given passes to the AST, in order, stopping at the first that
+    /// fails.
+    pub fn apply_all(self, passes: &[PassName]) -> Result<Self, PassError> {
+        passes.iter().copied().collect::<Pipeline>().run(self)
+    }
+
+    /// Applies the given passes repeatedly until the formatted AST stops changing,
+    /// since a pass may enable another that ran before it, at most
+    /// [DEFAULT_FIXPOINT_ITERATIONS] times. Returns how many times they were applied.
+    pub fn run_to_fixpoint(&mut self, passes: &[PassName]) -> Result<usize, (PassName, PassError)> {
+        self.run_to_fixpoint_with(passes, DEFAULT_FIXPOINT_ITERATIONS)
+    }
+
+    /// Applies the given passes repeatedly until the formatted AST stops changing,
+    /// at most `max_iterations` times. Returns how many times they were applied.
+    ///
+    /// A diagnostic emitted again by a later iteration is only kept once. If a pass
+    /// fails, it is returned with its error and the monad is left as the iteration
+    /// found it.
+    pub fn run_to_fixpoint_with(
+        &mut self,
+        passes: &[PassName],
+        max_iterations: usize,
+    ) -> Result<usize, (PassName, PassError)> {
+        let mut previous = self.result();
+        for iteration in 1..=max_iterations {
+            // The passes consume the monad, so they run on a copy, which replaces it
+            // only once they all succeed.
+            let mut monad = self.clone();
+            for &pass in passes {
+                monad = monad.apply(pass).map_err(|error| (pass, error))?;
+            }
+            *self = monad;
+            let mut diagnostics: Vec<Diagnostic> = Vec::new();
+            for diagnostic in self.diagnostics.drain(..) {
+                if !diagnostics.contains(&diagnostic) {
+                    diagnostics.push(diagnostic);
+                }
+            }
+            self.diagnostics = diagnostics;
+            let current = self.result();
+            if current == previous {
+                return Ok(iteration);
+            }
+            previous = current;
+        }
+        Ok(max_iterations)
+    }
+}
+
+impl fmt::Display for PassName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The error returned when parsing a name that is not that of a pass.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownPassName(pub String);
+
+impl fmt::Display for UnknownPassName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown pass `{}`; valid passes are: ", self.0)?;
+        let names: Vec<&str> = PassName::ALL.iter().map(|pass| pass.name()).collect();
+        f.write_str(&names.join(", "))
+    }
+}
+
+impl std::error::Error for UnknownPassName {}
+
+impl FromStr for PassName {
+    type Err = UnknownPassName;
+
+    /// Parses the name of a pass, accepting `-` for `_`.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let normalized = name.replace('-', "_");
+        PassName::ALL
+            .iter()
+            .copied()
+            .find(|pass| pass.name() == normalized)
+            .ok_or_else(|| UnknownPassName(name.to_string()))
+    }
+}
@@ -0,0 +1,60 @@
+use crate::monad::ast::{MonadicAst, Pass};
+use crate::monad::error::PassError;
+use crate::monad::pass_name::PassName;
+
+/// An ordered sequence of passes, applied to a [MonadicAst] one after the other.
+///
+/// Passes can be added in any order, and the same pass more than once. Besides the
+/// passes of the default pipeline, named by [PassName], any type implementing
+/// [Pass] can be added.
+///
+/// ```
+/// use monadicast::{MonadicAst, PassName, Pipeline};
+///
+/// let source = "unsafe extern \"C\" fn zero(x: libc::c_int) -> libc::c_int { x * 1 + 0 }";
+/// let monad = Pipeline::new()
+///     .add(PassName::FoldArithmeticIdentities)
+///     .add(PassName::ConvertFfiTypes)
+///     .add(PassName::FoldArithmeticIdentities)
+///     .run(MonadicAst::new(source).unwrap())
+///     .unwrap();
+/// assert_eq!(monad.result(), "unsafe fn zero(x: i32) -> i32 {\n    x\n}\n");
+/// ```
+#[derive(Default)]
+pub struct Pipeline {
+    passes: Vec<Box<dyn Pass>>,
+}
+
+impl Pipeline {
+    /// Returns an empty pipeline, which leaves the AST unchanged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a pass, which is applied after those added before it.
+    // Not `std::ops::Add`, as a pass is not itself a pipeline.
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(mut self, pass: impl Pass + 'static) -> Self {
+        self.passes.push(Box::new(pass));
+        self
+    }
+
+    /// Applies the passes to the AST in order, stopping at the first that fails.
+    pub fn run(self, monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.passes
+            .into_iter()
+            .try_fold(monad, |monad, mut pass| pass.bind(monad))
+    }
+}
+
+impl<P: Pass + 'static> FromIterator<P> for Pipeline {
+    fn from_iter<I: IntoIterator<Item = P>>(passes: I) -> Self {
+        passes.into_iter().fold(Self::new(), Self::add)
+    }
+}
+
+impl Pass for PassName {
+    fn bind(&mut self, monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        monad.apply(*self)
+    }
+}
@@ -0,0 +1,32 @@
+use std::fmt;
+use std::ops::AddAssign;
+
+/// Counts of the rewrites made by the passes applied so far, for measuring how
+/// often each transformation applies across a corpus.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// C FFI types replaced with their Rust equivalents.
+    pub ffi_types_converted: usize,
+    /// Raw pointer declarations given a safe type.
+    pub raw_pointers_rewritten: usize,
+    /// While loops replaced with for loops.
+    pub while_loops_converted: usize,
+}
+
+impl AddAssign<&Stats> for Stats {
+    fn add_assign(&mut self, other: &Stats) {
+        self.ffi_types_converted += other.ffi_types_converted;
+        self.raw_pointers_rewritten += other.raw_pointers_rewritten;
+        self.while_loops_converted += other.while_loops_converted;
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} FFI type(s) converted, {} raw pointer(s) rewritten, {} while loop(s) converted",
+            self.ffi_types_converted, self.raw_pointers_rewritten, self.while_loops_converted
+        )
+    }
+}
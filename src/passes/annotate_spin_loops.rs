@@ -0,0 +1,89 @@
+//! Adds a `std::hint::spin_loop()` call to the empty body of a `while` loop which
+//! busy-waits on a variable, e.g. `while flag.load(Ordering::Acquire) == 0 {}`.
+//!
+//! Loops with a non-empty body are left alone. When
+//! [PassConfig::report_spin_loops] is set, each such loop is also reported, since
+//! waiting on a `Condvar` or a channel is usually better than spinning.
+//!
+//! [PassConfig::report_spin_loops]: crate::PassConfig::report_spin_loops
+
+use crate::monad::ast::Pass;
+use crate::monad::diagnostic::Diagnostic;
+use crate::monad::error::PassError;
+use crate::MonadicAst;
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::{parse_quote, Expr, ExprCall, ExprPath, ExprWhile, Ident, ImplItemFn, ItemFn};
+
+const PASS: &str = "annotate_spin_loops";
+
+#[derive(Default)]
+pub struct SpinLoopAnnotate {
+    report: bool,
+    /// Name of the function currently being visited, for diagnostics.
+    current_fn: Option<Ident>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Finds whether an expression reads a variable, as opposed to only calling
+/// functions on constants.
+#[derive(Default)]
+struct ReadsVariable(bool);
+
+impl Visit<'_> for ReadsVariable {
+    fn visit_expr_path(&mut self, _: &ExprPath) {
+        self.0 = true;
+    }
+
+    fn visit_expr_call(&mut self, call: &ExprCall) {
+        // The called function itself is not a polled variable.
+        call.args.iter().for_each(|arg| self.visit_expr(arg));
+    }
+}
+
+impl VisitMut for SpinLoopAnnotate {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        let enclosing = self.current_fn.replace(item_fn.sig.ident.clone());
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        self.current_fn = enclosing;
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        let enclosing = self.current_fn.replace(item_fn.sig.ident.clone());
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+        self.current_fn = enclosing;
+    }
+
+    fn visit_expr_while_mut(&mut self, while_loop: &mut ExprWhile) {
+        syn::visit_mut::visit_expr_while_mut(self, while_loop);
+        if !while_loop.body.stmts.is_empty() || matches!(*while_loop.cond, Expr::Let(_)) {
+            return;
+        }
+        let mut reads = ReadsVariable::default();
+        reads.visit_expr(&while_loop.cond);
+        if !reads.0 {
+            return;
+        }
+        while_loop
+            .body
+            .stmts
+            .push(parse_quote!(std::hint::spin_loop();));
+        if self.report {
+            self.diagnostics.push(Diagnostic::new(
+                PASS,
+                self.current_fn.as_ref(),
+                "`while` loop busy-waits for its condition; consider a `Condvar` or a \
+                 channel",
+            ));
+        }
+    }
+}
+
+impl Pass for SpinLoopAnnotate {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.report = monad.config.report_spin_loops;
+        self.visit_file_mut(&mut monad.ast);
+        monad.diagnostics.append(&mut self.diagnostics);
+        Ok(monad)
+    }
+}
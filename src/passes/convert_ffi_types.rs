@@ -1,26 +1,58 @@
 use crate::monad::ast::{MonadicAst, Pass};
+use crate::monad::error::PassError;
+use crate::monad::event::Event;
 use quote::ToTokens;
 use std::collections::HashMap;
+use syn::spanned::Spanned;
 use syn::{visit_mut::VisitMut, Signature, Type};
 
-static RULES: &[(&str, &str)] = &[
-    ("libc::c_int", "i32"),
-    ("libc::c_uint", "u32"),
-    ("libc::c_void", "()"),
-    ("libc::c_char", "i8"),
-    ("libc::c_uchar", "u8"),
-    ("libc::c_long", "i64"),
-    ("libc::c_ulong", "u64"),
+/// C integer, floating point and character types, and the Rust primitives they are
+/// on the LP64 platforms C code is translated on.
+static C_TYPES: &[(&str, &str)] = &[
+    ("c_char", "i8"),
+    ("c_schar", "i8"),
+    ("c_uchar", "u8"),
+    ("c_short", "i16"),
+    ("c_ushort", "u16"),
+    ("c_int", "i32"),
+    ("c_uint", "u32"),
+    ("c_long", "i64"),
+    ("c_ulong", "u64"),
+    ("c_longlong", "i64"),
+    ("c_ulonglong", "u64"),
+    ("c_float", "f32"),
+    ("c_double", "f64"),
 ];
 
+/// The paths the C types are spelled with, e.g. `c_int`, `libc::c_int` and
+/// `std::os::raw::c_int`.
+static C_TYPE_PATHS: &[&str] = &["", "libc::", "std::os::raw::", "core::ffi::", "std::ffi::"];
+
+/// Other types, replaced only when spelled exactly so.
+static RULES: &[(&str, &str)] = &[("libc::c_void", "()")];
+
 pub struct TypeReplacer {
-    rules: HashMap<&'static str, &'static str>,
+    /// The type each type path is replaced with.
+    rules: HashMap<String, String>,
+    /// How many types were replaced.
+    converted: usize,
+    events: Vec<Event>,
 }
 
 impl TypeReplacer {
     pub fn new() -> Self {
+        let c_types = C_TYPES.iter().flat_map(|&(c_type, rust_type)| {
+            C_TYPE_PATHS
+                .iter()
+                .map(move |path| (format!("{path}{c_type}"), rust_type.to_string()))
+        });
+        let rules = RULES
+            .iter()
+            .map(|&(from, to)| (from.to_string(), to.to_string()));
         Self {
-            rules: RULES.iter().cloned().collect(),
+            rules: c_types.chain(rules).collect(),
+            converted: 0,
+            events: Vec::new(),
         }
     }
 }
@@ -38,9 +70,13 @@ impl VisitMut for TypeReplacer {
     fn visit_type_mut(&mut self, ty: &mut Type) {
         if let Type::Path(type_path) = ty {
             let type_string = type_path.to_token_stream().to_string().replace(" ", "");
-            if let Some(to) = self.rules.get(type_string.as_str()) {
+            // ::std::os::raw::c_int
+            if let Some(to) = self.rules.get(type_string.trim_start_matches("::")) {
                 if let Ok(new_type) = syn::parse_str::<Type>(to) {
+                    self.events
+                        .push(Event::new("ffi_type", ty.span()).with_types(ty, &new_type));
                     *ty = new_type;
+                    self.converted += 1;
                     return;
                 }
             }
@@ -50,8 +86,41 @@ impl VisitMut for TypeReplacer {
 }
 
 impl Pass for TypeReplacer {
-    fn bind(&mut self, mut monad: MonadicAst) -> MonadicAst {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        // Types mapped by the configuration, e.g. project-specific typedefs
+        self.rules.extend(monad.config.ffi_types.clone());
+        self.converted = 0;
         self.visit_file_mut(&mut monad.ast);
-        monad
+        monad.stats.ffi_types_converted += self.converted;
+        monad.events.append(&mut self.events);
+        Ok(monad)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_every_c_type_in_every_spelling() {
+        for &(c_type, rust_type) in C_TYPES {
+            for spelling in [
+                c_type.to_string(),
+                format!("libc::{c_type}"),
+                format!("std::os::raw::{c_type}"),
+            ] {
+                let source = format!("pub fn f(x: {spelling}) {{}}");
+                let monad = MonadicAst::new(&source)
+                    .unwrap()
+                    .convert_ffi_types()
+                    .unwrap();
+                assert_eq!(monad.stats.ffi_types_converted, 1, "{spelling}");
+                assert_eq!(
+                    monad.result(),
+                    format!("pub fn f(x: {rust_type}) {{}}\n"),
+                    "{spelling}"
+                );
+            }
+        }
     }
 }
@@ -0,0 +1,84 @@
+//! Merges functions which are identical apart from their names, as produced by
+//! translating code generated by C macros. The first of each group of identical
+//! functions is kept, and references to the others are renamed to it.
+//!
+//! Candidates are grouped by a hash of the function with its name and doc comments
+//! left out, and then compared in full. Any other attribute must be the same. A
+//! duplicate which is public or exported, e.g. with `#[no_mangle]`, is kept as it
+//! is, since code outside of the file may refer to it.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{count_ident, is_exported};
+use crate::MonadicAst;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use syn::visit_mut::VisitMut;
+use syn::{ExprPath, Ident, Item, ItemFn};
+
+#[derive(Default)]
+pub struct DeduplicateFunctions {
+    /// The function kept in place of each removed duplicate.
+    canonical: HashMap<Ident, Ident>,
+}
+
+/// Returns the function with the parts which do not affect its behavior removed.
+fn normalized(item_fn: &ItemFn) -> ItemFn {
+    let mut item_fn = item_fn.clone();
+    item_fn.sig.ident = quote::format_ident!("_");
+    item_fn.attrs.retain(|attr| !attr.path().is_ident("doc"));
+    item_fn
+}
+
+impl VisitMut for DeduplicateFunctions {
+    fn visit_expr_path_mut(&mut self, expr: &mut ExprPath) {
+        if expr.qself.is_none() && expr.path.segments.len() == 1 {
+            let segment = &mut expr.path.segments[0];
+            if let Some(canonical) = self.canonical.get(&segment.ident) {
+                segment.ident = canonical.clone();
+            }
+        }
+        syn::visit_mut::visit_expr_path_mut(self, expr);
+    }
+}
+
+impl Pass for DeduplicateFunctions {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        // Functions with the same hash, in the order they are defined.
+        let mut candidates: HashMap<u64, Vec<(ItemFn, Ident)>> = HashMap::new();
+        for item in &monad.ast.items {
+            let Item::Fn(item_fn) = item else {
+                continue;
+            };
+            let normalized = normalized(item_fn);
+            let mut hasher = DefaultHasher::new();
+            normalized.hash(&mut hasher);
+            let group = candidates.entry(hasher.finish()).or_default();
+            let original = group
+                .iter()
+                .find(|(other, _)| *other == normalized)
+                .map(|(_, ident)| ident.clone());
+            match original {
+                Some(original) if !is_exported(item_fn) => {
+                    self.canonical.insert(item_fn.sig.ident.clone(), original);
+                }
+                Some(_) => {}
+                None => group.push((normalized, item_fn.sig.ident.clone())),
+            }
+        }
+        if self.canonical.is_empty() {
+            return Ok(monad);
+        }
+        self.visit_file_mut(&mut monad.ast);
+        // References which were not renamed, e.g. inside of macros, keep a duplicate.
+        let file = &monad.ast;
+        self.canonical
+            .retain(|duplicate, _| count_ident(file, duplicate) == 1);
+        monad.ast.items.retain(|item| match item {
+            Item::Fn(item_fn) => !self.canonical.contains_key(&item_fn.sig.ident),
+            _ => true,
+        });
+        Ok(monad)
+    }
+}
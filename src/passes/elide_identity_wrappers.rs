@@ -0,0 +1,95 @@
+//! Replaces calls of functions which return their sole parameter unchanged, such as
+//! `fn wrap(x: u32) -> u32 { x }`, with their argument, so `wrap(a + 1)` becomes
+//! `a + 1`.
+//!
+//! A wrapper which is neither public nor exported is removed once nothing in the
+//! file refers to it anymore.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{
+    count_ident, is_exported, is_ident, path_ident, same_tokens, Parenthesize,
+};
+use crate::MonadicAst;
+use std::collections::HashSet;
+use syn::visit_mut::VisitMut;
+use syn::{Expr, ExprCall, ExprReturn, FnArg, Ident, Item, ItemFn, Pat, PatType, ReturnType, Stmt};
+
+#[derive(Default)]
+pub struct IdentityWrapperElision {
+    /// Names of the identity functions defined in the file.
+    wrappers: HashSet<Ident>,
+}
+
+/// Returns whether `item_fn` only returns its sole parameter.
+fn is_identity(item_fn: &ItemFn) -> bool {
+    let sig = &item_fn.sig;
+    let [FnArg::Typed(PatType { pat, ty, .. })] = sig.inputs.iter().collect::<Vec<_>>()[..] else {
+        return false;
+    };
+    let Pat::Ident(param) = pat.as_ref() else {
+        return false;
+    };
+    let ReturnType::Type(_, output) = &sig.output else {
+        return false;
+    };
+    let returned = match item_fn.block.stmts.as_slice() {
+        [Stmt::Expr(expr, None)] => expr,
+        [Stmt::Expr(
+            Expr::Return(ExprReturn {
+                expr: Some(expr), ..
+            }),
+            _,
+        )] => expr.as_ref(),
+        _ => return false,
+    };
+    param.by_ref.is_none()
+        && param.subpat.is_none()
+        && sig.variadic.is_none()
+        && same_tokens(ty, output)
+        && is_ident(returned, &param.ident)
+}
+
+impl VisitMut for IdentityWrapperElision {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        syn::visit_mut::visit_expr_mut(self, expr);
+        if let Expr::Call(ExprCall { func, args, .. }) = expr {
+            let is_wrapper = path_ident(func).is_some_and(|ident| self.wrappers.contains(ident));
+            if is_wrapper && args.len() == 1 {
+                *expr = args[0].clone();
+            }
+        }
+    }
+}
+
+impl Pass for IdentityWrapperElision {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.wrappers = monad
+            .ast
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Fn(item_fn) if is_identity(item_fn) => Some(item_fn.sig.ident.clone()),
+                _ => None,
+            })
+            .collect();
+        if self.wrappers.is_empty() {
+            return Ok(monad);
+        }
+        self.visit_file_mut(&mut monad.ast);
+        Parenthesize.visit_file_mut(&mut monad.ast);
+
+        // Only the definition itself may still mention a removable wrapper.
+        let unused: HashSet<Ident> = self
+            .wrappers
+            .iter()
+            .filter(|wrapper| count_ident(&monad.ast, wrapper) == 1)
+            .cloned()
+            .collect();
+        monad.ast.items.retain(|item| match item {
+            Item::Fn(item_fn) => !unused.contains(&item_fn.sig.ident) || is_exported(item_fn),
+            _ => true,
+        });
+        Ok(monad)
+    }
+}
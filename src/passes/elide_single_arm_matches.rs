@@ -0,0 +1,109 @@
+//! Removes `match` expressions with a single arm which does not inspect the
+//! scrutinee. `match x { v => v }` becomes `x`, and `match x { _ => body }`
+//! becomes `body` when `x` is free of side effects, since a wildcard neither
+//! binds nor moves it.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{is_ident, is_pure, Parenthesize};
+use crate::MonadicAst;
+use syn::visit_mut::VisitMut;
+use syn::{Arm, Block, Expr, ExprBlock, ExprMatch, Pat, PatIdent, Stmt};
+
+#[derive(Default)]
+pub struct SingleArmMatchElision;
+
+/// Returns the expression a block evaluates to if it only consists of it.
+fn block_value(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Block(ExprBlock {
+            attrs,
+            label: None,
+            block: Block { stmts, .. },
+        }) if attrs.is_empty() => match stmts.as_slice() {
+            [Stmt::Expr(value, None)] => block_value(value),
+            _ => expr,
+        },
+        expr => expr,
+    }
+}
+
+/// Returns the expression replacing the `match`, if it has a single arm which
+/// returns its binding or ignores the scrutinee.
+fn replacement(expr_match: &ExprMatch) -> Option<Expr> {
+    let [Arm {
+        attrs,
+        pat,
+        guard: None,
+        body,
+        ..
+    }] = expr_match.arms.as_slice()
+    else {
+        return None;
+    };
+    if !attrs.is_empty() || !expr_match.attrs.is_empty() {
+        return None;
+    }
+    match pat {
+        Pat::Ident(PatIdent {
+            attrs,
+            by_ref: None,
+            subpat: None,
+            ident,
+            ..
+        }) if attrs.is_empty() && is_ident(block_value(body), ident) => {
+            Some(expr_match.expr.as_ref().clone())
+        }
+        Pat::Wild(_) if is_pure(&expr_match.expr) => Some(body.as_ref().clone()),
+        _ => None,
+    }
+}
+
+/// Returns whether the expression can be a statement without a semicolon.
+fn is_block_like(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::Block(_)
+            | Expr::If(_)
+            | Expr::Match(_)
+            | Expr::Loop(_)
+            | Expr::While(_)
+            | Expr::ForLoop(_)
+            | Expr::Unsafe(_)
+            | Expr::Const(_)
+            | Expr::TryBlock(_)
+    )
+}
+
+impl VisitMut for SingleArmMatchElision {
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        syn::visit_mut::visit_block_mut(self, block);
+        // A `match` statement replaced by its arm's expression needs a semicolon.
+        let last = block.stmts.len().saturating_sub(1);
+        for stmt in &mut block.stmts[..last] {
+            if let Stmt::Expr(expr, semi @ None) = stmt {
+                if !is_block_like(expr) {
+                    *semi = Some(Default::default());
+                }
+            }
+        }
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        syn::visit_mut::visit_expr_mut(self, expr);
+        if let Expr::Match(expr_match) = expr {
+            if let Some(replacement) = replacement(expr_match) {
+                *expr = replacement;
+            }
+        }
+    }
+}
+
+impl Pass for SingleArmMatchElision {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        // The scrutinee or the arm may bind more loosely than the `match` did.
+        Parenthesize.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
@@ -0,0 +1,61 @@
+//! Removes the `return` around an `if` or `match` expression which is the last
+//! statement of a function, e.g. `return if c { a } else { b };` becomes the tail
+//! expression `if c { a } else { b }`.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::strip_parens;
+use crate::MonadicAst;
+use syn::visit_mut::VisitMut;
+use syn::{Block, Expr, ExprIf, ExprReturn, ImplItemFn, ItemFn, Stmt};
+
+#[derive(Default)]
+pub struct IfExprReturnFlatten;
+
+impl IfExprReturnFlatten {
+    fn flatten(body: &mut Block) {
+        let Some(last) = body.stmts.last_mut() else {
+            return;
+        };
+        let Stmt::Expr(
+            Expr::Return(ExprReturn {
+                expr: Some(returned),
+                ..
+            }),
+            _,
+        ) = last
+        else {
+            return;
+        };
+        let returned = strip_parens(returned);
+        let is_conditional = matches!(
+            returned,
+            Expr::If(ExprIf {
+                else_branch: Some(_),
+                ..
+            }) | Expr::Match(_)
+        );
+        if is_conditional {
+            *last = Stmt::Expr(returned.clone(), None);
+        }
+    }
+}
+
+impl VisitMut for IfExprReturnFlatten {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        Self::flatten(&mut item_fn.block);
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+        Self::flatten(&mut item_fn.block);
+    }
+}
+
+impl Pass for IfExprReturnFlatten {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
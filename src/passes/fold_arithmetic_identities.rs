@@ -0,0 +1,162 @@
+//! Removes integer operations which leave their operand unchanged, as left behind
+//! by translated macros: `x + 0`, `x - 0`, `x * 1`, `x / 1`, `x | 0`, `x ^ 0`,
+//! `x & !0`, `x << 0` and `x >> 0` become `x`, and `x * 0` and `x & 0` become `0`
+//! when `x` is free of side effects.
+//!
+//! Only integer operands are folded: the operand must be of a known integer type
+//! or itself an arithmetic expression, so that a reference operand does not turn a
+//! value into a borrow. Floats are left alone, since `-0.0 + 0.0` is `0.0` and
+//! `NaN * 0.0` is NaN. A literal zero replacing the expression keeps the operand's
+//! type as its suffix.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{
+    expr_type, is_int_lit, is_pure, is_signed_int, is_unsigned_int, local_types, primitive_name,
+    strip_parens, Parenthesize,
+};
+use crate::MonadicAst;
+use proc_macro2::Span;
+use std::collections::HashMap;
+use syn::visit_mut::VisitMut;
+use syn::{
+    BinOp, Expr, ExprBinary, ExprLit, ExprUnary, Ident, ImplItemFn, ItemFn, Lit, LitInt, Type, UnOp,
+};
+
+#[derive(Default)]
+pub struct ArithmeticIdentityFold {
+    /// Declared types of the locals of the function currently being visited.
+    types: HashMap<Ident, Type>,
+}
+
+/// Returns whether `expr` is `!0`, with all bits set.
+fn is_all_ones(expr: &Expr) -> bool {
+    matches!(strip_parens(expr), Expr::Unary(ExprUnary { op: UnOp::Not(_), expr, .. })
+        if is_int_lit(expr, 0))
+}
+
+/// Returns the suffix of an integer literal, if it has one.
+fn int_suffix(expr: &Expr) -> Option<String> {
+    match strip_parens(expr) {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(int), ..
+        }) if !int.suffix().is_empty() => Some(int.suffix().to_string()),
+        _ => None,
+    }
+}
+
+impl ArithmeticIdentityFold {
+    /// Returns the integer type of `expr`, if it is known.
+    fn int_type(&self, expr: &Expr) -> Option<String> {
+        expr_type(expr, &self.types)
+            .filter(|ty| is_signed_int(ty) || is_unsigned_int(ty))
+            .and_then(|ty| primitive_name(&ty))
+    }
+
+    /// Returns whether `expr` is an integer value, rather than a reference to one.
+    fn is_int_value(&self, expr: &Expr) -> bool {
+        match strip_parens(expr) {
+            Expr::Lit(ExprLit {
+                lit: Lit::Int(_), ..
+            }) => true,
+            Expr::Unary(ExprUnary {
+                op: UnOp::Neg(_) | UnOp::Not(_),
+                ..
+            }) => true,
+            Expr::Binary(ExprBinary { op, .. }) => matches!(
+                op,
+                BinOp::Add(_)
+                    | BinOp::Sub(_)
+                    | BinOp::Mul(_)
+                    | BinOp::Div(_)
+                    | BinOp::Rem(_)
+                    | BinOp::BitAnd(_)
+                    | BinOp::BitOr(_)
+                    | BinOp::BitXor(_)
+                    | BinOp::Shl(_)
+                    | BinOp::Shr(_)
+            ),
+            expr => self.int_type(expr).is_some(),
+        }
+    }
+
+    /// Returns the expression replacing `binary`, if it is an identity operation.
+    fn fold(&self, binary: &ExprBinary) -> Option<Expr> {
+        let (left, right) = (binary.left.as_ref(), binary.right.as_ref());
+        let zero = |expr: &Expr| is_int_lit(expr, 0);
+        let one = |expr: &Expr| is_int_lit(expr, 1);
+        // The operand which the operation leaves unchanged.
+        let kept = match binary.op {
+            BinOp::Add(_) | BinOp::BitOr(_) | BinOp::BitXor(_) if zero(right) => Some(left),
+            BinOp::Add(_) | BinOp::BitOr(_) | BinOp::BitXor(_) if zero(left) => Some(right),
+            BinOp::Sub(_) | BinOp::Shl(_) | BinOp::Shr(_) if zero(right) => Some(left),
+            BinOp::Mul(_) if one(right) => Some(left),
+            BinOp::Mul(_) if one(left) => Some(right),
+            BinOp::Div(_) if one(right) => Some(left),
+            BinOp::BitAnd(_) if is_all_ones(right) => Some(left),
+            BinOp::BitAnd(_) if is_all_ones(left) => Some(right),
+            _ => None,
+        };
+        if let Some(kept) = kept {
+            return self.is_int_value(kept).then(|| strip_parens(kept).clone());
+        }
+
+        // The operand absorbed by a zero.
+        let (operand, literal) = match binary.op {
+            BinOp::Mul(_) | BinOp::BitAnd(_) if zero(right) => (left, right),
+            BinOp::Mul(_) | BinOp::BitAnd(_) if zero(left) => (right, left),
+            _ => return None,
+        };
+        if !is_pure(operand) {
+            return None;
+        }
+        let suffix = int_suffix(literal).or_else(|| self.int_type(operand))?;
+        Some(Expr::Lit(ExprLit {
+            attrs: Vec::new(),
+            lit: Lit::Int(LitInt::new(&format!("0{suffix}"), Span::call_site())),
+        }))
+    }
+}
+
+impl VisitMut for ArithmeticIdentityFold {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        let parenthesized_binary =
+            matches!(expr, Expr::Paren(paren) if matches!(*paren.expr, Expr::Binary(_)));
+        syn::visit_mut::visit_expr_mut(self, expr);
+        match expr {
+            Expr::Binary(binary) => {
+                if let Some(folded) = self.fold(binary) {
+                    *expr = folded;
+                }
+            }
+            // The parentheses around an operation folded into a variable or a literal.
+            Expr::Paren(paren)
+                if parenthesized_binary && matches!(*paren.expr, Expr::Path(_) | Expr::Lit(_)) =>
+            {
+                *expr = *paren.expr.clone();
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Pass for ArithmeticIdentityFold {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        // The kept operand may bind more loosely than the operation did.
+        Parenthesize.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
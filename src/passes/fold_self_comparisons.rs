@@ -0,0 +1,95 @@
+//! Folds comparisons of a side-effect-free expression with itself, such as
+//! `x == x` (always `true`) and `x != x` (always `false`), which are left behind by
+//! macro expansion in translated code.
+//!
+//! Only operands of a known integer, `bool` or `char` type are folded. For floats
+//! these comparisons depend on whether `x` is NaN, so they are left alone with a
+//! diagnostic suggesting `is_nan()` instead.
+
+use crate::monad::ast::Pass;
+use crate::monad::diagnostic::Diagnostic;
+use crate::monad::error::PassError;
+use crate::passes::utils::{
+    expr_type, is_float, is_pure, is_totally_ordered, local_types, same_tokens,
+};
+use crate::MonadicAst;
+use quote::ToTokens;
+use std::collections::HashMap;
+use syn::visit_mut::VisitMut;
+use syn::{parse_quote, BinOp, Expr, ExprBinary, Ident, ImplItemFn, ItemFn, Type};
+
+const PASS: &str = "fold_self_comparisons";
+
+#[derive(Default)]
+pub struct SelfComparisonFold {
+    /// Declared types of the locals of the function currently being visited.
+    types: HashMap<Ident, Type>,
+    current_fn: Option<Ident>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl SelfComparisonFold {
+    fn fold(&mut self, expr: &mut Expr) {
+        let Expr::Binary(ExprBinary {
+            left, op, right, ..
+        }) = expr
+        else {
+            return;
+        };
+        let value = match op {
+            BinOp::Eq(_) | BinOp::Le(_) | BinOp::Ge(_) => true,
+            BinOp::Ne(_) | BinOp::Lt(_) | BinOp::Gt(_) => false,
+            _ => return,
+        };
+        if !is_pure(left) || !same_tokens(left, right) {
+            return;
+        }
+        match expr_type(left, &self.types) {
+            Some(ty) if is_totally_ordered(&ty) => *expr = parse_quote!(#value),
+            Some(ty) if is_float(&ty) => {
+                let operand = left.to_token_stream().to_string();
+                self.diagnostics.push(Diagnostic::new(
+                    PASS,
+                    self.current_fn.as_ref(),
+                    format!(
+                        "comparison of the float `{}` with itself depends on whether it \
+                         is NaN; consider `is_nan()`",
+                        operand
+                    ),
+                ));
+            }
+            _ => {}
+        }
+    }
+}
+
+impl VisitMut for SelfComparisonFold {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        let enclosing = self.current_fn.replace(item_fn.sig.ident.clone());
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        self.types = types;
+        self.current_fn = enclosing;
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        let enclosing = self.current_fn.replace(item_fn.sig.ident.clone());
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+        self.types = types;
+        self.current_fn = enclosing;
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        syn::visit_mut::visit_expr_mut(self, expr);
+        self.fold(expr);
+    }
+}
+
+impl Pass for SelfComparisonFold {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        monad.diagnostics.append(&mut self.diagnostics);
+        Ok(monad)
+    }
+}
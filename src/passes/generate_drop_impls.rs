@@ -0,0 +1,528 @@
+//! Identifies structs which own a single raw pointer released by a dedicated free
+//! function, following the C convention of `free_buffer(b: *mut Buffer)` for a
+//! `struct Buffer`, and reports them as candidates for an `impl Drop`.
+//!
+//! When [PassConfig::generate_drop_impls] is set, the simplest case is rewritten:
+//! a free function whose body only passes the owned pointer to `free`, e.g.
+//!
+//! ```ignore
+//! unsafe fn free_buffer(b: *mut Buffer) {
+//!     free((*b).data as *mut c_void);
+//! }
+//! ```
+//!
+//! becomes `impl Drop for Buffer` releasing `self.data`. Calls freeing a local,
+//! `free_buffer(&mut b)`, are removed, since the local is now released when it
+//! goes out of scope, and so is the free function once it is unused.
+//!
+//! As the `impl Drop` applies to every value of the struct, this is only done if
+//! each of them is a local initialized by a struct literal, which is only accessed
+//! through its fields, with the owned pointer only dereferenced, and is then freed
+//! exactly once, after which its scope only goes on without it. Otherwise, e.g. if
+//! it is destructured or freed twice, the struct is reported instead. A struct
+//! deriving `Copy` cannot implement `Drop`, so it is only reported, as is one
+//! whose free function is exported, since its callers would then free the
+//! pointer a second time.
+//!
+//! [PassConfig::generate_drop_impls]: crate::PassConfig::generate_drop_impls
+
+use crate::monad::ast::Pass;
+use crate::monad::diagnostic::Diagnostic;
+use crate::monad::error::PassError;
+use crate::passes::utils::{
+    count_ident, derives_copy, is_exported, is_ident, path_ident, strip_casts, strip_parens,
+};
+use crate::MonadicAst;
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, Block, Expr, ExprCall, ExprField, ExprPath, ExprReference, ExprStruct, ExprUnary,
+    FnArg, Ident, Item, ItemFn, ItemImpl, ItemStruct, Local, LocalInit, Member, Pat, PatIdent,
+    PatType, ReturnType, Stmt, Type, TypePath, TypePtr, TypeReference, UnOp,
+};
+
+const PASS: &str = "generate_drop_impls";
+
+#[derive(Default)]
+pub struct FreeFnToDrop {
+    /// Free functions replaced by a `Drop` impl, whose calls on locals are removed.
+    replaced: Vec<Ident>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// A struct's free function which only releases its owned pointer.
+struct FreeFn {
+    /// Index of the struct in the file.
+    item: usize,
+    /// The owned pointer field.
+    field: Ident,
+    name: Ident,
+    /// The `free(..)` call, rewritten to release the field of `self`.
+    release: Expr,
+}
+
+/// Returns the struct name in snake case, e.g. `hash_table` for `HashTable`, with a
+/// C `_t` suffix removed.
+fn snake_case(name: &Ident) -> String {
+    let name = name.to_string();
+    let name = name.strip_suffix("_t").unwrap_or(&name);
+    let mut snake = String::new();
+    for (index, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if index > 0 && !snake.ends_with('_') {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+/// Returns the single raw pointer field the struct owns.
+fn owned_pointer(item: &ItemStruct) -> Option<&Ident> {
+    let pointers: Vec<&Ident> = item
+        .fields
+        .iter()
+        .filter(|field| {
+            matches!(
+                field.ty,
+                Type::Ptr(TypePtr {
+                    mutability: Some(_),
+                    ..
+                })
+            )
+        })
+        .filter_map(|field| field.ident.as_ref())
+        .collect();
+    match pointers.as_slice() {
+        [field] => Some(field),
+        _ => None,
+    }
+}
+
+/// Returns whether `func` is `free` or `libc::free`.
+fn is_free(func: &Expr) -> bool {
+    let Expr::Path(ExprPath {
+        qself: None, path, ..
+    }) = func
+    else {
+        return false;
+    };
+    let segments: Vec<String> = path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    segments == ["free"] || segments == ["libc", "free"]
+}
+
+/// Returns the statements of a block, looking inside of a lone `unsafe` block.
+fn body_stmts(block: &Block) -> &[Stmt] {
+    match block.stmts.as_slice() {
+        [Stmt::Expr(Expr::Unsafe(unsafe_block), _)] => &unsafe_block.block.stmts,
+        stmts => stmts,
+    }
+}
+
+/// Rewrites accesses of the free function's parameter into accesses of `self`.
+struct ParamToSelf<'a>(&'a Ident);
+
+impl VisitMut for ParamToSelf<'_> {
+    fn visit_expr_field_mut(&mut self, field: &mut ExprField) {
+        let base = match strip_parens(&field.base) {
+            Expr::Unary(ExprUnary {
+                op: UnOp::Deref(_),
+                expr,
+                ..
+            }) => expr,
+            base => base,
+        };
+        if is_ident(base, self.0) {
+            *field.base = parse_quote!(self);
+        }
+        syn::visit_mut::visit_expr_field_mut(self, field);
+    }
+}
+
+/// Returns whether the statement is `free_x(&mut local);`.
+fn is_free_call(stmt: &Stmt, free_fn: &Ident, local: &Ident) -> bool {
+    matches!(stmt, Stmt::Expr(Expr::Call(ExprCall { func, args, .. }), Some(_))
+        if is_ident(func, free_fn)
+            && args.len() == 1
+            && matches!(strip_casts(&args[0]), Expr::Reference(ExprReference {
+                mutability: Some(_), expr, ..
+            }) if is_ident(expr, local)))
+}
+
+/// Counts the accesses of a local struct through its fields, where the owned
+/// pointer may only be dereferenced, so that it never leaves the struct.
+struct FieldUses<'a> {
+    local: &'a Ident,
+    field: &'a Ident,
+    count: usize,
+}
+
+impl Visit<'_> for FieldUses<'_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        let field_of_local = |expr: &Expr| match strip_parens(expr) {
+            Expr::Field(ExprField {
+                base,
+                member: Member::Named(member),
+                ..
+            }) if is_ident(base, self.local) => Some(member == self.field),
+            _ => None,
+        };
+        match expr {
+            // *b.data
+            Expr::Unary(ExprUnary {
+                op: UnOp::Deref(_),
+                expr,
+                ..
+            }) if field_of_local(expr) == Some(true) => self.count += 1,
+            // b.len
+            expr if field_of_local(expr) == Some(false) => self.count += 1,
+            expr => syn::visit::visit_expr(self, expr),
+        }
+    }
+}
+
+/// Checks that every value of the struct is a local which is only accessed through
+/// its fields and then freed once by the free function, at the end of its scope,
+/// so that an `impl Drop` releases the pointer exactly where the call did, or later
+/// with nothing in between.
+struct OwnerCheck<'a> {
+    strukt: &'a Ident,
+    field: &'a Ident,
+    free_fn: &'a Ident,
+    current_fn: Option<Ident>,
+    /// Why the struct cannot implement `Drop`, if it cannot.
+    error: Option<String>,
+}
+
+impl OwnerCheck<'_> {
+    fn is_struct(&self, ty: &Type) -> bool {
+        matches!(ty, Type::Path(TypePath { qself: None, path }) if path.is_ident(self.strukt))
+    }
+
+    /// Returns the local declared by `let b = X { .. };` or `let b: X = X { .. };`,
+    /// and the struct literal.
+    fn owner_decl<'b>(&self, stmt: &'b Stmt) -> Option<(&'b Ident, &'b ExprStruct)> {
+        let Stmt::Local(Local {
+            pat,
+            init:
+                Some(LocalInit {
+                    expr,
+                    diverge: None,
+                    ..
+                }),
+            ..
+        }) = stmt
+        else {
+            return None;
+        };
+        let pat = match pat {
+            Pat::Type(PatType { pat, ty, .. }) if self.is_struct(ty) => pat.as_ref(),
+            pat => pat,
+        };
+        let (Pat::Ident(PatIdent { ident, .. }), Expr::Struct(literal)) = (pat, expr.as_ref())
+        else {
+            return None;
+        };
+        literal
+            .path
+            .is_ident(self.strukt)
+            .then_some((ident, literal))
+    }
+
+    /// Checks the statements following the declaration of the local.
+    fn check_owner(&self, local: &Ident, rest: &[Stmt]) -> Result<(), String> {
+        let in_fn = match &self.current_fn {
+            Some(name) => format!(" in `{name}`"),
+            None => String::new(),
+        };
+        let frees: Vec<usize> = (0..rest.len())
+            .filter(|&index| is_free_call(&rest[index], self.free_fn, local))
+            .collect();
+        let [free] = frees[..] else {
+            return Err(format!("`{local}`{in_fn} is not freed exactly once"));
+        };
+        if rest[free + 1..]
+            .iter()
+            .any(|stmt| count_ident(stmt, local) > 0)
+        {
+            return Err(format!(
+                "`{local}`{in_fn} is used after it is freed, other than at the end of its scope"
+            ));
+        }
+        for stmt in &rest[..free] {
+            let mut uses = FieldUses {
+                local,
+                field: self.field,
+                count: 0,
+            };
+            uses.visit_stmt(stmt);
+            if uses.count != count_ident(stmt, local) {
+                return Err(format!(
+                    "`{local}`{in_fn} is used other than through its fields, or its pointer \
+                     is used other than through a dereference"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Visit<'_> for OwnerCheck<'_> {
+    fn visit_item_fn(&mut self, item_fn: &ItemFn) {
+        if item_fn.sig.ident == *self.free_fn {
+            return;
+        }
+        let enclosing = self.current_fn.replace(item_fn.sig.ident.clone());
+        syn::visit::visit_item_fn(self, item_fn);
+        self.current_fn = enclosing;
+    }
+
+    fn visit_item_impl(&mut self, item_impl: &ItemImpl) {
+        // The methods of the struct only take `self` by value if they are called so,
+        // which is not a use through its fields.
+        for item in &item_impl.items {
+            self.visit_impl_item(item);
+        }
+    }
+
+    fn visit_block(&mut self, block: &Block) {
+        for (index, stmt) in block.stmts.iter().enumerate() {
+            match self.owner_decl(stmt) {
+                Some((local, literal)) => {
+                    if let Err(error) = self.check_owner(local, &block.stmts[index + 1..]) {
+                        self.error.get_or_insert(error);
+                    }
+                    for field in &literal.fields {
+                        self.visit_expr(&field.expr);
+                    }
+                }
+                None => self.visit_stmt(stmt),
+            }
+        }
+    }
+
+    fn visit_expr_struct(&mut self, literal: &ExprStruct) {
+        if literal.path.is_ident(self.strukt) {
+            self.error.get_or_insert_with(|| {
+                format!("a `{}` is created other than as a local", self.strukt)
+            });
+        }
+        syn::visit::visit_expr_struct(self, literal);
+    }
+
+    fn visit_type(&mut self, ty: &Type) {
+        let pointee = match ty {
+            Type::Ptr(TypePtr { elem, .. }) | Type::Reference(TypeReference { elem, .. }) => {
+                Some(elem)
+            }
+            _ => None,
+        };
+        match pointee {
+            Some(elem) if self.is_struct(elem) => {}
+            _ if self.is_struct(ty) => {
+                self.error.get_or_insert_with(|| {
+                    format!("a `{}` is held other than by a local", self.strukt)
+                });
+            }
+            _ => syn::visit::visit_type(self, ty),
+        }
+    }
+}
+
+impl FreeFnToDrop {
+    /// Returns the rewritten release if `item_fn` is the free function of the
+    /// struct named `strukt` owning the pointer `field`.
+    fn release(item_fn: &ItemFn, strukt: &Ident, field: &Ident) -> Option<Expr> {
+        let sig = &item_fn.sig;
+        let snake = snake_case(strukt);
+        let name = sig.ident.to_string();
+        if name != format!("free_{}", snake) && name != format!("{}_free", snake) {
+            return None;
+        }
+        let [FnArg::Typed(PatType { pat, ty, .. })] = sig.inputs.iter().collect::<Vec<_>>()[..]
+        else {
+            return None;
+        };
+        let Pat::Ident(param) = pat.as_ref() else {
+            return None;
+        };
+        let pointee = match ty.as_ref() {
+            Type::Ptr(TypePtr {
+                mutability: Some(_),
+                elem,
+                ..
+            })
+            | Type::Reference(TypeReference {
+                mutability: Some(_),
+                elem,
+                ..
+            }) => elem,
+            _ => return None,
+        };
+        let takes_struct =
+            matches!(pointee.as_ref(), Type::Path(path) if path.path.is_ident(strukt));
+        if !takes_struct || !matches!(sig.output, ReturnType::Default) {
+            return None;
+        }
+        let [Stmt::Expr(expr @ Expr::Call(ExprCall { func, args, .. }), Some(_))] =
+            body_stmts(&item_fn.block)
+        else {
+            return None;
+        };
+        let releases_field = args.len() == 1
+            && matches!(strip_casts(&args[0]), Expr::Field(ExprField { member: Member::Named(member), .. })
+                if member == field);
+        if !is_free(func) || !releases_field || count_ident(expr, &param.ident) != 1 {
+            return None;
+        }
+        let mut release = expr.clone();
+        ParamToSelf(&param.ident).visit_expr_mut(&mut release);
+        (count_ident(&release, &param.ident) == 0).then_some(release)
+    }
+
+    /// Returns the free functions in the file which are candidates for a `Drop` impl.
+    fn free_fns(&mut self, items: &[Item]) -> Vec<FreeFn> {
+        let mut found = Vec::new();
+        for (index, item) in items.iter().enumerate() {
+            let Item::Struct(strukt) = item else {
+                continue;
+            };
+            let Some(field) = owned_pointer(strukt) else {
+                continue;
+            };
+            for item in items {
+                let Item::Fn(item_fn) = item else {
+                    continue;
+                };
+                if let Some(release) = Self::release(item_fn, &strukt.ident, field) {
+                    let copy = derives_copy(strukt);
+                    self.diagnostics.push(Diagnostic::new(
+                        PASS,
+                        Some(&item_fn.sig.ident),
+                        if copy {
+                            format!(
+                                "releases the pointer owned by `{}`, which derives `Copy` and so \
+                                 cannot implement `Drop`",
+                                strukt.ident
+                            )
+                        } else {
+                            format!(
+                                "releases the pointer owned by `{}`; consider an `impl Drop`",
+                                strukt.ident
+                            )
+                        },
+                    ));
+                    if !copy && !is_exported(item_fn) {
+                        found.push(FreeFn {
+                            item: index,
+                            field: field.clone(),
+                            name: item_fn.sig.ident.clone(),
+                            release,
+                        });
+                    }
+                }
+            }
+        }
+        found
+    }
+}
+
+impl VisitMut for FreeFnToDrop {
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        syn::visit_mut::visit_block_mut(self, block);
+        // free_x(&mut x);
+        block.stmts.retain(|stmt| {
+            let Stmt::Expr(Expr::Call(ExprCall { func, args, .. }), Some(_)) = stmt else {
+                return true;
+            };
+            let replaced = path_ident(func).is_some_and(|ident| self.replaced.contains(ident));
+            let frees_local = args.len() == 1
+                && matches!(strip_casts(&args[0]), Expr::Reference(ExprReference {
+                    mutability: Some(_), expr, ..
+                }) if path_ident(expr).is_some());
+            !(replaced && frees_local)
+        });
+    }
+}
+
+impl Pass for FreeFnToDrop {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        let free_fns = self.free_fns(&monad.ast.items);
+        if !monad.config.generate_drop_impls || free_fns.is_empty() {
+            monad.diagnostics.append(&mut self.diagnostics);
+            return Ok(monad);
+        }
+        // The diagnostics only apply to what is left as it was.
+        self.diagnostics.retain(|diagnostic| {
+            !free_fns
+                .iter()
+                .any(|free_fn| diagnostic.function == Some(free_fn.name.to_string()))
+        });
+        let mut replaced = Vec::new();
+        for free_fn in free_fns {
+            let Item::Struct(strukt) = &monad.ast.items[free_fn.item] else {
+                continue;
+            };
+            let mut check = OwnerCheck {
+                strukt: &strukt.ident,
+                field: &free_fn.field,
+                free_fn: &free_fn.name,
+                current_fn: None,
+                error: None,
+            };
+            check.visit_file(&monad.ast);
+            match check.error {
+                Some(error) => self.diagnostics.push(Diagnostic::new(
+                    PASS,
+                    Some(&free_fn.name),
+                    format!(
+                        "releases the pointer owned by `{}`, but {error}; keeping it rather \
+                         than an `impl Drop`",
+                        strukt.ident
+                    ),
+                )),
+                None => replaced.push(free_fn),
+            }
+        }
+        // Insert the impls from the last struct on, so the indices stay valid.
+        for free_fn in replaced.iter().rev() {
+            let Item::Struct(strukt) = &monad.ast.items[free_fn.item] else {
+                continue;
+            };
+            let ident = &strukt.ident;
+            let release = &free_fn.release;
+            let drop_impl: Item = parse_quote! {
+                impl Drop for #ident {
+                    fn drop(&mut self) {
+                        unsafe {
+                            #release;
+                        }
+                    }
+                }
+            };
+            monad.ast.items.insert(free_fn.item + 1, drop_impl);
+            self.replaced.push(free_fn.name.clone());
+        }
+        self.visit_file_mut(&mut monad.ast);
+        let file = &monad.ast;
+        let unused: Vec<Ident> = self
+            .replaced
+            .iter()
+            .filter(|name| count_ident(file, name) == 1)
+            .cloned()
+            .collect();
+        monad.ast.items.retain(|item| match item {
+            Item::Fn(item_fn) => !unused.contains(&item_fn.sig.ident) || is_exported(item_fn),
+            _ => true,
+        });
+        monad.diagnostics.append(&mut self.diagnostics);
+        Ok(monad)
+    }
+}
@@ -0,0 +1,256 @@
+//! Inlines small private functions which are called from exactly one site in the
+//! file, then removes them.
+//!
+//! The arguments are bound to fresh names in a block at the call site before the
+//! body runs, so they are still evaluated exactly once and in order, e.g.
+//! `let y = add_one(x);` becomes `let y = { let v: i32 = x; v + 1 };`. When every
+//! argument is a plain variable or literal and the body is a single expression,
+//! the arguments are substituted directly, producing `let y = x + 1;`.
+//!
+//! Only functions whose bodies have at most [PassConfig::max_inlined_statements]
+//! statements are inlined.
+//!
+//! [PassConfig::max_inlined_statements]: crate::PassConfig::max_inlined_statements
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{count_ident, path_ident, NameGen, Parenthesize};
+use crate::MonadicAst;
+use std::collections::{HashMap, HashSet};
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, Block, Expr, ExprCall, FnArg, Ident, Item, ItemFn, Pat, PatIdent, PatType, Stmt,
+    Type, Visibility,
+};
+
+#[derive(Default)]
+pub struct InlineSingleCallFn {
+    /// Functions whose bodies have more statements than this are never inlined.
+    max_statements: usize,
+    names: NameGen,
+}
+
+/// A function which may be inlined at its only call site.
+struct Candidate {
+    name: Ident,
+    params: Vec<(PatIdent, Type)>,
+    body: Block,
+    is_unsafe: bool,
+}
+
+impl InlineSingleCallFn {
+    /// Returns the function as an inlining candidate if it is private, not generic,
+    /// takes plain named parameters, and has a small body with no early exits.
+    fn candidate(&self, item_fn: &ItemFn) -> Option<Candidate> {
+        let ItemFn {
+            attrs,
+            vis,
+            sig,
+            block,
+        } = item_fn;
+        if !matches!(vis, Visibility::Inherited)
+            || attrs.iter().any(|attr| !attr.path().is_ident("inline"))
+            || sig.constness.is_some()
+            || sig.asyncness.is_some()
+            || sig.abi.is_some()
+            || sig.variadic.is_some()
+            || !sig.generics.params.is_empty()
+            || block.stmts.len() > self.max_statements
+            || has_early_exit(block)
+        {
+            return None;
+        }
+        let params = sig
+            .inputs
+            .iter()
+            .map(|input| match input {
+                FnArg::Typed(PatType { pat, ty, .. }) => match pat.as_ref() {
+                    Pat::Ident(ident) if ident.by_ref.is_none() && ident.subpat.is_none() => {
+                        Some((ident.clone(), ty.as_ref().clone()))
+                    }
+                    _ => None,
+                },
+                FnArg::Receiver(_) => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Candidate {
+            name: sig.ident.clone(),
+            params,
+            body: block.as_ref().clone(),
+            is_unsafe: sig.unsafety.is_some(),
+        })
+    }
+
+    /// Returns the expression replacing the call `candidate(args)`.
+    fn inlined(&mut self, candidate: &Candidate, args: Vec<Expr>) -> Expr {
+        let single_expr = matches!(candidate.body.stmts.as_slice(), [Stmt::Expr(_, None)]);
+        let simple_args = args
+            .iter()
+            .all(|arg| matches!(arg, Expr::Path(_) | Expr::Lit(_)));
+        let mut body = candidate.body.clone();
+        let expr: Expr = if single_expr && simple_args {
+            let substitutions = candidate
+                .params
+                .iter()
+                .map(|(param, _)| param.ident.clone())
+                .zip(args)
+                .collect();
+            Substitute { substitutions }.visit_block_mut(&mut body);
+            match body.stmts.pop() {
+                Some(Stmt::Expr(expr, None)) => expr,
+                _ => unreachable!(),
+            }
+        } else {
+            let mut substitutions = HashMap::new();
+            let mut bindings: Vec<Stmt> = Vec::new();
+            for ((param, ty), arg) in candidate.params.iter().zip(args) {
+                let fresh = self.names.fresh(&param.ident.to_string());
+                let mutability = &param.mutability;
+                bindings.push(parse_quote!(let #mutability #fresh: #ty = #arg;));
+                substitutions.insert(param.ident.clone(), parse_quote!(#fresh));
+            }
+            Substitute { substitutions }.visit_block_mut(&mut body);
+            bindings.append(&mut body.stmts);
+            parse_quote!({ #(#bindings)* })
+        };
+        match (candidate.is_unsafe, expr) {
+            (true, Expr::Block(block)) => {
+                let block = block.block;
+                parse_quote!(unsafe #block)
+            }
+            (true, expr) => parse_quote!(unsafe { #expr }),
+            (false, expr) => expr,
+        }
+    }
+}
+
+/// Returns whether the block contains a `return` or `?`, which would exit the
+/// caller instead of the inlined body.
+fn has_early_exit(block: &Block) -> bool {
+    struct EarlyExit(bool);
+    impl Visit<'_> for EarlyExit {
+        fn visit_expr(&mut self, expr: &Expr) {
+            self.0 |= matches!(expr, Expr::Return(_) | Expr::Try(_));
+            syn::visit::visit_expr(self, expr);
+        }
+        fn visit_item(&mut self, _: &Item) {
+            self.0 = true;
+        }
+    }
+    let mut visitor = EarlyExit(false);
+    visitor.visit_block(block);
+    visitor.0
+}
+
+/// Returns the names bound by patterns anywhere in the node.
+fn bound_names(item: &Item) -> HashSet<Ident> {
+    struct Bindings(HashSet<Ident>);
+    impl Visit<'_> for Bindings {
+        fn visit_pat_ident(&mut self, pat: &PatIdent) {
+            self.0.insert(pat.ident.clone());
+            syn::visit::visit_pat_ident(self, pat);
+        }
+    }
+    let mut visitor = Bindings(HashSet::new());
+    visitor.visit_item(item);
+    visitor.0
+}
+
+/// Replaces uses of the fn parameters with their substitutes.
+struct Substitute {
+    substitutions: HashMap<Ident, Expr>,
+}
+
+impl VisitMut for Substitute {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if let Some(substitute) = path_ident(expr).and_then(|ident| self.substitutions.get(ident)) {
+            *expr = substitute.clone();
+            return;
+        }
+        syn::visit_mut::visit_expr_mut(self, expr);
+    }
+}
+
+/// Replaces the single call to the candidate with its inlined body.
+struct CallSite<'a> {
+    pass: &'a mut InlineSingleCallFn,
+    candidate: &'a Candidate,
+    inlined: bool,
+}
+
+impl VisitMut for CallSite<'_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        syn::visit_mut::visit_expr_mut(self, expr);
+        if let Expr::Call(ExprCall { func, args, .. }) = expr {
+            if path_ident(func) == Some(&self.candidate.name)
+                && args.len() == self.candidate.params.len()
+            {
+                let args = args.iter().cloned().collect();
+                *expr = self.pass.inlined(self.candidate, args);
+                self.inlined = true;
+            }
+        }
+    }
+}
+
+impl Pass for InlineSingleCallFn {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.max_statements = monad.config.max_inlined_statements;
+        self.names = NameGen::new(&monad.ast);
+        let candidates: Vec<(usize, Candidate)> = monad
+            .ast
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item)| match item {
+                Item::Fn(item_fn) => self.candidate(item_fn).map(|candidate| (i, candidate)),
+                _ => None,
+            })
+            .collect();
+
+        let mut removed = Vec::new();
+        for (index, candidate) in candidates {
+            // The name must only appear in the definition and the single call.
+            if count_ident(&monad.ast, &candidate.name) != 2 {
+                continue;
+            }
+            let Some(caller) = monad.ast.items.iter().position(|item| {
+                !matches!(item, Item::Fn(item_fn) if item_fn.sig.ident == candidate.name)
+                    && count_ident(item, &candidate.name) == 1
+            }) else {
+                continue;
+            };
+            // Names the body refers to must not be captured by the caller's bindings.
+            let params: HashSet<Ident> = candidate
+                .params
+                .iter()
+                .map(|(param, _)| param.ident.clone())
+                .collect();
+            let caller_bindings = bound_names(&monad.ast.items[caller]);
+            let captured = caller_bindings
+                .iter()
+                .any(|name| !params.contains(name) && count_ident(&candidate.body, name) > 0);
+            if captured {
+                continue;
+            }
+            let mut call_site = CallSite {
+                pass: self,
+                candidate: &candidate,
+                inlined: false,
+            };
+            call_site.visit_item_mut(&mut monad.ast.items[caller]);
+            if call_site.inlined {
+                Parenthesize.visit_item_mut(&mut monad.ast.items[caller]);
+                removed.push(index);
+            }
+        }
+
+        let mut index = 0;
+        monad.ast.items.retain(|_| {
+            index += 1;
+            !removed.contains(&(index - 1))
+        });
+        Ok(monad)
+    }
+}
@@ -0,0 +1,157 @@
+//! Replaces conditional negations computing an absolute value with `abs`, e.g.
+//! `if x < 0 { -x } else { x }` and `if x >= 0 { x } else { -x }` become
+//! `x.abs()`.
+//!
+//! The operand must be side-effect free and of a known signed integer or float
+//! type. For floats, `abs` also clears the sign of `-0.0` and of NaN, which the
+//! conditional left alone.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{
+    expr_type, is_float, is_int_lit, is_pure, is_signed_int, local_types, same_tokens,
+    strip_parens, Parenthesize,
+};
+use crate::MonadicAst;
+use std::collections::HashMap;
+use syn::visit_mut::VisitMut;
+use syn::{
+    BinOp, Block, Expr, ExprBinary, ExprBlock, ExprIf, ExprLit, ExprMethodCall, ExprUnary, Ident,
+    ImplItemFn, ItemFn, Lit, Stmt, Type, UnOp,
+};
+
+#[derive(Default)]
+pub struct AbsIntro {
+    /// Declared types of the locals of the function currently being visited.
+    types: HashMap<Ident, Type>,
+}
+
+/// Returns the value of a block consisting only of a single expression.
+fn block_value(block: &Block) -> Option<&Expr> {
+    match block.stmts.as_slice() {
+        [Stmt::Expr(expr, None)] => Some(strip_parens(expr)),
+        _ => None,
+    }
+}
+
+/// Returns whether `expr` is the literal zero, `0` or `0.0`.
+fn is_zero(expr: &Expr) -> bool {
+    match strip_parens(expr) {
+        Expr::Lit(ExprLit {
+            lit: Lit::Float(float),
+            ..
+        }) => float.base10_parse::<f64>().is_ok_and(|value| value == 0.0),
+        expr => is_int_lit(expr, 0),
+    }
+}
+
+/// Returns whether `expr` is `-operand`.
+fn is_negation(expr: &Expr, operand: &Expr) -> bool {
+    matches!(expr, Expr::Unary(ExprUnary { op: UnOp::Neg(_), expr, .. })
+        if same_tokens(strip_parens(expr), operand))
+}
+
+impl AbsIntro {
+    /// Returns `x` if `expr` is a conditional negation of `x` to its absolute value.
+    fn abs_operand<'a>(&self, expr: &'a Expr) -> Option<&'a Expr> {
+        let Expr::If(ExprIf {
+            cond,
+            then_branch,
+            else_branch: Some((_, else_branch)),
+            ..
+        }) = expr
+        else {
+            return None;
+        };
+        let Expr::Block(ExprBlock {
+            block: else_branch,
+            label: None,
+            ..
+        }) = else_branch.as_ref()
+        else {
+            return None;
+        };
+        let then_value = block_value(then_branch)?;
+        let else_value = block_value(else_branch)?;
+        let Expr::Binary(ExprBinary {
+            left, op, right, ..
+        }) = strip_parens(cond)
+        else {
+            return None;
+        };
+        // Normalize the condition to `x op 0`.
+        let (operand, op) = match (is_zero(right), is_zero(left), op) {
+            (true, _, op) => (strip_parens(left), *op),
+            (false, true, BinOp::Lt(_)) => (strip_parens(right), BinOp::Gt(Default::default())),
+            (false, true, BinOp::Le(_)) => (strip_parens(right), BinOp::Ge(Default::default())),
+            (false, true, BinOp::Gt(_)) => (strip_parens(right), BinOp::Lt(Default::default())),
+            (false, true, BinOp::Ge(_)) => (strip_parens(right), BinOp::Le(Default::default())),
+            _ => return None,
+        };
+        let negated_when_true = match op {
+            BinOp::Lt(_) | BinOp::Le(_) => true,
+            BinOp::Gt(_) | BinOp::Ge(_) => false,
+            _ => return None,
+        };
+        let (negated, kept) = if negated_when_true {
+            (then_value, else_value)
+        } else {
+            (else_value, then_value)
+        };
+        let is_abs =
+            is_pure(operand) && same_tokens(kept, operand) && is_negation(negated, operand);
+        let zero_is_float = [left, right].into_iter().any(|side| {
+            matches!(
+                strip_parens(side),
+                Expr::Lit(ExprLit {
+                    lit: Lit::Float(_),
+                    ..
+                })
+            )
+        });
+        let signed = zero_is_float
+            || expr_type(operand, &self.types)
+                .is_some_and(|ty| is_signed_int(&ty) || is_float(&ty));
+        (is_abs && signed).then_some(operand)
+    }
+}
+
+impl VisitMut for AbsIntro {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        syn::visit_mut::visit_expr_mut(self, expr);
+        if let Some(operand) = self.abs_operand(expr) {
+            // Built directly, since the operand may be a cast the parser would not
+            // accept as a receiver without parentheses.
+            let mut abs = Expr::MethodCall(ExprMethodCall {
+                attrs: Vec::new(),
+                receiver: Box::new(operand.clone()),
+                dot_token: Default::default(),
+                method: quote::format_ident!("abs"),
+                turbofish: None,
+                paren_token: Default::default(),
+                args: Default::default(),
+            });
+            Parenthesize.visit_expr_mut(&mut abs);
+            *expr = abs;
+        }
+    }
+}
+
+impl Pass for AbsIntro {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
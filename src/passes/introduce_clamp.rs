@@ -0,0 +1,322 @@
+//! Replaces conditionals limiting a value to a range with `clamp`, e.g.
+//! `if x < lo { lo } else if x > hi { hi } else { x }` becomes `x.clamp(lo, hi)`,
+//! and the statement `if x < lo { x = lo; } else if x > hi { x = hi; }` becomes
+//! `x = x.clamp(lo, hi);`. Either bound may be tested first, and each comparison
+//! may be written either way around.
+//!
+//! Unlike the conditional, `clamp` panics if `lo > hi`, so the bounds must be
+//! literals in order. Conditionals with bounds other than literals are only
+//! reported.
+//!
+//! When [PassConfig::rewrite_max_min_clamps] is set, `x.max(lo).min(hi)` and
+//! `x.min(hi).max(lo)` with literal bounds become `x.clamp(lo, hi)` as well. For
+//! a float `x` which is NaN, these return a bound, whereas `clamp` returns NaN.
+//!
+//! [PassConfig::rewrite_max_min_clamps]: crate::PassConfig::rewrite_max_min_clamps
+
+use crate::monad::ast::Pass;
+use crate::monad::diagnostic::Diagnostic;
+use crate::monad::error::PassError;
+use crate::passes::utils::{is_pure, same_tokens, strip_parens, Parenthesize};
+use crate::MonadicAst;
+use quote::ToTokens;
+use syn::visit_mut::VisitMut;
+use syn::{
+    BinOp, Block, Expr, ExprAssign, ExprBinary, ExprIf, ExprLit, ExprMethodCall, ExprUnary, Ident,
+    ImplItemFn, ItemFn, Lit, Stmt, UnOp,
+};
+
+const PASS: &str = "introduce_clamp";
+
+#[derive(Default)]
+pub struct ClampIntro {
+    /// Whether to also rewrite `x.max(lo).min(hi)`.
+    max_min: bool,
+    current_fn: Option<Ident>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Which bound of the range a comparison tests.
+#[derive(PartialEq, Eq)]
+enum Bound {
+    Lower,
+    Upper,
+}
+
+/// The numeric value of a literal, possibly negated.
+enum Value {
+    Int(i128),
+    Float(f64),
+}
+
+/// Returns the value of `expr` if it is a number literal or its negation.
+fn literal_value(expr: &Expr) -> Option<Value> {
+    match strip_parens(expr) {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(int), ..
+        }) => int.base10_parse().ok().map(Value::Int),
+        Expr::Lit(ExprLit {
+            lit: Lit::Float(float),
+            ..
+        }) => float.base10_parse().ok().map(Value::Float),
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        }) => match literal_value(expr)? {
+            Value::Int(int) => Some(Value::Int(-int)),
+            Value::Float(float) => Some(Value::Float(-float)),
+        },
+        _ => None,
+    }
+}
+
+/// Returns whether `lo` and `hi` are literals with `lo <= hi`.
+fn is_ordered_range(lo: &Expr, hi: &Expr) -> bool {
+    match (literal_value(lo), literal_value(hi)) {
+        (Some(Value::Int(lo)), Some(Value::Int(hi))) => lo <= hi,
+        (Some(Value::Float(lo)), Some(Value::Float(hi))) => lo <= hi,
+        _ => false,
+    }
+}
+
+/// Returns the side of the range and the bound if `cond` is `x < b` or `x > b`,
+/// written either way around.
+fn compared_bound<'a>(cond: &'a Expr, x: &Expr) -> Option<(Bound, &'a Expr)> {
+    let Expr::Binary(ExprBinary {
+        left, op, right, ..
+    }) = strip_parens(cond)
+    else {
+        return None;
+    };
+    let (bound, below) = if same_tokens(strip_parens(left), x) {
+        (right, matches!(op, BinOp::Lt(_)))
+    } else if same_tokens(strip_parens(right), x) {
+        (left, matches!(op, BinOp::Gt(_)))
+    } else {
+        return None;
+    };
+    if !matches!(op, BinOp::Lt(_) | BinOp::Gt(_)) {
+        return None;
+    }
+    let side = if below { Bound::Lower } else { Bound::Upper };
+    Some((side, strip_parens(bound)))
+}
+
+/// Returns the value of a block consisting only of a single expression.
+fn block_value(block: &Block) -> Option<&Expr> {
+    match block.stmts.as_slice() {
+        [Stmt::Expr(expr, None)] => Some(strip_parens(expr)),
+        _ => None,
+    }
+}
+
+/// Returns the assigned place and value if the block only is `place = value;`.
+fn block_assignment(block: &Block) -> Option<(&Expr, &Expr)> {
+    match block.stmts.as_slice() {
+        [Stmt::Expr(Expr::Assign(ExprAssign { left, right, .. }), Some(_))] => {
+            Some((strip_parens(left), strip_parens(right)))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the `else if` branch of the conditional and its own `else` block.
+fn else_if(expr_if: &ExprIf) -> Option<(&ExprIf, Option<&Block>)> {
+    let Some((_, else_branch)) = &expr_if.else_branch else {
+        return None;
+    };
+    let Expr::If(inner) = else_branch.as_ref() else {
+        return None;
+    };
+    let inner_else = match &inner.else_branch {
+        None => None,
+        Some((_, expr)) => match expr.as_ref() {
+            Expr::Block(block) if block.attrs.is_empty() && block.label.is_none() => {
+                Some(&block.block)
+            }
+            _ => return None,
+        },
+    };
+    Some((inner, inner_else))
+}
+
+/// Returns `(lo, hi)` if the two comparisons test opposite bounds of `x` against the
+/// values the branches take.
+fn range<'a>(
+    x: &Expr,
+    (first_cond, first): (&'a Expr, &Expr),
+    (second_cond, second): (&'a Expr, &Expr),
+) -> Option<(&'a Expr, &'a Expr)> {
+    let (first_side, first_bound) = compared_bound(first_cond, x)?;
+    let (second_side, second_bound) = compared_bound(second_cond, x)?;
+    if !same_tokens(first_bound, first) || !same_tokens(second_bound, second) {
+        return None;
+    }
+    match (first_side, second_side) {
+        (Bound::Lower, Bound::Upper) => Some((first_bound, second_bound)),
+        (Bound::Upper, Bound::Lower) => Some((second_bound, first_bound)),
+        _ => None,
+    }
+}
+
+/// Returns `x.clamp(lo, hi)`, built directly since splicing an operation into
+/// `parse_quote!` would misplace the receiver.
+fn clamp(x: &Expr, lo: &Expr, hi: &Expr) -> Expr {
+    let mut clamp = Expr::MethodCall(ExprMethodCall {
+        attrs: Vec::new(),
+        receiver: Box::new(x.clone()),
+        dot_token: Default::default(),
+        method: quote::format_ident!("clamp"),
+        turbofish: None,
+        paren_token: Default::default(),
+        args: [lo.clone(), hi.clone()].into_iter().collect(),
+    });
+    Parenthesize.visit_expr_mut(&mut clamp);
+    clamp
+}
+
+/// Returns `(x, lo, hi)` if `expr` is the conditional expression clamping `x`.
+fn clamped_value(expr: &Expr) -> Option<(&Expr, &Expr, &Expr)> {
+    let Expr::If(expr_if) = expr else {
+        return None;
+    };
+    let (inner, Some(inner_else)) = else_if(expr_if)? else {
+        return None;
+    };
+    let x = block_value(inner_else)?;
+    let first = block_value(&expr_if.then_branch)?;
+    let second = block_value(&inner.then_branch)?;
+    let (lo, hi) = range(x, (&expr_if.cond, first), (&inner.cond, second))?;
+    Some((x, lo, hi))
+}
+
+/// Returns `(x, lo, hi)` if `expr` is the conditional statement clamping `x`.
+fn clamped_place(expr: &Expr) -> Option<(&Expr, &Expr, &Expr)> {
+    let Expr::If(expr_if) = expr else {
+        return None;
+    };
+    let (inner, None) = else_if(expr_if)? else {
+        return None;
+    };
+    let (x, first) = block_assignment(&expr_if.then_branch)?;
+    let (place, second) = block_assignment(&inner.then_branch)?;
+    if !same_tokens(x, place) {
+        return None;
+    }
+    let (lo, hi) = range(x, (&expr_if.cond, first), (&inner.cond, second))?;
+    Some((x, lo, hi))
+}
+
+/// Returns `(x, lo, hi)` if `expr` is `x.max(lo).min(hi)` or `x.min(hi).max(lo)`.
+fn max_min(expr: &Expr) -> Option<(&Expr, &Expr, &Expr)> {
+    let Expr::MethodCall(outer) = expr else {
+        return None;
+    };
+    let Expr::MethodCall(inner) = strip_parens(&outer.receiver) else {
+        return None;
+    };
+    if outer.args.len() != 1 || inner.args.len() != 1 {
+        return None;
+    }
+    let (outer_arg, inner_arg) = (&outer.args[0], &inner.args[0]);
+    let x = strip_parens(&inner.receiver);
+    match (
+        outer.method.to_string().as_str(),
+        inner.method.to_string().as_str(),
+    ) {
+        ("min", "max") => Some((x, strip_parens(inner_arg), strip_parens(outer_arg))),
+        ("max", "min") => Some((x, strip_parens(outer_arg), strip_parens(inner_arg))),
+        _ => None,
+    }
+}
+
+impl ClampIntro {
+    /// Returns whether the clamp may be rewritten, reporting it otherwise.
+    fn is_rewritable(&mut self, x: &Expr, lo: &Expr, hi: &Expr) -> bool {
+        if !is_pure(x) || !is_pure(lo) || !is_pure(hi) {
+            return false;
+        }
+        if is_ordered_range(lo, hi) {
+            return true;
+        }
+        // Literal bounds out of order do not describe a range.
+        if literal_value(lo).is_some() && literal_value(hi).is_some() {
+            return false;
+        }
+        let show = |expr: &Expr| expr.to_token_stream().to_string();
+        self.diagnostics.push(Diagnostic::new(
+            PASS,
+            self.current_fn.as_ref(),
+            format!(
+                "`{0}` is clamped to `{1}..={2}`; consider `{0}.clamp({1}, {2})`, which \
+                 panics if `{1} > {2}`",
+                show(x),
+                show(lo),
+                show(hi)
+            ),
+        ));
+        false
+    }
+}
+
+impl VisitMut for ClampIntro {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        let enclosing = self.current_fn.replace(item_fn.sig.ident.clone());
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        self.current_fn = enclosing;
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        let enclosing = self.current_fn.replace(item_fn.sig.ident.clone());
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+        self.current_fn = enclosing;
+    }
+
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        syn::visit_mut::visit_block_mut(self, block);
+        for stmt in &mut block.stmts {
+            let Stmt::Expr(expr, _) = stmt else {
+                continue;
+            };
+            let Some((x, lo, hi)) = clamped_place(expr) else {
+                continue;
+            };
+            if self.is_rewritable(x, lo, hi) {
+                let clamp = clamp(x, lo, hi);
+                let x = x.clone();
+                *stmt = Stmt::Expr(
+                    Expr::Assign(ExprAssign {
+                        attrs: Vec::new(),
+                        left: Box::new(x),
+                        eq_token: Default::default(),
+                        right: Box::new(clamp),
+                    }),
+                    Some(Default::default()),
+                );
+            }
+        }
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        syn::visit_mut::visit_expr_mut(self, expr);
+        if let Some((x, lo, hi)) = clamped_value(expr) {
+            if self.is_rewritable(x, lo, hi) {
+                *expr = clamp(x, lo, hi);
+            }
+        } else if let Some((x, lo, hi)) = max_min(expr).filter(|_| self.max_min) {
+            if is_pure(lo) && is_pure(hi) && is_ordered_range(lo, hi) {
+                *expr = clamp(x, lo, hi);
+            }
+        }
+    }
+}
+
+impl Pass for ClampIntro {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.max_min = monad.config.rewrite_max_min_clamps;
+        self.visit_file_mut(&mut monad.ast);
+        monad.diagnostics.append(&mut self.diagnostics);
+        Ok(monad)
+    }
+}
@@ -0,0 +1,163 @@
+//! Replaces emptiness tests on the length of a collection with `is_empty`, e.g.
+//! `v.len() == 0` becomes `v.is_empty()` and `v.len() > 0` becomes `!v.is_empty()`.
+//!
+//! Only receivers known to have an `is_empty` method are rewritten: locals declared
+//! as a standard collection, slice, array or string, and the results of methods
+//! such as `as_bytes()` which return one.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{is_int_lit, local_types, path_ident, strip_parens};
+use crate::MonadicAst;
+use std::collections::HashMap;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, BinOp, Expr, ExprBinary, ExprMethodCall, Ident, ImplItemFn, ItemFn, Type, TypePath,
+};
+
+/// Standard types with an `is_empty` method agreeing with `len() == 0`.
+static COLLECTIONS: &[&str] = &[
+    "Vec",
+    "VecDeque",
+    "LinkedList",
+    "BinaryHeap",
+    "HashMap",
+    "HashSet",
+    "BTreeMap",
+    "BTreeSet",
+    "String",
+    "str",
+    "OsStr",
+    "OsString",
+];
+
+/// Methods returning a slice, string or vector.
+static COLLECTION_METHODS: &[&str] = &[
+    "as_bytes",
+    "as_slice",
+    "as_str",
+    "to_vec",
+    "to_string",
+    "to_bytes",
+    "trim",
+];
+
+#[derive(Default)]
+pub struct IsEmptyIntro {
+    /// Declared types of the locals of the function currently being visited.
+    types: HashMap<Ident, Type>,
+}
+
+/// Returns whether a value of type `ty` has an `is_empty` method.
+fn has_is_empty(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(reference) => has_is_empty(&reference.elem),
+        Type::Paren(paren) => has_is_empty(&paren.elem),
+        Type::Slice(_) | Type::Array(_) => true,
+        Type::Path(TypePath { qself: None, path }) => path
+            .segments
+            .last()
+            .is_some_and(|segment| COLLECTIONS.contains(&segment.ident.to_string().as_str())),
+        _ => false,
+    }
+}
+
+/// Returns whether the comparison `left op right` holds exactly for `len == 0`
+/// (`Some(true)`) or for `len != 0` (`Some(false)`), where `left` is the length.
+fn emptiness(op: &BinOp, right: &Expr) -> Option<bool> {
+    match op {
+        BinOp::Eq(_) | BinOp::Le(_) if is_int_lit(right, 0) => Some(true),
+        BinOp::Lt(_) if is_int_lit(right, 1) => Some(true),
+        BinOp::Ne(_) | BinOp::Gt(_) if is_int_lit(right, 0) => Some(false),
+        BinOp::Ge(_) if is_int_lit(right, 1) => Some(false),
+        _ => None,
+    }
+}
+
+/// Returns the comparison with its operands swapped, e.g. `<` for `>`.
+fn flipped(op: &BinOp) -> Option<BinOp> {
+    Some(match op {
+        BinOp::Eq(_) => BinOp::Eq(Default::default()),
+        BinOp::Ne(_) => BinOp::Ne(Default::default()),
+        BinOp::Lt(_) => BinOp::Gt(Default::default()),
+        BinOp::Le(_) => BinOp::Ge(Default::default()),
+        BinOp::Gt(_) => BinOp::Lt(Default::default()),
+        BinOp::Ge(_) => BinOp::Le(Default::default()),
+        _ => return None,
+    })
+}
+
+impl IsEmptyIntro {
+    /// Returns the collection `c` if `expr` is `c.len()` on a receiver known to
+    /// have an `is_empty` method.
+    fn length_of<'a>(&self, expr: &'a Expr) -> Option<&'a Expr> {
+        let Expr::MethodCall(ExprMethodCall {
+            receiver,
+            method,
+            args,
+            ..
+        }) = strip_parens(expr)
+        else {
+            return None;
+        };
+        if method != "len" || !args.is_empty() {
+            return None;
+        }
+        let known = match strip_parens(receiver) {
+            Expr::MethodCall(call) => {
+                COLLECTION_METHODS.contains(&call.method.to_string().as_str())
+            }
+            Expr::Lit(_) => true,
+            receiver => path_ident(receiver)
+                .and_then(|ident| self.types.get(ident))
+                .is_some_and(has_is_empty),
+        };
+        known.then_some(receiver)
+    }
+
+    fn replacement(&self, expr: &Expr) -> Option<Expr> {
+        let Expr::Binary(ExprBinary {
+            left, op, right, ..
+        }) = expr
+        else {
+            return None;
+        };
+        let (collection, empty) = match self.length_of(left) {
+            Some(collection) => (collection, emptiness(op, right)?),
+            None => (self.length_of(right)?, emptiness(&flipped(op)?, left)?),
+        };
+        Some(if empty {
+            parse_quote!(#collection.is_empty())
+        } else {
+            parse_quote!(!#collection.is_empty())
+        })
+    }
+}
+
+impl VisitMut for IsEmptyIntro {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        syn::visit_mut::visit_expr_mut(self, expr);
+        if let Some(replacement) = self.replacement(expr) {
+            *expr = replacement;
+        }
+    }
+}
+
+impl Pass for IsEmptyIntro {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
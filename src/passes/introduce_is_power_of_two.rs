@@ -0,0 +1,191 @@
+//! Replaces the bit-twiddling power-of-two test `x != 0 && (x & (x - 1)) == 0` on
+//! an unsigned integer `x` with `x.is_power_of_two()`.
+//!
+//! The unguarded test `(x & (x - 1)) == 0` also holds for `x == 0`, unlike
+//! `is_power_of_two`, so it becomes `x == 0 || x.is_power_of_two()` and a
+//! diagnostic is emitted in case the zero case was not intended.
+
+use crate::monad::ast::Pass;
+use crate::monad::diagnostic::Diagnostic;
+use crate::monad::error::PassError;
+use crate::passes::utils::{
+    expr_type, is_int_lit, is_pure, is_unsigned_int, local_types, same_tokens, strip_parens,
+    Parenthesize,
+};
+use crate::MonadicAst;
+use quote::ToTokens;
+use std::collections::HashMap;
+use syn::visit_mut::VisitMut;
+use syn::{parse_quote, BinOp, Expr, ExprBinary, Ident, ImplItemFn, ItemFn, Type};
+
+const PASS: &str = "introduce_is_power_of_two";
+
+#[derive(Default)]
+pub struct PowerOfTwoIntro {
+    /// Declared types of the locals of the function currently being visited.
+    types: HashMap<Ident, Type>,
+    current_fn: Option<Ident>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Returns `x` if `expr` is `x - 1`.
+fn minus_one(expr: &Expr) -> Option<&Expr> {
+    match strip_parens(expr) {
+        Expr::Binary(ExprBinary {
+            left,
+            op: BinOp::Sub(_),
+            right,
+            ..
+        }) if is_int_lit(right, 1) => Some(left),
+        _ => None,
+    }
+}
+
+/// Returns `x` if `expr` is `x & (x - 1)` or `(x - 1) & x`.
+fn clear_lowest_bit(expr: &Expr) -> Option<&Expr> {
+    let Expr::Binary(ExprBinary {
+        left,
+        op: BinOp::BitAnd(_),
+        right,
+        ..
+    }) = strip_parens(expr)
+    else {
+        return None;
+    };
+    [(left, right), (right, left)]
+        .into_iter()
+        .find_map(|(x, decremented)| {
+            minus_one(decremented).filter(|inner| same_tokens(strip_parens(x), strip_parens(inner)))
+        })
+        .map(strip_parens)
+}
+
+/// Returns the two operands of `expr` if it is the comparison `left <op> right`
+/// for the operator `op`, trying both operand orders.
+fn compared_with_zero<'a>(expr: &'a Expr, ops: &[fn(&BinOp) -> bool]) -> Option<&'a Expr> {
+    let Expr::Binary(ExprBinary {
+        left, op, right, ..
+    }) = strip_parens(expr)
+    else {
+        return None;
+    };
+    if !ops.iter().any(|matches_op| matches_op(op)) {
+        return None;
+    }
+    if is_int_lit(right, 0) {
+        Some(left)
+    } else if is_int_lit(left, 0) && matches!(op, BinOp::Eq(_) | BinOp::Ne(_)) {
+        Some(right)
+    } else {
+        None
+    }
+}
+
+/// Returns `x` if `expr` is `(x & (x - 1)) == 0`.
+fn power_of_two_test(expr: &Expr) -> Option<&Expr> {
+    compared_with_zero(expr, &[|op| matches!(op, BinOp::Eq(_))]).and_then(clear_lowest_bit)
+}
+
+/// Returns `x` if `expr` is `x != 0` or `x > 0`.
+fn nonzero_guard(expr: &Expr) -> Option<&Expr> {
+    compared_with_zero(expr, &[|op| matches!(op, BinOp::Ne(_) | BinOp::Gt(_))]).map(strip_parens)
+}
+
+impl PowerOfTwoIntro {
+    /// Whether `x` may be evaluated as a receiver and is known to be unsigned.
+    fn is_candidate(&self, x: &Expr) -> bool {
+        is_pure(x) && expr_type(x, &self.types).is_some_and(|ty| is_unsigned_int(&ty))
+    }
+
+    /// Returns `x` if `guard && test` is the guarded power-of-two test on `x`.
+    fn guarded<'a>(&self, guard: &'a Expr, test: &Expr) -> Option<&'a Expr> {
+        let x = nonzero_guard(guard)?;
+        (power_of_two_test(test).is_some_and(|tested| same_tokens(x, tested))
+            && self.is_candidate(x))
+        .then_some(x)
+    }
+
+    fn rewrite(&mut self, expr: &mut Expr) -> bool {
+        if let Expr::Binary(ExprBinary {
+            left,
+            op: BinOp::And(_),
+            right,
+            ..
+        }) = expr
+        {
+            // x != 0 && (x & (x - 1)) == 0, in either order.
+            if let Some(x) = self
+                .guarded(left, right)
+                .or_else(|| self.guarded(right, left))
+            {
+                let x = x.clone();
+                *expr = parse_quote!(#x.is_power_of_two());
+                return true;
+            }
+            // ... && x != 0 && (x & (x - 1)) == 0
+            if let Expr::Binary(ExprBinary {
+                left: rest,
+                op: BinOp::And(_),
+                right: guard,
+                ..
+            }) = strip_parens(left)
+            {
+                if let Some(x) = self.guarded(guard, right) {
+                    let (rest, x) = (rest.clone(), x.clone());
+                    *expr = parse_quote!(#rest && #x.is_power_of_two());
+                    return true;
+                }
+            }
+        }
+        if let Some(x) = power_of_two_test(expr).filter(|x| self.is_candidate(x)) {
+            let x = x.clone();
+            let shown = x.to_token_stream().to_string();
+            self.diagnostics.push(Diagnostic::new(
+                PASS,
+                self.current_fn.as_ref(),
+                format!(
+                    "the power-of-two test on `{0}` also holds for zero; \
+                     rewrote it as `{0} == 0 || {0}.is_power_of_two()`",
+                    shown
+                ),
+            ));
+            *expr = parse_quote!(#x == 0 || #x.is_power_of_two());
+            return true;
+        }
+        false
+    }
+}
+
+impl VisitMut for PowerOfTwoIntro {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        let enclosing = self.current_fn.replace(item_fn.sig.ident.clone());
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        Parenthesize.visit_item_fn_mut(item_fn);
+        self.types = types;
+        self.current_fn = enclosing;
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        let enclosing = self.current_fn.replace(item_fn.sig.ident.clone());
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+        Parenthesize.visit_impl_item_fn_mut(item_fn);
+        self.types = types;
+        self.current_fn = enclosing;
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if !self.rewrite(expr) {
+            syn::visit_mut::visit_expr_mut(self, expr);
+        }
+    }
+}
+
+impl Pass for PowerOfTwoIntro {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        monad.diagnostics.append(&mut self.diagnostics);
+        Ok(monad)
+    }
+}
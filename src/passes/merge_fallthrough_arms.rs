@@ -0,0 +1,90 @@
+//! Merges consecutive `match` arms with identical bodies into a single arm with
+//! an or-pattern, as left behind by translating `switch` cases falling through to
+//! the same code, e.g. `1 => f(), 2 => f(), 3 => f(),` becomes `1 | 2 | 3 => f(),`.
+//!
+//! Only arms without guards or attributes are merged, and only if their patterns
+//! bind no variables, since every alternative of an or-pattern must bind the same
+//! ones. A wildcard arm is left on its own. Since only neighbouring arms are
+//! merged, which arm a value matches first is unchanged.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::MonadicAst;
+use syn::punctuated::Punctuated;
+use syn::visit_mut::VisitMut;
+use syn::{Arm, ExprMatch, Pat, PatOr};
+
+#[derive(Default)]
+pub struct FallthroughToOrPattern;
+
+/// Returns whether the pattern matches without binding a variable.
+fn binds_nothing(pat: &Pat) -> bool {
+    match pat {
+        Pat::Lit(_) | Pat::Range(_) | Pat::Path(_) | Pat::Wild(_) | Pat::Rest(_) => true,
+        Pat::Or(or) => or.cases.iter().all(binds_nothing),
+        Pat::Paren(paren) => binds_nothing(&paren.pat),
+        Pat::Reference(reference) => binds_nothing(&reference.pat),
+        Pat::Tuple(tuple) => tuple.elems.iter().all(binds_nothing),
+        Pat::TupleStruct(tuple_struct) => tuple_struct.elems.iter().all(binds_nothing),
+        Pat::Slice(slice) => slice.elems.iter().all(binds_nothing),
+        Pat::Struct(strukt) => strukt.fields.iter().all(|field| binds_nothing(&field.pat)),
+        _ => false,
+    }
+}
+
+/// Returns whether the arm may be merged with a neighbour with the same body.
+fn is_mergeable(arm: &Arm) -> bool {
+    arm.attrs.is_empty()
+        && arm.guard.is_none()
+        && !matches!(arm.pat, Pat::Wild(_))
+        && binds_nothing(&arm.pat)
+}
+
+/// Returns the alternatives of the pattern.
+fn cases(pat: Pat) -> Vec<Pat> {
+    match pat {
+        Pat::Or(PatOr {
+            attrs,
+            leading_vert: _,
+            cases,
+        }) if attrs.is_empty() => cases.into_iter().collect(),
+        pat => vec![pat],
+    }
+}
+
+impl VisitMut for FallthroughToOrPattern {
+    fn visit_expr_match_mut(&mut self, expr_match: &mut ExprMatch) {
+        syn::visit_mut::visit_expr_match_mut(self, expr_match);
+        let mut merged: Vec<Arm> = Vec::with_capacity(expr_match.arms.len());
+        for arm in std::mem::take(&mut expr_match.arms) {
+            match merged.last_mut() {
+                Some(previous)
+                    if is_mergeable(previous)
+                        && is_mergeable(&arm)
+                        && previous.body == arm.body =>
+                {
+                    let previous_pat =
+                        std::mem::replace(&mut previous.pat, Pat::Verbatim(Default::default()));
+                    let mut alternatives: Punctuated<Pat, _> = Punctuated::new();
+                    alternatives.extend(cases(previous_pat));
+                    alternatives.extend(cases(arm.pat));
+                    previous.pat = Pat::Or(PatOr {
+                        attrs: Vec::new(),
+                        leading_vert: None,
+                        cases: alternatives,
+                    });
+                    previous.comma = arm.comma;
+                }
+                _ => merged.push(arm),
+            }
+        }
+        expr_match.arms = merged;
+    }
+}
+
+impl Pass for FallthroughToOrPattern {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
@@ -0,0 +1,78 @@
+//! Merges the inherent `impl` blocks of a type which translation scattered over a
+//! file or module into the first of them, keeping their items in order.
+//!
+//! Blocks are only merged if they have the same self type, generics and `where`
+//! clause, and the same attributes, so that e.g. differently `cfg`-gated blocks
+//! stay apart. Trait impls are left alone. Since moving items across a macro could
+//! change what the macro sees, or invalidate its uses, no block is merged into
+//! one preceding a macro item.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::MonadicAst;
+use syn::visit_mut::VisitMut;
+use syn::{Item, ItemImpl, ItemMod};
+
+#[derive(Default)]
+pub struct MergeImplBlocks;
+
+/// Returns the block with its items removed, which is equal for blocks which may
+/// be merged.
+fn header(item_impl: &ItemImpl) -> ItemImpl {
+    ItemImpl {
+        items: Vec::new(),
+        brace_token: Default::default(),
+        ..item_impl.clone()
+    }
+}
+
+/// Merges the inherent `impl` blocks among the items.
+fn merge(items: &mut Vec<Item>) {
+    // The headers of the blocks which later ones may be merged into, with the
+    // blocks' positions.
+    let mut targets: Vec<(ItemImpl, usize)> = Vec::new();
+    let mut index = 0;
+    while index < items.len() {
+        let item_impl = match &items[index] {
+            Item::Impl(item_impl) if item_impl.trait_.is_none() => item_impl,
+            Item::Macro(_) => {
+                targets.clear();
+                index += 1;
+                continue;
+            }
+            _ => {
+                index += 1;
+                continue;
+            }
+        };
+        let header = header(item_impl);
+        let Some(&(_, target)) = targets.iter().find(|(other, _)| *other == header) else {
+            targets.push((header, index));
+            index += 1;
+            continue;
+        };
+        let Item::Impl(merged) = items.remove(index) else {
+            unreachable!();
+        };
+        if let Item::Impl(target) = &mut items[target] {
+            target.items.extend(merged.items);
+        }
+    }
+}
+
+impl VisitMut for MergeImplBlocks {
+    fn visit_item_mod_mut(&mut self, item_mod: &mut ItemMod) {
+        syn::visit_mut::visit_item_mod_mut(self, item_mod);
+        if let Some((_, items)) = &mut item_mod.content {
+            merge(items);
+        }
+    }
+}
+
+impl Pass for MergeImplBlocks {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        merge(&mut monad.ast.items);
+        Ok(monad)
+    }
+}
@@ -1,4 +1,50 @@
+pub mod annotate_spin_loops;
 pub mod convert_ffi_types;
+pub mod deduplicate_functions;
+pub mod elide_identity_wrappers;
+pub mod elide_single_arm_matches;
+pub mod flatten_tail_returns;
+pub mod fold_arithmetic_identities;
+pub mod fold_self_comparisons;
+pub mod generate_drop_impls;
+pub mod inline_single_call_fns;
+pub mod introduce_abs;
+pub mod introduce_clamp;
+pub mod introduce_is_empty;
+pub mod introduce_is_power_of_two;
+pub mod merge_fallthrough_arms;
+pub mod merge_impl_blocks;
+pub mod normalize_guard_polarity;
+pub mod promote_static_counters;
+pub mod recognize_known_algorithms;
 pub mod remove_dangling_identifiers;
+pub mod remove_dead_inits;
+pub mod remove_redundant_unsafe;
+pub mod remove_unit_let_bindings;
+pub mod replace_allocations;
+pub mod replace_bounds_checks;
+pub mod replace_box_field_inits;
+pub mod replace_branch_assignments;
+pub mod replace_byte_string_loops;
+pub mod replace_byte_transmutes;
+pub mod replace_char_buffers;
+pub mod replace_const_fns;
+pub mod replace_error_checks;
+pub mod replace_fill_loops;
+pub mod replace_find_loops;
+pub mod replace_flag_loops;
+pub mod replace_for_each_loops;
+pub mod replace_in_place_updates;
+pub mod replace_is_some_unwrap;
+pub mod replace_map_lookups;
+pub mod replace_mem_replace;
+pub mod replace_memcpy_calls;
+pub mod replace_pairwise_loops;
+pub mod replace_prefix_loops;
+pub mod replace_qsort;
 pub mod replace_raw_pointers;
+pub mod replace_reverse_index_loops;
+pub mod replace_trailing_break_loops;
 pub mod replace_while_loop;
+pub mod simplify_string_building;
+pub mod utils;
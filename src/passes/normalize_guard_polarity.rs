@@ -0,0 +1,123 @@
+//! Simplifies negated conditions, such as the guards translated code writes as
+//! `if !(x > 0) { return; }`, into their positive form `if x <= 0 { return; }`.
+//!
+//! A negated comparison is inverted, `!!e` becomes `e`, and `!(a && b)` and
+//! `!(a || b)` are rewritten by De Morgan's laws when both operands can themselves
+//! be simplified. The control flow is left as it is.
+//!
+//! `!(a < b)` is not `a >= b` when either operand is NaN, so ordering comparisons
+//! are only inverted when an operand is known to be of a totally ordered type.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{
+    expr_type, is_totally_ordered, local_types, strip_parens, Parenthesize,
+};
+use crate::MonadicAst;
+use std::collections::HashMap;
+use syn::visit_mut::VisitMut;
+use syn::{
+    BinOp, Expr, ExprBinary, ExprLit, ExprUnary, Ident, ImplItemFn, ItemFn, Lit, Type, UnOp,
+};
+
+#[derive(Default)]
+pub struct NormalizeGuardPolarity {
+    /// Declared types of the locals of the function currently being visited.
+    types: HashMap<Ident, Type>,
+}
+
+impl NormalizeGuardPolarity {
+    /// Returns whether the comparison of `left` with `right` is totally ordered.
+    fn is_ordered(&self, left: &Expr, right: &Expr) -> bool {
+        [left, right].into_iter().any(|operand| {
+            matches!(
+                strip_parens(operand),
+                Expr::Lit(ExprLit {
+                    lit: Lit::Int(_),
+                    ..
+                })
+            ) || expr_type(operand, &self.types).is_some_and(|ty| is_totally_ordered(&ty))
+        })
+    }
+
+    /// Returns the simplified negation of `expr`, if it has one.
+    fn negation(&self, expr: &Expr) -> Option<Expr> {
+        match strip_parens(expr) {
+            Expr::Unary(ExprUnary {
+                op: UnOp::Not(_),
+                expr,
+                ..
+            }) => Some(strip_parens(expr).clone()),
+            Expr::Binary(binary) => {
+                let ExprBinary {
+                    left, op, right, ..
+                } = binary;
+                let op = match op {
+                    BinOp::Eq(_) => BinOp::Ne(Default::default()),
+                    BinOp::Ne(_) => BinOp::Eq(Default::default()),
+                    BinOp::Lt(_) if self.is_ordered(left, right) => BinOp::Ge(Default::default()),
+                    BinOp::Le(_) if self.is_ordered(left, right) => BinOp::Gt(Default::default()),
+                    BinOp::Gt(_) if self.is_ordered(left, right) => BinOp::Le(Default::default()),
+                    BinOp::Ge(_) if self.is_ordered(left, right) => BinOp::Lt(Default::default()),
+                    BinOp::And(_) | BinOp::Or(_) => {
+                        let left = self.negation(left)?;
+                        let right = self.negation(right)?;
+                        let op = match op {
+                            BinOp::And(_) => BinOp::Or(Default::default()),
+                            _ => BinOp::And(Default::default()),
+                        };
+                        return Some(Expr::Binary(ExprBinary {
+                            left: Box::new(left),
+                            op,
+                            right: Box::new(right),
+                            ..binary.clone()
+                        }));
+                    }
+                    _ => return None,
+                };
+                Some(Expr::Binary(ExprBinary {
+                    op,
+                    ..binary.clone()
+                }))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl VisitMut for NormalizeGuardPolarity {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        syn::visit_mut::visit_expr_mut(self, expr);
+        if let Expr::Unary(ExprUnary {
+            op: UnOp::Not(_),
+            expr: inner,
+            ..
+        }) = expr
+        {
+            if let Some(simplified) = self.negation(inner) {
+                *expr = simplified;
+            }
+        }
+    }
+}
+
+impl Pass for NormalizeGuardPolarity {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        // The simplified conditions may bind more loosely than the negation did.
+        Parenthesize.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
@@ -0,0 +1,372 @@
+//! Promotes `static mut` integers which are only read, assigned and updated in
+//! place into atomics, e.g.
+//!
+//! ```ignore
+//! static mut COUNTER: i32 = 0;
+//!
+//! fn next_id() -> i32 {
+//!     unsafe {
+//!         COUNTER += 1;
+//!         COUNTER
+//!     }
+//! }
+//! ```
+//!
+//! becomes `static COUNTER: AtomicI32 = AtomicI32::new(0);`, with the increment
+//! turned into `COUNTER.fetch_add(1, Ordering::Relaxed);` and the read into
+//! `COUNTER.load(Ordering::Relaxed)`. Compound assignments map to the `fetch_*`
+//! operations, and `COUNTER = COUNTER + k` is treated like `COUNTER += k`. An
+//! `unsafe` block left wrapping only atomic operations is removed.
+//!
+//! The static must not be borrowed, used as a method receiver or mentioned inside
+//! of a macro, and it may only be assigned by expression statements. Note that the
+//! atomic operations wrap on overflow, where the integer operations panic in debug
+//! builds.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{
+    count_ident, is_assign_op, is_ident, is_signed_int, is_unsigned_int, path_ident, primitive_name,
+};
+use crate::MonadicAst;
+use std::collections::HashSet;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, BinOp, Block, Expr, ExprAssign, ExprBinary, ExprLit, ExprMethodCall, ExprUnary,
+    ExprUnsafe, File, Ident, Item, ItemStatic, Lit, StaticMutability, Stmt, UnOp,
+};
+
+/// The memory ordering of the atomic operations replacing accesses of a static.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum AtomicOrdering {
+    /// Only the operations on the static itself are ordered, which is all the
+    /// unsynchronized original guaranteed.
+    #[default]
+    Relaxed,
+    SeqCst,
+}
+
+#[derive(Default)]
+pub struct StaticMutToAtomic {
+    ordering: AtomicOrdering,
+    /// The statics promoted so far.
+    promoted: HashSet<Ident>,
+    /// The `static mut` items left as they are, whose accesses stay unsafe.
+    remaining: HashSet<Ident>,
+}
+
+/// Returns the atomic type replacing the integer type `ty`, e.g. `AtomicI32`.
+fn atomic_type(ty: &syn::Type) -> Option<Ident> {
+    let name = primitive_name(ty)?;
+    if !(is_signed_int(ty) || is_unsigned_int(ty)) || name.ends_with("128") {
+        return None;
+    }
+    let mut chars = name.chars();
+    let first = chars.next()?.to_ascii_uppercase();
+    Some(quote::format_ident!("Atomic{}{}", first, chars.as_str()))
+}
+
+/// Returns whether `expr` is an integer literal, possibly negated.
+fn is_int_literal(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(_), ..
+        }) => true,
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        }) => is_int_literal(expr),
+        _ => false,
+    }
+}
+
+/// Returns the `fetch_*` operation performing the compound assignment `op`.
+fn fetch_method(op: &BinOp) -> Option<&'static str> {
+    Some(match op {
+        BinOp::AddAssign(_) | BinOp::Add(_) => "fetch_add",
+        BinOp::SubAssign(_) | BinOp::Sub(_) => "fetch_sub",
+        BinOp::BitAndAssign(_) | BinOp::BitAnd(_) => "fetch_and",
+        BinOp::BitOrAssign(_) | BinOp::BitOr(_) => "fetch_or",
+        BinOp::BitXorAssign(_) | BinOp::BitXor(_) => "fetch_xor",
+        _ => return None,
+    })
+}
+
+/// Rewrites the accesses of one static into atomic operations, counting them, and
+/// noting any access which cannot be expressed.
+struct AccessRewriter<'a> {
+    name: &'a Ident,
+    ordering: Expr,
+    rewritten: usize,
+    unsupported: bool,
+}
+
+impl AccessRewriter<'_> {
+    fn operation(&self, method: &str, value: Option<Expr>) -> Expr {
+        let name = self.name;
+        let method = quote::format_ident!("{}", method);
+        let ordering = &self.ordering;
+        match value {
+            Some(value) => parse_quote!(#name.#method(#value, #ordering)),
+            None => parse_quote!(#name.#method(#ordering)),
+        }
+    }
+
+    /// Returns the atomic operation performing the assignment `expr`, if it
+    /// assigns the static.
+    fn assignment(&mut self, expr: &Expr) -> Option<Expr> {
+        match expr {
+            Expr::Assign(ExprAssign { left, right, .. }) if is_ident(left, self.name) => {
+                // STATIC = STATIC + k
+                if let Expr::Binary(ExprBinary {
+                    left: operand,
+                    op,
+                    right: value,
+                    ..
+                }) = right.as_ref()
+                {
+                    let method = fetch_method(op).filter(|_| is_ident(operand, self.name));
+                    if let Some(method) = method {
+                        if count_ident(value.as_ref(), self.name) == 0 {
+                            self.rewritten += 2;
+                            let mut value = value.as_ref().clone();
+                            self.visit_expr_mut(&mut value);
+                            return Some(self.operation(method, Some(value)));
+                        }
+                    }
+                }
+                self.rewritten += 1;
+                let mut value = right.as_ref().clone();
+                self.visit_expr_mut(&mut value);
+                Some(self.operation("store", Some(value)))
+            }
+            Expr::Binary(ExprBinary {
+                left, op, right, ..
+            }) if is_assign_op(op) && is_ident(left, self.name) => {
+                let method = fetch_method(op)?;
+                self.rewritten += 1;
+                let mut value = right.as_ref().clone();
+                self.visit_expr_mut(&mut value);
+                Some(self.operation(method, Some(value)))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl VisitMut for AccessRewriter<'_> {
+    fn visit_stmt_mut(&mut self, stmt: &mut Stmt) {
+        if let Stmt::Expr(expr, semi) = stmt {
+            if let Some(operation) = self.assignment(expr) {
+                *expr = operation;
+                *semi = Some(Default::default());
+                return;
+            }
+        }
+        syn::visit_mut::visit_stmt_mut(self, stmt);
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Assign(ExprAssign { left, .. }) if is_ident(left, self.name) => {
+                self.unsupported = true;
+            }
+            Expr::Binary(ExprBinary { left, op, .. })
+                if is_assign_op(op) && is_ident(left, self.name) =>
+            {
+                self.unsupported = true;
+            }
+            Expr::Reference(reference) if is_ident(&reference.expr, self.name) => {
+                self.unsupported = true;
+            }
+            Expr::MethodCall(ExprMethodCall { receiver, .. }) if is_ident(receiver, self.name) => {
+                self.unsupported = true;
+            }
+            expr if is_ident(expr, self.name) => {
+                self.rewritten += 1;
+                *expr = self.operation("load", None);
+            }
+            expr => syn::visit_mut::visit_expr_mut(self, expr),
+        }
+    }
+}
+
+impl StaticMutToAtomic {
+    pub fn new(ordering: AtomicOrdering) -> Self {
+        Self {
+            ordering,
+            promoted: HashSet::new(),
+            remaining: HashSet::new(),
+        }
+    }
+
+    fn ordering(&self) -> Expr {
+        match self.ordering {
+            AtomicOrdering::Relaxed => parse_quote!(std::sync::atomic::Ordering::Relaxed),
+            AtomicOrdering::SeqCst => parse_quote!(std::sync::atomic::Ordering::SeqCst),
+        }
+    }
+
+    /// Promotes the static at `index` if every access of it can be rewritten.
+    fn promote(&mut self, file: &mut File, index: usize) {
+        let Item::Static(ItemStatic {
+            attrs,
+            vis,
+            mutability: StaticMutability::Mut(_),
+            ident,
+            ty,
+            expr,
+            ..
+        }) = &file.items[index]
+        else {
+            return;
+        };
+        let Some(atomic) = atomic_type(ty) else {
+            return;
+        };
+        if !is_int_literal(expr) {
+            return;
+        }
+        let name = ident.clone();
+        let promoted: Item = parse_quote! {
+            #(#attrs)*
+            #vis static #name: std::sync::atomic::#atomic = std::sync::atomic::#atomic::new(#expr);
+        };
+
+        let mut rewritten = file.clone();
+        rewritten.items.remove(index);
+        let mut rewriter = AccessRewriter {
+            name: &name,
+            ordering: self.ordering(),
+            rewritten: 0,
+            unsupported: false,
+        };
+        rewriter.visit_file_mut(&mut rewritten);
+        // Accesses which were not rewritten, e.g. inside of macros, keep the static.
+        if rewriter.unsupported || rewriter.rewritten != count_ident(file, &name) - 1 {
+            return;
+        }
+        rewritten.items.insert(index, promoted);
+        *file = rewritten;
+        self.promoted.insert(name);
+    }
+
+    /// Returns whether evaluating `expr` needs no unsafety: it only applies operators
+    /// to literals, locals and atomic operations, and reads no remaining `static mut`.
+    fn is_safe_operand(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Lit(_) => true,
+            Expr::Path(path) => path
+                .path
+                .get_ident()
+                .map_or(path.path.segments.len() > 1, |name| {
+                    !self.remaining.contains(name)
+                }),
+            Expr::Paren(paren) => self.is_safe_operand(&paren.expr),
+            Expr::Cast(cast) => self.is_safe_operand(&cast.expr),
+            Expr::Unary(unary) => {
+                !matches!(unary.op, UnOp::Deref(_)) && self.is_safe_operand(&unary.expr)
+            }
+            Expr::Binary(binary) => {
+                !is_assign_op(&binary.op)
+                    && self.is_safe_operand(&binary.left)
+                    && self.is_safe_operand(&binary.right)
+            }
+            expr => self.is_atomic_operation(expr),
+        }
+    }
+
+    /// Returns whether `expr` is an atomic operation on a promoted static, whose
+    /// operands need no unsafety either.
+    fn is_atomic_operation(&self, expr: &Expr) -> bool {
+        let Expr::MethodCall(ExprMethodCall {
+            receiver,
+            method,
+            args,
+            ..
+        }) = expr
+        else {
+            return false;
+        };
+        let method = method.to_string();
+        (method == "load" || method == "store" || method.starts_with("fetch_"))
+            && path_ident(receiver).is_some_and(|name| self.promoted.contains(name))
+            && args.iter().all(|arg| self.is_safe_operand(arg))
+    }
+
+    /// Returns whether the `unsafe` block only performs atomic operations.
+    fn is_redundant(&self, unsafe_expr: &ExprUnsafe) -> bool {
+        !unsafe_expr.block.stmts.is_empty()
+            && unsafe_expr
+                .block
+                .stmts
+                .iter()
+                .all(|stmt| matches!(stmt, Stmt::Expr(expr, _) if self.is_atomic_operation(expr)))
+    }
+}
+
+/// Removes the `unsafe` blocks left wrapping only atomic operations.
+struct UnsafeElision<'a>(&'a StaticMutToAtomic);
+
+impl VisitMut for UnsafeElision<'_> {
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        syn::visit_mut::visit_block_mut(self, block);
+        let last = block.stmts.len().saturating_sub(1);
+        let mut stmts = Vec::with_capacity(block.stmts.len());
+        for (position, stmt) in std::mem::take(&mut block.stmts).into_iter().enumerate() {
+            match stmt {
+                Stmt::Expr(Expr::Unsafe(unsafe_expr), semi)
+                    if self.0.is_redundant(&unsafe_expr) =>
+                {
+                    let mut inner = unsafe_expr.block.stmts;
+                    // The block's value is discarded unless it is the tail.
+                    if position != last || semi.is_some() {
+                        if let Some(Stmt::Expr(_, inner_semi @ None)) = inner.last_mut() {
+                            *inner_semi = Some(Default::default());
+                        }
+                    }
+                    stmts.extend(inner);
+                }
+                stmt => stmts.push(stmt),
+            }
+        }
+        block.stmts = stmts;
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        syn::visit_mut::visit_expr_mut(self, expr);
+        if let Expr::Unsafe(unsafe_expr) = expr {
+            if let [Stmt::Expr(inner, None)] = unsafe_expr.block.stmts.as_slice() {
+                if self.0.is_atomic_operation(inner) {
+                    *expr = inner.clone();
+                }
+            }
+        }
+    }
+}
+
+impl Pass for StaticMutToAtomic {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        for index in 0..monad.ast.items.len() {
+            self.promote(&mut monad.ast, index);
+        }
+        if !self.promoted.is_empty() {
+            self.remaining = monad
+                .ast
+                .items
+                .iter()
+                .filter_map(|item| match item {
+                    Item::Static(ItemStatic {
+                        mutability: StaticMutability::Mut(_),
+                        ident,
+                        ..
+                    }) => Some(ident.clone()),
+                    _ => None,
+                })
+                .collect();
+            UnsafeElision(self).visit_file_mut(&mut monad.ast);
+        }
+        Ok(monad)
+    }
+}
@@ -0,0 +1,152 @@
+//! Reports functions which likely implement a well-known algorithm, so that a
+//! human can replace the translated byte-mixing loop with a crate. The math is
+//! left as it is.
+//!
+//! Algorithms are recognized by a loop in the function together with their
+//! characteristic constants:
+//!
+//! - FNV-1a and FNV-1, by the offset basis and the prime of the 32- or 64-bit
+//!   variant, e.g. `0x811c9dc5` and `0x01000193`. The order of the `^` and the `*`
+//!   in the loop tells the two apart.
+//! - djb2, by the initial value `5381` and a multiplication by `33`, possibly
+//!   written as `(h << 5) + h`.
+//! - CRC-32, by the reflected or normal polynomial, `0xedb88320` or `0x04c11db7`.
+
+use crate::monad::ast::Pass;
+use crate::monad::diagnostic::Diagnostic;
+use crate::monad::error::PassError;
+use crate::passes::utils::is_int_lit;
+use crate::MonadicAst;
+use syn::visit::Visit;
+use syn::{BinOp, Block, Expr, ExprBinary, ExprLit, Ident, ImplItemFn, ItemFn, Lit};
+
+const PASS: &str = "recognize_known_algorithms";
+
+/// The offset basis and prime of the 32- and 64-bit FNV hashes.
+const FNV: &[(u128, u128, u32)] = &[
+    (0x811c9dc5, 0x01000193, 32),
+    (0xcbf29ce484222325, 0x100000001b3, 64),
+];
+
+const DJB2_INIT: u128 = 5381;
+
+/// The reflected and normal CRC-32 polynomials.
+const CRC32_POLYNOMIALS: &[u128] = &[0xedb88320, 0x04c11db7];
+
+#[derive(Default)]
+pub struct RecognizeKnownAlgorithms {
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// The features of a function body its algorithm is recognized by.
+#[derive(Default)]
+struct Fingerprint {
+    /// The integer literals, in order of appearance.
+    literals: Vec<u128>,
+    has_loop: bool,
+    /// Whether a value is multiplied by 33 or shifted left by 5.
+    times_33: bool,
+    /// The operators `^` and `*`, or `wrapping_mul`, in order of appearance within
+    /// loops.
+    mixing: Vec<char>,
+    loop_depth: usize,
+}
+
+impl Visit<'_> for Fingerprint {
+    fn visit_expr(&mut self, expr: &Expr) {
+        let is_loop = matches!(expr, Expr::ForLoop(_) | Expr::While(_) | Expr::Loop(_));
+        self.has_loop |= is_loop;
+        match expr {
+            Expr::Lit(ExprLit {
+                lit: Lit::Int(int), ..
+            }) => self.literals.extend(int.base10_parse::<u128>().ok()),
+            Expr::Binary(ExprBinary {
+                left, op, right, ..
+            }) => {
+                let operand_33 = is_int_lit(left, 33) || is_int_lit(right, 33);
+                self.times_33 |= match op {
+                    BinOp::Mul(_) | BinOp::MulAssign(_) => operand_33,
+                    BinOp::Shl(_) | BinOp::ShlAssign(_) => is_int_lit(right, 5),
+                    _ => false,
+                };
+                if self.loop_depth > 0 {
+                    match op {
+                        BinOp::BitXor(_) | BinOp::BitXorAssign(_) => self.mixing.push('^'),
+                        BinOp::Mul(_) | BinOp::MulAssign(_) => self.mixing.push('*'),
+                        _ => {}
+                    }
+                }
+            }
+            Expr::MethodCall(call) if call.method == "wrapping_mul" => {
+                self.times_33 |= call.args.iter().any(|arg| is_int_lit(arg, 33));
+                if self.loop_depth > 0 {
+                    self.mixing.push('*');
+                }
+            }
+            _ => {}
+        }
+        self.loop_depth += usize::from(is_loop);
+        syn::visit::visit_expr(self, expr);
+        self.loop_depth -= usize::from(is_loop);
+    }
+}
+
+impl Fingerprint {
+    /// Returns a description of the algorithm the function likely implements.
+    fn algorithm(&self) -> Option<String> {
+        if !self.has_loop {
+            return None;
+        }
+        let has = |value: u128| self.literals.contains(&value);
+        for &(basis, prime, bits) in FNV {
+            if has(basis) && has(prime) {
+                let variant = match self.mixing.first() {
+                    Some('*') => "FNV-1",
+                    _ => "FNV-1a",
+                };
+                return Some(format!(
+                    "likely implements the {bits}-bit {variant} hash; consider the `fnv` crate"
+                ));
+            }
+        }
+        if has(DJB2_INIT) && self.times_33 {
+            return Some("likely implements the djb2 string hash".to_string());
+        }
+        if let Some(polynomial) = CRC32_POLYNOMIALS.iter().find(|&&value| has(value)) {
+            return Some(format!(
+                "likely computes a CRC-32 with the polynomial {polynomial:#010x}; consider the \
+                 `crc32fast` crate"
+            ));
+        }
+        None
+    }
+}
+
+impl RecognizeKnownAlgorithms {
+    fn recognize(&mut self, ident: &Ident, block: &Block) {
+        let mut fingerprint = Fingerprint::default();
+        fingerprint.visit_block(block);
+        if let Some(algorithm) = fingerprint.algorithm() {
+            self.diagnostics
+                .push(Diagnostic::new(PASS, Some(ident), algorithm));
+        }
+    }
+}
+
+impl Visit<'_> for RecognizeKnownAlgorithms {
+    fn visit_item_fn(&mut self, item_fn: &ItemFn) {
+        self.recognize(&item_fn.sig.ident, &item_fn.block);
+    }
+
+    fn visit_impl_item_fn(&mut self, item_fn: &ImplItemFn) {
+        self.recognize(&item_fn.sig.ident, &item_fn.block);
+    }
+}
+
+impl Pass for RecognizeKnownAlgorithms {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file(&monad.ast);
+        monad.diagnostics.append(&mut self.diagnostics);
+        Ok(monad)
+    }
+}
@@ -3,6 +3,7 @@
 //! Removes useless identifier expressions, e.g. x;
 
 use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
 use crate::MonadicAst;
 use syn::visit_mut::VisitMut;
 use syn::{Block, Expr, ExprPath, PathArguments, Stmt};
@@ -14,15 +15,14 @@ impl VisitMut for IdentifierExpressionRemover {
     fn visit_block_mut(&mut self, block: &mut Block) {
         let mut to_remove = Vec::new();
         for (i, statement) in block.stmts.iter_mut().enumerate() {
-            if let Stmt::Expr(Expr::Path(ExprPath { attrs, qself, path }), semicolon) = statement {
+            if let Stmt::Expr(Expr::Path(ExprPath { attrs, qself, path }), Some(_)) = statement {
                 if attrs.is_empty()
                     && qself.is_none()
                     && path.leading_colon.is_none()
                     && path.segments.len() == 1
+                    && path.segments[0].arguments == PathArguments::None
                 {
-                    if path.segments[0].arguments == PathArguments::None {
-                        to_remove.push(i);
-                    }
+                    to_remove.push(i);
                 }
             }
         }
@@ -38,8 +38,8 @@ impl VisitMut for IdentifierExpressionRemover {
 }
 
 impl Pass for IdentifierExpressionRemover {
-    fn bind(&mut self, mut monad: MonadicAst) -> MonadicAst {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
         self.visit_file_mut(&mut monad.ast);
-        monad
+        Ok(monad)
     }
 }
@@ -0,0 +1,109 @@
+//! Removes initial values which are overwritten before they are read, as in
+//! translated declarations such as `let mut x = 0; x = compute();`, which become
+//! `let x = compute();`.
+//!
+//! The assignment must directly follow the declaration and may not read the
+//! variable itself, and the dropped initializer must be free of side effects. The
+//! variable stays `mut` only if it is later assigned to, borrowed mutably, or used
+//! as the receiver of a method call.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{count_ident, is_ident, is_pure, is_reassigned, is_receiver};
+use crate::MonadicAst;
+use syn::token::Eq;
+use syn::visit_mut::VisitMut;
+use syn::{Block, Expr, ExprAssign, Ident, Local, LocalInit, Pat, PatIdent, PatType, Stmt};
+
+#[derive(Default)]
+pub struct RemoveDeadInit;
+
+/// Returns the ident pattern of the declaration `let mut x = <pure>;`, with or
+/// without a type and an initializer.
+fn declared(local: &mut Local) -> Option<&mut PatIdent> {
+    let dead = match &local.init {
+        None => true,
+        Some(LocalInit {
+            expr,
+            diverge: None,
+            ..
+        }) => is_pure(expr),
+        Some(_) => false,
+    };
+    let pat = match &mut local.pat {
+        Pat::Type(PatType { pat, .. }) => pat.as_mut(),
+        pat => pat,
+    };
+    match pat {
+        Pat::Ident(
+            pat @ PatIdent {
+                by_ref: None,
+                mutability: Some(_),
+                subpat: None,
+                ..
+            },
+        ) if dead => Some(pat),
+        _ => None,
+    }
+}
+
+/// Returns the assigned value if the statement is `ident = value;`, and the value
+/// does not read `ident`.
+fn overwrite(stmt: &Stmt, ident: &Ident) -> Option<Expr> {
+    match stmt {
+        Stmt::Expr(
+            Expr::Assign(ExprAssign {
+                attrs, left, right, ..
+            }),
+            Some(_),
+        ) if attrs.is_empty() && is_ident(left, ident) && count_ident(right, ident) == 0 => {
+            Some(right.as_ref().clone())
+        }
+        _ => None,
+    }
+}
+
+impl RemoveDeadInit {
+    fn rewrite_block(block: &mut Block) {
+        let mut position = 0;
+        while position + 1 < block.stmts.len() {
+            let (head, rest) = block.stmts.split_at_mut(position + 1);
+            let Stmt::Local(local) = &mut head[position] else {
+                position += 1;
+                continue;
+            };
+            let Some(pat) = declared(local) else {
+                position += 1;
+                continue;
+            };
+            let Some(value) = overwrite(&rest[0], &pat.ident) else {
+                position += 1;
+                continue;
+            };
+            if !is_reassigned(&rest[1..], &pat.ident) && !is_receiver(&rest[1..], &pat.ident) {
+                pat.mutability = None;
+            }
+            local.init = Some(LocalInit {
+                eq_token: Eq::default(),
+                expr: Box::new(value),
+                diverge: None,
+            });
+            block.stmts.remove(position + 1);
+            position += 1;
+        }
+    }
+}
+
+impl VisitMut for RemoveDeadInit {
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        syn::visit_mut::visit_block_mut(self, block);
+        Self::rewrite_block(block);
+    }
+}
+
+impl Pass for RemoveDeadInit {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
@@ -0,0 +1,431 @@
+//! Unwraps the `unsafe` blocks left performing no unsafe operation, e.g. once
+//! [replace_raw_pointers](crate::passes::replace_raw_pointers) has made the
+//! dereferenced pointers references, and reports the `unsafe fn`s whose body
+//! performs none either.
+//!
+//! Without type information, an operation is only deemed safe when it evidently
+//! is, so `unsafe` is kept for:
+//! * dereferences of anything but references and variables declared with a type
+//!   other than a raw pointer,
+//! * calls of anything but the safe functions of the file, `default`, `Box::new`
+//!   and the like, and tuple struct or variant constructors, which keeps FFI calls
+//!   and `transmute`,
+//! * calls of the unsafe methods of the file and of raw pointers, like `offset`,
+//! * uses of `static mut`s and foreign statics, fields of unions and macros other
+//!   than the standard formatting and assertion ones.
+//!
+//! Dropping the qualifier of an `unsafe fn` changes its signature, and so is only
+//! reported.
+
+use crate::monad::ast::Pass;
+use crate::monad::diagnostic::Diagnostic;
+use crate::monad::error::PassError;
+use crate::passes::utils::{local_types, path_ident, strip_parens, Parenthesize};
+use crate::MonadicAst;
+use std::collections::{HashMap, HashSet};
+use syn::punctuated::Punctuated;
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::{
+    Block, Expr, ExprBlock, ExprCall, ExprField, ExprMethodCall, ExprPath, ExprUnary, ExprUnsafe,
+    File, ForeignItem, Ident, ImplItem, ImplItemFn, Item, ItemFn, ItemImpl, Macro, Member, Pat,
+    PatIdent, PatType, Signature, StaticMutability, Stmt, Token, Type, UnOp,
+};
+
+const PASS: &str = "remove_redundant_unsafe";
+
+/// The unsafe methods of raw pointers.
+const RAW_POINTER_METHODS: &[&str] = &[
+    "add",
+    "as_mut",
+    "as_ref",
+    "byte_add",
+    "byte_offset",
+    "byte_sub",
+    "copy_from",
+    "copy_from_nonoverlapping",
+    "copy_to",
+    "copy_to_nonoverlapping",
+    "drop_in_place",
+    "offset",
+    "offset_from",
+    "read",
+    "read_unaligned",
+    "read_volatile",
+    "replace",
+    "sub",
+    "swap",
+    "write",
+    "write_bytes",
+    "write_unaligned",
+    "write_volatile",
+];
+
+/// The standard macros whose arguments are expressions, and which are safe if those
+/// are.
+const SAFE_MACROS: &[&str] = &[
+    "assert",
+    "assert_eq",
+    "assert_ne",
+    "debug_assert",
+    "debug_assert_eq",
+    "debug_assert_ne",
+    "eprint",
+    "eprintln",
+    "format",
+    "matches",
+    "panic",
+    "print",
+    "println",
+    "unreachable",
+    "vec",
+    "write",
+    "writeln",
+];
+
+#[derive(Default)]
+pub struct RedundantUnsafeRemover {
+    /// The functions of the file not declared `unsafe`, by name.
+    safe_fns: HashSet<Ident>,
+    /// The functions and methods of the file declared or imported as `unsafe`, by
+    /// name.
+    unsafe_fns: HashSet<Ident>,
+    /// The `static mut`s and foreign statics of the file.
+    unsafe_statics: HashSet<Ident>,
+    /// The names of the fields of the unions of the file.
+    union_fields: HashSet<Ident>,
+    /// Declared types of the locals of the function currently being visited.
+    types: HashMap<Ident, Type>,
+    /// Whether the methods being visited implement a trait, whose declaration
+    /// decides whether they are `unsafe`.
+    in_trait_impl: bool,
+    /// Whether an `unsafe` block was replaced with the expression it contained.
+    spliced: bool,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Finds whether code performs an unsafe operation, outside of the `unsafe` blocks
+/// it contains.
+struct UnsafeOperations<'a> {
+    remover: &'a RedundantUnsafeRemover,
+    found: bool,
+}
+
+impl RedundantUnsafeRemover {
+    fn record_items(&mut self, ast: &File) {
+        for item in &ast.items {
+            match item {
+                Item::Fn(item_fn) => self.record_fn(&item_fn.sig),
+                Item::Impl(ItemImpl { items, .. }) => {
+                    for item in items {
+                        if let ImplItem::Fn(item_fn) = item {
+                            self.record_fn(&item_fn.sig);
+                        }
+                    }
+                }
+                Item::ForeignMod(foreign) => {
+                    for item in &foreign.items {
+                        match item {
+                            ForeignItem::Fn(item_fn) => {
+                                self.unsafe_fns.insert(item_fn.sig.ident.clone());
+                            }
+                            ForeignItem::Static(item_static) => {
+                                self.unsafe_statics.insert(item_static.ident.clone());
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                Item::Static(item_static)
+                    if matches!(item_static.mutability, StaticMutability::Mut(_)) =>
+                {
+                    self.unsafe_statics.insert(item_static.ident.clone());
+                }
+                Item::Union(item_union) => self.union_fields.extend(
+                    item_union
+                        .fields
+                        .named
+                        .iter()
+                        .filter_map(|f| f.ident.clone()),
+                ),
+                _ => {}
+            }
+        }
+        // A name declared both ways might refer to either.
+        self.safe_fns.retain(|name| !self.unsafe_fns.contains(name));
+    }
+
+    fn record_fn(&mut self, sig: &Signature) {
+        if sig.unsafety.is_some() {
+            self.unsafe_fns.insert(sig.ident.clone());
+        } else {
+            self.safe_fns.insert(sig.ident.clone());
+        }
+    }
+
+    /// Returns whether the statements perform an unsafe operation.
+    fn is_unsafe(&self, stmts: &[Stmt]) -> bool {
+        let mut operations = UnsafeOperations {
+            remover: self,
+            found: false,
+        };
+        stmts.iter().for_each(|stmt| operations.visit_stmt(stmt));
+        operations.found
+    }
+
+    /// Reports an `unsafe fn` which performs no unsafe operation.
+    fn report_if_redundant(&mut self, sig: &Signature, block: &Block) {
+        if sig.unsafety.is_some() && !self.is_unsafe(&block.stmts) {
+            self.diagnostics.push(Diagnostic::new(
+                PASS,
+                Some(&sig.ident),
+                "performs no unsafe operation; the `unsafe` qualifier could be dropped \
+                 once its callers no longer need it",
+            ));
+        }
+    }
+
+    fn is_safe_call(&self, func: &Expr) -> bool {
+        let Expr::Path(ExprPath {
+            qself: None, path, ..
+        }) = strip_parens(func)
+        else {
+            return false;
+        };
+        let Some(last) = path.segments.last() else {
+            return false;
+        };
+        let local = path.segments.len() == 1 || path.segments[0].ident == "Self";
+        let name = last.ident.to_string();
+        let segments: Vec<String> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+        let std_constructor = matches!(
+            segments
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .as_slice(),
+            ["Box" | "Vec" | "String", "new" | "with_capacity" | "from"]
+        );
+        (local && self.safe_fns.contains(&last.ident))
+            || std_constructor
+            || name == "default"
+            || name.starts_with(|c: char| c.is_ascii_uppercase())
+    }
+
+    fn is_safe_method(&self, method: &Ident) -> bool {
+        let name = method.to_string();
+        !self.unsafe_fns.contains(method)
+            && !RAW_POINTER_METHODS.contains(&name.as_str())
+            && !name.contains("unchecked")
+    }
+
+    /// Returns whether `*expr` evidently dereferences a reference or a smart pointer.
+    fn is_safe_deref(&self, expr: &Expr) -> bool {
+        match strip_parens(expr) {
+            Expr::Reference(_) => true,
+            expr => path_ident(expr)
+                .and_then(|ident| self.types.get(ident))
+                .is_some_and(|ty| !matches!(ty, Type::Ptr(_))),
+        }
+    }
+}
+
+/// Returns the declared types of a function's parameters and annotated locals, less
+/// those whose name is also bound without a type, e.g. by `let p = p as *mut T;`,
+/// which may have any type.
+fn declared_types(sig: &Signature, block: &Block) -> HashMap<Ident, Type> {
+    #[derive(Default)]
+    struct Unannotated(HashSet<Ident>);
+    impl Visit<'_> for Unannotated {
+        fn visit_pat_type(&mut self, pat_type: &PatType) {
+            if !matches!(*pat_type.pat, Pat::Ident(_)) {
+                self.visit_pat(&pat_type.pat);
+            }
+        }
+
+        fn visit_pat_ident(&mut self, pat_ident: &PatIdent) {
+            self.0.insert(pat_ident.ident.clone());
+            syn::visit::visit_pat_ident(self, pat_ident)
+        }
+    }
+    let mut unannotated = Unannotated::default();
+    unannotated.visit_signature(sig);
+    unannotated.visit_block(block);
+    let mut types = local_types(sig, block);
+    types.retain(|ident, _| !unannotated.0.contains(ident));
+    types
+}
+
+impl Visit<'_> for UnsafeOperations<'_> {
+    fn visit_expr_unary(&mut self, unary: &'_ ExprUnary) {
+        if matches!(unary.op, UnOp::Deref(_)) && !self.remover.is_safe_deref(&unary.expr) {
+            self.found = true;
+        }
+        syn::visit::visit_expr_unary(self, unary)
+    }
+
+    fn visit_expr_call(&mut self, call: &'_ ExprCall) {
+        if !self.remover.is_safe_call(&call.func) {
+            self.found = true;
+        }
+        syn::visit::visit_expr_call(self, call)
+    }
+
+    fn visit_expr_method_call(&mut self, call: &'_ ExprMethodCall) {
+        if !self.remover.is_safe_method(&call.method) {
+            self.found = true;
+        }
+        syn::visit::visit_expr_method_call(self, call)
+    }
+
+    fn visit_expr_path(&mut self, path: &'_ ExprPath) {
+        if path
+            .path
+            .get_ident()
+            .is_some_and(|ident| self.remover.unsafe_statics.contains(ident))
+        {
+            self.found = true;
+        }
+        syn::visit::visit_expr_path(self, path)
+    }
+
+    fn visit_expr_field(&mut self, field: &'_ ExprField) {
+        if matches!(&field.member, Member::Named(name) if self.remover.union_fields.contains(name))
+        {
+            self.found = true;
+        }
+        syn::visit::visit_expr_field(self, field)
+    }
+
+    fn visit_macro(&mut self, mac: &'_ Macro) {
+        let known = mac
+            .path
+            .get_ident()
+            .is_some_and(|name| SAFE_MACROS.contains(&name.to_string().as_str()));
+        if !known {
+            self.found = true;
+            return;
+        }
+        let args = mac
+            .parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated)
+            .or_else(|_| {
+                // vec![value; count]
+                mac.parse_body_with(|input: syn::parse::ParseStream| {
+                    let value: Expr = input.parse()?;
+                    input.parse::<Token![;]>()?;
+                    let count: Expr = input.parse()?;
+                    Ok(Punctuated::from_iter([value, count]))
+                })
+            });
+        match args {
+            Ok(args) => args.iter().for_each(|arg| self.visit_expr(arg)),
+            Err(_) => self.found = true,
+        }
+    }
+
+    /// The operations of a nested `unsafe` block are allowed by the block itself.
+    fn visit_expr_unsafe(&mut self, _: &'_ ExprUnsafe) {}
+
+    /// Nested items are not in the scope of an enclosing `unsafe` block.
+    fn visit_item(&mut self, _: &'_ Item) {}
+}
+
+impl VisitMut for RedundantUnsafeRemover {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        let types = std::mem::replace(
+            &mut self.types,
+            declared_types(&item_fn.sig, &item_fn.block),
+        );
+        self.report_if_redundant(&item_fn.sig, &item_fn.block);
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_item_impl_mut(&mut self, item_impl: &mut ItemImpl) {
+        let enclosing = std::mem::replace(&mut self.in_trait_impl, item_impl.trait_.is_some());
+        syn::visit_mut::visit_item_impl_mut(self, item_impl);
+        self.in_trait_impl = enclosing;
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        let types = std::mem::replace(
+            &mut self.types,
+            declared_types(&item_fn.sig, &item_fn.block),
+        );
+        if !self.in_trait_impl {
+            self.report_if_redundant(&item_fn.sig, &item_fn.block);
+        }
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    /// Splices the statements of a redundant `unsafe` block statement into the
+    /// enclosing block, unless it declares variables or items whose scope this
+    /// would widen.
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        let last = block.stmts.len().saturating_sub(1);
+        let mut stmts = Vec::with_capacity(block.stmts.len());
+        for (position, mut stmt) in std::mem::take(&mut block.stmts).into_iter().enumerate() {
+            match stmt {
+                Stmt::Expr(Expr::Unsafe(mut unsafe_expr), semi)
+                    if !self.is_unsafe(&unsafe_expr.block.stmts)
+                        && !unsafe_expr
+                            .block
+                            .stmts
+                            .iter()
+                            .any(|stmt| matches!(stmt, Stmt::Local(_) | Stmt::Item(_))) =>
+                {
+                    self.visit_block_mut(&mut unsafe_expr.block);
+                    let mut inner = unsafe_expr.block.stmts;
+                    // The block's value is discarded unless it is the tail.
+                    if position != last || semi.is_some() {
+                        if let Some(Stmt::Expr(_, inner_semi @ None)) = inner.last_mut() {
+                            *inner_semi = Some(Default::default());
+                        }
+                    }
+                    stmts.extend(inner);
+                }
+                _ => {
+                    self.visit_stmt_mut(&mut stmt);
+                    stmts.push(stmt);
+                }
+            }
+        }
+        block.stmts = stmts;
+    }
+
+    /// Replaces a redundant `unsafe` block expression with the expression it
+    /// contains, or with a plain block.
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        syn::visit_mut::visit_expr_mut(self, expr);
+        let Expr::Unsafe(unsafe_expr) = expr else {
+            return;
+        };
+        if self.is_unsafe(&unsafe_expr.block.stmts) {
+            return;
+        }
+        *expr = match unsafe_expr.block.stmts.as_slice() {
+            [Stmt::Expr(inner, None)] => {
+                self.spliced = true;
+                inner.clone()
+            }
+            _ => Expr::Block(ExprBlock {
+                attrs: std::mem::take(&mut unsafe_expr.attrs),
+                label: None,
+                block: unsafe_expr.block.clone(),
+            }),
+        };
+    }
+}
+
+impl Pass for RedundantUnsafeRemover {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.record_items(&monad.ast);
+        self.visit_file_mut(&mut monad.ast);
+        if self.spliced {
+            Parenthesize.visit_file_mut(&mut monad.ast);
+        }
+        monad.diagnostics.append(&mut self.diagnostics);
+        Ok(monad)
+    }
+}
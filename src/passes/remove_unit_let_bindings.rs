@@ -0,0 +1,145 @@
+//! Removes `let _ =` from statements whose value is known to be `()`, e.g.
+//! `let _ = reset(&mut state);` becomes `reset(&mut state);`. Unlike removing a
+//! binding of a pure value, the expression is kept for its side effects.
+//!
+//! The value is known to be `()` for calls of functions defined in the file
+//! without a return type, for invocations of the printing and assertion macros,
+//! for assignments and `for` and `while` loops, and for blocks and conditionals
+//! all of whose branches end in a statement.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{is_assign_op, path_ident};
+use crate::MonadicAst;
+use std::collections::HashSet;
+use syn::visit_mut::VisitMut;
+use syn::{
+    Block, Expr, ExprIf, Ident, Item, Local, LocalInit, Pat, PatType, ReturnType, Stmt, Type,
+};
+
+#[derive(Default)]
+pub struct RemoveUnitLetBinding {
+    /// The functions in the file without a return type.
+    unit_fns: HashSet<Ident>,
+}
+
+/// Macros which expand to an expression of type `()`.
+const UNIT_MACROS: &[&str] = &[
+    "print",
+    "println",
+    "eprint",
+    "eprintln",
+    "assert",
+    "assert_eq",
+    "assert_ne",
+    "debug_assert",
+    "debug_assert_eq",
+    "debug_assert_ne",
+];
+
+/// Returns whether the expression is block-like, and so needs no semicolon to be
+/// a statement.
+fn is_block_like(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::Block(_) | Expr::If(_) | Expr::ForLoop(_) | Expr::While(_) | Expr::Unsafe(_)
+    )
+}
+
+/// Returns whether the pattern is `_` or `_: ()`.
+fn is_unit_wildcard(pat: &Pat) -> bool {
+    match pat {
+        Pat::Wild(_) => true,
+        Pat::Type(PatType { pat, ty, .. }) => {
+            matches!(pat.as_ref(), Pat::Wild(_))
+                && matches!(ty.as_ref(), Type::Tuple(tuple) if tuple.elems.is_empty())
+        }
+        _ => false,
+    }
+}
+
+impl RemoveUnitLetBinding {
+    /// Returns whether the block ends in a statement, so that its value is `()`.
+    fn is_unit_block(&self, block: &Block) -> bool {
+        match block.stmts.last() {
+            None | Some(Stmt::Local(_)) | Some(Stmt::Item(_)) => true,
+            Some(Stmt::Expr(_, Some(_))) => true,
+            Some(Stmt::Macro(mac)) => mac.semi_token.is_some(),
+            Some(Stmt::Expr(expr, None)) => self.is_unit(expr),
+        }
+    }
+
+    /// Returns whether the expression is known to be of type `()`.
+    fn is_unit(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Paren(paren) => self.is_unit(&paren.expr),
+            Expr::Call(call) => path_ident(&call.func).is_some_and(|f| self.unit_fns.contains(f)),
+            Expr::Macro(mac) => mac
+                .mac
+                .path
+                .get_ident()
+                .is_some_and(|name| UNIT_MACROS.contains(&name.to_string().as_str())),
+            Expr::Assign(_) | Expr::ForLoop(_) | Expr::While(_) => true,
+            Expr::Binary(binary) => is_assign_op(&binary.op),
+            Expr::Block(block) => block.label.is_none() && self.is_unit_block(&block.block),
+            Expr::Unsafe(unsafe_block) => self.is_unit_block(&unsafe_block.block),
+            Expr::If(ExprIf {
+                then_branch,
+                else_branch,
+                ..
+            }) => {
+                self.is_unit_block(then_branch)
+                    && else_branch
+                        .as_ref()
+                        .is_none_or(|(_, else_branch)| self.is_unit(else_branch))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl VisitMut for RemoveUnitLetBinding {
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        syn::visit_mut::visit_block_mut(self, block);
+        for stmt in &mut block.stmts {
+            let Stmt::Local(Local {
+                attrs,
+                pat,
+                init:
+                    Some(LocalInit {
+                        expr,
+                        diverge: None,
+                        ..
+                    }),
+                ..
+            }) = stmt
+            else {
+                continue;
+            };
+            if !attrs.is_empty() || !is_unit_wildcard(pat) || !self.is_unit(expr) {
+                continue;
+            }
+            let expr = expr.as_ref().clone();
+            let semi = (!is_block_like(&expr)).then(Default::default);
+            *stmt = Stmt::Expr(expr, semi);
+        }
+    }
+}
+
+impl Pass for RemoveUnitLetBinding {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.unit_fns = monad
+            .ast
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Fn(item_fn) if matches!(item_fn.sig.output, ReturnType::Default) => {
+                    Some(item_fn.sig.ident.clone())
+                }
+                _ => None,
+            })
+            .collect();
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
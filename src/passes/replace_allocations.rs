@@ -0,0 +1,218 @@
+//! Replaces raw pointers initialized by `malloc` or `calloc` with the owning Rust
+//! type the allocation calls for, e.g.
+//!
+//! ```ignore
+//! let mut p: *mut i32 = malloc(::core::mem::size_of::<i32>() as u64) as *mut i32;
+//! let mut a: *mut i32 = malloc(n.wrapping_mul(::core::mem::size_of::<i32>() as u64)) as *mut i32;
+//! ```
+//!
+//! becomes `let mut p: Box<i32> = Box::new(i32::default());` and
+//! `let mut a: Vec<i32> = vec![i32::default(); n as usize];`. The elements are
+//! then accessed as `*p` and `a[i]` instead of `*a.add(i)`, and `free(p)` becomes
+//! `drop(p)`.
+//!
+//! A pointer is only replaced if every later use of it is one of these accesses,
+//! so it is never copied or passed on as a raw pointer. The pass runs before
+//! `replace_raw_pointers`, which so only classifies the pointers that are not
+//! already owning.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{
+    allocation, as_usize, callee_name, count_ident, default_of, is_assign_op, path_ident,
+    same_tokens, strip_casts, strip_parens, Allocation,
+};
+use crate::MonadicAst;
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, Block, Expr, ExprAssign, ExprBinary, ExprCall, ExprMethodCall, ExprUnary, Ident,
+    Local, LocalInit, Pat, PatIdent, PatType, Stmt, Type, TypePtr, UnOp,
+};
+
+#[derive(Default)]
+pub struct AllocationReplacer;
+
+/// Returns the pointer dereferenced by `*p`, or `*p.add(i)` and `*p.offset(i)` when
+/// `indexed`, along with the index.
+fn element_access<'a>(expr: &'a Expr, pointer: &Ident, indexed: bool) -> Option<Option<&'a Expr>> {
+    let Expr::Unary(ExprUnary {
+        op: UnOp::Deref(_),
+        expr,
+        ..
+    }) = expr
+    else {
+        return None;
+    };
+    match strip_casts(expr) {
+        Expr::MethodCall(ExprMethodCall {
+            receiver,
+            method,
+            args,
+            ..
+        }) if indexed
+            && (method == "add" || method == "offset")
+            && args.len() == 1
+            && path_ident(receiver) == Some(pointer) =>
+        {
+            Some(Some(&args[0]))
+        }
+        expr if path_ident(expr) == Some(pointer) => Some(None),
+        _ => None,
+    }
+}
+
+/// Returns whether `expr` is `free(p)`, ignoring casts of `p`.
+fn is_free_of(expr: &Expr, pointer: &Ident) -> bool {
+    matches!(expr, Expr::Call(ExprCall { func, args, .. })
+        if callee_name(func).as_deref() == Some("free")
+            && args.len() == 1
+            && path_ident(strip_casts(&args[0])) == Some(pointer))
+}
+
+/// Tallies the recognized uses of the pointer after its declaration.
+struct PointerUses<'a> {
+    pointer: &'a Ident,
+    /// Whether `*p.add(i)` accesses are recognized, for an array pointer.
+    indexed: bool,
+    /// Occurrences of the pointer accounted for by a recognized use.
+    recognized: usize,
+    /// Whether an element is written through the pointer.
+    written: bool,
+}
+
+impl Visit<'_> for PointerUses<'_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        let written = match expr {
+            Expr::Assign(ExprAssign { left, .. }) => Some(left.as_ref()),
+            Expr::Binary(ExprBinary { left, op, .. }) if is_assign_op(op) => Some(left.as_ref()),
+            _ => None,
+        };
+        if written.is_some_and(|left| element_access(left, self.pointer, self.indexed).is_some()) {
+            self.written = true;
+        }
+        if element_access(expr, self.pointer, self.indexed).is_some()
+            || is_free_of(expr, self.pointer)
+        {
+            self.recognized += 1;
+        }
+        syn::visit::visit_expr(self, expr)
+    }
+}
+
+/// Rewrites the recognized uses of the pointer in terms of its owning type.
+struct PointerRewriter<'a> {
+    pointer: &'a Ident,
+    indexed: bool,
+}
+
+impl VisitMut for PointerRewriter<'_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        let pointer = self.pointer;
+        if is_free_of(expr, pointer) {
+            *expr = parse_quote!(drop(#pointer));
+            return;
+        }
+        match element_access(expr, pointer, self.indexed) {
+            // *a.add(i) -> a[i as usize]
+            Some(Some(index)) => {
+                let index = as_usize(strip_casts(index));
+                *expr = parse_quote!(#pointer[#index]);
+            }
+            // *a -> a[0]
+            Some(None) if self.indexed => *expr = parse_quote!(#pointer[0]),
+            _ => syn::visit_mut::visit_expr_mut(self, expr),
+        }
+    }
+}
+
+impl AllocationReplacer {
+    /// Returns the identifier declared by `let p: *mut T = <allocation>;`, and the
+    /// allocation.
+    fn allocation_decl(local: &Local) -> Option<(&PatIdent, Allocation)> {
+        let Pat::Type(PatType { pat, ty, .. }) = &local.pat else {
+            return None;
+        };
+        let Pat::Ident(pat_ident) = pat.as_ref() else {
+            return None;
+        };
+        let Type::Ptr(TypePtr {
+            mutability: Some(_),
+            elem,
+            ..
+        }) = ty.as_ref()
+        else {
+            return None;
+        };
+        let Some(LocalInit {
+            expr,
+            diverge: None,
+            ..
+        }) = &local.init
+        else {
+            return None;
+        };
+        let allocation = allocation(strip_parens(expr))?;
+        let (Allocation::Single(allocated) | Allocation::Array(allocated, _)) = &allocation;
+        same_tokens(allocated, elem.as_ref()).then_some((pat_ident, allocation))
+    }
+
+    fn replace_allocation(block: &mut Block, index: usize) {
+        let Stmt::Local(local) = &block.stmts[index] else {
+            return;
+        };
+        let Some((pat_ident, allocation)) = Self::allocation_decl(local) else {
+            return;
+        };
+        let pointer = pat_ident.ident.clone();
+        let indexed = matches!(allocation, Allocation::Array(..));
+        let rest = &block.stmts[index + 1..];
+        let mut uses = PointerUses {
+            pointer: &pointer,
+            indexed,
+            recognized: 0,
+            written: false,
+        };
+        rest.iter().for_each(|stmt| uses.visit_stmt(stmt));
+        let total: usize = rest.iter().map(|stmt| count_ident(stmt, &pointer)).sum();
+        if uses.recognized != total {
+            return;
+        }
+        let mutability =
+            (pat_ident.mutability.is_some() || uses.written).then(|| quote::quote!(mut));
+        block.stmts[index] = match allocation {
+            Allocation::Single(elem) => {
+                let value = default_of(&elem);
+                parse_quote!(let #mutability #pointer: Box<#elem> = Box::new(#value);)
+            }
+            Allocation::Array(elem, count) => {
+                let value = default_of(&elem);
+                let count = as_usize(&count);
+                parse_quote!(let #mutability #pointer: Vec<#elem> = vec![#value; #count];)
+            }
+        };
+        let mut rewriter = PointerRewriter {
+            pointer: &pointer,
+            indexed,
+        };
+        block.stmts[index + 1..]
+            .iter_mut()
+            .for_each(|stmt| rewriter.visit_stmt_mut(stmt));
+    }
+}
+
+impl VisitMut for AllocationReplacer {
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        syn::visit_mut::visit_block_mut(self, block);
+        for index in 0..block.stmts.len() {
+            Self::replace_allocation(block, index);
+        }
+    }
+}
+
+impl Pass for AllocationReplacer {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
@@ -0,0 +1,221 @@
+//! Replaces an explicit bounds check which guards the following indexing with a
+//! `let`-`else` over `get`, e.g.
+//!
+//! ```ignore
+//! if i < 0 || i >= a.len() as i32 {
+//!     return -1;
+//! }
+//! let x = a[i as usize];
+//! ```
+//!
+//! becomes `let Some(&x) = a.get(i as usize) else { return -1; };`.
+//!
+//! The guard must reject exactly the indices outside of `0..a.len()`, and its body
+//! must only leave the enclosing function or loop. The check `i < 0` may only be
+//! left out when the index is known to be unsigned; a negative index cast to
+//! `usize` is out of bounds as well, so `get` rejects it like the guard did.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{
+    expr_type, is_ident, is_int_lit, is_unsigned_int, local_types, path_ident, same_tokens,
+    strip_casts, strip_parens,
+};
+use crate::MonadicAst;
+use std::collections::HashMap;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, BinOp, Block, Expr, ExprBinary, ExprIf, ExprIndex, ExprMethodCall, ExprReference,
+    Ident, ImplItemFn, ItemFn, Local, LocalInit, Pat, Stmt, Type,
+};
+
+#[derive(Default)]
+pub struct BoundsCheckToGet {
+    /// Declared types of the locals of the function currently being visited.
+    types: HashMap<Ident, Type>,
+}
+
+/// A bounds guard `if index < 0 || index >= slice.len() { ... }`.
+struct BoundsGuard<'a> {
+    slice: &'a Ident,
+    index: &'a Expr,
+    /// Whether the guard also rejects negative indices.
+    checks_negative: bool,
+    body: &'a Block,
+}
+
+/// Returns `(index, slice)` if `expr` is `index >= slice.len()`, ignoring casts.
+fn upper_check(expr: &Expr) -> Option<(&Expr, &Ident)> {
+    let Expr::Binary(ExprBinary {
+        left, op, right, ..
+    }) = strip_parens(expr)
+    else {
+        return None;
+    };
+    let (index, len) = match op {
+        BinOp::Ge(_) => (left, right),
+        BinOp::Le(_) => (right, left),
+        _ => return None,
+    };
+    match strip_casts(len) {
+        Expr::MethodCall(ExprMethodCall {
+            receiver,
+            method,
+            args,
+            ..
+        }) if method == "len" && args.is_empty() => Some((index, path_ident(receiver)?)),
+        _ => None,
+    }
+}
+
+/// Returns the index if `expr` is `index < 0`.
+fn lower_check(expr: &Expr) -> Option<&Expr> {
+    let Expr::Binary(ExprBinary {
+        left, op, right, ..
+    }) = strip_parens(expr)
+    else {
+        return None;
+    };
+    match op {
+        BinOp::Lt(_) if is_int_lit(right, 0) => Some(left),
+        BinOp::Gt(_) if is_int_lit(left, 0) => Some(right),
+        _ => None,
+    }
+}
+
+/// Returns the guard if `stmt` is a bounds check whose body only diverges.
+fn bounds_guard(stmt: &Stmt) -> Option<BoundsGuard<'_>> {
+    let Stmt::Expr(
+        Expr::If(ExprIf {
+            cond,
+            then_branch,
+            else_branch: None,
+            ..
+        }),
+        _,
+    ) = stmt
+    else {
+        return None;
+    };
+    let diverges = matches!(
+        then_branch.stmts.as_slice(),
+        [Stmt::Expr(
+            Expr::Return(_) | Expr::Break(_) | Expr::Continue(_),
+            _
+        )]
+    );
+    if !diverges {
+        return None;
+    }
+    if let Some((index, slice)) = upper_check(cond) {
+        return Some(BoundsGuard {
+            slice,
+            index,
+            checks_negative: false,
+            body: then_branch,
+        });
+    }
+    let Expr::Binary(ExprBinary {
+        left,
+        op: BinOp::Or(_),
+        right,
+        ..
+    }) = strip_parens(cond)
+    else {
+        return None;
+    };
+    let ((index, slice), lower) = match (upper_check(left), upper_check(right)) {
+        (Some(upper), None) => (upper, lower_check(right)?),
+        (None, Some(upper)) => (upper, lower_check(left)?),
+        _ => return None,
+    };
+    same_tokens(strip_parens(index), strip_parens(lower)).then_some(BoundsGuard {
+        slice,
+        index,
+        checks_negative: true,
+        body: then_branch,
+    })
+}
+
+impl BoundsCheckToGet {
+    /// Returns the `let`-`else` replacing the guard and the `let x = a[i];` after it.
+    fn replacement(&self, guard: &Stmt, access: &Stmt) -> Option<Stmt> {
+        let guard = bounds_guard(guard)?;
+        let Stmt::Local(Local {
+            pat: pat @ Pat::Ident(_),
+            init:
+                Some(LocalInit {
+                    expr,
+                    diverge: None,
+                    ..
+                }),
+            ..
+        }) = access
+        else {
+            return None;
+        };
+        let (indexed, borrowed) = match expr.as_ref() {
+            Expr::Reference(ExprReference {
+                expr,
+                mutability: None,
+                ..
+            }) => (expr.as_ref(), true),
+            expr => (expr, false),
+        };
+        let Expr::Index(ExprIndex { expr, index, .. }) = indexed else {
+            return None;
+        };
+        // The access must index the guarded slice by the guarded index.
+        if !is_ident(expr, guard.slice)
+            || !same_tokens(strip_casts(index), strip_casts(guard.index))
+        {
+            return None;
+        }
+        // Without the lower check, the guard's index must not be negative.
+        let unsigned = same_tokens(strip_parens(index), strip_parens(guard.index))
+            || expr_type(guard.index, &self.types).is_some_and(|ty| is_unsigned_int(&ty));
+        if !guard.checks_negative && !unsigned {
+            return None;
+        }
+        let slice = guard.slice;
+        let body = guard.body;
+        let index = strip_parens(index);
+        Some(if borrowed {
+            parse_quote!(let Some(#pat) = #slice.get(#index) else #body;)
+        } else {
+            parse_quote!(let Some(&#pat) = #slice.get(#index) else #body;)
+        })
+    }
+}
+
+impl VisitMut for BoundsCheckToGet {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        syn::visit_mut::visit_block_mut(self, block);
+        let mut index = 0;
+        while index + 1 < block.stmts.len() {
+            if let Some(let_else) = self.replacement(&block.stmts[index], &block.stmts[index + 1]) {
+                block.stmts.splice(index..index + 2, [let_else]);
+            }
+            index += 1;
+        }
+    }
+}
+
+impl Pass for BoundsCheckToGet {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
@@ -0,0 +1,304 @@
+//! Folds field assignments directly following the creation of a defaulted box into
+//! a struct literal, e.g.
+//!
+//! ```ignore
+//! let mut b = Box::new(Node::default());
+//! b.value = v;
+//! b.next = None;
+//! ```
+//!
+//! becomes `let b = Box::new(Node { value: v, next: None, ..Default::default() });`.
+//! The rest expression is left out when every field of a struct defined in the file
+//! is assigned.
+//!
+//! Only the assignments immediately following the declaration are folded, and none
+//! of the assigned values may read the box. A field assigned twice ends the fold.
+//! The box stays `mut` only if it is later assigned to, borrowed mutably, or used
+//! as the receiver of a method call.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{count_ident, is_assign_op, is_ident, strip_parens};
+use crate::MonadicAst;
+use quote::quote;
+use std::collections::HashMap;
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, Block, Expr, ExprAssign, ExprCall, ExprField, ExprMethodCall, ExprReference,
+    ExprUnary, Fields, GenericArgument, Ident, Item, Local, LocalInit, Member, Pat, PatIdent,
+    PatType, Path, PathArguments, Stmt, Type, TypePath, UnOp,
+};
+
+#[derive(Default)]
+pub struct BoxFieldInitToLiteral {
+    /// The field names of the structs with named fields defined in the file.
+    fields: HashMap<Ident, Vec<Ident>>,
+}
+
+/// Returns the boxed struct's path if `expr` is `Box::new(T::default())`, or
+/// `Box::new(Default::default())` for a box declared as `Box<T>`.
+fn defaulted_box(expr: &Expr, declared: Option<&Type>) -> Option<Path> {
+    let Expr::Call(ExprCall { func, args, .. }) = strip_parens(expr) else {
+        return None;
+    };
+    let Expr::Path(func) = func.as_ref() else {
+        return None;
+    };
+    let segments: Vec<String> = func
+        .path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    if segments != ["Box", "new"] || args.len() != 1 {
+        return None;
+    }
+    let Expr::Call(ExprCall {
+        func: constructor,
+        args: constructor_args,
+        ..
+    }) = strip_parens(&args[0])
+    else {
+        return None;
+    };
+    let Expr::Path(constructor) = constructor.as_ref() else {
+        return None;
+    };
+    let mut path = constructor.path.clone();
+    let last = path.segments.pop()?.into_value();
+    if !constructor_args.is_empty() || last.ident != "default" || constructor.qself.is_some() {
+        return None;
+    }
+    path.segments.pop_punct();
+    if path.is_ident("Default") {
+        return boxed_type(declared?);
+    }
+    (!path.segments.is_empty()).then_some(path)
+}
+
+/// Returns `T` if `ty` is `Box<T>` for a path `T`.
+fn boxed_type(ty: &Type) -> Option<Path> {
+    let Type::Path(TypePath { qself: None, path }) = ty else {
+        return None;
+    };
+    let segment = path.segments.last()?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.iter().collect::<Vec<_>>()[..] {
+        [GenericArgument::Type(Type::Path(TypePath { qself: None, path }))]
+            if segment.ident == "Box" =>
+        {
+            Some(path.clone())
+        }
+        _ => None,
+    }
+}
+
+/// Returns the field and the value if the statement is `b.field = value;` or
+/// `(*b).field = value;`.
+fn field_assignment<'a>(stmt: &'a Stmt, boxed: &Ident) -> Option<(&'a Ident, &'a Expr)> {
+    let Stmt::Expr(
+        Expr::Assign(ExprAssign {
+            attrs, left, right, ..
+        }),
+        Some(_),
+    ) = stmt
+    else {
+        return None;
+    };
+    let Expr::Field(ExprField {
+        base,
+        member: Member::Named(field),
+        ..
+    }) = left.as_ref()
+    else {
+        return None;
+    };
+    let base = match strip_parens(base) {
+        Expr::Unary(ExprUnary {
+            op: UnOp::Deref(_),
+            expr,
+            ..
+        }) => expr,
+        base => base,
+    };
+    (attrs.is_empty() && is_ident(base, boxed)).then_some((field, right))
+}
+
+/// Returns whether any of the statements may mutate `ident` in place: assign to
+/// it or a place within it, borrow either mutably, or call a method on it.
+fn mutates(stmts: &[Stmt], ident: &Ident) -> bool {
+    struct Finder<'a> {
+        ident: &'a Ident,
+        found: bool,
+    }
+    impl Finder<'_> {
+        fn is_rooted(&self, place: &Expr) -> bool {
+            match strip_parens(place) {
+                Expr::Field(ExprField { base, .. }) => self.is_rooted(base),
+                Expr::Index(index) => self.is_rooted(&index.expr),
+                Expr::Unary(ExprUnary {
+                    op: UnOp::Deref(_),
+                    expr,
+                    ..
+                }) => self.is_rooted(expr),
+                place => is_ident(place, self.ident),
+            }
+        }
+    }
+    impl Visit<'_> for Finder<'_> {
+        fn visit_expr(&mut self, expr: &Expr) {
+            let place = match expr {
+                Expr::Assign(assign) => Some(&assign.left),
+                Expr::Binary(binary) if is_assign_op(&binary.op) => Some(&binary.left),
+                Expr::Reference(ExprReference {
+                    mutability: Some(_),
+                    expr,
+                    ..
+                }) => Some(expr),
+                Expr::MethodCall(ExprMethodCall { receiver, .. }) => Some(receiver),
+                _ => None,
+            };
+            self.found |= place.is_some_and(|place| self.is_rooted(place));
+            syn::visit::visit_expr(self, expr);
+        }
+
+        fn visit_macro(&mut self, mac: &syn::Macro) {
+            self.found |= count_ident(&mac.tokens, self.ident) > 0;
+        }
+    }
+    let mut finder = Finder {
+        ident,
+        found: false,
+    };
+    for stmt in stmts {
+        finder.visit_stmt(stmt);
+    }
+    finder.found
+}
+
+impl BoxFieldInitToLiteral {
+    fn rewrite_block(&self, block: &mut Block) {
+        let mut position = 0;
+        while position + 1 < block.stmts.len() {
+            let Stmt::Local(Local {
+                pat,
+                init:
+                    Some(LocalInit {
+                        expr,
+                        diverge: None,
+                        ..
+                    }),
+                ..
+            }) = &block.stmts[position]
+            else {
+                position += 1;
+                continue;
+            };
+            let (ident_pat, declared) = match pat {
+                Pat::Type(PatType { pat, ty, .. }) => (pat.as_ref(), Some(ty.as_ref())),
+                pat => (pat, None),
+            };
+            let (Pat::Ident(PatIdent { ident, .. }), Some(strukt)) =
+                (ident_pat, defaulted_box(expr, declared))
+            else {
+                position += 1;
+                continue;
+            };
+            let boxed = ident.clone();
+
+            // The assignments directly following the declaration.
+            let mut assigned: Vec<(Ident, Expr)> = Vec::new();
+            for stmt in &block.stmts[position + 1..] {
+                let Some((field, value)) = field_assignment(stmt, &boxed) else {
+                    break;
+                };
+                if count_ident(value, &boxed) > 0
+                    || assigned.iter().any(|(assigned, _)| assigned == field)
+                {
+                    break;
+                }
+                assigned.push((field.clone(), value.clone()));
+            }
+            if assigned.is_empty() {
+                position += 1;
+                continue;
+            }
+
+            let complete = strukt
+                .get_ident()
+                .and_then(|name| self.fields.get(name))
+                .is_some_and(|all| {
+                    all.len() == assigned.len()
+                        && all
+                            .iter()
+                            .all(|field| assigned.iter().any(|(assigned, _)| assigned == field))
+                });
+            // `field: field` is written in its shorthand form.
+            let initializers = assigned.iter().map(|(field, value)| {
+                if is_ident(value, field) {
+                    quote!(#field)
+                } else {
+                    quote!(#field: #value)
+                }
+            });
+            let literal: Expr = if complete {
+                parse_quote!(#strukt { #(#initializers),* })
+            } else {
+                parse_quote!(#strukt { #(#initializers,)* ..Default::default() })
+            };
+            let folded = assigned.len();
+            let keep_mut = mutates(&block.stmts[position + 1 + folded..], &boxed);
+            let Stmt::Local(local) = &mut block.stmts[position] else {
+                unreachable!();
+            };
+            if let Some(init) = &mut local.init {
+                *init.expr = parse_quote!(Box::new(#literal));
+            }
+            let ident_pat = match &mut local.pat {
+                Pat::Type(PatType { pat, .. }) => pat.as_mut(),
+                pat => pat,
+            };
+            if let (Pat::Ident(ident_pat), false) = (ident_pat, keep_mut) {
+                ident_pat.mutability = None;
+            }
+            block.stmts.drain(position + 1..position + 1 + folded);
+            position += 1;
+        }
+    }
+}
+
+impl VisitMut for BoxFieldInitToLiteral {
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        syn::visit_mut::visit_block_mut(self, block);
+        self.rewrite_block(block);
+    }
+}
+
+impl Pass for BoxFieldInitToLiteral {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.fields = monad
+            .ast
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Struct(strukt) => match &strukt.fields {
+                    Fields::Named(named) => Some((
+                        strukt.ident.clone(),
+                        named
+                            .named
+                            .iter()
+                            .filter_map(|field| field.ident.clone())
+                            .collect(),
+                    )),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
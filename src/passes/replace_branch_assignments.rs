@@ -0,0 +1,165 @@
+//! Replaces an `if`/`else` whose branches only assign to the same place with a
+//! single assignment of a conditional expression, e.g.
+//!
+//! ```ignore
+//! if c {
+//!     x = a;
+//! } else {
+//!     x = b;
+//! }
+//! ```
+//!
+//! becomes `x = if c { a } else { b };`. Chains of `else if` are rewritten as long
+//! as every branch assigns the same place. When the `if` directly follows the
+//! declaration `let x;`, or one initializing `x` with a literal, the conditional
+//! becomes the initializer instead, i.e. `let x = if c { a } else { b };`.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{count_ident, same_tokens};
+use crate::MonadicAst;
+use syn::token::Eq;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, Block, Expr, ExprAssign, ExprBlock, ExprIf, Ident, Local, LocalInit, Pat,
+    PatIdent, PatType, Stmt,
+};
+
+#[derive(Default)]
+pub struct BranchAssignToCondExpr;
+
+/// Returns the place and the value if the block only assigns to a place.
+fn assignment(block: &Block) -> Option<(&Expr, &Expr)> {
+    match block.stmts.as_slice() {
+        [Stmt::Expr(
+            Expr::Assign(ExprAssign {
+                attrs, left, right, ..
+            }),
+            _,
+        )] if attrs.is_empty() => Some((left, right)),
+        _ => None,
+    }
+}
+
+/// Returns a block evaluating to `value`.
+fn value_block(block: &Block, value: &Expr) -> Block {
+    Block {
+        brace_token: block.brace_token,
+        stmts: vec![Stmt::Expr(value.clone(), None)],
+    }
+}
+
+/// Returns the place every branch assigns to, the conditional expression of the
+/// assigned values, and the number of branches.
+fn conditional(expr_if: &ExprIf) -> Option<(&Expr, ExprIf, usize)> {
+    let (else_token, else_branch) = expr_if.else_branch.as_ref()?;
+    let (place, value) = assignment(&expr_if.then_branch)?;
+    let (else_place, else_value, branches) = match else_branch.as_ref() {
+        Expr::Block(ExprBlock {
+            attrs,
+            label: None,
+            block,
+        }) if attrs.is_empty() => {
+            let (else_place, else_value) = assignment(block)?;
+            let else_value = Expr::Block(ExprBlock {
+                attrs: Vec::new(),
+                label: None,
+                block: value_block(block, else_value),
+            });
+            (else_place, else_value, 1)
+        }
+        Expr::If(else_if) if else_if.attrs.is_empty() => {
+            let (else_place, else_if, branches) = conditional(else_if)?;
+            (else_place, Expr::If(else_if), branches)
+        }
+        _ => return None,
+    };
+    if !same_tokens(place, else_place) {
+        return None;
+    }
+    let conditional = ExprIf {
+        attrs: Vec::new(),
+        if_token: expr_if.if_token,
+        cond: expr_if.cond.clone(),
+        then_branch: value_block(&expr_if.then_branch, value),
+        else_branch: Some((*else_token, Box::new(else_value))),
+    };
+    Some((place, conditional, branches + 1))
+}
+
+/// Returns the variable declared by `let x;`, `let x: T;` or `let x = <literal>;`.
+fn declared(local: &Local) -> Option<&Ident> {
+    let pat = match &local.pat {
+        Pat::Type(PatType { pat, .. }) => pat.as_ref(),
+        pat => pat,
+    };
+    let Pat::Ident(PatIdent {
+        by_ref: None,
+        subpat: None,
+        ident,
+        ..
+    }) = pat
+    else {
+        return None;
+    };
+    match &local.init {
+        None => Some(ident),
+        Some(LocalInit {
+            expr,
+            diverge: None,
+            ..
+        }) if matches!(expr.as_ref(), Expr::Lit(_)) => Some(ident),
+        _ => None,
+    }
+}
+
+impl BranchAssignToCondExpr {
+    fn rewrite_block(block: &mut Block) {
+        let mut position = 0;
+        while position < block.stmts.len() {
+            let Stmt::Expr(Expr::If(expr_if), _) = &block.stmts[position] else {
+                position += 1;
+                continue;
+            };
+            let Some((place, conditional, branches)) = conditional(expr_if) else {
+                position += 1;
+                continue;
+            };
+            let place = place.clone();
+            // The declaration's initial value must not be read by the `if`.
+            let merges = position > 0
+                && matches!(&block.stmts[position - 1], Stmt::Local(local)
+                if declared(local).is_some_and(|ident| {
+                    same_tokens(&place, ident) && count_ident(expr_if, ident) == branches
+                }));
+            if merges {
+                let Stmt::Local(local) = &mut block.stmts[position - 1] else {
+                    unreachable!();
+                };
+                local.init = Some(LocalInit {
+                    eq_token: Eq::default(),
+                    expr: Box::new(Expr::If(conditional)),
+                    diverge: None,
+                });
+                block.stmts.remove(position);
+            } else {
+                block.stmts[position] = parse_quote!(#place = #conditional;);
+                position += 1;
+            }
+        }
+    }
+}
+
+impl VisitMut for BranchAssignToCondExpr {
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        syn::visit_mut::visit_block_mut(self, block);
+        Self::rewrite_block(block);
+    }
+}
+
+impl Pass for BranchAssignToCondExpr {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
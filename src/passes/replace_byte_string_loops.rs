@@ -0,0 +1,442 @@
+//! Replaces loops copying every byte of a buffer into a new `String` as a `char`
+//! with a conversion of the whole buffer, e.g.
+//!
+//! ```ignore
+//! let mut s = String::new();
+//! for &b in bytes {
+//!     s.push(b as char);
+//! }
+//! ```
+//!
+//! becomes `let s = String::from_utf8_lossy(bytes).into_owned();`.
+//!
+//! The loop may iterate the buffer by reference or by index, and must push each
+//! byte as it is, with `as char` or `char::from`. The two forms agree for ASCII
+//! text only, since the loop turns each byte above `0x7f` into the `char` with that
+//! code point rather than decoding UTF-8. So the loop is only replaced if the
+//! buffer is provably ASCII: a byte string literal or an array of integer or byte
+//! literals below `0x80`, or an immutable local initialized with one. Other loops are kept
+//! and reported.
+
+use crate::monad::ast::Pass;
+use crate::monad::diagnostic::Diagnostic;
+use crate::monad::error::PassError;
+use crate::passes::utils::{
+    count_ident, is_ident, is_int_lit, is_reassigned, is_receiver, local_types, path_ident,
+    same_tokens, strip_casts, strip_parens,
+};
+use crate::MonadicAst;
+use quote::ToTokens;
+use std::collections::HashMap;
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, Block, Expr, ExprArray, ExprCall, ExprCast, ExprForLoop, ExprIndex, ExprLit,
+    ExprMethodCall, ExprRange, ExprReference, ExprUnary, Ident, ImplItemFn, ItemFn, Lit, Local,
+    LocalInit, Pat, PatIdent, PatReference, PatType, RangeLimits, Signature, Stmt, Type,
+    TypeReference, UnOp,
+};
+
+const PASS: &str = "replace_byte_string_loops";
+
+#[derive(Default)]
+pub struct ByteLoopToFromUtf8 {
+    /// Locals of the function currently being visited which only ever hold ASCII
+    /// bytes, and whether each is a reference to them rather than an array.
+    ascii: HashMap<Ident, bool>,
+    /// Declared types of the locals of the function currently being visited.
+    types: HashMap<Ident, Type>,
+    current_fn: Option<Ident>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Returns the string if the statement is `let mut s = String::new();` or
+/// `let mut s = String::with_capacity(n);`.
+fn empty_string(stmt: &Stmt) -> Option<&Ident> {
+    let Stmt::Local(Local {
+        pat,
+        init:
+            Some(LocalInit {
+                expr,
+                diverge: None,
+                ..
+            }),
+        ..
+    }) = stmt
+    else {
+        return None;
+    };
+    let pat = match pat {
+        Pat::Type(PatType { pat, .. }) => pat.as_ref(),
+        pat => pat,
+    };
+    let Pat::Ident(PatIdent {
+        by_ref: None,
+        mutability: Some(_),
+        subpat: None,
+        ident,
+        ..
+    }) = pat
+    else {
+        return None;
+    };
+    let Expr::Call(ExprCall { func, args, .. }) = strip_parens(expr) else {
+        return None;
+    };
+    let Expr::Path(func) = func.as_ref() else {
+        return None;
+    };
+    let segments: Vec<String> = func
+        .path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    match (&segments[..], args.len()) {
+        ([string, new], 0) if string == "String" && new == "new" => Some(ident),
+        ([string, with_capacity], 1) if string == "String" && with_capacity == "with_capacity" => {
+            Some(ident)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the byte converted to a `char` if `expr` is `byte as char` or
+/// `char::from(byte)`.
+fn as_char(expr: &Expr) -> Option<&Expr> {
+    match strip_parens(expr) {
+        Expr::Cast(ExprCast { expr, ty, .. }) if matches!(ty.as_ref(), Type::Path(ty) if ty.path.is_ident("char")) => {
+            Some(strip_parens(expr))
+        }
+        Expr::Call(ExprCall { func, args, .. }) if args.len() == 1 => {
+            let Expr::Path(func) = func.as_ref() else {
+                return None;
+            };
+            let segments: Vec<String> = func
+                .path
+                .segments
+                .iter()
+                .map(|segment| segment.ident.to_string())
+                .collect();
+            (segments == ["char", "from"]).then(|| strip_parens(&args[0]))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the pushed value if the loop body only is `string.push(value);`.
+fn pushed<'a>(body: &'a Block, string: &Ident) -> Option<&'a Expr> {
+    let [Stmt::Expr(
+        Expr::MethodCall(ExprMethodCall {
+            receiver,
+            method,
+            args,
+            ..
+        }),
+        Some(_),
+    )] = body.stmts.as_slice()
+    else {
+        return None;
+    };
+    (method == "push" && args.len() == 1 && is_ident(receiver, string)).then(|| &args[0])
+}
+
+/// Returns whether `expr` is a byte string literal, which is a reference, or an
+/// array of integer or byte literals, which is not, of ASCII bytes only.
+fn ascii_bytes(expr: &Expr) -> Option<bool> {
+    let is_ascii = |expr: &Expr| match strip_casts(expr) {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(int), ..
+        }) => int.base10_parse::<u8>().is_ok_and(|byte| byte.is_ascii()),
+        Expr::Lit(ExprLit {
+            lit: Lit::Byte(byte),
+            ..
+        }) => byte.value().is_ascii(),
+        _ => false,
+    };
+    match strip_casts(expr) {
+        Expr::Lit(ExprLit {
+            lit: Lit::ByteStr(bytes),
+            ..
+        }) => bytes.value().is_ascii().then_some(true),
+        Expr::Array(ExprArray { elems, .. }) => elems.iter().all(is_ascii).then_some(false),
+        Expr::Reference(ExprReference {
+            mutability: None,
+            expr,
+            ..
+        }) => ascii_bytes(expr).map(|_| true),
+        _ => None,
+    }
+}
+
+/// Collects the locals of a function which are only ever declared immutable and
+/// initialized with ASCII bytes, and whether each is a reference to them.
+#[derive(Default)]
+struct AsciiLocals {
+    locals: HashMap<Ident, Option<bool>>,
+}
+
+impl AsciiLocals {
+    fn of(sig: &Signature, block: &Block) -> HashMap<Ident, bool> {
+        let mut ascii = Self::default();
+        ascii.visit_signature(sig);
+        ascii.visit_block(block);
+        ascii
+            .locals
+            .into_iter()
+            .filter_map(|(ident, reference)| Some((ident, reference?)))
+            .collect()
+    }
+
+    fn record(&mut self, ident: &Ident, reference: Option<bool>) {
+        let known = self.locals.entry(ident.clone()).or_insert(reference);
+        if *known != reference {
+            *known = None;
+        }
+    }
+}
+
+impl Visit<'_> for AsciiLocals {
+    fn visit_local(&mut self, local: &Local) {
+        let pat = match &local.pat {
+            Pat::Type(PatType { pat, .. }) => pat.as_ref(),
+            pat => pat,
+        };
+        match (pat, &local.init) {
+            (
+                Pat::Ident(PatIdent {
+                    by_ref: None,
+                    mutability: None,
+                    subpat: None,
+                    ident,
+                    ..
+                }),
+                Some(LocalInit {
+                    expr,
+                    diverge: None,
+                    ..
+                }),
+            ) => {
+                self.record(ident, ascii_bytes(expr));
+                self.visit_expr(expr);
+            }
+            _ => syn::visit::visit_local(self, local),
+        }
+    }
+
+    /// Any other binding, e.g. a parameter, may hold any bytes.
+    fn visit_pat_ident(&mut self, pat_ident: &PatIdent) {
+        self.record(&pat_ident.ident, None);
+        syn::visit::visit_pat_ident(self, pat_ident);
+    }
+}
+
+impl ByteLoopToFromUtf8 {
+    /// Returns whether `expr` is a variable declared as a shared reference.
+    fn is_reference(&self, expr: &Expr) -> bool {
+        path_ident(expr)
+            .and_then(|ident| self.types.get(ident))
+            .is_some_and(|ty| {
+                matches!(
+                    ty,
+                    Type::Reference(TypeReference {
+                        mutability: None,
+                        ..
+                    })
+                )
+            })
+    }
+
+    /// Returns the buffer if `expr` iterates over references to its bytes: `&bytes`,
+    /// `bytes.iter()` or a reference variable `bytes`.
+    fn borrowed_buffer<'a>(&self, expr: &'a Expr) -> Option<&'a Expr> {
+        match strip_parens(expr) {
+            Expr::Reference(ExprReference {
+                mutability: None,
+                expr,
+                ..
+            }) => Some(strip_parens(expr)),
+            Expr::MethodCall(ExprMethodCall {
+                receiver,
+                method,
+                args,
+                ..
+            }) if method == "iter" && args.is_empty() => Some(strip_parens(receiver)),
+            expr => self.is_reference(expr).then_some(expr),
+        }
+    }
+
+    /// Returns the buffer whose bytes the loop pushes, in order, to `string`.
+    fn copied_buffer<'a>(&self, for_loop: &'a ExprForLoop, string: &Ident) -> Option<&'a Expr> {
+        if for_loop.label.is_some() || count_ident(for_loop, string) != 1 {
+            return None;
+        }
+        let byte = as_char(pushed(&for_loop.body, string)?)?;
+        let used_once = |ident: &Ident| count_ident(&for_loop.body, ident) == 1;
+        match for_loop.pat.as_ref() {
+            // for i in 0..bytes.len() { s.push(bytes[i] as char); }
+            Pat::Ident(PatIdent { ident, .. })
+                if matches!(strip_parens(&for_loop.expr), Expr::Range(_)) =>
+            {
+                let Expr::Range(ExprRange {
+                    start: Some(start),
+                    limits: RangeLimits::HalfOpen(_),
+                    end: Some(end),
+                    ..
+                }) = strip_parens(&for_loop.expr)
+                else {
+                    return None;
+                };
+                let Expr::Index(ExprIndex { expr, index, .. }) = byte else {
+                    return None;
+                };
+                let Expr::MethodCall(len) = strip_parens(end) else {
+                    return None;
+                };
+                let buffer = strip_parens(expr);
+                (is_int_lit(start, 0)
+                    && len.method == "len"
+                    && len.args.is_empty()
+                    && same_tokens(strip_parens(&len.receiver), buffer)
+                    && is_ident(index, ident)
+                    && used_once(ident)
+                    && path_ident(buffer).is_some())
+                .then_some(buffer)
+            }
+            // for b in bytes { s.push(*b as char); }
+            Pat::Ident(PatIdent {
+                by_ref: None,
+                subpat: None,
+                ident,
+                ..
+            }) => {
+                let Expr::Unary(ExprUnary {
+                    op: UnOp::Deref(_),
+                    expr,
+                    ..
+                }) = byte
+                else {
+                    return None;
+                };
+                (is_ident(expr, ident) && used_once(ident))
+                    .then(|| self.borrowed_buffer(&for_loop.expr))
+                    .flatten()
+            }
+            // for &b in bytes { s.push(b as char); }
+            Pat::Reference(PatReference {
+                mutability: None,
+                pat,
+                ..
+            }) => match pat.as_ref() {
+                Pat::Ident(PatIdent {
+                    by_ref: None,
+                    subpat: None,
+                    ident,
+                    ..
+                }) if is_ident(byte, ident) && used_once(ident) => {
+                    self.borrowed_buffer(&for_loop.expr)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn rewrite_block(&mut self, block: &mut Block) {
+        let mut position = 0;
+        while position + 1 < block.stmts.len() {
+            let conversion = match (&block.stmts[position], &block.stmts[position + 1]) {
+                (stmt, Stmt::Expr(Expr::ForLoop(for_loop), _)) => empty_string(stmt)
+                    .and_then(|string| Some((string, self.copied_buffer(for_loop, string)?))),
+                _ => None,
+            };
+            let Some((string, buffer)) = conversion else {
+                position += 1;
+                continue;
+            };
+            let (string, buffer) = (string.clone(), buffer.clone());
+            let reference = match path_ident(&buffer) {
+                Some(ident) => self.ascii.get(ident).copied(),
+                None => ascii_bytes(&buffer),
+            };
+            let Some(reference) = reference else {
+                self.diagnostics.push(Diagnostic::new(
+                    PASS,
+                    self.current_fn.as_ref(),
+                    format!(
+                        "`{}` is built from `{}` a byte per `char`; keeping the loop, as its \
+                         bytes are not known to be ASCII, which `String::from_utf8_lossy` \
+                         would decode differently",
+                        string,
+                        buffer.to_token_stream()
+                    ),
+                ));
+                position += 2;
+                continue;
+            };
+            let conversion: Expr = if reference || self.is_reference(&buffer) {
+                parse_quote!(String::from_utf8_lossy(#buffer).into_owned())
+            } else {
+                parse_quote!(String::from_utf8_lossy(&#buffer).into_owned())
+            };
+            let rest = &block.stmts[position + 2..];
+            let mutable = is_reassigned(rest, &string) || is_receiver(rest, &string);
+            let Stmt::Local(local) = &mut block.stmts[position] else {
+                unreachable!();
+            };
+            if let Some(init) = &mut local.init {
+                *init.expr = conversion;
+            }
+            let pat = match &mut local.pat {
+                Pat::Type(PatType { pat, .. }) => pat.as_mut(),
+                pat => pat,
+            };
+            if let (Pat::Ident(pat), false) = (pat, mutable) {
+                pat.mutability = None;
+            }
+            block.stmts.remove(position + 1);
+            position += 1;
+        }
+    }
+}
+
+impl VisitMut for ByteLoopToFromUtf8 {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        let ascii = std::mem::replace(
+            &mut self.ascii,
+            AsciiLocals::of(&item_fn.sig, &item_fn.block),
+        );
+        let enclosing = self.current_fn.replace(item_fn.sig.ident.clone());
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        self.ascii = ascii;
+        self.types = types;
+        self.current_fn = enclosing;
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        let ascii = std::mem::replace(
+            &mut self.ascii,
+            AsciiLocals::of(&item_fn.sig, &item_fn.block),
+        );
+        let enclosing = self.current_fn.replace(item_fn.sig.ident.clone());
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+        self.ascii = ascii;
+        self.types = types;
+        self.current_fn = enclosing;
+    }
+
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        syn::visit_mut::visit_block_mut(self, block);
+        self.rewrite_block(block);
+    }
+}
+
+impl Pass for ByteLoopToFromUtf8 {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        monad.diagnostics.append(&mut self.diagnostics);
+        Ok(monad)
+    }
+}
@@ -0,0 +1,222 @@
+//! Replaces transmutes of byte arrays into primitive numbers with the safe
+//! `from_*_bytes` constructors, e.g. `transmute::<[u8; 4], u32>(bytes)` becomes
+//! `u32::from_ne_bytes(bytes)`.
+//!
+//! `from_ne_bytes` reinterprets the bytes exactly like the transmute does; the
+//! pass can be configured to emit `from_le_bytes`/`from_be_bytes` instead when the
+//! data is known to have a fixed byte order. An `unsafe` block left wrapping only
+//! the converted call is removed.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{expr_type, local_types, primitive_name, strip_parens};
+use crate::MonadicAst;
+use std::collections::HashMap;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, Expr, ExprCall, ExprLit, ExprPath, ExprUnsafe, GenericArgument, Ident, ImplItemFn,
+    ItemFn, Lit, Local, LocalInit, Pat, PathArguments, Stmt, Type, TypeArray,
+};
+
+/// The byte order assumed for the transmuted bytes.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Endianness {
+    /// Reinterpret the bytes in the platform's byte order, exactly like `transmute`.
+    #[default]
+    Native,
+    Little,
+    Big,
+}
+
+#[derive(Default)]
+pub struct TransmuteBytesToFromBytes {
+    endianness: Endianness,
+    /// Declared types of the locals of the function currently being visited.
+    types: HashMap<Ident, Type>,
+}
+
+/// Returns the size in bytes of the supported primitive `ty`.
+fn primitive_size(ty: &Type) -> Option<usize> {
+    Some(match primitive_name(ty)?.as_str() {
+        "u8" | "i8" => 1,
+        "u16" | "i16" => 2,
+        "u32" | "i32" | "f32" => 4,
+        "u64" | "i64" | "f64" => 8,
+        "u128" | "i128" => 16,
+        _ => return None,
+    })
+}
+
+/// Returns `N` if `ty` is `[u8; N]`.
+fn byte_array_len(ty: &Type) -> Option<usize> {
+    match ty {
+        Type::Array(TypeArray { elem, len, .. }) if primitive_name(elem)? == "u8" => {
+            match strip_parens(len) {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Int(int), ..
+                }) => int.base10_parse().ok(),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns the call's explicit `<From, To>` types if `func` is a `transmute` path.
+fn transmute_types(func: &Expr) -> Option<(Option<Type>, Option<Type>)> {
+    let Expr::Path(ExprPath {
+        qself: None, path, ..
+    }) = func
+    else {
+        return None;
+    };
+    let last = path.segments.last()?;
+    let prefix: Vec<String> = path
+        .segments
+        .iter()
+        .rev()
+        .skip(1)
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    let is_transmute = last.ident == "transmute"
+        && matches!(
+            prefix
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .as_slice(),
+            [] | ["mem"] | ["mem", "std" | "core"]
+        );
+    if !is_transmute {
+        return None;
+    }
+    match &last.arguments {
+        PathArguments::None => Some((None, None)),
+        PathArguments::AngleBracketed(generics) => {
+            let types: Vec<Type> = generics
+                .args
+                .iter()
+                .map(|arg| match arg {
+                    GenericArgument::Type(ty) => Some(ty.clone()),
+                    _ => None,
+                })
+                .collect::<Option<_>>()?;
+            match types.as_slice() {
+                [from, to] => Some((
+                    Some(from.clone()).filter(|ty| !matches!(ty, Type::Infer(_))),
+                    Some(to.clone()).filter(|ty| !matches!(ty, Type::Infer(_))),
+                )),
+                _ => None,
+            }
+        }
+        PathArguments::Parenthesized(_) => None,
+    }
+}
+
+impl TransmuteBytesToFromBytes {
+    pub fn new(endianness: Endianness) -> Self {
+        Self {
+            endianness,
+            types: HashMap::new(),
+        }
+    }
+
+    /// Returns the `from_*_bytes` replacement of `expr` if it is a supported
+    /// transmute, using `target` as the result type when the call does not name it.
+    fn replacement(&self, expr: &Expr, target: Option<&Type>) -> Option<Expr> {
+        let Expr::Call(ExprCall { func, args, .. }) = expr else {
+            return None;
+        };
+        let (from, to) = transmute_types(func)?;
+        if args.len() != 1 {
+            return None;
+        }
+        let bytes = &args[0];
+        let from = from.or_else(|| expr_type(bytes, &self.types))?;
+        let to = to.or_else(|| target.cloned())?;
+        if byte_array_len(&from)? != primitive_size(&to)? {
+            return None;
+        }
+        let constructor = match self.endianness {
+            Endianness::Native => quote::format_ident!("from_ne_bytes"),
+            Endianness::Little => quote::format_ident!("from_le_bytes"),
+            Endianness::Big => quote::format_ident!("from_be_bytes"),
+        };
+        Some(parse_quote!(#to::#constructor(#bytes)))
+    }
+
+    /// Returns the expression an `unsafe` block evaluates to, if the block contains
+    /// only that expression.
+    fn sole_expr(unsafe_expr: &mut ExprUnsafe) -> Option<&mut Expr> {
+        match unsafe_expr.block.stmts.as_mut_slice() {
+            [Stmt::Expr(expr, None)] => Some(expr),
+            _ => None,
+        }
+    }
+
+    fn rewrite(&mut self, expr: &mut Expr, target: Option<&Type>) -> bool {
+        if let Some(replacement) = self.replacement(expr, target) {
+            *expr = replacement;
+            return true;
+        }
+        // unsafe { transmute(bytes) }, whose argument needs no unsafety itself.
+        if let Expr::Unsafe(unsafe_expr) = expr {
+            if let Some(inner) = Self::sole_expr(unsafe_expr) {
+                let safe_arg = matches!(inner, Expr::Call(call)
+                    if call.args.len() == 1
+                        && matches!(call.args[0], Expr::Path(_) | Expr::Field(_) | Expr::Lit(_)));
+                if safe_arg && self.rewrite(inner, target) {
+                    *expr = inner.clone();
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+impl VisitMut for TransmuteBytesToFromBytes {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    /// `let x: T = transmute(bytes);` names the target type outside of the call.
+    fn visit_local_mut(&mut self, local: &mut Local) {
+        if let (
+            Pat::Type(pat_type),
+            Some(LocalInit {
+                expr,
+                diverge: None,
+                ..
+            }),
+        ) = (&local.pat, &mut local.init)
+        {
+            if matches!(pat_type.pat.as_ref(), Pat::Ident(_)) {
+                let target = pat_type.ty.as_ref().clone();
+                self.rewrite(expr, Some(&target));
+            }
+        }
+        syn::visit_mut::visit_local_mut(self, local);
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if !self.rewrite(expr, None) {
+            syn::visit_mut::visit_expr_mut(self, expr);
+        }
+    }
+}
+
+impl Pass for TransmuteBytesToFromBytes {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
@@ -0,0 +1,338 @@
+//! Identifies fixed-size `[c_char; N]` buffers which are only used as storage for
+//! a NUL-terminated string, and reports them as candidates for a `String`.
+//!
+//! When [PassConfig::rewrite_char_buffers] is set, the simplest such buffers are
+//! rewritten: those which are only written by `strcpy`/`strcat` of string
+//! literals, and only read back through `CStr::from_ptr`, e.g.
+//!
+//! ```ignore
+//! let mut buf: [c_char; 64] = [0; 64];
+//! strcpy(buf.as_mut_ptr(), b"id: \0" as *const u8 as *const c_char);
+//! strcat(buf.as_mut_ptr(), b"42\0" as *const u8 as *const c_char);
+//! let s = CStr::from_ptr(buf.as_ptr()).to_str().unwrap();
+//! ```
+//!
+//! becomes `let mut buf = String::from("id: "); buf.push_str("42");
+//! let s = buf.as_str();`, declared where it is first written if that is its
+//! first use, and with the later writes rewritten in place. Buffers that are also
+//! passed to other string functions, e.g. `sprintf`, are only reported.
+//!
+//! [PassConfig::rewrite_char_buffers]: crate::PassConfig::rewrite_char_buffers
+
+use crate::monad::ast::Pass;
+use crate::monad::diagnostic::Diagnostic;
+use crate::monad::error::PassError;
+use crate::passes::utils::{count_ident, is_int_lit, path_ident, primitive_name, strip_parens};
+use crate::MonadicAst;
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, Block, Expr, ExprCall, ExprCast, ExprMethodCall, ExprPath, ExprRepeat, Ident,
+    ImplItemFn, ItemFn, Lit, LitStr, Local, LocalInit, Pat, PatType, Stmt, Type, TypeArray,
+};
+
+const PASS: &str = "replace_char_buffers";
+
+/// C library functions which take a NUL-terminated string argument.
+static STRING_FNS: &[&str] = &[
+    "strcpy", "strncpy", "strcat", "strncat", "strlen", "strcmp", "strncmp", "sprintf", "snprintf",
+    "printf", "puts", "fputs", "from_ptr",
+];
+
+#[derive(Default)]
+pub struct CharBufferToString {
+    rewrite: bool,
+    /// Name of the function currently being visited, for diagnostics.
+    current_fn: Option<Ident>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// A recognized use of the buffer.
+enum BufferUse {
+    /// `strcpy(buf.as_mut_ptr(), "...")`, or `strcat` when `append` is set.
+    Write { text: String, append: bool },
+    /// `CStr::from_ptr(buf.as_ptr())` followed by `.to_str().unwrap()`.
+    Read,
+    /// Any other string function taking a pointer to the buffer.
+    Other,
+}
+
+/// Returns the final path segment of a called function, e.g. `strcpy`.
+fn callee_name(func: &Expr) -> Option<String> {
+    match func {
+        Expr::Path(ExprPath { path, .. }) => path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Returns whether `expr` is `buf.as_ptr()` or `buf.as_mut_ptr()`, ignoring casts.
+fn is_buffer_ptr(expr: &Expr, buffer: &Ident) -> bool {
+    let mut expr = strip_parens(expr);
+    while let Expr::Cast(ExprCast { expr: inner, .. }) = expr {
+        expr = strip_parens(inner);
+    }
+    matches!(expr, Expr::MethodCall(ExprMethodCall { receiver, method, args, .. })
+        if (method == "as_ptr" || method == "as_mut_ptr")
+            && args.is_empty()
+            && path_ident(receiver) == Some(buffer))
+}
+
+/// Returns the text of a C string literal argument such as
+/// `b"abc\0" as *const u8 as *const c_char`, without its terminating NUL.
+fn c_string_literal(expr: &Expr) -> Option<String> {
+    let mut expr = strip_parens(expr);
+    loop {
+        match expr {
+            Expr::Cast(ExprCast { expr: inner, .. }) => expr = strip_parens(inner),
+            Expr::MethodCall(ExprMethodCall {
+                receiver,
+                method,
+                args,
+                ..
+            }) if method == "as_ptr" && args.is_empty() => expr = strip_parens(receiver),
+            _ => break,
+        }
+    }
+    let Expr::Lit(lit) = expr else {
+        return None;
+    };
+    let Lit::ByteStr(bytes) = &lit.lit else {
+        return None;
+    };
+    let bytes = bytes.value();
+    let (&0, text) = bytes.split_last()? else {
+        return None;
+    };
+    if text.contains(&0) {
+        return None;
+    }
+    String::from_utf8(text.to_vec()).ok()
+}
+
+/// Returns the use of the buffer made by `expr`, if it is a recognized use.
+fn buffer_use(expr: &Expr, buffer: &Ident) -> Option<BufferUse> {
+    // CStr::from_ptr(buf.as_ptr()).to_str().unwrap()
+    if let Expr::MethodCall(ExprMethodCall {
+        receiver, method, ..
+    }) = expr
+    {
+        if let Expr::MethodCall(ExprMethodCall {
+            receiver: from_ptr,
+            method: to_str,
+            ..
+        }) = strip_parens(receiver)
+        {
+            if method == "unwrap" && to_str == "to_str" {
+                if let Expr::Call(ExprCall { func, args, .. }) = strip_parens(from_ptr) {
+                    if callee_name(func).as_deref() == Some("from_ptr")
+                        && args.len() == 1
+                        && is_buffer_ptr(&args[0], buffer)
+                    {
+                        return Some(BufferUse::Read);
+                    }
+                }
+            }
+        }
+    }
+    let Expr::Call(ExprCall { func, args, .. }) = expr else {
+        return None;
+    };
+    let name = callee_name(func)?;
+    if !STRING_FNS.contains(&name.as_str()) {
+        return None;
+    }
+    let uses = args.iter().filter(|arg| is_buffer_ptr(arg, buffer)).count();
+    if uses == 0 || uses != count_ident(args, buffer) {
+        return None;
+    }
+    let write = match (name.as_str(), args.len()) {
+        ("strcpy" | "strcat", 2) if is_buffer_ptr(&args[0], buffer) => c_string_literal(&args[1])
+            .map(|text| BufferUse::Write {
+                text,
+                append: name == "strcat",
+            }),
+        _ => None,
+    };
+    Some(write.unwrap_or(BufferUse::Other))
+}
+
+/// Tallies the recognized uses of the buffer after its declaration.
+struct BufferUses<'a> {
+    buffer: &'a Ident,
+    /// Occurrences of the buffer accounted for by a recognized use.
+    recognized: usize,
+    writes: usize,
+    reads: usize,
+    others: usize,
+}
+
+impl Visit<'_> for BufferUses<'_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        match buffer_use(expr, self.buffer) {
+            Some(found) => {
+                self.recognized += count_ident(expr, self.buffer);
+                match found {
+                    BufferUse::Write { .. } => self.writes += 1,
+                    BufferUse::Read => self.reads += 1,
+                    BufferUse::Other => self.others += 1,
+                }
+            }
+            None => syn::visit::visit_expr(self, expr),
+        }
+    }
+}
+
+/// Rewrites the recognized uses of the buffer in terms of a `String`.
+struct BufferRewriter<'a> {
+    buffer: &'a Ident,
+}
+
+impl VisitMut for BufferRewriter<'_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        let buffer = self.buffer;
+        match buffer_use(expr, buffer) {
+            Some(BufferUse::Write { text, append }) => {
+                let text = LitStr::new(&text, proc_macro2::Span::call_site());
+                *expr = if append {
+                    parse_quote!(#buffer.push_str(#text))
+                } else {
+                    parse_quote!(#buffer = String::from(#text))
+                };
+            }
+            Some(BufferUse::Read) => *expr = parse_quote!(#buffer.as_str()),
+            _ => syn::visit_mut::visit_expr_mut(self, expr),
+        }
+    }
+}
+
+impl CharBufferToString {
+    /// Returns the identifier of `let mut buf: [c_char; N] = [0; N];`.
+    fn char_buffer_decl(local: &Local) -> Option<&Ident> {
+        let Pat::Type(PatType { pat, ty, .. }) = &local.pat else {
+            return None;
+        };
+        let Pat::Ident(pat_ident) = pat.as_ref() else {
+            return None;
+        };
+        let Type::Array(TypeArray { elem, .. }) = ty.as_ref() else {
+            return None;
+        };
+        let is_char = match elem.as_ref() {
+            Type::Path(path)
+                if path
+                    .path
+                    .segments
+                    .last()
+                    .is_some_and(|segment| segment.ident == "c_char") =>
+            {
+                true
+            }
+            elem => primitive_name(elem).is_some_and(|name| name == "i8" || name == "u8"),
+        };
+        let zeroed = matches!(&local.init, Some(LocalInit { expr, diverge: None, .. })
+            if matches!(strip_parens(expr), Expr::Repeat(ExprRepeat { expr, .. }) if is_int_lit(expr, 0)));
+        (pat_ident.mutability.is_some() && is_char && zeroed).then_some(&pat_ident.ident)
+    }
+
+    /// Rewrites the buffer declared by the statement at `index`, if it is one, and
+    /// returns whether its declaration was removed.
+    fn simplify_buffer(&mut self, block: &mut Block, index: usize) -> bool {
+        let Stmt::Local(local) = &block.stmts[index] else {
+            return false;
+        };
+        let Some(buffer) = Self::char_buffer_decl(local).cloned() else {
+            return false;
+        };
+        let rest = &block.stmts[index + 1..];
+        let mut uses = BufferUses {
+            buffer: &buffer,
+            recognized: 0,
+            writes: 0,
+            reads: 0,
+            others: 0,
+        };
+        rest.iter().for_each(|stmt| uses.visit_stmt(stmt));
+        let total: usize = rest.iter().map(|stmt| count_ident(stmt, &buffer)).sum();
+        if total == 0 || uses.recognized != total {
+            return false;
+        }
+        if !self.rewrite || uses.others > 0 || uses.writes == 0 || uses.reads == 0 {
+            self.diagnostics.push(Diagnostic::new(
+                PASS,
+                self.current_fn.as_ref(),
+                format!(
+                    "`{}` is only used as NUL-terminated string storage; \
+                     consider a `String`",
+                    buffer
+                ),
+            ));
+            return false;
+        }
+        // The first use of the buffer, if it is a write, initializes the string
+        // instead
+        let first_use = rest
+            .iter()
+            .position(|stmt| count_ident(stmt, &buffer) > 0)
+            .map(|offset| index + 1 + offset);
+        let first_write = first_use.and_then(|position| match &block.stmts[position] {
+            Stmt::Expr(expr, Some(_)) => match buffer_use(expr, &buffer) {
+                Some(BufferUse::Write { text, .. }) => Some((position, text)),
+                _ => None,
+            },
+            _ => None,
+        });
+        let removed = first_write.is_some();
+        let rest_start = match first_write {
+            Some((position, text)) => {
+                let text = LitStr::new(&text, proc_macro2::Span::call_site());
+                let mutability = (uses.writes > 1).then(|| quote::quote!(mut));
+                block.stmts[position] =
+                    parse_quote!(let #mutability #buffer = String::from(#text););
+                block.stmts.remove(index);
+                position
+            }
+            None => {
+                block.stmts[index] = parse_quote!(let mut #buffer = String::new(););
+                index + 1
+            }
+        };
+        let mut rewriter = BufferRewriter { buffer: &buffer };
+        block.stmts[rest_start..]
+            .iter_mut()
+            .for_each(|stmt| rewriter.visit_stmt_mut(stmt));
+        removed
+    }
+}
+
+impl VisitMut for CharBufferToString {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        let enclosing = self.current_fn.replace(item_fn.sig.ident.clone());
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        self.current_fn = enclosing;
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        let enclosing = self.current_fn.replace(item_fn.sig.ident.clone());
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+        self.current_fn = enclosing;
+    }
+
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        syn::visit_mut::visit_block_mut(self, block);
+        let mut index = 0;
+        while index < block.stmts.len() {
+            if !self.simplify_buffer(block, index) {
+                index += 1;
+            }
+        }
+    }
+}
+
+impl Pass for CharBufferToString {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.rewrite = monad.config.rewrite_char_buffers;
+        self.visit_file_mut(&mut monad.ast);
+        monad.diagnostics.append(&mut self.diagnostics);
+        Ok(monad)
+    }
+}
@@ -0,0 +1,197 @@
+//! Replaces private functions without parameters whose body is a single constant
+//! expression with `const` items, as left behind by translated `#define`s, e.g.
+//! `fn MAX() -> i32 { 100 }` becomes `const MAX: i32 = 100;` and its calls `MAX()`
+//! become `MAX`.
+//!
+//! The body may only consist of literals, operators and casts applied to them, the
+//! other `const` items of the file, and calls of other functions being replaced.
+//! The function must only ever be called, not referred to as a value. A name which
+//! is not upper case is turned into one, as long as that name is free in the file.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{count_ident, is_assign_op, is_exported, path_ident};
+use crate::MonadicAst;
+use quote::format_ident;
+use std::collections::{HashMap, HashSet};
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, Expr, ExprCall, ExprUnary, File, Ident, Item, ItemFn, ReturnType, Stmt, Type, UnOp,
+};
+
+#[derive(Default)]
+pub struct ConstFnToConst {
+    /// The replaced functions and the names of their `const` items.
+    renames: HashMap<Ident, Ident>,
+}
+
+/// Returns the number of calls without arguments of each function in the file.
+fn nullary_calls(file: &File) -> HashMap<Ident, usize> {
+    struct Counter(HashMap<Ident, usize>);
+    impl Visit<'_> for Counter {
+        fn visit_expr_call(&mut self, call: &ExprCall) {
+            if let (Some(callee), true) = (path_ident(&call.func), call.args.is_empty()) {
+                *self.0.entry(callee.clone()).or_default() += 1;
+            }
+            syn::visit::visit_expr_call(self, call);
+        }
+    }
+    let mut counter = Counter(HashMap::new());
+    counter.visit_file(file);
+    counter.0
+}
+
+/// Returns the constant the function returns, if it has no parameters and its
+/// body is only that expression.
+fn constant_body(item_fn: &ItemFn) -> Option<(&Type, &Expr)> {
+    let sig = &item_fn.sig;
+    let ReturnType::Type(_, ty) = &sig.output else {
+        return None;
+    };
+    // Besides docs, only attributes which do not apply to a `const` are dropped.
+    let droppable = item_fn.attrs.iter().all(|attr| {
+        let path = attr.path();
+        path.is_ident("doc") || path.is_ident("inline") || path.is_ident("allow")
+    });
+    if is_exported(item_fn)
+        || !droppable
+        || !sig.inputs.is_empty()
+        || sig.variadic.is_some()
+        || sig.asyncness.is_some()
+        || !sig.generics.params.is_empty()
+        || sig.generics.where_clause.is_some()
+        || matches!(ty.as_ref(), Type::ImplTrait(_))
+    {
+        return None;
+    }
+    match item_fn.block.stmts.as_slice() {
+        [Stmt::Expr(expr, None)] => Some((ty, expr)),
+        _ => None,
+    }
+}
+
+/// Returns whether the expression is constant, given the names of the file's
+/// `const` items and of the functions replaced by one.
+fn is_constant(expr: &Expr, consts: &HashSet<Ident>, fns: &HashSet<Ident>) -> bool {
+    match expr {
+        Expr::Lit(_) => true,
+        Expr::Paren(paren) => is_constant(&paren.expr, consts, fns),
+        Expr::Group(group) => is_constant(&group.expr, consts, fns),
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_) | UnOp::Not(_),
+            expr,
+            ..
+        }) => is_constant(expr, consts, fns),
+        Expr::Binary(binary) => {
+            !is_assign_op(&binary.op)
+                && is_constant(&binary.left, consts, fns)
+                && is_constant(&binary.right, consts, fns)
+        }
+        Expr::Cast(cast) => is_constant(&cast.expr, consts, fns),
+        Expr::Call(call) => {
+            call.args.is_empty() && path_ident(&call.func).is_some_and(|f| fns.contains(f))
+        }
+        expr => path_ident(expr).is_some_and(|ident| consts.contains(ident)),
+    }
+}
+
+/// Returns the upper case name of the `const` item replacing the function.
+fn const_name(name: &Ident) -> Ident {
+    format_ident!("{}", name.to_string().to_uppercase(), span = name.span())
+}
+
+impl ConstFnToConst {
+    /// Returns the functions to replace, mapped to their `const` items' names.
+    fn candidates(file: &File) -> HashMap<Ident, Ident> {
+        let consts: HashSet<Ident> = file
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Const(item_const) => Some(item_const.ident.clone()),
+                _ => None,
+            })
+            .collect();
+        let calls = nullary_calls(file);
+        let bodies: Vec<(&Ident, &Expr)> = file
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Fn(item_fn) => {
+                    let name = &item_fn.sig.ident;
+                    let (_, body) = constant_body(item_fn)?;
+                    let new_name = const_name(name);
+                    // Every mention of the function besides its definition is a call.
+                    let only_called = count_ident(file, name) == calls.get(name)? + 1;
+                    let name_free = new_name == *name || count_ident(file, &new_name) == 0;
+                    (only_called && name_free).then_some((name, body))
+                }
+                _ => None,
+            })
+            .collect();
+
+        // A body may call functions which are replaced themselves.
+        let mut fns = HashSet::new();
+        loop {
+            let before = fns.len();
+            for (name, body) in &bodies {
+                if is_constant(body, &consts, &fns) {
+                    fns.insert((*name).clone());
+                }
+            }
+            if fns.len() == before {
+                break;
+            }
+        }
+        fns.into_iter()
+            .map(|name| {
+                let new_name = const_name(&name);
+                (name, new_name)
+            })
+            .collect()
+    }
+}
+
+impl VisitMut for ConstFnToConst {
+    fn visit_item_mut(&mut self, item: &mut Item) {
+        syn::visit_mut::visit_item_mut(self, item);
+        let Item::Fn(item_fn) = item else {
+            return;
+        };
+        let Some(name) = self.renames.get(&item_fn.sig.ident) else {
+            return;
+        };
+        let Some((ty, body)) = constant_body(item_fn) else {
+            return;
+        };
+        let docs = item_fn
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("doc"));
+        *item = parse_quote! {
+            #(#docs)*
+            const #name: #ty = #body;
+        };
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        syn::visit_mut::visit_expr_mut(self, expr);
+        if let Expr::Call(call) = expr {
+            let renamed = path_ident(&call.func).and_then(|f| self.renames.get(f));
+            if let (Some(name), true) = (renamed, call.args.is_empty()) {
+                *expr = parse_quote!(#name);
+            }
+        }
+    }
+}
+
+impl Pass for ConstFnToConst {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.renames = Self::candidates(&monad.ast);
+        if self.renames.is_empty() {
+            return Ok(monad);
+        }
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
@@ -0,0 +1,211 @@
+//! Replaces checks propagating the error of a call with the `?` operator, in
+//! functions which return a `Result`, e.g.
+//!
+//! ```ignore
+//! let rc = open_device(id);
+//! if rc.is_err() {
+//!     return rc;
+//! }
+//! ```
+//!
+//! becomes `open_device(id)?;`. The check may also be written as
+//! `if let Err(e) = rc { return Err(e); }`.
+//!
+//! The checked value must come directly from a call of a function defined in the
+//! file whose `Result` has the same error type as the caller's, and it may not be
+//! used after the check.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{count_ident, is_ident, path_ident, same_tokens, strip_parens};
+use crate::MonadicAst;
+use std::collections::HashMap;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, Block, Expr, ExprCall, ExprClosure, ExprIf, ExprLet, ExprMethodCall, ExprReturn,
+    GenericArgument, Ident, ImplItemFn, Item, ItemFn, Local, LocalInit, Pat, PatIdent,
+    PathArguments, ReturnType, Stmt, Type, TypePath,
+};
+
+#[derive(Default)]
+pub struct PropagateViaTry {
+    /// The error types of the functions in the file returning a `Result`.
+    errors: HashMap<Ident, Type>,
+    /// The error type of the function currently being visited.
+    current: Option<Type>,
+}
+
+/// Returns `E` if the function returns `Result<T, E>`.
+fn error_type(output: &ReturnType) -> Option<Type> {
+    let ReturnType::Type(_, ty) = output else {
+        return None;
+    };
+    let Type::Path(TypePath { qself: None, path }) = ty.as_ref() else {
+        return None;
+    };
+    let segment = path.segments.last()?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.iter().collect::<Vec<_>>()[..] {
+        [GenericArgument::Type(_), GenericArgument::Type(error)] if segment.ident == "Result" => {
+            Some(error.clone())
+        }
+        _ => None,
+    }
+}
+
+/// Returns the variable and the call if the statement is `let rc = f(..);`.
+fn checked_call(stmt: &Stmt) -> Option<(&Ident, &ExprCall)> {
+    let Stmt::Local(Local {
+        pat:
+            Pat::Ident(PatIdent {
+                by_ref: None,
+                subpat: None,
+                ident,
+                ..
+            }),
+        init:
+            Some(LocalInit {
+                expr,
+                diverge: None,
+                ..
+            }),
+        ..
+    }) = stmt
+    else {
+        return None;
+    };
+    match strip_parens(expr) {
+        Expr::Call(call) => Some((ident, call)),
+        _ => None,
+    }
+}
+
+/// Returns whether the block only returns `value`.
+fn returns(block: &Block, value: impl Fn(&Expr) -> bool) -> bool {
+    matches!(block.stmts.as_slice(), [Stmt::Expr(Expr::Return(ExprReturn {
+        expr: Some(returned), ..
+    }), _)] if value(returned))
+}
+
+/// Returns whether the statement is `if rc.is_err() { return rc; }` or
+/// `if let Err(e) = rc { return Err(e); }`.
+fn is_error_check(stmt: &Stmt, rc: &Ident) -> bool {
+    let Stmt::Expr(
+        Expr::If(ExprIf {
+            cond,
+            then_branch,
+            else_branch: None,
+            ..
+        }),
+        _,
+    ) = stmt
+    else {
+        return false;
+    };
+    match strip_parens(cond) {
+        Expr::MethodCall(ExprMethodCall {
+            receiver,
+            method,
+            args,
+            ..
+        }) if method == "is_err" && args.is_empty() && is_ident(receiver, rc) => {
+            returns(then_branch, |returned| is_ident(returned, rc))
+        }
+        Expr::Let(ExprLet { pat, expr, .. }) if is_ident(expr, rc) => {
+            let Pat::TupleStruct(pat) = pat.as_ref() else {
+                return false;
+            };
+            let [Pat::Ident(error)] = pat.elems.iter().collect::<Vec<_>>()[..] else {
+                return false;
+            };
+            pat.path.is_ident("Err")
+                && returns(then_branch, |returned| {
+                    matches!(strip_parens(returned), Expr::Call(ExprCall { func, args, .. })
+                        if matches!(path_ident(func), Some(func) if func == "Err")
+                            && args.len() == 1
+                            && is_ident(&args[0], &error.ident))
+                })
+        }
+        _ => false,
+    }
+}
+
+impl PropagateViaTry {
+    fn rewrite_block(&self, block: &mut Block) {
+        let Some(error) = &self.current else {
+            return;
+        };
+        let mut position = 0;
+        while position + 1 < block.stmts.len() {
+            let propagated = checked_call(&block.stmts[position]).and_then(|(rc, call)| {
+                let callee = path_ident(&call.func)?;
+                let same_error = self
+                    .errors
+                    .get(callee)
+                    .is_some_and(|callee_error| same_tokens(callee_error, error));
+                let used_later = block.stmts[position + 2..]
+                    .iter()
+                    .any(|stmt| count_ident(stmt, rc) > 0);
+                (same_error
+                    && count_ident(call, rc) == 0
+                    && is_error_check(&block.stmts[position + 1], rc)
+                    && !used_later)
+                    .then(|| call.clone())
+            });
+            if let Some(call) = propagated {
+                block.stmts[position] = parse_quote!(#call?;);
+                block.stmts.remove(position + 1);
+            }
+            position += 1;
+        }
+    }
+}
+
+impl VisitMut for PropagateViaTry {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        let current = std::mem::replace(&mut self.current, error_type(&item_fn.sig.output));
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        self.current = current;
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        let current = std::mem::replace(&mut self.current, error_type(&item_fn.sig.output));
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+        self.current = current;
+    }
+
+    /// A `return` inside of a closure leaves the closure, not the function.
+    fn visit_expr_closure_mut(&mut self, closure: &mut ExprClosure) {
+        let current = self.current.take();
+        syn::visit_mut::visit_expr_closure_mut(self, closure);
+        self.current = current;
+    }
+
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        syn::visit_mut::visit_block_mut(self, block);
+        self.rewrite_block(block);
+    }
+}
+
+impl Pass for PropagateViaTry {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.errors = monad
+            .ast
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Fn(item_fn) => {
+                    Some((item_fn.sig.ident.clone(), error_type(&item_fn.sig.output)?))
+                }
+                _ => None,
+            })
+            .collect();
+        if self.errors.is_empty() {
+            return Ok(monad);
+        }
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
@@ -0,0 +1,125 @@
+//! Replaces loops which assign the same constant to every element of a slice or
+//! array with a call to `fill`, e.g. `for i in 0..a.len() { a[i] = 0; }` becomes
+//! `a.fill(0);`.
+//!
+//! The loop must cover exactly the whole slice, written either as `0..a.len()` or
+//! as `0..N` for an array declared as `[T; N]`, and the assigned value must be a
+//! literal or a `const` item.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{
+    is_ident, is_int_lit, local_types, path_ident, same_tokens, strip_parens,
+};
+use crate::MonadicAst;
+use std::collections::{HashMap, HashSet};
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, Expr, ExprAssign, ExprForLoop, ExprIndex, ExprMethodCall, ExprRange, Ident,
+    ImplItemFn, Item, ItemFn, Pat, RangeLimits, Stmt, Type, TypeArray, UnOp,
+};
+
+#[derive(Default)]
+pub struct ZeroLoopToFill {
+    /// Names of the `const` items declared in the file.
+    consts: HashSet<Ident>,
+    /// Declared types of the locals of the function currently being visited.
+    types: HashMap<Ident, Type>,
+}
+
+impl ZeroLoopToFill {
+    /// Returns whether `expr` is a literal, a `const` item, or a cast or negation
+    /// of one.
+    fn is_constant(&self, expr: &Expr) -> bool {
+        match strip_parens(expr) {
+            Expr::Lit(_) => true,
+            Expr::Unary(unary) if matches!(unary.op, UnOp::Neg(_)) => self.is_constant(&unary.expr),
+            Expr::Cast(cast) => self.is_constant(&cast.expr),
+            expr => path_ident(expr).is_some_and(|ident| self.consts.contains(ident)),
+        }
+    }
+
+    /// Returns whether the range `0..end` covers every element of `slice`.
+    fn covers(&self, end: &Expr, slice: &Ident) -> bool {
+        let len_call = matches!(strip_parens(end), Expr::MethodCall(ExprMethodCall {
+            receiver, method, args, ..
+        }) if method == "len" && args.is_empty() && is_ident(receiver, slice));
+        let array_len = match self.types.get(slice) {
+            Some(Type::Array(TypeArray { len, .. })) => {
+                same_tokens(strip_parens(len), strip_parens(end))
+            }
+            _ => false,
+        };
+        len_call || array_len
+    }
+
+    /// Returns the `fill` call replacing the loop, if it is a fill loop.
+    fn replacement(&self, for_loop: &ExprForLoop) -> Option<Stmt> {
+        let Pat::Ident(pat) = for_loop.pat.as_ref() else {
+            return None;
+        };
+        let Expr::Range(ExprRange {
+            start: Some(start),
+            limits: RangeLimits::HalfOpen(_),
+            end: Some(end),
+            ..
+        }) = for_loop.expr.as_ref()
+        else {
+            return None;
+        };
+        let assign = match for_loop.body.stmts.as_slice() {
+            [Stmt::Expr(Expr::Assign(assign), _)] => assign,
+            _ => return None,
+        };
+        let ExprAssign { left, right, .. } = assign;
+        let Expr::Index(ExprIndex { expr, index, .. }) = left.as_ref() else {
+            return None;
+        };
+        let slice = path_ident(expr)?;
+        (is_int_lit(start, 0)
+            && is_ident(index, &pat.ident)
+            && self.covers(end, slice)
+            && self.is_constant(right))
+        .then(|| parse_quote!(#slice.fill(#right);))
+    }
+}
+
+impl VisitMut for ZeroLoopToFill {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_stmt_mut(&mut self, stmt: &mut Stmt) {
+        if let Stmt::Expr(Expr::ForLoop(for_loop), _) = stmt {
+            if let Some(fill) = self.replacement(for_loop) {
+                *stmt = fill;
+                return;
+            }
+        }
+        syn::visit_mut::visit_stmt_mut(self, stmt);
+    }
+}
+
+impl Pass for ZeroLoopToFill {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.consts = monad
+            .ast
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Const(item_const) => Some(item_const.ident.clone()),
+                _ => None,
+            })
+            .collect();
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
@@ -0,0 +1,425 @@
+//! Replaces loops recording the index of the first element of a slice matching a
+//! condition with `position`, e.g.
+//!
+//! ```ignore
+//! let mut idx = -1;
+//! for i in 0..a.len() {
+//!     if a[i] == target {
+//!         idx = i as i32;
+//!         break;
+//!     }
+//! }
+//! if idx == -1 {
+//!     return;
+//! }
+//! ```
+//!
+//! becomes `let idx = a.iter().position(|&x| x == target);` followed by
+//! `if idx.is_none() { .. }`. The sentinel can only be replaced by `None` when the
+//! index is afterwards just compared with it, through `idx == -1`, `idx != -1`,
+//! `idx < 0` or `idx >= 0`. Otherwise the index keeps its type, and the search
+//! maps a missing element to the sentinel with `map_or(-1, ..)`.
+//!
+//! The loop body may only assign the index and `break`, and the condition may only
+//! use the index to read the element.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{
+    count_ident, expr_type, has_primitive_elements, is_ident, is_int_lit, is_reassigned,
+    len_as_usize, local_types, path_ident, strip_casts, strip_parens, NameGen, Parenthesize,
+};
+use crate::MonadicAst;
+use std::collections::HashMap;
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, BinOp, Block, Expr, ExprBinary, ExprBreak, ExprCast, ExprForLoop, ExprIf,
+    ExprIndex, ExprMethodCall, ExprRange, ExprUnary, Ident, ImplItemFn, ItemFn, Local, LocalInit,
+    Pat, PatIdent, PatType, RangeLimits, Stmt, Type, UnOp,
+};
+
+#[derive(Default)]
+pub struct FindLoopToPosition {
+    /// Declared types of the locals of the function currently being visited.
+    types: HashMap<Ident, Type>,
+    /// Generates names unused in the function currently being visited.
+    names: NameGen,
+}
+
+/// A search loop matched against its preceding declaration.
+struct Search {
+    /// The `position` call, evaluating to `Option<usize>`.
+    position: Expr,
+    /// The type the index is cast to when it is assigned, if any.
+    cast: Option<Type>,
+}
+
+/// Returns the index variable if the statement is `let mut idx = -1;`, with or
+/// without a type.
+fn sentinel_decl(stmt: &Stmt) -> Option<(&Ident, Option<&Type>)> {
+    let Stmt::Local(Local {
+        pat,
+        init:
+            Some(LocalInit {
+                expr,
+                diverge: None,
+                ..
+            }),
+        ..
+    }) = stmt
+    else {
+        return None;
+    };
+    let (pat, ty) = match pat {
+        Pat::Type(PatType { pat, ty, .. }) => (pat.as_ref(), Some(ty.as_ref())),
+        pat => (pat, None),
+    };
+    let Pat::Ident(PatIdent {
+        by_ref: None,
+        mutability: Some(_),
+        subpat: None,
+        ident,
+        ..
+    }) = pat
+    else {
+        return None;
+    };
+    is_sentinel(expr).then_some((ident, ty))
+}
+
+/// Returns whether `expr` is `-1`.
+fn is_sentinel(expr: &Expr) -> bool {
+    matches!(strip_parens(expr), Expr::Unary(ExprUnary {
+        op: UnOp::Neg(_), expr, ..
+    }) if is_int_lit(expr, 1))
+}
+
+/// Returns whether `expr` is `slice[index]`, with the index possibly cast.
+fn is_element(expr: &Expr, slice: &Ident, index: &Ident) -> bool {
+    matches!(strip_parens(expr), Expr::Index(ExprIndex { expr, index: found, .. })
+        if is_ident(expr, slice) && is_ident(strip_casts(found), index))
+}
+
+/// Returns the slice of the first element read, `slice[index]`, in the condition.
+fn indexed_slice<'a>(cond: &'a Expr, index: &Ident) -> Option<&'a Ident> {
+    struct Finder<'a, 'b> {
+        index: &'b Ident,
+        slice: Option<&'a Ident>,
+    }
+    impl<'a> Visit<'a> for Finder<'a, '_> {
+        fn visit_expr_index(&mut self, expr: &'a ExprIndex) {
+            if self.slice.is_none() && is_ident(strip_casts(&expr.index), self.index) {
+                self.slice = path_ident(&expr.expr);
+            }
+            syn::visit::visit_expr_index(self, expr);
+        }
+    }
+    let mut finder = Finder { index, slice: None };
+    finder.visit_expr(cond);
+    finder.slice
+}
+
+/// Counts the element reads `slice[index]` in the condition.
+fn count_elements(cond: &Expr, slice: &Ident, index: &Ident) -> usize {
+    struct Counter<'a> {
+        slice: &'a Ident,
+        index: &'a Ident,
+        count: usize,
+    }
+    impl Visit<'_> for Counter<'_> {
+        fn visit_expr(&mut self, expr: &Expr) {
+            if is_element(expr, self.slice, self.index) {
+                self.count += 1;
+                return;
+            }
+            syn::visit::visit_expr(self, expr);
+        }
+    }
+    let mut counter = Counter {
+        slice,
+        index,
+        count: 0,
+    };
+    counter.visit_expr(cond);
+    counter.count
+}
+
+/// Replaces the element reads in the condition with the closure's binding.
+struct ElementToBinding<'a> {
+    slice: &'a Ident,
+    index: &'a Ident,
+    binding: Expr,
+}
+
+impl VisitMut for ElementToBinding<'_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if is_element(expr, self.slice, self.index) {
+            *expr = self.binding.clone();
+            return;
+        }
+        syn::visit_mut::visit_expr_mut(self, expr);
+    }
+}
+
+/// Returns `is_none` or `is_some` if `expr` compares `idx` with the sentinel.
+fn sentinel_test(expr: &Expr, idx: &Ident) -> Option<&'static str> {
+    let Expr::Binary(ExprBinary {
+        left, op, right, ..
+    }) = strip_parens(expr)
+    else {
+        return None;
+    };
+    match op {
+        BinOp::Eq(_) | BinOp::Ne(_) => {
+            let compares = (is_ident(left, idx) && is_sentinel(right))
+                || (is_sentinel(left) && is_ident(right, idx));
+            let method = if matches!(op, BinOp::Eq(_)) {
+                "is_none"
+            } else {
+                "is_some"
+            };
+            compares.then_some(method)
+        }
+        BinOp::Lt(_) if is_ident(left, idx) && is_int_lit(right, 0) => Some("is_none"),
+        BinOp::Ge(_) if is_ident(left, idx) && is_int_lit(right, 0) => Some("is_some"),
+        _ => None,
+    }
+}
+
+/// Counts the comparisons of `idx` with the sentinel, or rewrites them into tests
+/// of the `Option` when `rewrite` is set.
+struct SentinelTests<'a> {
+    idx: &'a Ident,
+    count: usize,
+    rewrite: bool,
+}
+
+impl VisitMut for SentinelTests<'_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if let Some(method) = sentinel_test(expr, self.idx) {
+            self.count += 1;
+            if self.rewrite {
+                let idx = self.idx;
+                let method = quote::format_ident!("{}", method);
+                *expr = parse_quote!(#idx.#method());
+            }
+            return;
+        }
+        syn::visit_mut::visit_expr_mut(self, expr);
+    }
+}
+
+impl FindLoopToPosition {
+    /// Returns the search replacing the loop if it assigns the index of the first
+    /// matching element to `idx`.
+    fn search(&mut self, for_loop: &ExprForLoop, idx: &Ident) -> Option<Search> {
+        let Pat::Ident(pat) = for_loop.pat.as_ref() else {
+            return None;
+        };
+        let index = &pat.ident;
+        let Expr::Range(ExprRange {
+            start: Some(start),
+            limits: RangeLimits::HalfOpen(_),
+            end: Some(end),
+            ..
+        }) = for_loop.expr.as_ref()
+        else {
+            return None;
+        };
+        let [Stmt::Expr(
+            Expr::If(ExprIf {
+                attrs,
+                cond,
+                then_branch,
+                else_branch: None,
+                ..
+            }),
+            _,
+        )] = for_loop.body.stmts.as_slice()
+        else {
+            return None;
+        };
+        let [Stmt::Expr(Expr::Assign(assign), Some(_)), Stmt::Expr(
+            Expr::Break(ExprBreak {
+                label: None,
+                expr: None,
+                ..
+            }),
+            _,
+        )] = then_branch.stmts.as_slice()
+        else {
+            return None;
+        };
+        let cast = match strip_parens(&assign.right) {
+            Expr::Cast(ExprCast { expr, ty, .. }) if is_ident(expr, index) => {
+                Some(ty.as_ref().clone())
+            }
+            expr if is_ident(expr, index) => None,
+            _ => return None,
+        };
+        let slice = indexed_slice(cond, index)?;
+        let elements = count_elements(cond, slice, index);
+        if for_loop.label.is_some()
+            || !attrs.is_empty()
+            || !is_ident(&assign.left, idx)
+            || !is_int_lit(start, 0)
+            || count_ident(for_loop, idx) != 1
+            || count_ident(end, index) > 0
+            || count_ident(cond, index) != elements
+            || count_ident(cond, slice) != elements
+        {
+            return None;
+        }
+
+        // Search the prefix the loop covers, unless it is the whole slice.
+        let covers_all = matches!(strip_casts(end), Expr::MethodCall(ExprMethodCall {
+            receiver, method, args, ..
+        }) if method == "len" && args.is_empty() && is_ident(receiver, slice));
+        let index_is_cast = cond_casts_index(cond, index);
+        let receiver: Expr = match (covers_all, index_is_cast) {
+            (true, _) => parse_quote!(#slice),
+            (false, false) => parse_quote!(#slice[..#end]),
+            // A signed bound may be negative, for which the loop runs no iteration.
+            (false, true) => {
+                let end = len_as_usize(strip_parens(end), expr_type(end, &self.types).as_ref());
+                parse_quote!(#slice[..#end])
+            }
+        };
+
+        let binding = self.names.fresh("x");
+        let by_value = self.types.get(slice).is_some_and(has_primitive_elements);
+        let (pat, element): (Pat, Expr) = if by_value {
+            (parse_quote!(&#binding), parse_quote!(#binding))
+        } else {
+            (parse_quote!(#binding), parse_quote!(*#binding))
+        };
+        let mut predicate = cond.as_ref().clone();
+        ElementToBinding {
+            slice,
+            index,
+            binding: element,
+        }
+        .visit_expr_mut(&mut predicate);
+        Parenthesize.visit_expr_mut(&mut predicate);
+        Some(Search {
+            position: parse_quote!(#receiver.iter().position(|#pat| #predicate)),
+            cast,
+        })
+    }
+
+    fn rewrite_block(&mut self, block: &mut Block) {
+        let mut position = 0;
+        while position + 1 < block.stmts.len() {
+            let Some((idx, ty)) = sentinel_decl(&block.stmts[position]) else {
+                position += 1;
+                continue;
+            };
+            let (idx, ty) = (idx.clone(), ty.cloned());
+            let Stmt::Expr(Expr::ForLoop(for_loop), _) = &block.stmts[position + 1] else {
+                position += 1;
+                continue;
+            };
+            let Some(Search {
+                position: search,
+                cast,
+            }) = self.search(for_loop, &idx)
+            else {
+                position += 1;
+                continue;
+            };
+
+            let rest = &mut block.stmts[position + 2..];
+            let mut tests = SentinelTests {
+                idx: &idx,
+                count: 0,
+                rewrite: false,
+            };
+            for stmt in rest.iter_mut() {
+                tests.visit_stmt_mut(stmt);
+            }
+            let uses: usize = rest.iter().map(|stmt| count_ident(stmt, &idx)).sum();
+            let init = if tests.count == uses {
+                tests.rewrite = true;
+                for stmt in rest.iter_mut() {
+                    tests.visit_stmt_mut(stmt);
+                }
+                search
+            } else {
+                // The index stays an integer, of the declared or the assigned type.
+                let Some(ty) = ty.or(cast) else {
+                    position += 1;
+                    continue;
+                };
+                parse_quote!(#search.map_or(-1, |i| i as #ty))
+            };
+            let reassigned = is_reassigned(rest, &idx);
+            let Stmt::Local(local) = &mut block.stmts[position] else {
+                unreachable!();
+            };
+            let mut pat = local.pat.clone();
+            // The declared integer type no longer applies to the `Option`.
+            if let (true, Pat::Type(PatType { pat: untyped, .. })) = (tests.rewrite, &local.pat) {
+                pat = untyped.as_ref().clone();
+            }
+            if !reassigned {
+                let ident_pat = match &mut pat {
+                    Pat::Type(PatType { pat, .. }) => pat.as_mut(),
+                    pat => pat,
+                };
+                if let Pat::Ident(ident_pat) = ident_pat {
+                    ident_pat.mutability = None;
+                }
+            }
+            local.pat = pat;
+            if let Some(local_init) = &mut local.init {
+                *local_init.expr = init;
+            }
+            block.stmts.remove(position + 1);
+            position += 1;
+        }
+    }
+}
+
+/// Returns whether the condition reads elements through `slice[index as usize]`.
+fn cond_casts_index(cond: &Expr, index: &Ident) -> bool {
+    struct Finder<'a>(&'a Ident, bool);
+    impl Visit<'_> for Finder<'_> {
+        fn visit_expr_index(&mut self, expr: &ExprIndex) {
+            self.1 |= matches!(strip_parens(&expr.index), Expr::Cast(ExprCast { expr, .. })
+                if is_ident(expr, self.0));
+            syn::visit::visit_expr_index(self, expr);
+        }
+    }
+    let mut finder = Finder(index, false);
+    finder.visit_expr(cond);
+    finder.1
+}
+
+impl VisitMut for FindLoopToPosition {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        self.names = NameGen::new(item_fn);
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        self.names = NameGen::new(item_fn);
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        syn::visit_mut::visit_block_mut(self, block);
+        self.rewrite_block(block);
+    }
+}
+
+impl Pass for FindLoopToPosition {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
@@ -0,0 +1,272 @@
+//! Replaces loops searching for an element by setting a flag with `any` or `all`,
+//! e.g.
+//!
+//! ```ignore
+//! let mut found = false;
+//! for x in &v {
+//!     if pred(x) {
+//!         found = true;
+//!         break;
+//!     }
+//! }
+//! ```
+//!
+//! becomes `let found = v.iter().any(|x| pred(x));`. A flag starting out `true`
+//! and cleared when an element fails the condition becomes a test of `all`.
+//!
+//! The loop body may only set the flag if the condition holds, and then `break`.
+//! Without the `break` every element is visited, so the condition must be free of
+//! side effects. The condition becomes the body of a closure, so it may not
+//! `return` or otherwise leave the loop.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{
+    count_ident, is_ident, is_pure, is_reassigned, strip_parens, Parenthesize,
+};
+use crate::MonadicAst;
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, Block, Expr, ExprBreak, ExprForLoop, ExprIf, ExprLit, ExprMethodCall,
+    ExprReference, ExprUnary, Ident, Lit, Local, LocalInit, Pat, PatIdent, PatType, Stmt, UnOp,
+};
+
+#[derive(Default)]
+pub struct FlagLoopToAnyAll;
+
+/// Methods returning an iterator, whose result can be searched directly.
+const ITERATOR_METHODS: &[&str] = &[
+    "iter",
+    "iter_mut",
+    "into_iter",
+    "chars",
+    "bytes",
+    "char_indices",
+    "keys",
+    "values",
+    "lines",
+    "windows",
+    "chunks",
+    "enumerate",
+    "rev",
+    "zip",
+    "skip",
+    "take",
+    "step_by",
+];
+
+/// Returns the flag and its initial value if the statement is `let mut f = b;`.
+fn flag(stmt: &Stmt) -> Option<(&Ident, bool)> {
+    let Stmt::Local(Local {
+        pat,
+        init:
+            Some(LocalInit {
+                expr,
+                diverge: None,
+                ..
+            }),
+        ..
+    }) = stmt
+    else {
+        return None;
+    };
+    let pat = match pat {
+        Pat::Type(PatType { pat, .. }) => pat.as_ref(),
+        pat => pat,
+    };
+    let Pat::Ident(PatIdent {
+        by_ref: None,
+        mutability: Some(_),
+        subpat: None,
+        ident,
+        ..
+    }) = pat
+    else {
+        return None;
+    };
+    match strip_parens(expr) {
+        Expr::Lit(ExprLit {
+            lit: Lit::Bool(value),
+            ..
+        }) => Some((ident, value.value)),
+        _ => None,
+    }
+}
+
+/// Returns whether the condition could leave the loop, which it cannot do from
+/// inside of a closure.
+fn escapes(expr: &Expr) -> bool {
+    struct Finder(bool);
+    impl Visit<'_> for Finder {
+        fn visit_expr(&mut self, expr: &Expr) {
+            self.0 |= matches!(
+                expr,
+                Expr::Return(_)
+                    | Expr::Break(_)
+                    | Expr::Continue(_)
+                    | Expr::Try(_)
+                    | Expr::Await(_)
+            );
+            syn::visit::visit_expr(self, expr);
+        }
+    }
+    let mut finder = Finder(false);
+    finder.visit_expr(expr);
+    finder.0
+}
+
+/// Returns a call of the method on `receiver`, built directly since splicing a
+/// range or cast into `parse_quote!` would misplace the receiver.
+fn method_call(receiver: Expr, method: &str, args: Option<Expr>) -> Expr {
+    Expr::MethodCall(ExprMethodCall {
+        attrs: Vec::new(),
+        receiver: Box::new(receiver),
+        dot_token: Default::default(),
+        method: quote::format_ident!("{}", method),
+        turbofish: None,
+        paren_token: Default::default(),
+        args: args.into_iter().collect(),
+    })
+}
+
+/// Returns an iterator over the elements the loop visits.
+fn iterator(expr: &Expr) -> Option<Expr> {
+    match strip_parens(expr) {
+        Expr::Reference(ExprReference {
+            mutability: None,
+            expr,
+            ..
+        }) => Some(method_call(strip_parens(expr).clone(), "iter", None)),
+        Expr::Reference(_) => None,
+        Expr::Range(range) => Some(Expr::Range(range.clone())),
+        Expr::MethodCall(call) if ITERATOR_METHODS.contains(&call.method.to_string().as_str()) => {
+            Some(Expr::MethodCall(call.clone()))
+        }
+        expr => Some(method_call(expr.clone(), "into_iter", None)),
+    }
+}
+
+/// Returns the negation of the condition.
+fn negation(expr: &Expr) -> Expr {
+    match strip_parens(expr) {
+        Expr::Unary(ExprUnary {
+            op: UnOp::Not(_),
+            expr,
+            ..
+        }) => strip_parens(expr).clone(),
+        expr => Expr::Unary(ExprUnary {
+            attrs: Vec::new(),
+            op: UnOp::Not(Default::default()),
+            expr: Box::new(expr.clone()),
+        }),
+    }
+}
+
+impl FlagLoopToAnyAll {
+    /// Returns the search replacing the loop if it sets `flag` to `!initial` when
+    /// an element matches the condition.
+    fn search(for_loop: &ExprForLoop, flag: &Ident, initial: bool) -> Option<Expr> {
+        if for_loop.label.is_some() {
+            return None;
+        }
+        let [Stmt::Expr(
+            Expr::If(ExprIf {
+                attrs,
+                cond,
+                then_branch,
+                else_branch: None,
+                ..
+            }),
+            _,
+        )] = for_loop.body.stmts.as_slice()
+        else {
+            return None;
+        };
+        let (assign, breaks) = match then_branch.stmts.as_slice() {
+            [assign] => (assign, false),
+            [assign, Stmt::Expr(
+                Expr::Break(ExprBreak {
+                    label: None,
+                    expr: None,
+                    ..
+                }),
+                _,
+            )] => (assign, true),
+            _ => return None,
+        };
+        let sets_flag = matches!(assign, Stmt::Expr(Expr::Assign(assign), _)
+            if is_ident(&assign.left, flag)
+                && matches!(strip_parens(&assign.right), Expr::Lit(ExprLit {
+                    lit: Lit::Bool(value), ..
+                }) if value.value != initial));
+        if !attrs.is_empty()
+            || !sets_flag
+            || count_ident(for_loop, flag) != 1
+            || !(breaks || is_pure(cond))
+            || escapes(cond)
+        {
+            return None;
+        }
+
+        let iterator = iterator(&for_loop.expr)?;
+        let pat = &for_loop.pat;
+        let mut search = if initial {
+            let predicate = negation(cond);
+            method_call(iterator, "all", Some(parse_quote!(|#pat| #predicate)))
+        } else {
+            method_call(iterator, "any", Some(parse_quote!(|#pat| #cond)))
+        };
+        Parenthesize.visit_expr_mut(&mut search);
+        Some(search)
+    }
+
+    fn rewrite_block(block: &mut Block) {
+        let mut position = 0;
+        while position + 1 < block.stmts.len() {
+            let search = match (&block.stmts[position], &block.stmts[position + 1]) {
+                (stmt, Stmt::Expr(Expr::ForLoop(for_loop), _)) => {
+                    flag(stmt).and_then(|(flag, initial)| {
+                        let search = Self::search(for_loop, flag, initial)?;
+                        let reassigned = is_reassigned(&block.stmts[position + 2..], flag);
+                        Some((search, reassigned))
+                    })
+                }
+                _ => None,
+            };
+            if let Some((search, reassigned)) = search {
+                let Stmt::Local(local) = &mut block.stmts[position] else {
+                    unreachable!();
+                };
+                if !reassigned {
+                    let pat = match &mut local.pat {
+                        Pat::Type(PatType { pat, .. }) => pat.as_mut(),
+                        pat => pat,
+                    };
+                    if let Pat::Ident(pat) = pat {
+                        pat.mutability = None;
+                    }
+                }
+                if let Some(init) = &mut local.init {
+                    *init.expr = search;
+                }
+                block.stmts.remove(position + 1);
+            }
+            position += 1;
+        }
+    }
+}
+
+impl VisitMut for FlagLoopToAnyAll {
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        syn::visit_mut::visit_block_mut(self, block);
+        Self::rewrite_block(block);
+    }
+}
+
+impl Pass for FlagLoopToAnyAll {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
@@ -0,0 +1,213 @@
+//! Rewrites `for` loops whose body only calls a function per element into a
+//! `for_each` over the iterated elements, e.g. `for x in &v { process(x); }`
+//! becomes `v.iter().for_each(|x| process(x));`. Since both forms are idiomatic,
+//! the rewrite only happens when [PassConfig::rewrite_for_each_loops] is set.
+//!
+//! The body must be a single function or method call which does not `return`,
+//! `break`, `continue` or use `?`, as none of them can leave a closure the way they
+//! leave the loop. Unless the callee is a function defined in the file without a
+//! return value, the call is kept a statement in the closure, since `for_each`
+//! expects closures returning `()`.
+//!
+//! [PassConfig::rewrite_for_each_loops]: crate::PassConfig::rewrite_for_each_loops
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{local_types, path_ident, strip_parens, Parenthesize};
+use crate::MonadicAst;
+use proc_macro2::{TokenStream, TokenTree};
+use std::collections::{HashMap, HashSet};
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, Block, Expr, ExprForLoop, ExprMethodCall, ExprReference, Ident, ImplItemFn, Item,
+    ItemFn, Macro, ReturnType, Stmt, Type, TypeReference,
+};
+
+#[derive(Default)]
+pub struct ForToForEach {
+    /// The functions in the file without a return value.
+    unit_fns: HashSet<Ident>,
+    /// The declared types of the current function's locals.
+    types: HashMap<Ident, Type>,
+}
+
+/// Methods returning an iterator, on which `for_each` can be called directly.
+const ITERATOR_METHODS: &[&str] = &[
+    "iter",
+    "iter_mut",
+    "into_iter",
+    "chars",
+    "bytes",
+    "char_indices",
+    "keys",
+    "values",
+    "values_mut",
+    "lines",
+    "windows",
+    "chunks",
+    "enumerate",
+    "rev",
+    "zip",
+    "skip",
+    "take",
+    "step_by",
+    "chain",
+    "map",
+    "filter",
+    "cloned",
+    "copied",
+];
+
+/// Returns whether the tokens contain a keyword or operator leaving the loop.
+fn tokens_escape(tokens: &TokenStream) -> bool {
+    tokens.clone().into_iter().any(|token| match token {
+        TokenTree::Ident(ident) => ident == "return" || ident == "break" || ident == "continue",
+        TokenTree::Punct(punct) => punct.as_char() == '?',
+        TokenTree::Group(group) => tokens_escape(&group.stream()),
+        TokenTree::Literal(_) => false,
+    })
+}
+
+/// Returns whether the call could leave the loop, which it cannot do from inside
+/// of a closure.
+fn escapes(expr: &Expr) -> bool {
+    struct Finder(bool);
+    impl Visit<'_> for Finder {
+        fn visit_expr(&mut self, expr: &Expr) {
+            self.0 |= matches!(
+                expr,
+                Expr::Return(_)
+                    | Expr::Break(_)
+                    | Expr::Continue(_)
+                    | Expr::Try(_)
+                    | Expr::Await(_)
+            );
+            syn::visit::visit_expr(self, expr);
+        }
+
+        fn visit_macro(&mut self, mac: &Macro) {
+            self.0 |= tokens_escape(&mac.tokens);
+        }
+    }
+    let mut finder = Finder(false);
+    finder.visit_expr(expr);
+    finder.0
+}
+
+/// Returns a call of the method on `receiver`, built directly since splicing a
+/// range into `parse_quote!` would misplace the receiver.
+fn method_call(receiver: Expr, method: &str, args: Option<Expr>) -> Expr {
+    Expr::MethodCall(ExprMethodCall {
+        attrs: Vec::new(),
+        receiver: Box::new(receiver),
+        dot_token: Default::default(),
+        method: quote::format_ident!("{}", method),
+        turbofish: None,
+        paren_token: Default::default(),
+        args: args.into_iter().collect(),
+    })
+}
+
+impl ForToForEach {
+    /// Returns an iterator over the elements the loop visits.
+    fn iterator(&self, expr: &Expr) -> Expr {
+        let borrowed = |expr: &Expr, mutable: bool| {
+            let method = if mutable { "iter_mut" } else { "iter" };
+            method_call(strip_parens(expr).clone(), method, None)
+        };
+        match strip_parens(expr) {
+            Expr::Reference(ExprReference {
+                mutability, expr, ..
+            }) => borrowed(expr, mutability.is_some()),
+            Expr::Range(range) => Expr::Range(range.clone()),
+            Expr::MethodCall(call)
+                if ITERATOR_METHODS.contains(&call.method.to_string().as_str()) =>
+            {
+                Expr::MethodCall(call.clone())
+            }
+            // A reference iterates like its borrowed elements.
+            expr => match path_ident(expr).and_then(|ident| self.types.get(ident)) {
+                Some(Type::Reference(TypeReference { mutability, .. })) => {
+                    borrowed(expr, mutability.is_some())
+                }
+                _ => method_call(expr.clone(), "into_iter", None),
+            },
+        }
+    }
+
+    /// Returns the `for_each` replacing the loop if its body is a single call.
+    fn for_each(&self, for_loop: &ExprForLoop) -> Option<Expr> {
+        if for_loop.label.is_some() || !for_loop.attrs.is_empty() {
+            return None;
+        }
+        let [Stmt::Expr(call @ (Expr::Call(_) | Expr::MethodCall(_)), _)] =
+            for_loop.body.stmts.as_slice()
+        else {
+            return None;
+        };
+        if escapes(call) {
+            return None;
+        }
+        let returns_unit = match call {
+            Expr::Call(call) => path_ident(&call.func).is_some_and(|f| self.unit_fns.contains(f)),
+            _ => false,
+        };
+        let pat = &for_loop.pat;
+        let closure: Expr = if returns_unit {
+            parse_quote!(|#pat| #call)
+        } else {
+            parse_quote!(|#pat| { #call; })
+        };
+        let mut for_each = method_call(self.iterator(&for_loop.expr), "for_each", Some(closure));
+        Parenthesize.visit_expr_mut(&mut for_each);
+        Some(for_each)
+    }
+}
+
+impl VisitMut for ForToForEach {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        syn::visit_mut::visit_block_mut(self, block);
+        for stmt in &mut block.stmts {
+            let Stmt::Expr(Expr::ForLoop(for_loop), _) = stmt else {
+                continue;
+            };
+            if let Some(for_each) = self.for_each(for_loop) {
+                *stmt = Stmt::Expr(for_each, Some(Default::default()));
+            }
+        }
+    }
+}
+
+impl Pass for ForToForEach {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        if !monad.config.rewrite_for_each_loops {
+            return Ok(monad);
+        }
+        self.unit_fns = monad
+            .ast
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Fn(item_fn) if matches!(item_fn.sig.output, ReturnType::Default) => {
+                    Some(item_fn.sig.ident.clone())
+                }
+                _ => None,
+            })
+            .collect();
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
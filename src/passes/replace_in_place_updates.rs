@@ -0,0 +1,206 @@
+//! Replaces index loops which only update each element of a single slice in place
+//! with an `iter_mut()` loop, e.g. `for i in 0..a.len() { a[i] = a[i] * 2; }`
+//! becomes `for x in a.iter_mut() { *x *= 2; }`.
+//!
+//! A loop over `0..n` for some other bound `n` iterates over `a[..n].iter_mut()`
+//! instead, which keeps the bound (and the panic if `n` exceeds the length).
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{
+    count_ident, is_assign_op, is_ident, is_int_lit, path_ident, NameGen, Parenthesize,
+};
+use crate::MonadicAst;
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, BinOp, Expr, ExprAssign, ExprBinary, ExprForLoop, ExprIndex, ExprMethodCall,
+    ExprRange, Ident, ImplItemFn, ItemFn, Pat, RangeLimits,
+};
+
+#[derive(Default)]
+pub struct InPlaceUpdateToIterMut {
+    /// Generates names unused in the function currently being visited.
+    names: NameGen,
+}
+
+/// Returns whether `expr` is `slice[index]`.
+fn is_element(expr: &Expr, slice: &Ident, index: &Ident) -> bool {
+    matches!(expr, Expr::Index(ExprIndex { expr, index: i, .. })
+        if is_ident(expr, slice) && is_ident(i, index))
+}
+
+/// Counts the `slice[index]` accesses in the loop body, and whether any of them
+/// is written to.
+struct ElementAccesses<'a> {
+    slice: &'a Ident,
+    index: &'a Ident,
+    count: usize,
+    written: bool,
+}
+
+impl Visit<'_> for ElementAccesses<'_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        let target = match expr {
+            Expr::Assign(ExprAssign { left, .. }) => Some(left),
+            Expr::Binary(ExprBinary { left, op, .. }) if is_assign_op(op) => Some(left),
+            _ => None,
+        };
+        self.written |= target.is_some_and(|left| is_element(left, self.slice, self.index));
+        if is_element(expr, self.slice, self.index) {
+            self.count += 1;
+        } else {
+            syn::visit::visit_expr(self, expr);
+        }
+    }
+}
+
+/// Rewrites the element accesses in terms of the element reference.
+struct ElementRewriter<'a> {
+    slice: &'a Ident,
+    index: &'a Ident,
+    element: &'a Ident,
+}
+
+impl ElementRewriter<'_> {
+    /// Returns the compound assignment operator for `op`, if it has one.
+    fn compound(op: &BinOp) -> Option<BinOp> {
+        Some(match op {
+            BinOp::Add(_) => BinOp::AddAssign(Default::default()),
+            BinOp::Sub(_) => BinOp::SubAssign(Default::default()),
+            BinOp::Mul(_) => BinOp::MulAssign(Default::default()),
+            BinOp::Div(_) => BinOp::DivAssign(Default::default()),
+            BinOp::Rem(_) => BinOp::RemAssign(Default::default()),
+            BinOp::BitAnd(_) => BinOp::BitAndAssign(Default::default()),
+            BinOp::BitOr(_) => BinOp::BitOrAssign(Default::default()),
+            BinOp::BitXor(_) => BinOp::BitXorAssign(Default::default()),
+            BinOp::Shl(_) => BinOp::ShlAssign(Default::default()),
+            BinOp::Shr(_) => BinOp::ShrAssign(Default::default()),
+            _ => return None,
+        })
+    }
+}
+
+impl VisitMut for ElementRewriter<'_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        // a[i] = a[i] op e  ->  a[i] op= e
+        if let Expr::Assign(ExprAssign { left, right, .. }) = expr {
+            if let Expr::Binary(ExprBinary {
+                left: operand,
+                op,
+                right: value,
+                ..
+            }) = right.as_ref()
+            {
+                if is_element(left, self.slice, self.index)
+                    && is_element(operand, self.slice, self.index)
+                {
+                    if let Some(op) = Self::compound(op) {
+                        *expr = parse_quote!(#left #op #value);
+                    }
+                }
+            }
+        }
+        if is_element(expr, self.slice, self.index) {
+            let element = self.element;
+            *expr = parse_quote!(*#element);
+        } else {
+            syn::visit_mut::visit_expr_mut(self, expr);
+        }
+    }
+}
+
+impl InPlaceUpdateToIterMut {
+    fn rewrite(&mut self, for_loop: &mut ExprForLoop) {
+        let Pat::Ident(pat) = for_loop.pat.as_ref() else {
+            return;
+        };
+        let index = pat.ident.clone();
+        let Expr::Range(ExprRange {
+            start: Some(start),
+            limits: RangeLimits::HalfOpen(_),
+            end: Some(end),
+            ..
+        }) = for_loop.expr.as_ref()
+        else {
+            return;
+        };
+        if !is_int_lit(start, 0) {
+            return;
+        }
+        // Find the slice from the first indexing by the loop variable.
+        struct FirstIndexed<'a>(&'a Ident, Option<Ident>);
+        impl Visit<'_> for FirstIndexed<'_> {
+            fn visit_expr_index(&mut self, index: &ExprIndex) {
+                if self.1.is_none() && is_ident(&index.index, self.0) {
+                    self.1 = path_ident(&index.expr).cloned();
+                }
+                syn::visit::visit_expr_index(self, index);
+            }
+        }
+        let mut first = FirstIndexed(&index, None);
+        first.visit_block(&for_loop.body);
+        let Some(slice) = first.1 else {
+            return;
+        };
+
+        let mut accesses = ElementAccesses {
+            slice: &slice,
+            index: &index,
+            count: 0,
+            written: false,
+        };
+        accesses.visit_block(&for_loop.body);
+        // Both the index and the slice may only be used in `slice[index]`.
+        if !accesses.written
+            || count_ident(&for_loop.body, &index) != accesses.count
+            || count_ident(&for_loop.body, &slice) != accesses.count
+            || count_ident(end.as_ref(), &index) > 0
+        {
+            return;
+        }
+
+        let whole_slice = matches!(end.as_ref(), Expr::MethodCall(ExprMethodCall {
+            receiver, method, args, ..
+        }) if method == "len" && args.is_empty() && is_ident(receiver, &slice));
+        let iter: Expr = if whole_slice {
+            parse_quote!(#slice.iter_mut())
+        } else {
+            parse_quote!(#slice[..#end].iter_mut())
+        };
+        let element = self.names.fresh("x");
+        ElementRewriter {
+            slice: &slice,
+            index: &index,
+            element: &element,
+        }
+        .visit_block_mut(&mut for_loop.body);
+        *for_loop.pat = parse_quote!(#element);
+        *for_loop.expr = iter;
+        Parenthesize.visit_block_mut(&mut for_loop.body);
+    }
+}
+
+impl VisitMut for InPlaceUpdateToIterMut {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        self.names = NameGen::new(item_fn);
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        self.names = NameGen::new(item_fn);
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+    }
+
+    fn visit_expr_for_loop_mut(&mut self, for_loop: &mut ExprForLoop) {
+        syn::visit_mut::visit_expr_for_loop_mut(self, for_loop);
+        self.rewrite(for_loop);
+    }
+}
+
+impl Pass for InPlaceUpdateToIterMut {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
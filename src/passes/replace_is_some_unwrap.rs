@@ -0,0 +1,109 @@
+//! Replaces `Option::unwrap` calls guarded by an `is_some` check with an `if let`
+//! binding, e.g. `if opt.is_some() { use(opt.unwrap()); }` becomes
+//! `if let Some(opt_value) = opt { use(opt_value); }`.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{count_ident, is_ident, path_ident, NameGen};
+use crate::MonadicAst;
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::{parse_quote, Block, Expr, ExprIf, ExprMethodCall, Ident};
+
+#[derive(Default)]
+pub struct IsSomeUnwrapToIfLet {
+    names: NameGen,
+}
+
+/// Returns the checked option `opt` of the condition `opt.is_some()`.
+fn checked_option(cond: &Expr) -> Option<&Ident> {
+    match cond {
+        Expr::MethodCall(ExprMethodCall {
+            receiver,
+            method,
+            args,
+            ..
+        }) if method == "is_some" && args.is_empty() => path_ident(receiver),
+        _ => None,
+    }
+}
+
+/// Returns whether `expr` is the call `opt.unwrap()`.
+fn is_unwrap_of(expr: &Expr, opt: &Ident) -> bool {
+    matches!(expr, Expr::MethodCall(ExprMethodCall { receiver, method, args, .. })
+        if method == "unwrap" && args.is_empty() && is_ident(receiver, opt))
+}
+
+/// Counts the `opt.unwrap()` calls in a block.
+struct UnwrapCounter<'a> {
+    opt: &'a Ident,
+    count: usize,
+}
+
+impl Visit<'_> for UnwrapCounter<'_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if is_unwrap_of(expr, self.opt) {
+            self.count += 1;
+        } else {
+            syn::visit::visit_expr(self, expr);
+        }
+    }
+}
+
+/// Replaces every `opt.unwrap()` call with the bound value.
+struct UnwrapReplacer<'a> {
+    opt: &'a Ident,
+    value: &'a Ident,
+}
+
+impl VisitMut for UnwrapReplacer<'_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if is_unwrap_of(expr, self.opt) {
+            let value = self.value;
+            *expr = parse_quote!(#value);
+        } else {
+            syn::visit_mut::visit_expr_mut(self, expr);
+        }
+    }
+}
+
+impl IsSomeUnwrapToIfLet {
+    /// Returns whether every use of `opt` in the block is an `opt.unwrap()` call, so
+    /// that `opt` is neither reassigned nor otherwise used after the check.
+    fn only_unwrapped(block: &Block, opt: &Ident) -> bool {
+        let mut counter = UnwrapCounter { opt, count: 0 };
+        counter.visit_block(block);
+        counter.count > 0 && counter.count == count_ident(block, opt)
+    }
+
+    fn rewrite(&mut self, expr_if: &mut ExprIf) {
+        let Some(opt) = checked_option(&expr_if.cond).cloned() else {
+            return;
+        };
+        if !Self::only_unwrapped(&expr_if.then_branch, &opt) {
+            return;
+        }
+        let value = self.names.fresh(&format!("{}_value", opt));
+        *expr_if.cond = parse_quote!(let Some(#value) = #opt);
+        UnwrapReplacer {
+            opt: &opt,
+            value: &value,
+        }
+        .visit_block_mut(&mut expr_if.then_branch);
+    }
+}
+
+impl VisitMut for IsSomeUnwrapToIfLet {
+    fn visit_expr_if_mut(&mut self, expr_if: &mut ExprIf) {
+        self.rewrite(expr_if);
+        syn::visit_mut::visit_expr_if_mut(self, expr_if);
+    }
+}
+
+impl Pass for IsSomeUnwrapToIfLet {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.names = NameGen::new(&monad.ast);
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
@@ -0,0 +1,189 @@
+//! Replaces a `match` over a `get` lookup which falls back to a default with an
+//! `Option` combinator, e.g.
+//!
+//! ```ignore
+//! match map.get(&k) {
+//!     Some(v) => *v,
+//!     None => 0,
+//! }
+//! ```
+//!
+//! becomes `map.get(&k).copied().unwrap_or(0)`. An arm returning `v.clone()` uses
+//! `cloned()` instead. A default which is not a literal or a variable is only
+//! evaluated when the key is missing, as it was in the `match`, by passing it to
+//! `unwrap_or_else` in a closure.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{count_ident, is_ident, strip_parens};
+use crate::MonadicAst;
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, Arm, Expr, ExprMatch, ExprMethodCall, ExprUnary, Ident, Pat, PatIdent,
+    PatReference, PatTupleStruct, UnOp,
+};
+
+#[derive(Default)]
+pub struct MapLookupDefault;
+
+/// How the looked-up value is turned into the result.
+enum Access {
+    Copied,
+    Cloned,
+}
+
+/// Returns whether `pat` is the `None` arm's pattern, `None` or `_`.
+fn is_none_pat(pat: &Pat) -> bool {
+    match pat {
+        Pat::Wild(_) => true,
+        Pat::Ident(PatIdent {
+            ident,
+            by_ref: None,
+            mutability: None,
+            subpat: None,
+            ..
+        }) => ident == "None",
+        Pat::Path(path) => path.path.is_ident("None"),
+        _ => false,
+    }
+}
+
+/// Returns the binding `v` and whether it is dereferenced by the pattern, if `pat`
+/// is `Some(v)` or `Some(&v)`.
+fn some_binding(pat: &Pat) -> Option<(&Ident, bool)> {
+    let Pat::TupleStruct(PatTupleStruct { path, elems, .. }) = pat else {
+        return None;
+    };
+    if !path.is_ident("Some") || elems.len() != 1 {
+        return None;
+    }
+    let (binding, deref) = match &elems[0] {
+        Pat::Reference(PatReference {
+            pat,
+            mutability: None,
+            ..
+        }) => (pat.as_ref(), true),
+        pat => (pat, false),
+    };
+    match binding {
+        Pat::Ident(PatIdent {
+            ident,
+            by_ref: None,
+            mutability: None,
+            subpat: None,
+            ..
+        }) => Some((ident, deref)),
+        _ => None,
+    }
+}
+
+/// Returns how the `Some(..)` arm's body uses the value bound by its pattern.
+fn access(pat: &Pat, body: &Expr) -> Option<Access> {
+    let (binding, deref) = some_binding(pat)?;
+    let body = strip_parens(body);
+    let access = match body {
+        _ if deref && is_ident(body, binding) => Access::Copied,
+        Expr::Unary(ExprUnary {
+            op: UnOp::Deref(_),
+            expr,
+            ..
+        }) if !deref && is_ident(expr, binding) => Access::Copied,
+        Expr::MethodCall(ExprMethodCall {
+            receiver,
+            method,
+            args,
+            ..
+        }) if !deref && method == "clone" && args.is_empty() && is_ident(receiver, binding) => {
+            Access::Cloned
+        }
+        _ => return None,
+    };
+    (count_ident(body, binding) == 1).then_some(access)
+}
+
+/// Returns whether evaluating `expr` up front cannot be observed.
+fn is_cheap(expr: &Expr) -> bool {
+    match strip_parens(expr) {
+        Expr::Lit(_) | Expr::Path(_) => true,
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        }) => matches!(strip_parens(expr), Expr::Lit(_)),
+        _ => false,
+    }
+}
+
+/// Finds whether an expression leaves the enclosing function or loop, which it could
+/// not do from inside of a closure.
+#[derive(Default)]
+struct Diverges(bool);
+
+impl Visit<'_> for Diverges {
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Return(_) | Expr::Break(_) | Expr::Continue(_) | Expr::Try(_) => self.0 = true,
+            Expr::Closure(_) => {}
+            expr => syn::visit::visit_expr(self, expr),
+        }
+    }
+}
+
+impl MapLookupDefault {
+    fn replacement(match_expr: &ExprMatch) -> Option<Expr> {
+        let lookup = strip_parens(&match_expr.expr);
+        let is_get = matches!(lookup, Expr::MethodCall(ExprMethodCall { method, args, .. })
+            if method == "get" && args.len() == 1);
+        if !is_get {
+            return None;
+        }
+        let [first, second] = match_expr.arms.as_slice() else {
+            return None;
+        };
+        if first.guard.is_some() || second.guard.is_some() {
+            return None;
+        }
+        let (some, none): (&Arm, &Arm) = if is_none_pat(&second.pat) {
+            (first, second)
+        } else if matches!(second.pat, Pat::TupleStruct(_)) && is_none_pat(&first.pat) {
+            (second, first)
+        } else {
+            return None;
+        };
+        let access = access(&some.pat, &some.body)?;
+        let default = strip_parens(&none.body);
+        let mut diverges = Diverges::default();
+        diverges.visit_expr(default);
+        if diverges.0 {
+            return None;
+        }
+        let value: Expr = match access {
+            Access::Copied => parse_quote!(#lookup.copied()),
+            Access::Cloned => parse_quote!(#lookup.cloned()),
+        };
+        Some(if is_cheap(default) {
+            parse_quote!(#value.unwrap_or(#default))
+        } else {
+            parse_quote!(#value.unwrap_or_else(|| #default))
+        })
+    }
+}
+
+impl VisitMut for MapLookupDefault {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        syn::visit_mut::visit_expr_mut(self, expr);
+        if let Expr::Match(match_expr) = expr {
+            if let Some(replacement) = Self::replacement(match_expr) {
+                *expr = replacement;
+            }
+        }
+    }
+}
+
+impl Pass for MapLookupDefault {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
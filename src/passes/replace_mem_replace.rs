@@ -0,0 +1,159 @@
+//! Replaces `mem::replace` calls whose result is discarded with an assignment, e.g.
+//! `let _ = std::mem::replace(&mut x, 0);` becomes `x = 0;`.
+//!
+//! The assignment drops the old value before storing the new one, whereas
+//! `replace` drops it after. This only makes a difference when dropping has an
+//! effect, so the call is only rewritten when the value is known to be of a type
+//! without drop glue, such as a number or a pointer. Other calls, e.g. on a lock
+//! guard, are kept.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{expr_type, local_types, path_ident, primitive_name, strip_parens};
+use crate::MonadicAst;
+use std::collections::HashMap;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, Expr, ExprCall, ExprLit, ExprPath, ExprReference, Ident, ImplItemFn, ItemFn, Lit,
+    Local, LocalInit, Pat, Stmt, Type,
+};
+
+#[derive(Default)]
+pub struct ReplaceToAssign {
+    /// Declared types of the locals of the function currently being visited.
+    types: HashMap<Ident, Type>,
+}
+
+/// Returns whether `func` is `mem::replace`, qualified by `std` or `core` or not.
+fn is_mem_replace(func: &Expr) -> bool {
+    let Expr::Path(ExprPath {
+        qself: None, path, ..
+    }) = func
+    else {
+        return false;
+    };
+    let segments: Vec<String> = path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    matches!(
+        segments
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .as_slice(),
+        ["mem", "replace"] | ["std" | "core", "mem", "replace"]
+    )
+}
+
+/// Returns whether dropping a value of type `ty` does nothing.
+fn is_trivially_droppable(ty: &Type) -> bool {
+    match ty {
+        Type::Ptr(_) | Type::Reference(_) => true,
+        Type::Array(array) => is_trivially_droppable(&array.elem),
+        Type::Tuple(tuple) => tuple.elems.iter().all(is_trivially_droppable),
+        Type::Paren(paren) => is_trivially_droppable(&paren.elem),
+        ty => primitive_name(ty).is_some_and(|name| {
+            matches!(
+                name.as_str(),
+                "u8" | "u16"
+                    | "u32"
+                    | "u64"
+                    | "u128"
+                    | "usize"
+                    | "i8"
+                    | "i16"
+                    | "i32"
+                    | "i64"
+                    | "i128"
+                    | "isize"
+                    | "f32"
+                    | "f64"
+                    | "bool"
+                    | "char"
+            )
+        }),
+    }
+}
+
+impl ReplaceToAssign {
+    /// Returns whether the value replaced in `target` by `value` has no drop glue.
+    fn is_trivial(&self, target: &Expr, value: &Expr) -> bool {
+        let literal = matches!(
+            strip_parens(value),
+            Expr::Lit(ExprLit {
+                lit: Lit::Int(_) | Lit::Float(_) | Lit::Bool(_) | Lit::Char(_) | Lit::Byte(_),
+                ..
+            })
+        );
+        let target_type = path_ident(target).and_then(|ident| self.types.get(ident));
+        literal
+            || target_type.is_some_and(is_trivially_droppable)
+            || expr_type(value, &self.types).is_some_and(|ty| is_trivially_droppable(&ty))
+    }
+
+    /// Returns the assignment replacing the discarded call `expr`, if it is one.
+    fn replacement(&self, expr: &Expr) -> Option<Stmt> {
+        let Expr::Call(ExprCall { func, args, .. }) = expr else {
+            return None;
+        };
+        if !is_mem_replace(func) || args.len() != 2 {
+            return None;
+        }
+        let Expr::Reference(ExprReference {
+            mutability: Some(_),
+            expr: target,
+            ..
+        }) = strip_parens(&args[0])
+        else {
+            return None;
+        };
+        let value = &args[1];
+        self.is_trivial(target, value)
+            .then(|| parse_quote!(#target = #value;))
+    }
+}
+
+impl VisitMut for ReplaceToAssign {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_stmt_mut(&mut self, stmt: &mut Stmt) {
+        let discarded = match &*stmt {
+            Stmt::Expr(expr, Some(_)) => Some(expr),
+            Stmt::Local(Local {
+                pat: Pat::Wild(_),
+                init:
+                    Some(LocalInit {
+                        expr,
+                        diverge: None,
+                        ..
+                    }),
+                ..
+            }) => Some(expr.as_ref()),
+            _ => None,
+        };
+        if let Some(assign) = discarded.and_then(|expr| self.replacement(expr)) {
+            *stmt = assign;
+            return;
+        }
+        syn::visit_mut::visit_stmt_mut(self, stmt);
+    }
+}
+
+impl Pass for ReplaceToAssign {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
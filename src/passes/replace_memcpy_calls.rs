@@ -0,0 +1,328 @@
+//! Replaces `memcpy`, `memmove` and `memset` calls between slices with slice
+//! methods, e.g. `memcpy(a.as_mut_ptr() as *mut c_void, b.as_ptr() as *const
+//! c_void, n * size_of::<i32>())` becomes `a[..n].copy_from_slice(&b[..n]);`, and
+//! `memset(a.as_mut_ptr() as *mut c_void, 0, size_of::<[i32; 8]>())` becomes
+//! `a.fill(0);`.
+//!
+//! An operand is a slice if it is a local array or `Vec` whose `as_ptr` or
+//! `as_mut_ptr` is passed, or a slice reference, such as a pointer argument the raw
+//! pointer replacement promoted to one. The size must count elements of the
+//! slices' type: `n * size_of::<T>()`, or `size_of::<[T; N]>()` for a whole array.
+//! A `memset` must set every byte to zero, or be on bytes.
+//!
+//! Other calls are kept and reported, with a `wrapping_mul` size written as a
+//! plain product.
+
+use crate::monad::ast::Pass;
+use crate::monad::diagnostic::Diagnostic;
+use crate::monad::error::PassError;
+use crate::passes::utils::{
+    as_usize, callee_name, derives_copy, element_count, is_float, is_int_lit, is_primitive,
+    is_signed_int, is_size_of, is_unsigned_int, local_types, path_ident, primitive_name,
+    same_tokens, strip_casts, strip_parens, Parenthesize,
+};
+use crate::MonadicAst;
+use std::collections::{HashMap, HashSet};
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, Expr, ExprBinary, ExprCall, ExprLit, ExprMethodCall, GenericArgument, Ident,
+    ImplItemFn, Item, ItemFn, Lit, LitInt, PathArguments, Stmt, Type, TypeArray, TypePath,
+    TypeReference, TypeSlice,
+};
+
+const PASS: &str = "replace_memcpy_calls";
+
+#[derive(Default)]
+pub struct MemcpyToSliceOp {
+    /// Names of the structs declared in the file that derive `Copy`.
+    copy_structs: HashSet<Ident>,
+    /// Declared types of the locals of the function currently being visited.
+    types: HashMap<Ident, Type>,
+    current_fn: Option<Ident>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// A slice read or written by a call.
+struct Operand<'a> {
+    ident: &'a Ident,
+    /// Its declared type.
+    ty: &'a Type,
+    elem: &'a Type,
+}
+
+impl Operand<'_> {
+    /// Returns whether the slice can be written through.
+    fn is_mutable(&self) -> bool {
+        !matches!(
+            self.ty,
+            Type::Reference(TypeReference {
+                mutability: None,
+                ..
+            })
+        )
+    }
+
+    /// Returns whether the slice is a reference, rather than an owned array or
+    /// vector.
+    fn is_reference(&self) -> bool {
+        matches!(self.ty, Type::Reference(_))
+    }
+
+    /// Returns the slice itself if it holds exactly `count` elements, which only
+    /// an array is known to, or else its first `count` elements.
+    fn place(&self, count: &Expr) -> Expr {
+        let ident = self.ident;
+        match self.ty {
+            Type::Array(TypeArray { len, .. })
+                if same_tokens(strip_parens(len), strip_parens(count)) =>
+            {
+                parse_quote!(#ident)
+            }
+            _ => {
+                let count = as_usize(count);
+                parse_quote!(#ident[..#count])
+            }
+        }
+    }
+
+    /// Returns the slice of `count` elements as the argument of `copy_from_slice`.
+    fn borrowed(&self, count: &Expr) -> Expr {
+        let place = self.place(count);
+        match place {
+            Expr::Path(_) if self.is_reference() => place,
+            place => parse_quote!(&#place),
+        }
+    }
+}
+
+/// Returns the element type of `Vec<T>`.
+fn vec_elem(ty: &Type) -> Option<&Type> {
+    let Type::Path(TypePath { qself: None, path }) = ty else {
+        return None;
+    };
+    let segment = path.segments.last()?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first() {
+        Some(GenericArgument::Type(elem)) if segment.ident == "Vec" => Some(elem),
+        _ => None,
+    }
+}
+
+/// Returns the `T` a `memset` of every byte of a `T` to `value` sets it to, which
+/// is known for zero bytes of a primitive, or for any byte if `T` is `u8`, or why
+/// it is not.
+fn fill_value(value: &Expr, elem: &Type) -> Result<Expr, &'static str> {
+    let value = strip_casts(value);
+    if is_int_lit(value, 0) {
+        return if is_signed_int(elem) || is_unsigned_int(elem) {
+            Ok(parse_quote!(0))
+        } else if is_float(elem) {
+            Ok(parse_quote!(0.0))
+        } else if primitive_name(elem).as_deref() == Some("bool") {
+            Ok(parse_quote!(false))
+        } else {
+            Err("it zeroes elements that are not numbers")
+        };
+    }
+    match value {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(int), ..
+        }) if primitive_name(elem).as_deref() == Some("u8") && int.base10_parse::<u8>().is_ok() => {
+            let byte = LitInt::new(int.base10_digits(), int.span());
+            Ok(parse_quote!(#byte))
+        }
+        _ => Err("it sets elements other than `u8`s to a byte other than zero"),
+    }
+}
+
+/// Rewrites a `x.wrapping_mul(size_of::<T>())` size, or one with the operands
+/// swapped, into `x * size_of::<T>()`, returning whether it did.
+fn as_product(size: &mut Expr) -> bool {
+    let is_size_of = |expr: &Expr| {
+        matches!(strip_casts(expr), Expr::Call(ExprCall { func, .. })
+            if callee_name(func).as_deref() == Some("size_of"))
+    };
+    let Expr::MethodCall(ExprMethodCall {
+        receiver,
+        method,
+        args,
+        ..
+    }) = strip_parens(size)
+    else {
+        return false;
+    };
+    if method != "wrapping_mul"
+        || args.len() != 1
+        || !(is_size_of(receiver) || is_size_of(&args[0]))
+    {
+        return false;
+    }
+    let (left, right) = (receiver.as_ref(), &args[0]);
+    *size = Expr::Binary(ExprBinary {
+        attrs: Vec::new(),
+        left: Box::new(left.clone()),
+        op: syn::BinOp::Mul(Default::default()),
+        right: Box::new(right.clone()),
+    });
+    Parenthesize.visit_expr_mut(size);
+    true
+}
+
+impl MemcpyToSliceOp {
+    /// Returns the slice whose pointer `expr` is, ignoring casts.
+    fn operand<'a>(&'a self, expr: &'a Expr) -> Option<Operand<'a>> {
+        let (ident, as_ptr) = match strip_casts(expr) {
+            // a.as_mut_ptr()
+            Expr::MethodCall(ExprMethodCall {
+                receiver,
+                method,
+                args,
+                ..
+            }) if args.is_empty() && (method == "as_ptr" || method == "as_mut_ptr") => {
+                (path_ident(receiver)?, true)
+            }
+            expr => (path_ident(expr)?, false),
+        };
+        let ty = self.types.get(ident)?;
+        let elem = match ty {
+            Type::Array(TypeArray { elem, .. }) if as_ptr => elem.as_ref(),
+            Type::Reference(TypeReference { elem, .. }) => match elem.as_ref() {
+                Type::Slice(TypeSlice { elem, .. }) => elem.as_ref(),
+                _ => return None,
+            },
+            ty if as_ptr => vec_elem(ty)?,
+            _ => return None,
+        };
+        Some(Operand { ident, ty, elem })
+    }
+
+    /// Returns how many elements of `dest` a call of `size` bytes covers.
+    fn count(&self, size: &Expr, dest: &Operand) -> Option<Expr> {
+        if let Type::Array(TypeArray { len, .. }) = dest.ty {
+            if is_size_of(size, dest.ty) {
+                return Some(len.clone());
+            }
+        }
+        match element_count(size, dest.elem)? {
+            Some(count) => Some(count),
+            None => Some(parse_quote!(1)),
+        }
+    }
+
+    /// Returns whether values of type `elem` can be copied bitwise.
+    fn is_copy(&self, elem: &Type) -> bool {
+        is_primitive(elem)
+            || path_ident_of(elem).is_some_and(|ident| self.copy_structs.contains(ident))
+    }
+
+    /// Returns the slice method call replacing the `mem*` call, or why it is kept.
+    fn replacement(&self, name: &str, args: &[Expr]) -> Result<Expr, &'static str> {
+        let [dest, source_or_value, size] = args else {
+            return Err("its arguments are not a pointer, a source and a size");
+        };
+        let dest = self
+            .operand(dest)
+            .filter(Operand::is_mutable)
+            .ok_or("its destination is not known to be a mutable slice")?;
+        let count = self
+            .count(size, &dest)
+            .ok_or("its size is not a number of elements of the destination")?;
+        if name == "memset" {
+            let value = fill_value(source_or_value, dest.elem)?;
+            let place = dest.place(&count);
+            return Ok(parse_quote!(#place.fill(#value)));
+        }
+        let source = self
+            .operand(source_or_value)
+            .ok_or("its source is not known to be a slice")?;
+        if !same_tokens(source.elem, dest.elem) {
+            return Err("it copies between slices of different types");
+        }
+        if source.ident == dest.ident {
+            return Err("its source and destination are the same slice");
+        }
+        let (place, source) = (dest.place(&count), source.borrowed(&count));
+        Ok(if self.is_copy(dest.elem) {
+            parse_quote!(#place.copy_from_slice(#source))
+        } else {
+            parse_quote!(#place.clone_from_slice(#source))
+        })
+    }
+}
+
+/// Returns the name of a type spelled as a single identifier.
+fn path_ident_of(ty: &Type) -> Option<&Ident> {
+    match ty {
+        Type::Path(TypePath { qself: None, path }) => path.get_ident(),
+        _ => None,
+    }
+}
+
+impl VisitMut for MemcpyToSliceOp {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        let enclosing = self.current_fn.replace(item_fn.sig.ident.clone());
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        self.current_fn = enclosing;
+        self.types = types;
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        let enclosing = self.current_fn.replace(item_fn.sig.ident.clone());
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+        self.current_fn = enclosing;
+        self.types = types;
+    }
+
+    /// Replaces a `mem*` call whose result is discarded.
+    fn visit_stmt_mut(&mut self, stmt: &mut Stmt) {
+        if let Stmt::Expr(expr, Some(_)) = stmt {
+            if let Expr::Call(call) = expr {
+                let name = callee_name(&call.func);
+                if let Some(name @ ("memcpy" | "memmove" | "memset")) = name.as_deref() {
+                    let args: Vec<Expr> = call.args.iter().cloned().collect();
+                    match self.replacement(name, &args) {
+                        Ok(replacement) => *expr = replacement,
+                        Err(reason) => {
+                            let size = call.args.iter_mut().nth(2);
+                            let note = if size.is_some_and(as_product) {
+                                ", though its size is written as a product"
+                            } else {
+                                ""
+                            };
+                            self.diagnostics.push(Diagnostic::new(
+                                PASS,
+                                self.current_fn.as_ref(),
+                                format!("`{name}` is kept, as {reason}{note}"),
+                            ));
+                        }
+                    }
+                    return;
+                }
+            }
+        }
+        syn::visit_mut::visit_stmt_mut(self, stmt);
+    }
+}
+
+impl Pass for MemcpyToSliceOp {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.copy_structs = monad
+            .ast
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Struct(item_struct) if derives_copy(item_struct) => {
+                    Some(item_struct.ident.clone())
+                }
+                _ => None,
+            })
+            .collect();
+        self.visit_file_mut(&mut monad.ast);
+        monad.diagnostics.append(&mut self.diagnostics);
+        Ok(monad)
+    }
+}
@@ -0,0 +1,243 @@
+//! Replaces index loops which only read pairs of adjacent elements of a slice with
+//! a loop over `windows(2)`, e.g.
+//!
+//! ```ignore
+//! for i in 0..a.len() - 1 {
+//!     total += a[i + 1] - a[i];
+//! }
+//! ```
+//!
+//! becomes `for w in a.windows(2) { total += w[1] - w[0]; }`. Loops over
+//! `1..a.len()` reading `a[i - 1]` and `a[i]` are rewritten the same way.
+//!
+//! The index and the slice may only be used in these two element reads. Note that
+//! `a.len() - 1` overflows for an empty slice, while `windows` yields no pairs.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{
+    count_ident, is_assign_op, is_ident, is_int_lit, path_ident, strip_parens, NameGen,
+};
+use crate::MonadicAst;
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, BinOp, Expr, ExprAssign, ExprBinary, ExprForLoop, ExprIndex, ExprMethodCall,
+    ExprRange, ExprReference, Ident, ImplItemFn, ItemFn, Pat, RangeLimits,
+};
+
+#[derive(Default)]
+pub struct PairwiseLoopToWindows {
+    /// Generates names unused in the function currently being visited.
+    names: NameGen,
+}
+
+/// Returns the offset `k` if `expr` is `index`, `index + k` or `index - k` for
+/// `k` of at most one.
+fn offset(expr: &Expr, index: &Ident) -> Option<i8> {
+    let expr = strip_parens(expr);
+    if is_ident(expr, index) {
+        return Some(0);
+    }
+    match expr {
+        Expr::Binary(ExprBinary {
+            left,
+            op: BinOp::Add(_),
+            right,
+            ..
+        }) if (is_ident(left, index) && is_int_lit(right, 1))
+            || (is_int_lit(left, 1) && is_ident(right, index)) =>
+        {
+            Some(1)
+        }
+        Expr::Binary(ExprBinary {
+            left,
+            op: BinOp::Sub(_),
+            right,
+            ..
+        }) if is_ident(left, index) && is_int_lit(right, 1) => Some(-1),
+        _ => None,
+    }
+}
+
+/// Returns the slice `a` if `expr` is `a.len()`.
+fn len_receiver(expr: &Expr) -> Option<&Ident> {
+    match strip_parens(expr) {
+        Expr::MethodCall(ExprMethodCall {
+            receiver,
+            method,
+            args,
+            ..
+        }) if method == "len" && args.is_empty() => path_ident(receiver),
+        _ => None,
+    }
+}
+
+/// Returns the slice and the offset of the first element of each pair if the loop
+/// ranges over `0..a.len() - 1` or `1..a.len()`.
+fn pair_range<'a>(start: &Expr, end: &'a Expr) -> Option<(&'a Ident, i8)> {
+    if is_int_lit(start, 1) {
+        return Some((len_receiver(end)?, -1));
+    }
+    match strip_parens(end) {
+        Expr::Binary(ExprBinary {
+            left,
+            op: BinOp::Sub(_),
+            right,
+            ..
+        }) if is_int_lit(start, 0) && is_int_lit(right, 1) => Some((len_receiver(left)?, 0)),
+        _ => None,
+    }
+}
+
+/// Collects the offsets of the `slice[index + k]` reads in the loop body, and
+/// whether any element is written to.
+struct PairAccesses<'a> {
+    slice: &'a Ident,
+    index: &'a Ident,
+    offsets: Vec<i8>,
+    written: bool,
+}
+
+impl PairAccesses<'_> {
+    /// Returns whether the place `expr` is, or is part of, an element of the slice.
+    fn is_element_place(&self, expr: &Expr) -> bool {
+        match strip_parens(expr) {
+            Expr::Field(field) => self.is_element_place(&field.base),
+            Expr::Index(ExprIndex { expr, .. }) if is_ident(expr, self.slice) => true,
+            Expr::Index(ExprIndex { expr, .. }) => self.is_element_place(expr),
+            _ => false,
+        }
+    }
+}
+
+impl Visit<'_> for PairAccesses<'_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        let place = match expr {
+            Expr::Assign(ExprAssign { left, .. }) => Some(left),
+            Expr::Binary(ExprBinary { left, op, .. }) if is_assign_op(op) => Some(left),
+            Expr::Reference(ExprReference {
+                mutability: Some(_),
+                expr,
+                ..
+            }) => Some(expr),
+            _ => None,
+        };
+        self.written |= place.is_some_and(|place| self.is_element_place(place));
+        if let Expr::Index(ExprIndex {
+            expr: slice, index, ..
+        }) = expr
+        {
+            if is_ident(slice, self.slice) {
+                if let Some(offset) = offset(index, self.index) {
+                    self.offsets.push(offset);
+                    return;
+                }
+            }
+        }
+        syn::visit::visit_expr(self, expr);
+    }
+}
+
+/// Rewrites the element reads in terms of the window.
+struct WindowRewriter<'a> {
+    slice: &'a Ident,
+    index: &'a Ident,
+    window: &'a Ident,
+    /// The offset of the window's first element.
+    first: i8,
+}
+
+impl VisitMut for WindowRewriter<'_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if let Expr::Index(ExprIndex {
+            expr: slice, index, ..
+        }) = expr
+        {
+            if is_ident(slice, self.slice) {
+                if let Some(offset) = offset(index, self.index) {
+                    let window = self.window;
+                    let position = syn::Index::from((offset - self.first) as usize);
+                    *expr = parse_quote!(#window[#position]);
+                    return;
+                }
+            }
+        }
+        syn::visit_mut::visit_expr_mut(self, expr);
+    }
+}
+
+impl PairwiseLoopToWindows {
+    fn rewrite(&mut self, for_loop: &mut ExprForLoop) {
+        let Pat::Ident(pat) = for_loop.pat.as_ref() else {
+            return;
+        };
+        let index = pat.ident.clone();
+        let Expr::Range(ExprRange {
+            start: Some(start),
+            limits: RangeLimits::HalfOpen(_),
+            end: Some(end),
+            ..
+        }) = for_loop.expr.as_ref()
+        else {
+            return;
+        };
+        let Some((slice, first)) = pair_range(start, end) else {
+            return;
+        };
+        let slice = slice.clone();
+
+        let mut accesses = PairAccesses {
+            slice: &slice,
+            index: &index,
+            offsets: Vec::new(),
+            written: false,
+        };
+        accesses.visit_block(&for_loop.body);
+        let count = accesses.offsets.len();
+        let pair = [first, first + 1];
+        if accesses.written
+            || !pair.iter().all(|offset| accesses.offsets.contains(offset))
+            || !accesses.offsets.iter().all(|offset| pair.contains(offset))
+            || count_ident(&for_loop.body, &index) != count
+            || count_ident(&for_loop.body, &slice) != count
+        {
+            return;
+        }
+
+        let window = self.names.fresh("w");
+        WindowRewriter {
+            slice: &slice,
+            index: &index,
+            window: &window,
+            first,
+        }
+        .visit_block_mut(&mut for_loop.body);
+        *for_loop.pat = parse_quote!(#window);
+        *for_loop.expr = parse_quote!(#slice.windows(2));
+    }
+}
+
+impl VisitMut for PairwiseLoopToWindows {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        self.names = NameGen::new(item_fn);
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        self.names = NameGen::new(item_fn);
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+    }
+
+    fn visit_expr_for_loop_mut(&mut self, for_loop: &mut ExprForLoop) {
+        syn::visit_mut::visit_expr_for_loop_mut(self, for_loop);
+        self.rewrite(for_loop);
+    }
+}
+
+impl Pass for PairwiseLoopToWindows {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
@@ -0,0 +1,272 @@
+//! Replaces loops comparing a slice element by element with a prefix or suffix with
+//! `starts_with` or `ends_with`, e.g.
+//!
+//! ```ignore
+//! for i in 0..prefix.len() {
+//!     if s[i] != prefix[i] {
+//!         return false;
+//!     }
+//! }
+//! true
+//! ```
+//!
+//! becomes `s.starts_with(prefix)`. A loop comparing `s[s.len() - n + i]` with
+//! `suffix[i]` for `i` in `0..n` becomes a test of `s.ends_with(..)`.
+//!
+//! On a mismatch the loop must only return, or set a flag and `break`, and the
+//! index and both slices may only be used in the comparison. The loop panics when
+//! `s` is shorter than the prefix, whereas `starts_with` returns `false`.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{
+    count_ident, is_ident, is_int_lit, local_types, path_ident, same_tokens, strip_parens,
+};
+use crate::MonadicAst;
+use std::collections::HashMap;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, BinOp, Block, Expr, ExprBinary, ExprBreak, ExprForLoop, ExprIf, ExprIndex,
+    ExprLit, ExprMethodCall, ExprRange, Ident, ImplItemFn, ItemFn, Lit, Pat, RangeLimits, Stmt,
+    Type,
+};
+
+#[derive(Default)]
+pub struct PrefixLoopToStartsWith {
+    /// Declared types of the locals of the function currently being visited.
+    types: HashMap<Ident, Type>,
+}
+
+/// The comparison made by a prefix or suffix loop.
+struct Comparison<'a> {
+    /// The slice whose start or end is compared.
+    slice: &'a Ident,
+    /// The prefix or suffix it is compared with.
+    affix: &'a Ident,
+    suffix: bool,
+}
+
+/// Returns whether `expr` is `slice.len()`.
+fn is_len(expr: &Expr, slice: &Ident) -> bool {
+    matches!(strip_parens(expr), Expr::MethodCall(ExprMethodCall {
+        receiver, method, args, ..
+    }) if method == "len" && args.is_empty() && is_ident(receiver, slice))
+}
+
+/// Returns whether `expr` is `slice.len() - count + index`.
+fn is_suffix_index(expr: &Expr, slice: &Ident, count: &Expr, index: &Ident) -> bool {
+    let Expr::Binary(ExprBinary {
+        left,
+        op: BinOp::Add(_),
+        right,
+        ..
+    }) = strip_parens(expr)
+    else {
+        return false;
+    };
+    is_ident(right, index)
+        && matches!(strip_parens(left), Expr::Binary(ExprBinary {
+            left: len, op: BinOp::Sub(_), right: subtracted, ..
+        }) if is_len(len, slice) && same_tokens(strip_parens(subtracted), count))
+}
+
+/// Returns the comparison if `expr` is `s[i] != affix[i]`, or the suffix form, in
+/// either order.
+fn comparison<'a>(expr: &'a Expr, index: &Ident, count: &Expr) -> Option<Comparison<'a>> {
+    let Expr::Binary(ExprBinary {
+        left,
+        op: BinOp::Ne(_),
+        right,
+        ..
+    }) = strip_parens(expr)
+    else {
+        return None;
+    };
+    let (Expr::Index(left), Expr::Index(right)) = (strip_parens(left), strip_parens(right)) else {
+        return None;
+    };
+    [(left, right), (right, left)].into_iter().find_map(
+        |(slice, affix): (&ExprIndex, &ExprIndex)| {
+            let slice_ident = path_ident(&slice.expr)?;
+            let affix_ident = path_ident(&affix.expr)?;
+            if slice_ident == affix_ident || !is_ident(&affix.index, index) {
+                return None;
+            }
+            let suffix = if is_ident(&slice.index, index) {
+                false
+            } else if is_suffix_index(&slice.index, slice_ident, count, index) {
+                true
+            } else {
+                return None;
+            };
+            Some(Comparison {
+                slice: slice_ident,
+                affix: affix_ident,
+                suffix,
+            })
+        },
+    )
+}
+
+/// Returns the body with a trailing `break` removed, if the body only leaves the
+/// loop after setting flags.
+fn mismatch_action(block: &Block) -> Option<Block> {
+    match block.stmts.as_slice() {
+        [Stmt::Expr(Expr::Return(_), _)] => Some(block.clone()),
+        [assigns @ .., Stmt::Expr(
+            Expr::Break(ExprBreak {
+                label: None,
+                expr: None,
+                ..
+            }),
+            _,
+        )] if !assigns.is_empty() => {
+            let flags_only = assigns.iter().all(|stmt| {
+                matches!(stmt, Stmt::Expr(Expr::Assign(assign), Some(_))
+                    if path_ident(&assign.left).is_some()
+                        && matches!(strip_parens(&assign.right), Expr::Lit(_)))
+            });
+            flags_only.then(|| Block {
+                brace_token: block.brace_token,
+                stmts: assigns.to_vec(),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Returns whether `expr` is the literal `value`.
+fn is_bool_lit(expr: &Expr, value: bool) -> bool {
+    matches!(strip_parens(expr), Expr::Lit(ExprLit { lit: Lit::Bool(lit), .. }) if lit.value == value)
+}
+
+impl PrefixLoopToStartsWith {
+    /// Returns the `starts_with`/`ends_with` test replacing the loop and the body to
+    /// run when it fails.
+    fn replacement(&self, for_loop: &ExprForLoop) -> Option<(Expr, Block)> {
+        let Pat::Ident(pat) = for_loop.pat.as_ref() else {
+            return None;
+        };
+        let index = &pat.ident;
+        let Expr::Range(ExprRange {
+            start: Some(start),
+            limits: RangeLimits::HalfOpen(_),
+            end: Some(count),
+            ..
+        }) = for_loop.expr.as_ref()
+        else {
+            return None;
+        };
+        let count = strip_parens(count);
+        let [Stmt::Expr(
+            Expr::If(ExprIf {
+                cond,
+                then_branch,
+                else_branch: None,
+                ..
+            }),
+            _,
+        )] = for_loop.body.stmts.as_slice()
+        else {
+            return None;
+        };
+        if !is_int_lit(start, 0) || count_ident(count, index) > 0 {
+            return None;
+        }
+        let action = mismatch_action(then_branch)?;
+        let Comparison {
+            slice,
+            affix,
+            suffix,
+        } = comparison(cond, index, count)?;
+        // Both orders match the prefix form; the prefix is the one the loop spans.
+        let (slice, affix) = if !suffix && is_len(count, slice) {
+            (affix, slice)
+        } else {
+            (slice, affix)
+        };
+        // The suffix form also mentions `s.len()` and the count in the index.
+        let (slice_uses, affix_uses) = if suffix {
+            (2 + count_ident(count, slice), 1 + count_ident(count, affix))
+        } else {
+            (1, 1)
+        };
+        if count_ident(cond, index) != 2
+            || count_ident(cond, slice) != slice_uses
+            || count_ident(cond, affix) != affix_uses
+            || count_ident(&action, index) > 0
+        {
+            return None;
+        }
+
+        let is_reference = matches!(self.types.get(affix), Some(Type::Reference(_)));
+        let affix: Expr = if is_len(count, affix) {
+            if is_reference {
+                parse_quote!(#affix)
+            } else {
+                parse_quote!(&#affix)
+            }
+        } else {
+            parse_quote!(&#affix[..#count])
+        };
+        let test = if suffix {
+            parse_quote!(#slice.ends_with(#affix))
+        } else {
+            parse_quote!(#slice.starts_with(#affix))
+        };
+        Some((test, action))
+    }
+
+    fn rewrite_block(&self, block: &mut Block) {
+        let mut position = 0;
+        while position < block.stmts.len() {
+            let Stmt::Expr(Expr::ForLoop(for_loop), _) = &block.stmts[position] else {
+                position += 1;
+                continue;
+            };
+            let Some((test, action)) = self.replacement(for_loop) else {
+                position += 1;
+                continue;
+            };
+            // `loop; true` at the end of a function returning `false` on a mismatch.
+            let returns_false = matches!(action.stmts.as_slice(), [Stmt::Expr(
+                Expr::Return(ret), _
+            )] if ret.expr.as_deref().is_some_and(|expr| is_bool_lit(expr, false)));
+            let tail_true = position + 2 == block.stmts.len()
+                && matches!(&block.stmts[position + 1], Stmt::Expr(expr, None) if is_bool_lit(expr, true));
+            if returns_false && tail_true {
+                block.stmts.truncate(position);
+                block.stmts.push(Stmt::Expr(test, None));
+            } else {
+                block.stmts[position] = parse_quote!(if !#test #action);
+            }
+            position += 1;
+        }
+    }
+}
+
+impl VisitMut for PrefixLoopToStartsWith {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        syn::visit_mut::visit_block_mut(self, block);
+        self.rewrite_block(block);
+    }
+}
+
+impl Pass for PrefixLoopToStartsWith {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
@@ -0,0 +1,164 @@
+//! Replaces `qsort` calls on the elements of a slice or array with `sort_by`, e.g.
+//!
+//! ```ignore
+//! qsort(a.as_mut_ptr() as *mut c_void, n as size_t, size_of::<i32>() as size_t, Some(cmp));
+//! ```
+//!
+//! becomes a `sort_by` over `a[..n as usize]` whose closure calls the original C
+//! comparator. The closure passes the element references as the pointers the
+//! comparator expects, and turns its `i32` result into an `Ordering` by comparing
+//! it against zero.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{is_ident, path_ident, strip_casts};
+use crate::MonadicAst;
+use std::collections::HashSet;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, Expr, ExprCall, ExprMethodCall, ExprPath, GenericArgument, Ident, Item,
+    PathArguments, Type,
+};
+
+#[derive(Default)]
+pub struct QsortToSort {
+    /// Functions defined in the file which are safe to call.
+    safe_fns: HashSet<Ident>,
+}
+
+/// Returns whether `func` is `qsort` or `libc::qsort`.
+fn is_qsort(func: &Expr) -> bool {
+    match func {
+        Expr::Path(ExprPath {
+            qself: None, path, ..
+        }) => {
+            let segments: Vec<String> = path
+                .segments
+                .iter()
+                .map(|segment| segment.ident.to_string())
+                .collect();
+            matches!(
+                segments
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .as_slice(),
+                ["qsort"] | ["libc", "qsort"]
+            )
+        }
+        _ => false,
+    }
+}
+
+/// Returns the slice `a` if `base` is `a.as_mut_ptr()`, ignoring casts.
+fn sorted_slice(base: &Expr) -> Option<&Ident> {
+    match strip_casts(base) {
+        Expr::MethodCall(ExprMethodCall {
+            receiver,
+            method,
+            args,
+            ..
+        }) if method == "as_mut_ptr" && args.is_empty() => path_ident(receiver),
+        _ => None,
+    }
+}
+
+/// Returns `T` if `size` is `size_of::<T>()`, ignoring casts.
+fn element_type(size: &Expr) -> Option<&Type> {
+    let Expr::Call(ExprCall { func, args, .. }) = strip_casts(size) else {
+        return None;
+    };
+    let Expr::Path(ExprPath { path, .. }) = func.as_ref() else {
+        return None;
+    };
+    let last = path.segments.last()?;
+    if last.ident != "size_of" || !args.is_empty() {
+        return None;
+    }
+    match &last.arguments {
+        PathArguments::AngleBracketed(generics) => match generics.args.first() {
+            Some(GenericArgument::Type(ty)) if generics.args.len() == 1 => Some(ty),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the comparator `f` if `cmp` is `f` or `Some(f)`, ignoring casts.
+fn comparator(cmp: &Expr) -> Option<&Ident> {
+    match strip_casts(cmp) {
+        Expr::Call(ExprCall { func, args, .. }) if args.len() == 1 => {
+            let Expr::Path(ExprPath { path, .. }) = func.as_ref() else {
+                return None;
+            };
+            if path.is_ident("Some") {
+                path_ident(strip_casts(&args[0]))
+            } else {
+                None
+            }
+        }
+        expr => path_ident(expr),
+    }
+}
+
+impl QsortToSort {
+    fn replacement(&self, expr: &Expr) -> Option<Expr> {
+        let Expr::Call(ExprCall { func, args, .. }) = expr else {
+            return None;
+        };
+        if !is_qsort(func) || args.len() != 4 {
+            return None;
+        }
+        let slice = sorted_slice(&args[0])?;
+        let len = strip_casts(&args[1]);
+        let ty = element_type(&args[2])?;
+        let cmp = comparator(&args[3])?;
+
+        let whole = matches!(len, Expr::MethodCall(ExprMethodCall {
+            receiver, method, args, ..
+        }) if method == "len" && args.is_empty() && is_ident(receiver, slice));
+        let elements: Expr = if whole {
+            parse_quote!(#slice)
+        } else if matches!(len, Expr::Lit(_)) {
+            parse_quote!(#slice[..#len])
+        } else {
+            parse_quote!(#slice[..#len as usize])
+        };
+        let call: Expr =
+            parse_quote!(#cmp(a as *const #ty as *const _, b as *const #ty as *const _));
+        let call: Expr = if self.safe_fns.contains(cmp) {
+            call
+        } else {
+            parse_quote!(unsafe { #call })
+        };
+        Some(parse_quote!(#elements.sort_by(|a, b| #call.cmp(&0))))
+    }
+}
+
+impl VisitMut for QsortToSort {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if let Some(replacement) = self.replacement(expr) {
+            *expr = replacement;
+        } else {
+            syn::visit_mut::visit_expr_mut(self, expr);
+        }
+    }
+}
+
+impl Pass for QsortToSort {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.safe_fns = monad
+            .ast
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Fn(item_fn) if item_fn.sig.unsafety.is_none() => {
+                    Some(item_fn.sig.ident.clone())
+                }
+                _ => None,
+            })
+            .collect();
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
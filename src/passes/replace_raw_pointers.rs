@@ -2,23 +2,41 @@
 //! pointer derivation graph (PDG) matching.
 
 use crate::monad::ast::Pass;
+use crate::monad::diagnostic::Diagnostic;
+use crate::monad::error::PassError;
+use crate::monad::event::Event;
+use crate::passes::utils::{
+    allocation, as_usize, callee_name, count_ident, default_of, derives_copy, element_count,
+    is_assign_op, is_int_lit, is_primitive, is_pure, is_reassigned, is_signed_int, is_unsigned_int,
+    len_as_usize, local_types, parenthesized, path_ident, primitive_name, same_tokens, strip_casts,
+    strip_parens, Allocation, Parenthesize,
+};
 use crate::MonadicAst;
 use std::collections::{HashMap, HashSet};
+use syn::spanned::Spanned;
 use syn::visit::Visit;
 use syn::visit_mut::VisitMut;
 use syn::{
-    Expr, ExprAssign, ExprMethodCall, ExprPath, ExprUnary, File, FnArg, Ident, Local, Pat,
-    PatIdent, PatType, Type, TypePtr, UnOp,
+    parse_quote, BinOp, Block, Expr, ExprAssign, ExprBinary, ExprCall, ExprCast, ExprClosure,
+    ExprField, ExprForLoop, ExprIf, ExprIndex, ExprLit, ExprMethodCall, ExprParen, ExprPath,
+    ExprRange, ExprReference, ExprReturn, ExprStruct, ExprUnary, ExprUnsafe, ExprWhile, FieldValue,
+    File, FnArg, ForeignItem, ForeignItemFn, Ident, ImplItemFn, Item, ItemFn, ItemForeignMod,
+    ItemImpl, ItemStruct, Lit, Local, Member, Pat, PatIdent, PatType, Path, RangeLimits,
+    ReturnType, Signature, Stmt, TraitItemFn, Type, TypePath, TypePtr, TypeReference, UnOp,
 };
 
+const PASS: &str = "replace_raw_pointers";
+
 /// Represents a permission that a raw pointer *p will need at the point in the
 /// program p is defined and used.
 #[derive(Copy, Clone, Debug, Hash, Eq, Ord, PartialEq, PartialOrd)]
 enum PointerAccess {
-    Write,  // The program writes to the pointee.
-    Unique, // The pointer is the only way to access the given memory location.
-    Free,   // The pointer will eventually be passed to free.
-    Offset, // We'll add/subtract an offset to the pointer, e.g. array element access.
+    Write,     // The program writes to the pointee.
+    Read,      // The program reads the pointee.
+    Unique,    // The pointer is the only way to access the given memory location.
+    Free,      // The pointer will eventually be passed to free.
+    OffsetAdd, // We'll add an offset to the pointer, e.g. array element access.
+    OffsetSub, // We'll subtract an offset from the pointer.
 }
 
 #[derive(Copy, Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
@@ -33,17 +51,37 @@ enum RustPointerType {
     Undefined,          // ...for unsupported combinations
 }
 
-static ACCESSES: &[PointerAccess] = &[
-    PointerAccess::Write,
-    PointerAccess::Unique,
-    PointerAccess::Free,
-    PointerAccess::Offset,
-];
+impl RustPointerType {
+    /// Returns whether the type is a reference to a single value.
+    fn is_reference(self) -> bool {
+        matches!(
+            self,
+            RustPointerType::ImmutableReference
+                | RustPointerType::MutableReference
+                | RustPointerType::CellReference
+        )
+    }
+
+    /// Returns whether the type is a slice, which needs a length.
+    fn is_slice(self) -> bool {
+        matches!(
+            self,
+            RustPointerType::ImmutableSlice
+                | RustPointerType::MutableSlice
+                | RustPointerType::UniqueSlicePointer
+        )
+    }
+}
 
 impl PointerAccess {
     /// Returns the Rust safe pointer type corresponding to the given pointer access
     /// permissions, if any exists, and RustPointerType::Undefined otherwise.
     ///
+    /// Adding and subtracting offsets both count as the Offset permission. A Write
+    /// permission is ignored unless the pointer is `mutable`, i.e. a `*mut T`, and a
+    /// Unique permission unless the pointer is also written to or freed. A Read
+    /// permission only tells the pointer is used, and leaves the type as it is.
+    ///
     /// The permissions to type mapping is determined by the following table:
     /// Write - Unique - Free - Offset  |  Resulting Type
     ///                                 |      &T
@@ -53,13 +91,12 @@ impl PointerAccess {
     ///                           X     |      &[T]
     ///   X       X               X     |      &mut [T]
     ///           X       X       X     |      Box<[T]>
-    fn determine_rust_type(permissions: &[PointerAccess]) -> RustPointerType {
-        let [has_write, has_unique, has_free, has_offset]: [bool; 4] = ACCESSES
-            .iter()
-            .map(|access_type| permissions.contains(access_type))
-            .collect::<Vec<_>>()
-            .try_into()
-            .unwrap();
+    fn determine_rust_type(permissions: &[PointerAccess], mutable: bool) -> RustPointerType {
+        let has_write = mutable && permissions.contains(&PointerAccess::Write);
+        let has_free = permissions.contains(&PointerAccess::Free);
+        let has_unique = (has_write || has_free) && permissions.contains(&PointerAccess::Unique);
+        let has_offset = permissions.contains(&PointerAccess::OffsetAdd)
+            || permissions.contains(&PointerAccess::OffsetSub);
         match (has_write, has_unique, has_free, has_offset) {
             // &T
             (false, false, false, false) => RustPointerType::ImmutableReference,
@@ -88,88 +125,1410 @@ enum TypeMappingStateMachine {
     Uninitialized,
     /// Currently in the process of mapping identifiers to their appropriate Rust
     /// safe reference types.
-    Computing(HashMap<Ident, RustPointerType>),
+    Computing(HashMap<PointerId, RustPointerType>),
     /// All raw pointer identifiers have been mapped to their appropriate Rust
     /// safe reference type.
-    Initialized(HashMap<Ident, RustPointerType>),
+    Initialized(HashMap<PointerId, RustPointerType>),
+}
+
+/// Identifies a pointer variable by the order of its declaration in the file, so
+/// that variables of the same name, like one shadowing another, are told apart.
+#[derive(Copy, Clone, Debug, Hash, Eq, Ord, PartialEq, PartialOrd)]
+struct PointerId(usize);
+
+/// A parameter of a function of the file.
+#[derive(Clone)]
+struct Param {
+    /// The binding of the parameter, if it is a plain one, and its id if it is a raw
+    /// pointer.
+    pat: Option<PatIdent>,
+    id: Option<PointerId>,
+    ty: Type,
+    /// Whether the function assigns the parameter.
+    reassigned: bool,
+}
+
+/// Where a slice parameter takes its length from.
+#[derive(Clone)]
+enum SliceLength {
+    /// From the slice, which replaces the length parameter at the given index.
+    Param(usize),
+    /// From the expression, the parameter staying a raw pointer the function builds
+    /// the slice from.
+    FromRawParts(Box<Expr>),
+}
+
+/// The variables in scope at the point of the file being visited, with the id of
+/// those that are raw pointers. Each visit of the file declares the same variables
+/// in the same order, and so numbers the pointers alike.
+#[derive(Default)]
+struct Scopes {
+    /// The scopes being visited, innermost last. A function only sees its own.
+    stack: Vec<HashMap<Ident, Option<PointerId>>>,
+    /// How many pointers were declared.
+    declared: usize,
+}
+
+impl Scopes {
+    /// Enters a function, which cannot see the variables of an enclosing function.
+    /// Returns the scopes to restore with [Scopes::exit_fn].
+    fn enter_fn(&mut self) -> Vec<HashMap<Ident, Option<PointerId>>> {
+        std::mem::replace(&mut self.stack, vec![HashMap::new()])
+    }
+
+    fn exit_fn(&mut self, outer: Vec<HashMap<Ident, Option<PointerId>>>) {
+        self.stack = outer;
+    }
+
+    fn push(&mut self) {
+        self.stack.push(HashMap::new());
+    }
+
+    fn pop(&mut self) {
+        self.stack.pop();
+    }
+
+    /// Declares the variable `pat` binds, of type `ty`, in the innermost scope,
+    /// shadowing any other of its name. Returns its id if it is a raw pointer.
+    fn declare(&mut self, pat: &Pat, ty: Option<&Type>) -> Option<PointerId> {
+        let Pat::Ident(PatIdent { ident, .. }) = pat else {
+            return None;
+        };
+        let id = matches!(ty, Some(Type::Ptr(_))).then(|| {
+            self.declared += 1;
+            PointerId(self.declared - 1)
+        });
+        if let Some(scope) = self.stack.last_mut() {
+            scope.insert(ident.clone(), id);
+        }
+        id
+    }
+
+    /// Returns the id [Scopes::declare] would give the variable `pat` binds, of type
+    /// `ty`, if it is a raw pointer.
+    fn peek(&self, pat: &Pat, ty: Option<&Type>) -> Option<PointerId> {
+        (matches!(pat, Pat::Ident(_)) && matches!(ty, Some(Type::Ptr(_))))
+            .then_some(PointerId(self.declared))
+    }
+
+    /// Returns whether `ident` names a variable in scope.
+    fn is_local(&self, ident: &Ident) -> bool {
+        self.stack.iter().any(|scope| scope.contains_key(ident))
+    }
+
+    /// Returns the id of the raw pointer the variable `ident` names in scope, if it
+    /// is one.
+    fn resolve(&self, ident: &Ident) -> Option<PointerId> {
+        self.stack
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(ident))
+            .copied()
+            .flatten()
+    }
 }
 
 #[derive(Default)]
 pub struct RawPointerSanitizer {
-    /// Keeps track of pointer variables and their access permissions.
-    pointers: HashMap<Ident, (TypePtr, HashSet<PointerAccess>)>,
+    /// Keeps track of pointer variables, with their name, type and access
+    /// permissions.
+    pointers: HashMap<PointerId, (Ident, TypePtr, HashSet<PointerAccess>)>,
+    /// The variables in scope, to tell which pointer a name refers to.
+    scopes: Scopes,
+    /// The function being visited, and the function each pointer is declared in,
+    /// for diagnostics.
+    current_fn: Option<Ident>,
+    declared_in: HashMap<PointerId, Ident>,
     /// Mapping between the pointer variables and their memory safe equivalent types.
     types: TypeMappingStateMachine,
+    /// The counters of the loops being visited, with the bound each stays below.
+    loop_bounds: Vec<(Ident, Expr)>,
+    /// Pointers offset by a loop counter, with the counter's bound: a candidate
+    /// length for the slice the pointer may become.
+    slice_lengths: HashMap<PointerId, Expr>,
+    /// Pointers a slice is built from with `slice::from_raw_parts`, which must stay
+    /// raw, like those [Self::bind_slice_lengths] keeps.
+    sliced: HashSet<PointerId>,
+    /// How many times each pointer is compared with null.
+    null_checks: HashMap<PointerId, usize>,
+    /// How many of those comparisons are early-return null guards on a function
+    /// argument, like `if p.is_null() { return; }`.
+    null_guards: HashMap<PointerId, usize>,
+    /// Pointers whose pointee is written in place, through a field, an index or a
+    /// mutable borrow.
+    written_in_place: HashSet<PointerId>,
+    /// Names of the structs declared in the file that derive `Copy`.
+    copy_structs: HashSet<Ident>,
+    /// Pointers whose every null check is a guard, mapped to an `Option` of a
+    /// reference whose guards become `let Some(p) = p else { return; };`.
+    nullable: HashSet<PointerId>,
+    /// Functions returning a raw pointer that is freshly allocated wherever they
+    /// return, keyed by the function, with the permissions the caller's ownership
+    /// of the pointer calls for.
+    returns: HashMap<Ident, (TypePtr, HashSet<PointerAccess>)>,
+    /// Mapping between those functions and the safe equivalent of their return type.
+    return_types: HashMap<Ident, RustPointerType>,
+    /// Raw pointer fields, keyed by the struct and the field, with whether the
+    /// struct derives `Copy`.
+    fields: HashMap<(Ident, Ident), (TypePtr, bool)>,
+    /// The access permissions of the pointer fields, gathered from `self.f` and
+    /// `(*s).f` where the struct of `self` or `s` is known.
+    field_accesses: HashMap<(Ident, Ident), HashSet<PointerAccess>>,
+    /// Fields accessed through a value of unknown struct, as those accesses cannot
+    /// be attributed to the field of any one struct.
+    unresolved_fields: HashSet<Ident>,
+    /// The struct each variable holds, or points to or references.
+    struct_vars: HashMap<Ident, Ident>,
+    /// The struct the impl block being visited implements, which `self` is.
+    self_type: Option<Ident>,
+    /// Mapping between the pointer fields and their memory safe equivalent types.
+    field_types: HashMap<(Ident, Ident), RustPointerType>,
+    /// The parameter types of the functions declared in `extern` blocks.
+    foreign_fns: HashMap<Ident, Vec<Type>>,
+    /// The parameters of the functions of the file, whose calls are rewritten along
+    /// with their signatures.
+    fn_params: HashMap<Ident, Vec<Param>>,
+    /// The pointer parameters of the methods of the file, keyed by name, which are
+    /// kept raw if the method is called, as its calls are not rewritten.
+    method_params: HashMap<Ident, Vec<PointerId>>,
+    /// Declared types of the locals of the function being rewritten.
+    local_types: HashMap<Ident, Type>,
+    /// Whether the code being rewritten may dereference raw pointers.
+    unsafe_context: bool,
+    /// Pointers mapped to a safe type that are used in a way the type cannot be, like
+    /// being returned, with how. They are kept raw when the file is rewritten again.
+    rejected: HashMap<PointerId, String>,
+    /// Functions whose safe return type is used in a way it cannot be, like being
+    /// passed on, with how. Their return type is kept raw likewise.
+    rejected_returns: HashMap<Ident, String>,
+    /// Pointer fields whose safe type is used in a way it cannot be, with how. They
+    /// are kept raw likewise.
+    rejected_fields: HashMap<(Ident, Ident), String>,
+    /// How many declarations were given a safe type.
+    rewritten: usize,
+    events: Vec<Event>,
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl RawPointerSanitizer {
-    fn record_if_pointer(&mut self, pat: &Pat, ty: &Type) {
-        match (pat, ty) {
-            (
-                Pat::Ident(PatIdent {
-                    mutability: _,
-                    ident,
+    /// Declares the variable `pat` binds, of type `ty`, adding it to the `pointers`
+    /// map if it is a raw pointer.
+    fn record_declaration(&mut self, pat: &Pat, ty: Option<&Type>) {
+        if let (Some(id), Pat::Ident(PatIdent { ident, .. }), Some(Type::Ptr(pointer))) =
+            (self.scopes.declare(pat, ty), pat, ty)
+        {
+            self.pointers
+                .insert(id, (ident.clone(), pointer.clone(), HashSet::new()));
+            if let Some(function) = &self.current_fn {
+                self.declared_in.insert(id, function.clone());
+            }
+        }
+    }
+
+    /// Returns whether values of type `elem` can be copied out of a `Cell`.
+    fn is_copy(&self, elem: &Type) -> bool {
+        is_primitive(elem) || struct_name(elem).is_some_and(|name| self.copy_structs.contains(name))
+    }
+
+    /// Returns the id of the pointer variable `expr` names, if it is one.
+    fn pointer_id(&self, expr: &Expr) -> Option<PointerId> {
+        self.scopes.resolve(path_ident(expr)?)
+    }
+
+    /// Records the struct the variable `pat` declares holds, or points to or
+    /// references, if `ty` names one.
+    fn record_if_struct(&mut self, pat: &Pat, ty: &Type) {
+        if let (Pat::Ident(PatIdent { ident, .. }), Some(name)) = (pat, struct_name(ty)) {
+            self.struct_vars.insert(ident.clone(), name.clone());
+        }
+    }
+
+    /// Returns the struct and field `expr` accesses, if it is `self.f`, `s.f` or
+    /// `(*s).f` for a variable `s` of known struct.
+    fn field_key(&self, expr: &ExprField) -> Option<(Ident, Ident)> {
+        let Member::Named(field) = &expr.member else {
+            return None;
+        };
+        let base = strip_parens(&expr.base);
+        let var = path_ident(expr_if_unary_deref(base).map_or(base, strip_parens))?;
+        let name = if var == "self" {
+            self.self_type.as_ref()?
+        } else {
+            self.struct_vars.get(var)?
+        };
+        Some((name.clone(), field.clone()))
+    }
+
+    /// Returns the access set of the tracked pointer `expr` evaluates to, whether a
+    /// pointer variable or a field of a known struct.
+    fn access_set(&mut self, expr: &Expr) -> Option<&mut HashSet<PointerAccess>> {
+        match strip_parens(expr) {
+            Expr::Field(field) => {
+                let key = self.field_key(field)?;
+                Some(self.field_accesses.entry(key).or_default())
+            }
+            expr => {
+                let id = self.pointer_id(expr)?;
+                self.pointers
+                    .get_mut(&id)
+                    .map(|(_, _, access_set)| access_set)
+            }
+        }
+    }
+
+    /// Replaces `ty` with the safe type computed for the pointer `id`, declared with
+    /// it, if it is a recorded raw pointer mapped to one.
+    fn replace_if_mapped(&mut self, id: Option<PointerId>, ty: &mut Type) {
+        let TypeMappingStateMachine::Initialized(map) = &self.types else {
+            panic!("Must be in Initialized state")
+        };
+        let Some(id) = id else {
+            return;
+        };
+        let (Some((_, pointer, _)), Some(&rust_type)) = (self.pointers.get(&id), map.get(&id))
+        else {
+            return;
+        };
+        if let Some(safe) = safe_type(rust_type, &pointer.elem) {
+            let safe = if self.nullable.contains(&id) {
+                parse_quote!(Option<#safe>)
+            } else {
+                safe
+            };
+            self.record_rewrite(rust_type, ty, &safe);
+            *ty = safe;
+        }
+    }
+
+    /// Counts the rewrite of the raw pointer type `before` into the safe `after`,
+    /// and records it as an event.
+    fn record_rewrite(&mut self, rust_type: RustPointerType, before: &Type, after: &Type) {
+        let kind = match rust_type {
+            RustPointerType::UniquePointer | RustPointerType::UniqueSlicePointer => "ptr_to_box",
+            _ => "ptr_to_ref",
+        };
+        self.events
+            .push(Event::new(kind, before.span()).with_types(before, after));
+        self.rewritten += 1;
+    }
+
+    /// Returns the parameters of the function, once they are declared.
+    fn params(&self, sig: &Signature, block: &Block) -> Vec<Param> {
+        sig.inputs
+            .iter()
+            .map(|arg| match arg {
+                FnArg::Typed(PatType { pat, ty, .. }) => match pat.as_ref() {
+                    Pat::Ident(pat_ident) => Param {
+                        pat: Some(pat_ident.clone()),
+                        id: self.scopes.resolve(&pat_ident.ident),
+                        ty: ty.as_ref().clone(),
+                        reassigned: is_reassigned(&block.stmts, &pat_ident.ident),
+                    },
+                    _ => Param {
+                        pat: None,
+                        id: None,
+                        ty: ty.as_ref().clone(),
+                        reassigned: false,
+                    },
+                },
+                FnArg::Receiver(receiver) => Param {
+                    pat: None,
+                    id: None,
+                    ty: receiver.ty.as_ref().clone(),
+                    reassigned: false,
+                },
+            })
+            .collect()
+    }
+
+    /// Returns where the slice parameters among `params` take their length from,
+    /// keyed by their index, from the bound they are indexed below, as noted by
+    /// [Self::record_if_loop_bounded].
+    ///
+    /// The length is taken from the slice if the bound is a sibling parameter, cast
+    /// or not, of an integer type the function never assigns, like `n` in `while i <
+    /// n { *p.offset(i) }` for `(p: *mut i32, n: usize)`. Otherwise, a length which
+    /// only reads such parameters and constants, and is known not to be negative,
+    /// like `n + 1` for an unsigned `n`, keeps the parameter a raw pointer, the slice
+    /// being built from it. Any other slice is left for the caller to build.
+    fn slice_plan(&self, params: &[Param]) -> HashMap<usize, SliceLength> {
+        let length_param = |ident: &Ident| {
+            params.iter().position(|param| {
+                param.pat.as_ref().is_some_and(|pat| pat.ident == *ident)
+                    && !param.reassigned
+                    && (is_signed_int(&param.ty) || is_unsigned_int(&param.ty))
+            })
+        };
+        let unsigned =
+            |ident: &Ident| length_param(ident).is_some_and(|i| is_unsigned_int(&params[i].ty));
+        let (mut plan, mut taken) = (HashMap::new(), HashSet::new());
+        for (index, param) in params.iter().enumerate() {
+            let Some(id) = param.id else {
+                continue;
+            };
+            let (Some(rust_type), Some(length)) =
+                (self.mapped_type(id), self.slice_lengths.get(&id))
+            else {
+                continue;
+            };
+            if !matches!(
+                rust_type,
+                RustPointerType::ImmutableSlice | RustPointerType::MutableSlice
+            ) {
+                continue;
+            }
+            match path_ident(strip_casts(strip_parens(length))).and_then(length_param) {
+                Some(len) if taken.insert(len) => {
+                    plan.insert(index, SliceLength::Param(len));
+                }
+                Some(_) => {}
+                None if is_pure(length)
+                    && reads_only(length, |ident| length_param(ident).is_some())
+                    && non_negative(length, &unsigned) =>
+                {
+                    plan.insert(index, SliceLength::FromRawParts(Box::new(length.clone())));
+                }
+                None => {}
+            }
+        }
+        plan
+    }
+
+    /// Gives the slice parameters of the function their length as planned by
+    /// [Self::slice_plan], once the function is rewritten. A length parameter
+    /// replaced by the slice's is dropped, and redeclared at the top of the function
+    /// as `let n: usize = p.len();`. A slice built from a raw pointer is declared
+    /// there too, with `slice::from_raw_parts`.
+    fn bind_slice_lengths(&mut self, sig: &mut Signature, block: &mut Block) {
+        let params = self.params(sig, block);
+        let mut plan: Vec<_> = self.slice_plan(&params).into_iter().collect();
+        plan.sort_by_key(|(index, _)| *index);
+        let (mut lengths, mut slices) = (Vec::<Stmt>::new(), Vec::<Stmt>::new());
+        let (mut dropped, mut kept_raw) = (HashSet::new(), HashMap::new());
+        for (index, length) in plan {
+            let (Some(pat), Some(id)) = (&params[index].pat, params[index].id) else {
+                continue;
+            };
+            let pointer = &pat.ident;
+            let raw = &self.pointers[&id].1;
+            let Some(safe) = self.mapped_type(id).and_then(|t| safe_type(t, &raw.elem)) else {
+                continue;
+            };
+            match length {
+                SliceLength::Param(len) => {
+                    let Param {
+                        pat: Some(len_pat),
+                        ty: len_ty,
+                        ..
+                    } = &params[len]
+                    else {
+                        continue;
+                    };
+                    let init: Expr = if primitive_name(len_ty).as_deref() == Some("usize") {
+                        parse_quote!(#pointer.len())
+                    } else {
+                        parse_quote!(#pointer.len() as #len_ty)
+                    };
+                    lengths.push(parse_quote!(let #len_pat: #len_ty = #init;));
+                    dropped.insert(len);
+                    let len = &len_pat.ident;
+                    self.diagnostics.push(Diagnostic::new(
+                        PASS,
+                        Some(&sig.ident),
+                        format!(
+                            "`{pointer}` is indexed below `{len}`; took its length from the \
+                             slice and dropped the `{len}` argument"
+                        ),
+                    ));
+                }
+                SliceLength::FromRawParts(length) => {
+                    let count = as_usize(strip_parens(&length));
+                    let mut slice: Expr = if raw.mutability.is_some() {
+                        parse_quote!(std::slice::from_raw_parts_mut(#pointer, #count))
+                    } else {
+                        parse_quote!(std::slice::from_raw_parts(#pointer, #count))
+                    };
+                    if sig.unsafety.is_none() {
+                        slice = parse_quote!(unsafe { #slice });
+                    }
+                    slices.push(parse_quote!(let #pointer: #safe = #slice;));
+                    kept_raw.insert(index, raw.clone());
+                }
+            }
+        }
+        sig.inputs = std::mem::take(&mut sig.inputs)
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| !dropped.contains(index))
+            .map(|(index, mut arg)| {
+                if let (FnArg::Typed(PatType { ty, .. }), Some(raw)) =
+                    (&mut arg, kept_raw.get(&index))
+                {
+                    **ty = Type::Ptr(raw.clone());
+                }
+                arg
+            })
+            .collect();
+        // The lengths are declared first, as the slices may read them.
+        block.stmts.splice(0..0, lengths.into_iter().chain(slices));
+    }
+
+    /// Notes the bound of the loop counter `offset` as a candidate slice length for
+    /// `pointer`, if `offset` is the counter of a loop being visited, cast or not.
+    fn record_if_loop_bounded(&mut self, pointer: PointerId, offset: Option<&Expr>) {
+        let Some(counter) = offset.map(strip_casts).and_then(path_ident) else {
+            return;
+        };
+        if let Some((_, bound)) = self.loop_bounds.iter().rev().find(|(c, _)| c == counter) {
+            self.slice_lengths
+                .entry(pointer)
+                .or_insert_with(|| bound.clone());
+        }
+    }
+
+    /// Records the `access` of the pointer `place` dereferences, if any.
+    fn record_deref(&mut self, place: &Expr, access: PointerAccess) {
+        let Some(pointer) = dereferenced(place) else {
+            return;
+        };
+        if let Some(access_set) = self.access_set(pointer) {
+            access_set.insert(access);
+        }
+    }
+
+    /// Records the `access` of the pointer `pointer`, passed as `memcpy(pointer, ..,
+    /// size)` or the like, to a range of its pointee: along with an OffsetAdd
+    /// permission, unless the call covers a single element. Untyped `void` pointers
+    /// are left alone.
+    fn record_mem_access(&mut self, pointer: &Expr, size: &Expr, access: PointerAccess) {
+        let Some(id) = self.pointer_id(strip_casts(pointer)) else {
+            return;
+        };
+        let Some((_, TypePtr { elem, .. }, access_set)) = self.pointers.get_mut(&id) else {
+            return;
+        };
+        let untyped = match elem.as_ref() {
+            Type::Tuple(tuple) => tuple.elems.is_empty(),
+            Type::Path(TypePath { path, .. }) => {
+                path.segments.last().is_some_and(|s| s.ident == "c_void")
+            }
+            _ => false,
+        };
+        if untyped {
+            return;
+        }
+        access_set.insert(access);
+        if !matches!(element_count(size, elem), Some(None)) {
+            access_set.insert(PointerAccess::OffsetAdd);
+        }
+    }
+
+    /// Records a Write permission for the pointer `lvalue` writes through, if any,
+    /// whether to the whole pointee, as in `*p = v`, or to a part of it, as in
+    /// `(*p).f = v` or `(*p).a[i] = v`.
+    fn record_if_written(&mut self, lvalue: &Expr) {
+        let mut place = strip_parens(lvalue);
+        let mut whole = true;
+        while let Expr::Field(ExprField { base, .. }) | Expr::Index(ExprIndex { expr: base, .. }) =
+            place
+        {
+            place = strip_parens(base);
+            whole = false;
+        }
+        self.record_deref(place, PointerAccess::Write);
+        if !whole {
+            self.record_if_written_in_place(place);
+        }
+    }
+
+    /// Records that the pointer `place` dereferences is written other than by
+    /// storing a whole new pointee, which a `Cell` cannot do.
+    fn record_if_written_in_place(&mut self, place: &Expr) {
+        if let Some(id) = dereferenced(place).and_then(|pointer| self.pointer_id(pointer)) {
+            self.written_in_place.insert(id);
+        }
+    }
+
+    /// Counts the null guards on the pointer arguments among the function's
+    /// top-level statements, once the arguments are declared. A guard only counts if
+    /// it comes before any other use of the pointer.
+    fn record_null_guards(&mut self, sig: &Signature, block: &Block) {
+        for arg in &sig.inputs {
+            let FnArg::Typed(PatType { pat, .. }) = arg else {
+                continue;
+            };
+            let Pat::Ident(PatIdent { ident, .. }) = pat.as_ref() else {
+                continue;
+            };
+            let Some(id) = self.scopes.resolve(ident) else {
+                continue;
+            };
+            let mut seen = false;
+            for stmt in &block.stmts {
+                if null_guard(stmt, ident).is_some() && !seen {
+                    *self.null_guards.entry(id).or_default() += 1;
+                }
+                seen |= count_ident(stmt, ident) > 0;
+            }
+        }
+    }
+
+    /// Records the raw pointer the function returns if every returned expression is
+    /// an allocation of its pointee, as in `return malloc(..) as *mut T;`. Ownership
+    /// of the allocation passes to the caller, so the pointer is Unique and Freed, and
+    /// Offset too if any holds an array.
+    fn record_returned_pointer(&mut self, sig: &Signature, block: &Block) {
+        let ReturnType::Type(_, ty) = &sig.output else {
+            return;
+        };
+        let Type::Ptr(pointer) = ty.as_ref() else {
+            return;
+        };
+        let mut returned = ReturnedExprs::default();
+        returned.visit_block(block);
+        if let Some(Stmt::Expr(tail, None)) = block.stmts.last() {
+            returned.exprs.push(tail);
+        }
+        let mut indexed = false;
+        for expr in &returned.exprs {
+            let (is_array, elem) = match allocation(strip_parens(expr)) {
+                Some(Allocation::Single(elem)) => (false, elem),
+                Some(Allocation::Array(elem, _)) => (true, elem),
+                None => return,
+            };
+            if !same_tokens(&elem, &pointer.elem) {
+                return;
+            }
+            indexed |= is_array;
+        }
+        if returned.exprs.is_empty() {
+            return;
+        }
+        let mut access_set = HashSet::from([PointerAccess::Unique, PointerAccess::Free]);
+        if indexed {
+            access_set.insert(PointerAccess::OffsetAdd);
+        }
+        self.returns
+            .insert(sig.ident.clone(), (pointer.clone(), access_set));
+    }
+
+    /// Replaces the raw pointer return type of the function with its memory safe
+    /// equivalent, if one was computed, and the allocations it returns with those of
+    /// the safe type.
+    fn replace_return_if_mapped(&mut self, sig: &mut Signature, block: &mut Block) {
+        let (Some((pointer, _)), Some(&rust_type)) = (
+            self.returns.get(&sig.ident),
+            self.return_types.get(&sig.ident),
+        ) else {
+            return;
+        };
+        let Some(safe) = safe_type(rust_type, &pointer.elem) else {
+            return;
+        };
+        if let ReturnType::Type(_, before) = &sig.output {
+            let before = before.as_ref().clone();
+            self.record_rewrite(rust_type, &before, &safe);
+        }
+        sig.output = parse_quote!(-> #safe);
+        let mut returned = ReturnedAllocations {
+            indexed: rust_type.is_slice(),
+        };
+        returned.visit_block_mut(block);
+        if let Some(Stmt::Expr(tail, None)) = block.stmts.last_mut() {
+            replace_allocation(tail, returned.indexed);
+        }
+    }
+
+    /// Returns the pointer `expr` dereferences, if it is `*p` for a pointer `p`
+    /// mapped to a `&Cell<T>`.
+    fn cell_deref(&self, expr: &Expr) -> Option<Ident> {
+        self.cell_pointer(expr_if_unary_deref(strip_parens(expr))?)
+    }
+
+    /// Returns the pointer `expr` is, if it is a pointer mapped to a `&Cell<T>`.
+    fn cell_pointer(&self, expr: &Expr) -> Option<Ident> {
+        let TypeMappingStateMachine::Initialized(map) = &self.types else {
+            return None;
+        };
+        let id = self.pointer_id(expr)?;
+        (map.get(&id) == Some(&RustPointerType::CellReference)).then_some(path_ident(expr)?.clone())
+    }
+
+    /// Returns the id of the pointer variable `expr` names and the safe type it is
+    /// mapped to, if it is mapped to one.
+    fn mapped(&self, expr: &Expr) -> Option<(PointerId, RustPointerType)> {
+        let id = self.pointer_id(expr)?;
+        Some((id, self.mapped_type(id)?))
+    }
+
+    /// Returns the safe type the pointer `id` is mapped to, if any.
+    fn mapped_type(&self, id: PointerId) -> Option<RustPointerType> {
+        let TypeMappingStateMachine::Initialized(map) = &self.types else {
+            return None;
+        };
+        map.get(&id).copied()
+    }
+
+    /// Keeps the pointer `id` raw, as it `reason`, once the file is rewritten again.
+    fn reject(&mut self, id: PointerId, reason: impl Into<String>) {
+        self.rejected.entry(id).or_insert_with(|| reason.into());
+    }
+
+    /// Returns the pointer field `expr` accesses and its safe type, if it is mapped
+    /// to one.
+    fn mapped_field(&self, expr: &Expr) -> Option<((Ident, Ident), RustPointerType)> {
+        let Expr::Field(field) = strip_parens(expr) else {
+            return None;
+        };
+        let key = self.field_key(field)?;
+        let rust_type = *self.field_types.get(&key)?;
+        Some((key, rust_type))
+    }
+
+    /// Keeps the pointer field `key` raw when the file is rewritten again.
+    fn reject_field(&mut self, key: (Ident, Ident), reason: impl Into<String>) {
+        self.rejected_fields
+            .entry(key)
+            .or_insert_with(|| reason.into());
+    }
+
+    /// Rewrites the value `init` given to the pointer field `key`, which must be an
+    /// allocation of its owning safe type.
+    fn visit_field_init_mut(
+        &mut self,
+        key: (Ident, Ident),
+        rust_type: RustPointerType,
+        init: &mut Expr,
+    ) {
+        let elem = &self.fields[&key].0.elem;
+        let allocated = allocation(strip_parens(init)).is_some_and(|allocation| {
+            matches!(allocation, Allocation::Single(e) | Allocation::Array(e, _) if same_tokens(&e, elem))
+        });
+        if allocated {
+            replace_allocation(init, rust_type.is_slice());
+        } else {
+            self.reject_field(key, "is given a value other than an allocation");
+            self.visit_expr_mut(init);
+        }
+    }
+
+    /// Returns the raw pointer the pointer variable `expr`, mapped to a borrowing
+    /// safe type, can be passed on as: `p as *const T` for a `&T`, or `p.as_ptr()`
+    /// for a `&Cell<T>`, say. An owning pointer cannot be passed on without giving up
+    /// its ownership.
+    fn as_raw(&self, expr: &Expr) -> Option<Expr> {
+        let (id, rust_type) = self.mapped(expr)?;
+        let elem = &self.pointers[&id].1.elem;
+        Some(match rust_type {
+            RustPointerType::ImmutableReference => parse_quote!(#expr as *const #elem),
+            RustPointerType::MutableReference => parse_quote!(#expr as *mut #elem),
+            RustPointerType::CellReference | RustPointerType::ImmutableSlice => {
+                parse_quote!(#expr.as_ptr())
+            }
+            RustPointerType::MutableSlice => parse_quote!(#expr.as_mut_ptr()),
+            _ => return None,
+        })
+    }
+
+    /// Rewrites the pointers mapped to a safe type among the arguments of a call
+    /// into the raw pointers they stand for, if the callee only reads or writes
+    /// through them: `memcpy` and the like, `ptr::read` and `ptr::write` and their
+    /// variants, and the foreign functions. A foreign function taking a `*mut T` is
+    /// only passed a pointer its type lets it write through. An owning pointer freed
+    /// becomes `drop(p)`. Any other pointer argument is kept raw.
+    fn visit_call_mut(&mut self, expr: &mut Expr) {
+        let Expr::Call(call) = expr else {
+            return;
+        };
+        // free(p as *mut c_void)
+        if let (
+            Expr::Path(ExprPath {
+                qself: None, path, ..
+            }),
+            Some(arg),
+            1,
+        ) = (call.func.as_ref(), call.args.first(), call.args.len())
+        {
+            let pointer = strip_casts(arg);
+            let owned = matches!(
+                self.mapped(pointer),
+                Some((
+                    _,
+                    RustPointerType::UniquePointer | RustPointerType::UniqueSlicePointer
+                ))
+            );
+            if is_free(path) && owned {
+                let pointer = pointer.clone();
+                *expr = parse_quote!(drop(#pointer));
+                return;
+            }
+            // free((*s).f as *mut c_void), leaving an empty box in the field
+            if let (true, Some((key, _))) = (is_free(path), self.mapped_field(pointer)) {
+                let Expr::Field(field) = strip_parens(pointer).clone() else {
+                    return;
+                };
+                if path_ident(strip_parens(&field.base)).is_some_and(|base| base == "self") {
+                    self.reject_field(key, "is freed through `self`");
+                } else {
+                    let mut field = Expr::Field(field);
+                    if let Expr::Field(ExprField { base, .. }) = &mut field {
+                        self.visit_expr_mut(base);
+                    }
+                    *expr = parse_quote!(drop(std::mem::take(&mut #field)));
+                    return;
+                }
+            }
+        }
+        let name = callee_name(&call.func).unwrap_or_else(|| "a function".to_string());
+        let callee = path_ident(&call.func).filter(|f| !self.scopes.is_local(f));
+        // Only `let q = make();` takes the safe return type, for a `q` of that type.
+        if let Some(callee) = callee.filter(|f| self.return_types.contains_key(*f)) {
+            let caller = self
+                .current_fn
+                .as_ref()
+                .map_or("a caller".to_string(), |f| format!("`{f}`"));
+            self.rejected_returns
+                .entry(callee.clone())
+                .or_insert_with(|| {
+                    format!("its result is used in {caller} other than to initialize a pointer")
+                });
+        }
+        let params = callee.and_then(|f| self.fn_params.get(f)).cloned();
+        let param_types = callee
+            .and_then(|f| self.foreign_fns.get(f).cloned())
+            .or_else(|| Some(params.as_ref()?.iter().map(|p| p.ty.clone()).collect()));
+        let writes_first =
+            is_mem_fn(&call.func) || ptr_access(&call.func) == Some(PointerAccess::Write);
+        let passes_raw =
+            is_mem_fn(&call.func) || ptr_access(&call.func).is_some() || param_types.is_some();
+        let plan = params
+            .as_ref()
+            .map(|params| self.slice_plan(params))
+            .unwrap_or_default();
+        let dropped: HashSet<usize> = plan
+            .values()
+            .filter_map(|length| match length {
+                SliceLength::Param(len) => Some(*len),
+                SliceLength::FromRawParts(_) => None,
+            })
+            .collect();
+        let args: Vec<Expr> = std::mem::take(&mut call.args).into_iter().collect();
+        for (index, mut arg) in args.iter().cloned().enumerate() {
+            if dropped.contains(&index) {
+                continue;
+            }
+            // The parameter of a function of the file given a safe type.
+            let safe_param = params
+                .as_ref()
+                .and_then(|params| params.get(index)?.id)
+                .filter(|&id| self.mapped_type(id).is_some())
+                .filter(|_| !matches!(plan.get(&index), Some(SliceLength::FromRawParts(_))));
+            match safe_param {
+                Some(param) => {
+                    let length = match plan.get(&index) {
+                        Some(SliceLength::Param(len)) => {
+                            let ty = &params.as_ref().unwrap()[*len].ty;
+                            Some((args[*len].clone(), ty.clone()))
+                        }
+                        _ => None,
+                    };
+                    self.visit_safe_arg_mut(&mut arg, param, length);
+                }
+                None => {
+                    let param = param_types.as_ref().and_then(|params| params.get(index));
+                    let writes = (index == 0 && writes_first)
+                        || matches!(
+                            param,
+                            Some(Type::Ptr(TypePtr {
+                                mutability: Some(_),
+                                ..
+                            }))
+                        );
+                    self.visit_raw_arg_mut(&mut arg, passes_raw, writes, &name);
+                }
+            }
+            call.args.push(arg);
+        }
+        match call.func.as_ref() {
+            // S::f(p)
+            Expr::Path(ExprPath { path, .. }) if path.segments.len() > 1 => {
+                self.visit_path_use(&call.func)
+            }
+            Expr::Path(_) => {}
+            _ => self.visit_expr_mut(&mut call.func),
+        }
+    }
+
+    /// Returns the offset `offset` of a pointer as the index of a slice, a `usize`.
+    fn index(&self, offset: &Expr) -> Expr {
+        let offset = strip_casts(offset);
+        match path_ident(offset).and_then(|ident| self.local_types.get(ident)) {
+            Some(ty) if primitive_name(ty).as_deref() == Some("usize") => offset.clone(),
+            _ => as_usize(offset),
+        }
+    }
+
+    /// Keeps raw the pointer a path names, used other than through a dereference,
+    /// or the parameters of the function it names other than as the callee of a
+    /// call, as in `qsort(.., compare)` or `S::f`: only direct calls are rewritten.
+    fn visit_path_use(&mut self, expr: &Expr) {
+        let Expr::Path(ExprPath { path, .. }) = expr else {
+            return;
+        };
+        if let Some((id, _)) = self.mapped(expr) {
+            self.reject(id, "is used other than through a dereference");
+        }
+        let mut rejected = Vec::new();
+        if let Some(function) = path_ident(expr).filter(|f| !self.scopes.is_local(f)) {
+            if let Some(params) = self.fn_params.get(function) {
+                let reason =
+                    format!("is a parameter of `{function}`, which is used other than by a call");
+                rejected.extend(
+                    params
+                        .iter()
+                        .filter_map(|param| Some((param.id?, reason.clone()))),
+                );
+            }
+        }
+        if let (Some(method), true) = (path.segments.last(), path.segments.len() > 1) {
+            if let Some(ids) = self.method_params.get(&method.ident) {
+                let reason = format!(
+                    "is a parameter of `{}`, whose calls are not rewritten",
+                    method.ident
+                );
+                rejected.extend(ids.iter().map(|&id| (id, reason.clone())));
+            }
+        }
+        for (id, reason) in rejected {
+            self.reject(id, reason);
+        }
+    }
+
+    /// Rewrites the argument `arg` of a call to `callee`, which takes a raw pointer
+    /// and may write through it if `writes`, into the raw pointer it stands for, if
+    /// it is a pointer mapped to a safe type and the callee `passes_raw`, only
+    /// accessing the pointee. Otherwise such a pointer is kept raw.
+    fn visit_raw_arg_mut(&mut self, arg: &mut Expr, passes_raw: bool, writes: bool, callee: &str) {
+        let uncast = matches!(strip_parens(arg), Expr::Path(_));
+        let operand = cast_operand_mut(arg);
+        let Some((id, rust_type)) = self.mapped(operand) else {
+            self.visit_expr_mut(arg);
+            return;
+        };
+        let writable = matches!(
+            rust_type,
+            RustPointerType::MutableReference
+                | RustPointerType::CellReference
+                | RustPointerType::MutableSlice
+        );
+        let raw = if !passes_raw || (writes && !writable) {
+            None
+        } else if uncast
+            && matches!(
+                rust_type,
+                RustPointerType::ImmutableReference | RustPointerType::MutableReference
+            )
+        {
+            // A reference coerces to the raw pointer the callee takes.
+            Some(operand.clone())
+        } else {
+            self.as_raw(operand)
+        };
+        match raw {
+            Some(raw) => *operand = raw,
+            None => self.reject(id, format!("is passed to `{callee}`")),
+        }
+    }
+
+    /// Rewrites the argument `arg` of a call to a function of the file into a value
+    /// of the safe type its parameter `param` is mapped to, as by [Self::safe_arg],
+    /// with the given length and its type if the parameter is a slice whose length
+    /// parameter is dropped. If it cannot be, the parameter is kept raw.
+    fn visit_safe_arg_mut(
+        &mut self,
+        arg: &mut Expr,
+        param: PointerId,
+        length: Option<(Expr, Type)>,
+    ) {
+        let length = length.map(|(mut len, ty)| {
+            self.visit_expr_mut(&mut len);
+            len_as_usize(&len, Some(&ty))
+        });
+        match self.safe_arg(arg, param, length) {
+            Some(value) => *arg = value,
+            None => {
+                if self.mapped(arg).is_none() {
+                    self.visit_expr_mut(arg);
+                }
+                let caller = self
+                    .current_fn
+                    .as_ref()
+                    .map_or("a caller".to_string(), |f| format!("`{f}`"));
+                self.reject(
+                    param,
+                    format!("is passed an argument in {caller} that cannot be borrowed as its safe type"),
+                );
+            }
+        }
+    }
+
+    /// Returns the value of the safe type the parameter `param` is mapped to that
+    /// the argument `arg` stands for, with `length` elements if it is a slice: a
+    /// pointer of the caller mapped to a type it can be borrowed as, a borrow like
+    /// `&mut x as *mut T`, the pointer `x.as_mut_ptr()` to a local array or `Vec`,
+    /// `None` for a null pointer, or, where raw pointers may be dereferenced, any
+    /// other raw pointer.
+    fn safe_arg(&mut self, arg: &Expr, param: PointerId, length: Option<Expr>) -> Option<Expr> {
+        use RustPointerType::*;
+        let rust_type = self.mapped_type(param)?;
+        let mutable = self.pointers[&param].1.mutability.is_some();
+        let nullable = self.nullable.contains(&param);
+        // f(null_mut())
+        if is_null(arg) || is_int_lit(strip_casts(arg), 0) {
+            return nullable.then(|| parse_quote!(None));
+        }
+        let value: Expr = if let Some((source, source_type)) = self.mapped(arg) {
+            let q = strip_parens(arg).clone();
+            if self.nullable.contains(&source) {
+                let copied = nullable
+                    && source_type == rust_type
+                    && matches!(rust_type, ImmutableReference | CellReference);
+                return copied.then_some(q);
+            }
+            match (source_type, rust_type, length) {
+                (source_type, _, None)
+                    if source_type == rust_type
+                        && matches!(
+                            rust_type,
+                            ImmutableReference | CellReference | ImmutableSlice
+                        ) =>
+                {
+                    q
+                }
+                (MutableReference, ImmutableReference, None)
+                | (MutableSlice, ImmutableSlice, None) => parse_quote!(&*#q),
+                (MutableReference, MutableReference, None) | (MutableSlice, MutableSlice, None) => {
+                    parse_quote!(&mut *#q)
+                }
+                (MutableReference, CellReference, None) => {
+                    parse_quote!(std::cell::Cell::from_mut(&mut *#q))
+                }
+                (ImmutableSlice | MutableSlice, ImmutableSlice, Some(len)) => {
+                    parse_quote!(&#q[..#len])
+                }
+                (MutableSlice, MutableSlice, Some(len)) => parse_quote!(&mut #q[..#len]),
+                _ => return None,
+            }
+        } else {
+            match strip_casts(arg) {
+                // f(&mut x as *mut T)
+                Expr::Reference(reference) if rust_type.is_reference() && length.is_none() => {
+                    let mut reference = reference.clone();
+                    if reference.mutability.is_none() && rust_type != ImmutableReference {
+                        return None;
+                    }
+                    self.visit_expr_mut(&mut reference.expr);
+                    if rust_type == ImmutableReference {
+                        reference.mutability = None;
+                    }
+                    match rust_type {
+                        CellReference => parse_quote!(std::cell::Cell::from_mut(#reference)),
+                        _ => Expr::Reference(reference),
+                    }
+                }
+                // f(x.as_mut_ptr())
+                Expr::MethodCall(ExprMethodCall {
+                    receiver,
+                    method,
+                    args,
+                    ..
+                }) if args.is_empty()
+                    && (method == "as_ptr" || method == "as_mut_ptr")
+                    && path_ident(receiver)
+                        .and_then(|x| self.local_types.get(x))
+                        .is_some_and(is_array_or_vec) =>
+                {
+                    let unique = method == "as_mut_ptr";
+                    match (rust_type, length) {
+                        (ImmutableSlice, Some(len)) => parse_quote!(&#receiver[..#len]),
+                        (ImmutableSlice, None) => parse_quote!(&#receiver),
+                        (MutableSlice, Some(len)) if unique => parse_quote!(&mut #receiver[..#len]),
+                        (MutableSlice, None) if unique => parse_quote!(&mut #receiver),
+                        (ImmutableReference, None) => parse_quote!(&#receiver[0]),
+                        (MutableReference, None) if unique => parse_quote!(&mut #receiver[0]),
+                        _ => return None,
+                    }
+                }
+                // f(q), for a raw pointer q
+                raw if self.unsafe_context && self.mapped(raw).is_none() => {
+                    let mut raw = parenthesized(arg);
+                    self.visit_expr_mut(&mut raw);
+                    match (rust_type, length) {
+                        (ImmutableReference, None) if nullable => {
+                            return Some(parse_quote!(#raw.as_ref()))
+                        }
+                        (MutableReference, None) if nullable && mutable => {
+                            return Some(parse_quote!(#raw.as_mut()))
+                        }
+                        (ImmutableReference, None) => parse_quote!(&*#raw),
+                        (MutableReference, None) if mutable => parse_quote!(&mut *#raw),
+                        (CellReference, None) if mutable && !nullable => {
+                            parse_quote!(std::cell::Cell::from_mut(&mut *#raw))
+                        }
+                        (ImmutableSlice, Some(len)) => {
+                            parse_quote!(std::slice::from_raw_parts(#raw, #len))
+                        }
+                        (MutableSlice, Some(len)) if mutable => {
+                            parse_quote!(std::slice::from_raw_parts_mut(#raw, #len))
+                        }
+                        _ => return None,
+                    }
+                }
+                _ => return None,
+            }
+        };
+        Some(if nullable {
+            parse_quote!(Some(#value))
+        } else {
+            value
+        })
+    }
+
+    /// Visits the value `init` the pointer variable `target`, mapped to a safe type,
+    /// is initialized or assigned with, rewriting it into one of that type: a borrow
+    /// like `&mut x as *mut T`, stripped of its casts, a pointer mapped to the same
+    /// shared type, or an allocation, possibly by a function returning it as the same
+    /// owning type. With any other value, `target` is kept raw.
+    fn visit_pointer_init_mut(&mut self, target: (PointerId, RustPointerType), init: &mut Expr) {
+        let (id, rust_type) = target;
+        let elem = self.pointers[&id].1.elem.clone();
+        // let q = p;
+        if let Some((source, source_type)) = self.mapped(init) {
+            let shared = matches!(
+                rust_type,
+                RustPointerType::ImmutableReference
+                    | RustPointerType::CellReference
+                    | RustPointerType::ImmutableSlice
+            );
+            if !(shared
+                && source_type == rust_type
+                && same_tokens(&self.pointers[&source].1.elem, &elem))
+            {
+                self.reject(id, "is assigned a pointer of another type");
+                self.reject(source, "is assigned to a pointer of another type");
+            }
+            return;
+        }
+        let borrow = match strip_casts(init) {
+            Expr::Reference(reference) => Some(reference.clone()),
+            _ => None,
+        };
+        match (borrow, rust_type) {
+            // let q = &mut x as *mut T;
+            (Some(mut borrow), RustPointerType::ImmutableReference)
+            | (
+                Some(mut borrow @ ExprReference {
+                    mutability: Some(_),
                     ..
                 }),
-                Type::Ptr(pointer),
+                RustPointerType::MutableReference | RustPointerType::CellReference,
             ) => {
-                self.pointers
-                    .insert(ident.clone(), (pointer.clone(), HashSet::new()));
+                self.visit_expr_mut(&mut borrow.expr);
+                *init = if rust_type == RustPointerType::CellReference {
+                    parse_quote!(std::cell::Cell::from_mut(#borrow))
+                } else {
+                    Expr::Reference(borrow)
+                };
+            }
+            // let q = make();
+            _ if matches!(strip_parens(init), Expr::Call(ExprCall { func, .. })
+                if path_ident(func).is_some_and(|f| self.return_types.get(f) == Some(&rust_type))) =>
+            {
+                if let Expr::Call(ExprCall { args, .. }) = strip_parens_mut(init) {
+                    args.iter_mut().for_each(|arg| self.visit_expr_mut(arg));
+                }
+            }
+            // let q = malloc(size_of::<T>()) as *mut T;
+            (_, RustPointerType::UniquePointer | RustPointerType::UniqueSlicePointer)
+                if allocation(strip_parens(init))
+                    .is_some_and(|a| matches!(a, Allocation::Single(e) | Allocation::Array(e, _) if same_tokens(&e, &elem))) =>
+            {
+                replace_allocation(init, rust_type.is_slice())
+            }
+            _ => {
+                self.reject(id, "is assigned a value other than a borrow or an allocation");
+                self.visit_expr_mut(init)
             }
-            _ => {}
         }
     }
 
-    fn identify_raw_pointer_args(&mut self, ast: &mut File) {
+    fn identify_raw_pointer_args(&mut self, ast: &mut File) -> Result<(), PassError> {
         self.visit_file(ast);
-        // // TODO(eyoon): delete debug log
-        // println!("{:?}", &self.pointers.iter().map(|(id, (_, set))| (id.to_string(), set))
-        //         .collect::<Vec<_>>()
-        // );
+
+        // Pointers are unique unless they might be aliased.
+        let field_names: HashSet<Ident> = self.fields.keys().map(|(_, f)| f.clone()).collect();
+        let mut aliases = AliasFinder {
+            scopes: Scopes::default(),
+            fields: &field_names,
+            aliased: HashSet::new(),
+            aliased_fields: HashSet::new(),
+        };
+        aliases.visit_file(ast);
+        let (aliased, aliased_fields) = (aliases.aliased, aliases.aliased_fields);
+        for (id, (_, _, access_set)) in &mut self.pointers {
+            if !aliased.contains(id) {
+                access_set.insert(PointerAccess::Unique);
+            }
+        }
+        for key in self.fields.keys() {
+            if !aliased_fields.contains(&key.1) {
+                let access_set = self.field_accesses.entry(key.clone()).or_default();
+                access_set.insert(PointerAccess::Unique);
+            }
+        }
 
         // Advance state from 'Uninitialized' to 'Computing'
         match self.types {
             TypeMappingStateMachine::Uninitialized => {
-                self.types = TypeMappingStateMachine::Computing(HashMap::new())
+                self.types = TypeMappingStateMachine::Computing(HashMap::new());
+                Ok(())
             }
-            _ => panic!("Must be in Uninitialized state"),
+            _ => Err(PassError::Unsupported(
+                "pointer types must be identified in the Uninitialized state".to_string(),
+            )),
         }
     }
 
-    fn compute_equivalent_safe_types(&mut self) {
-        // TODO: compute type equivalents
-
+    /// Maps each pointer to the safe type its access permissions call for. Pointers
+    /// whose permissions have no safe equivalent, or that are never dereferenced, are
+    /// left out of the map, and so remain raw pointers.
+    fn compute_equivalent_safe_types(&mut self) -> Result<(), PassError> {
         // Advance state from `Computing` to `Initialized`.
         let old_state = std::mem::replace(&mut self.types, TypeMappingStateMachine::Uninitialized);
         match old_state {
-            TypeMappingStateMachine::Computing(map) => {
-                self.types = TypeMappingStateMachine::Initialized(map)
+            TypeMappingStateMachine::Computing(mut map) => {
+                let mut pointers: Vec<_> = self.pointers.iter().collect();
+                pointers.sort_by_key(|(&id, _)| id);
+                for (&id, (ident, pointer, access_set)) in pointers {
+                    if self.sliced.contains(&id) {
+                        continue;
+                    }
+                    let permissions: Vec<PointerAccess> = access_set.iter().copied().collect();
+                    let mutable = pointer.mutability.is_some();
+                    if !mutable && access_set.contains(&PointerAccess::Write) {
+                        self.diagnostics.push(Diagnostic::new(
+                            PASS,
+                            None,
+                            format!(
+                                "`{ident}` is written through but declared `*const`; keeping it \
+                                 immutable"
+                            ),
+                        ));
+                    }
+                    let used = [
+                        PointerAccess::Read,
+                        PointerAccess::Write,
+                        PointerAccess::Free,
+                    ];
+                    if !used.iter().any(|access| access_set.contains(access)) {
+                        self.diagnostics.push(Diagnostic::new(
+                            PASS,
+                            self.declared_in.get(&id),
+                            format!(
+                                "`{ident}` is never dereferenced, only passed around; keeping \
+                                 it a raw pointer"
+                            ),
+                        ));
+                        continue;
+                    }
+                    let mut rust_type = PointerAccess::determine_rust_type(&permissions, mutable);
+                    if rust_type == RustPointerType::CellReference {
+                        let reason = if self.written_in_place.contains(&id) {
+                            Some("is written in place, which a `Cell` cannot be")
+                        } else if !self.is_copy(&pointer.elem) {
+                            Some("points to a value that is not `Copy`, which a `Cell` cannot get")
+                        } else {
+                            None
+                        };
+                        if let Some(reason) = reason {
+                            rust_type = RustPointerType::Undefined;
+                            self.diagnostics.push(Diagnostic::new(
+                                PASS,
+                                self.declared_in.get(&id),
+                                format!("`{ident}` {reason}; keeping it a raw pointer"),
+                            ));
+                        }
+                    }
+                    if let Some(&checks) = self.null_checks.get(&id) {
+                        let guarded = self.null_guards.get(&id) == Some(&checks);
+                        if guarded && rust_type.is_reference() {
+                            self.nullable.insert(id);
+                        } else if rust_type != RustPointerType::Undefined {
+                            rust_type = RustPointerType::Undefined;
+                            self.diagnostics.push(Diagnostic::new(
+                                PASS,
+                                None,
+                                format!(
+                                    "`{ident}` is compared with null other than in an early \
+                                     return guard; keeping it a raw pointer"
+                                ),
+                            ));
+                        }
+                    }
+                    match rust_type {
+                        RustPointerType::Undefined => {}
+                        rust_type => {
+                            map.insert(id, rust_type);
+                        }
+                    }
+                }
+                self.types = TypeMappingStateMachine::Initialized(map);
+                for (ident, (pointer, access_set)) in &self.returns {
+                    let permissions: Vec<PointerAccess> = access_set.iter().copied().collect();
+                    let mutable = pointer.mutability.is_some();
+                    match PointerAccess::determine_rust_type(&permissions, mutable) {
+                        RustPointerType::Undefined => {}
+                        rust_type => {
+                            self.return_types.insert(ident.clone(), rust_type);
+                        }
+                    }
+                }
+                self.compute_field_types();
+                Ok(())
             }
             _ => {
                 let _ = std::mem::replace(&mut self.types, old_state);
-                panic!("Must be in Computing state")
+                Err(PassError::Unsupported(
+                    "safe types must be computed in the Computing state".to_string(),
+                ))
             }
         }
     }
 }
 
+impl RawPointerSanitizer {
+    /// Maps each pointer field to the safe type its access permissions call for, if
+    /// it owns its pointee. A borrowing field would need a lifetime parameter on its
+    /// struct, and an owning one cannot be a field of a struct deriving `Copy`, so
+    /// those are reported instead.
+    fn compute_field_types(&mut self) {
+        let mut fields: Vec<_> = self.fields.iter().collect();
+        fields.sort_by_key(|((name, field), _)| (name.to_string(), field.to_string()));
+        for (key, (pointer, copy)) in fields {
+            let (name, field) = key;
+            let reason = if self.unresolved_fields.contains(field) {
+                "is accessed through a value whose struct is not known".to_string()
+            } else {
+                let permissions: Vec<PointerAccess> = self
+                    .field_accesses
+                    .get(key)
+                    .map(|access_set| access_set.iter().copied().collect())
+                    .unwrap_or_default();
+                let mutable = pointer.mutability.is_some();
+                match PointerAccess::determine_rust_type(&permissions, mutable) {
+                    RustPointerType::Undefined => continue,
+                    RustPointerType::UniquePointer | RustPointerType::UniqueSlicePointer
+                        if *copy =>
+                    {
+                        format!("would own its pointee, but `{name}` derives `Copy`")
+                    }
+                    rust_type @ (RustPointerType::UniquePointer
+                    | RustPointerType::UniqueSlicePointer) => {
+                        self.field_types.insert(key.clone(), rust_type);
+                        continue;
+                    }
+                    _ => format!("only borrows its pointee, which needs a lifetime on `{name}`"),
+                }
+            };
+            self.diagnostics.push(Diagnostic::new(
+                PASS,
+                None,
+                format!("field `{field}` of `{name}` {reason}; keeping it a raw pointer"),
+            ));
+        }
+    }
+}
+
 impl Visit<'_> for RawPointerSanitizer {
+    /// Records the parameter types of the functions declared in `extern` blocks,
+    /// whose signatures must keep their C types.
+    fn visit_item_foreign_mod(&mut self, item: &ItemForeignMod) {
+        for foreign in &item.items {
+            if let ForeignItem::Fn(ForeignItemFn { sig, .. }) = foreign {
+                let params = sig
+                    .inputs
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        FnArg::Typed(PatType { ty, .. }) => Some(ty.as_ref().clone()),
+                        FnArg::Receiver(_) => None,
+                    })
+                    .collect();
+                self.foreign_fns.insert(sig.ident.clone(), params);
+            }
+        }
+    }
+
+    /// Records the raw pointer fields of the struct.
+    fn visit_item_struct(&mut self, item: &'_ ItemStruct) {
+        let copy = derives_copy(item);
+        if copy {
+            self.copy_structs.insert(item.ident.clone());
+        }
+        for field in &item.fields {
+            if let (Some(ident), Type::Ptr(pointer)) = (&field.ident, &field.ty) {
+                let key = (item.ident.clone(), ident.clone());
+                self.fields.insert(key, (pointer.clone(), copy));
+            }
+        }
+        syn::visit::visit_item_struct(self, item)
+    }
+
+    /// Visits an impl block knowing the struct `self` is.
+    fn visit_item_impl(&mut self, item: &'_ ItemImpl) {
+        let self_type = std::mem::replace(&mut self.self_type, struct_name(&item.self_ty).cloned());
+        syn::visit::visit_item_impl(self, item);
+        self.self_type = self_type;
+    }
+
+    /// Notes the fields accessed through a value of unknown struct.
+    fn visit_expr_field(&mut self, field: &'_ ExprField) {
+        if let Member::Named(ident) = &field.member {
+            if self.field_key(field).is_none() {
+                self.unresolved_fields.insert(ident.clone());
+            }
+        }
+        syn::visit::visit_expr_field(self, field)
+    }
+
     /// Inspects a function argument and adds it to the `pointers` map if it is a
     /// raw pointer type.
     fn visit_fn_arg(&mut self, arg: &FnArg) {
         if let FnArg::Typed(PatType { pat, ty, .. }) = arg {
-            self.record_if_pointer(&**pat, &**ty)
+            self.record_declaration(pat, Some(ty));
+            self.record_if_struct(pat, ty);
         }
         syn::visit::visit_fn_arg(self, arg)
     }
 
     /// Inspects a local variable declaration and adds it to the `pointers` map if it
-    /// is a raw pointer type declaration.
+    /// is a raw pointer type declaration. The variable is declared after its
+    /// initializer, which sees any variable it shadows.
     fn visit_local(&mut self, assignment: &Local) {
-        if let Pat::Type(PatType { pat, ty, .. }) = &assignment.pat {
-            self.record_if_pointer(&**pat, &**ty)
+        if let Some(init) = &assignment.init {
+            self.visit_local_init(init);
+        }
+        let (pat, ty) = local_binding(&assignment.pat);
+        self.record_declaration(pat, ty);
+        if let Some(ty) = ty {
+            self.record_if_struct(pat, ty);
         }
-        syn::visit::visit_local(self, assignment)
+    }
+
+    /// Visits a block in a scope of its own.
+    fn visit_block(&mut self, block: &'_ Block) {
+        self.scopes.push();
+        syn::visit::visit_block(self, block);
+        self.scopes.pop();
     }
 
     /// Inspects assignment instructions for lvalue pointer writes, updating the access
@@ -181,95 +1540,1307 @@ impl Visit<'_> for RawPointerSanitizer {
     /// - Raw pointer variables are not reassigned to a different pointer when in lvalue
     ///   expressions.
     fn visit_expr_assign(&mut self, assign: &'_ ExprAssign) {
-        fn access_set_if_pointer_access<'expr, 'vis>(
-            input_expr: &'expr Box<Expr>,
-            pointers: &'vis mut HashMap<Ident, (TypePtr, HashSet<PointerAccess>)>,
-        ) -> Option<&'vis mut HashSet<PointerAccess>> {
-            match input_expr.as_ref() {
-                Expr::MethodCall(ExprMethodCall {
-                    method: _,
-                    receiver,
-                    ..
-                }) => access_set_if_raw_ptr(receiver, pointers),
-                _ => None,
-            }
+        self.record_if_written(&assign.left);
+        // `*p = v` only writes the pointee, unlike `*p += v`.
+        match expr_if_unary_deref(strip_parens(&assign.left)) {
+            Some(pointer) => self.visit_expr(pointer),
+            None => self.visit_expr(&assign.left),
         }
+        self.visit_expr(&assign.right)
+    }
 
-        // Identify lvalue raw pointer accesses.
-        expr_if_unary_deref(&assign.left).map(|expr| {
-            access_set_if_pointer_access(expr, &mut self.pointers).map(|access_set| {
-                // *p = ...
-                access_set.insert(PointerAccess::Write);
-            })
-        });
+    /// Inspects mutable borrows like `&mut *p` or `&mut (*p).f`, which may write
+    /// through the pointer.
+    fn visit_expr_reference(&mut self, reference: &'_ ExprReference) {
+        if reference.mutability.is_some() {
+            self.record_if_written(&reference.expr);
+            self.record_if_written_in_place(&reference.expr);
+        }
+        syn::visit::visit_expr_reference(self, reference)
+    }
 
-        syn::visit::visit_expr_assign(self, assign)
+    /// Inspects rvalue dereferences like `let v = *p;`, which read the pointee.
+    fn visit_expr_unary(&mut self, unary: &'_ ExprUnary) {
+        self.record_deref(&Expr::Unary(unary.clone()), PointerAccess::Read);
+        syn::visit::visit_expr_unary(self, unary)
+    }
+
+    /// Counts the null guards on the function's pointer arguments, and records the
+    /// pointer it returns.
+    fn visit_item_fn(&mut self, item_fn: &'_ ItemFn) {
+        let outer = self.scopes.enter_fn();
+        let enclosing = self.current_fn.replace(item_fn.sig.ident.clone());
+        self.visit_signature(&item_fn.sig);
+        self.record_null_guards(&item_fn.sig, &item_fn.block);
+        self.record_returned_pointer(&item_fn.sig, &item_fn.block);
+        let params = self.params(&item_fn.sig, &item_fn.block);
+        self.fn_params.insert(item_fn.sig.ident.clone(), params);
+        self.visit_block(&item_fn.block);
+        self.current_fn = enclosing;
+        self.scopes.exit_fn(outer);
+    }
+
+    /// Counts the null guards on the method's pointer arguments, and records the
+    /// pointer it returns.
+    fn visit_impl_item_fn(&mut self, item_fn: &'_ ImplItemFn) {
+        let outer = self.scopes.enter_fn();
+        let enclosing = self.current_fn.replace(item_fn.sig.ident.clone());
+        self.visit_signature(&item_fn.sig);
+        self.record_null_guards(&item_fn.sig, &item_fn.block);
+        self.record_returned_pointer(&item_fn.sig, &item_fn.block);
+        let params = self.params(&item_fn.sig, &item_fn.block);
+        let ids = params.iter().filter_map(|param| param.id);
+        self.method_params
+            .entry(item_fn.sig.ident.clone())
+            .or_default()
+            .extend(ids);
+        self.visit_block(&item_fn.block);
+        self.current_fn = enclosing;
+        self.scopes.exit_fn(outer);
+    }
+
+    /// Visits a trait method in a scope of its own.
+    fn visit_trait_item_fn(&mut self, item_fn: &'_ TraitItemFn) {
+        let outer = self.scopes.enter_fn();
+        syn::visit::visit_trait_item_fn(self, item_fn);
+        self.scopes.exit_fn(outer);
+    }
+
+    /// Counts the comparisons of pointers with null.
+    fn visit_expr(&mut self, expr: &'_ Expr) {
+        if let Some(id) = null_checked(expr).and_then(|pointer| self.scopes.resolve(pointer)) {
+            *self.null_checks.entry(id).or_default() += 1;
+        }
+        syn::visit::visit_expr(self, expr)
+    }
+
+    /// Inspects compound assignments like `*p += 1` for lvalue pointer writes.
+    fn visit_expr_binary(&mut self, binary: &'_ ExprBinary) {
+        if is_assign_op(&binary.op) {
+            self.record_if_written(&binary.left);
+        }
+        syn::visit::visit_expr_binary(self, binary)
+    }
+
+    /// Visits a `while i < n` loop knowing its counter `i` stays below `n`.
+    fn visit_expr_while(&mut self, while_loop: &'_ ExprWhile) {
+        if let Expr::Binary(ExprBinary {
+            left,
+            op: BinOp::Lt(_),
+            right,
+            ..
+        }) = strip_parens(&while_loop.cond)
+        {
+            if let Some(counter) = path_ident(left) {
+                let bound = strip_parens(right).clone();
+                self.loop_bounds.push((counter.clone(), bound));
+                syn::visit::visit_expr_while(self, while_loop);
+                self.loop_bounds.pop();
+                return;
+            }
+        }
+        syn::visit::visit_expr_while(self, while_loop)
+    }
+
+    /// Visits a `for i in a..n` loop knowing its counter `i` stays below `n`.
+    fn visit_expr_for_loop(&mut self, for_loop: &'_ ExprForLoop) {
+        if let (
+            Pat::Ident(PatIdent { ident, .. }),
+            Expr::Range(ExprRange {
+                limits: RangeLimits::HalfOpen(_),
+                end: Some(end),
+                ..
+            }),
+        ) = (for_loop.pat.as_ref(), strip_parens(&for_loop.expr))
+        {
+            let bound = strip_parens(end).clone();
+            self.loop_bounds.push((ident.clone(), bound));
+            syn::visit::visit_expr_for_loop(self, for_loop);
+            self.loop_bounds.pop();
+            return;
+        }
+        syn::visit::visit_expr_for_loop(self, for_loop)
     }
 
     /// Inspects method calls, updating the pointer access map if a raw pointer
-    /// offset access is identified.
+    /// offset access is identified, whether the offset pointer is read, as in
+    /// `let v = *p.add(i);`, or written, as in `*p.add(i) = v;`.
     fn visit_expr_method_call(&mut self, i: &'_ ExprMethodCall) {
         let ExprMethodCall {
-            method, receiver, ..
+            method,
+            receiver,
+            args,
+            ..
         } = i;
 
-        access_set_if_raw_ptr(receiver, &mut self.pointers).map(|access_set| {
-            if is_offset(method) {
-                access_set.insert(PointerAccess::Offset);
+        if let Some(access_set) = self.access_set(receiver) {
+            if let Some(access) = offset_access(method, args.first()) {
+                access_set.insert(access);
+                if let Some(id) = self.pointer_id(receiver) {
+                    self.record_if_loop_bounded(id, args.first());
+                }
             }
-        });
+        }
 
         syn::visit::visit_expr_method_call(self, i)
     }
+
+    /// Inspects function calls, updating the pointer access map if a raw pointer is
+    /// passed to a deallocation function, read or written through `ptr::read` and
+    /// `ptr::write` and their variants, or copied to or from by `memcpy` and the
+    /// like.
+    fn visit_expr_call(&mut self, i: &'_ ExprCall) {
+        if let (
+            Expr::Path(ExprPath {
+                qself: None, path, ..
+            }),
+            Some(arg),
+        ) = (i.func.as_ref(), i.args.first())
+        {
+            if is_free(path) {
+                // free(p), or free(p as *mut c_void)
+                let arg = match strip_parens(arg) {
+                    Expr::Cast(ExprCast { expr, .. }) => strip_parens(expr),
+                    arg => arg,
+                };
+                if let Some(access_set) = self.access_set(arg) {
+                    access_set.insert(PointerAccess::Free);
+                }
+                // free((*s).f) takes the field out of `*s`.
+                if let Expr::Field(_) = arg {
+                    self.record_if_written(arg);
+                }
+            }
+            // memcpy(p, q, n * size_of::<T>())
+            if is_mem_fn(&i.func) && i.args.len() == 3 {
+                self.record_mem_access(&i.args[0], &i.args[2], PointerAccess::Write);
+                if callee_name(&i.func).as_deref() != Some("memset") {
+                    self.record_mem_access(&i.args[1], &i.args[2], PointerAccess::Read);
+                }
+            }
+            // ptr::read(p), ptr::write_volatile(p, x), ...
+            if let (Some(access), Some(access_set)) = (ptr_access(&i.func), self.access_set(arg)) {
+                access_set.insert(access);
+            }
+            if matches!(
+                callee_name(&i.func).as_deref(),
+                Some("from_raw_parts" | "from_raw_parts_mut")
+            ) {
+                if let Some(id) = self.pointer_id(arg) {
+                    self.sliced.insert(id);
+                }
+            }
+        }
+
+        syn::visit::visit_expr_call(self, i)
+    }
 }
 
 impl VisitMut for RawPointerSanitizer {
-    // TODO
+    /// Skips `extern` blocks, whose signatures must keep their C types.
+    fn visit_item_foreign_mod_mut(&mut self, _: &mut ItemForeignMod) {}
+
+    /// Replaces the type of a raw pointer function argument with its memory safe
+    /// equivalent, if one was computed.
+    fn visit_fn_arg_mut(&mut self, arg: &mut FnArg) {
+        if let FnArg::Typed(PatType { pat, ty, .. }) = arg {
+            let id = self.scopes.declare(pat, Some(ty));
+            self.replace_if_mapped(id, ty)
+        }
+        syn::visit_mut::visit_fn_arg_mut(self, arg)
+    }
+
+    /// Visits an impl block knowing the struct `self` is.
+    fn visit_item_impl_mut(&mut self, item: &mut ItemImpl) {
+        let self_type = std::mem::replace(&mut self.self_type, struct_name(&item.self_ty).cloned());
+        syn::visit_mut::visit_item_impl_mut(self, item);
+        self.self_type = self_type;
+    }
+
+    /// Replaces the types of the struct's raw pointer fields with their memory safe
+    /// equivalents, if computed.
+    fn visit_item_struct_mut(&mut self, item: &mut ItemStruct) {
+        for field in &mut item.fields {
+            let Some(ident) = &field.ident else {
+                continue;
+            };
+            let key = (item.ident.clone(), ident.clone());
+            let (Some((pointer, _)), Some(&rust_type)) =
+                (self.fields.get(&key), self.field_types.get(&key))
+            else {
+                continue;
+            };
+            if let Some(safe) = safe_type(rust_type, &pointer.elem) {
+                self.record_rewrite(rust_type, &field.ty, &safe);
+                field.ty = safe;
+            }
+        }
+        syn::visit_mut::visit_item_struct_mut(self, item)
+    }
+
+    /// Replaces the raw pointer return type of a function with its memory safe
+    /// equivalent, if one was computed.
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        self.replace_return_if_mapped(&mut item_fn.sig, &mut item_fn.block);
+        let outer = self.scopes.enter_fn();
+        let enclosing = self.current_fn.replace(item_fn.sig.ident.clone());
+        self.local_types = local_types(&item_fn.sig, &item_fn.block);
+        self.unsafe_context = item_fn.sig.unsafety.is_some();
+        self.visit_signature_mut(&mut item_fn.sig);
+        self.visit_block_mut(&mut item_fn.block);
+        self.bind_slice_lengths(&mut item_fn.sig, &mut item_fn.block);
+        self.current_fn = enclosing;
+        self.scopes.exit_fn(outer);
+    }
+
+    /// Replaces the raw pointer return type of a method with its memory safe
+    /// equivalent, if one was computed.
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        self.replace_return_if_mapped(&mut item_fn.sig, &mut item_fn.block);
+        let outer = self.scopes.enter_fn();
+        let enclosing = self.current_fn.replace(item_fn.sig.ident.clone());
+        self.local_types = local_types(&item_fn.sig, &item_fn.block);
+        self.unsafe_context = item_fn.sig.unsafety.is_some();
+        self.visit_signature_mut(&mut item_fn.sig);
+        self.visit_block_mut(&mut item_fn.block);
+        self.bind_slice_lengths(&mut item_fn.sig, &mut item_fn.block);
+        self.current_fn = enclosing;
+        self.scopes.exit_fn(outer);
+    }
+
+    /// Visits an unsafe block knowing raw pointers may be dereferenced in it.
+    fn visit_expr_unsafe_mut(&mut self, block: &mut ExprUnsafe) {
+        let unsafe_context = std::mem::replace(&mut self.unsafe_context, true);
+        syn::visit_mut::visit_expr_unsafe_mut(self, block);
+        self.unsafe_context = unsafe_context;
+    }
+
+    /// Visits a trait method in a scope of its own.
+    fn visit_trait_item_fn_mut(&mut self, item_fn: &mut TraitItemFn) {
+        let outer = self.scopes.enter_fn();
+        syn::visit_mut::visit_trait_item_fn_mut(self, item_fn);
+        self.scopes.exit_fn(outer);
+    }
+
+    /// Visits a block in a scope of its own, rewriting the null guards on pointers
+    /// mapped to an `Option`, e.g. `if p.is_null() { return; }` into
+    /// `let Some(p) = p else { return; };`.
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        self.scopes.push();
+        for stmt in &mut block.stmts {
+            self.visit_stmt_mut(stmt);
+            let Stmt::Expr(Expr::If(ExprIf { cond, .. }), _) = &*stmt else {
+                continue;
+            };
+            let Some(pointer) = null_checked(cond).cloned() else {
+                continue;
+            };
+            let nullable = self
+                .scopes
+                .resolve(&pointer)
+                .is_some_and(|id| self.nullable.contains(&id));
+            let Some(diverging) = null_guard(stmt, &pointer).filter(|_| nullable) else {
+                continue;
+            };
+            let diverging = diverging.clone();
+            *stmt = parse_quote!(let Some(#pointer) = #pointer else #diverging;);
+        }
+        self.scopes.pop();
+    }
+
+    /// Rewrites the accesses through pointers mapped to a `&Cell<T>` into calls to
+    /// its methods: `*p = x` into `p.set(x)`, `*p += x` into `p.set(p.get() + x)`, and
+    /// any other `*p` into `p.get()`.
+    ///
+    /// A pointer mapped to a safe type must only be dereferenced, checked for null if
+    /// it is an `Option`, passed to a function which only accesses its pointee, as by
+    /// [Self::visit_call_mut], or copied into a pointer of the same type. Any other
+    /// use, like returning it, keeps it raw.
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        // if p.is_null() { return; }
+        if null_checked(expr)
+            .and_then(|pointer| self.scopes.resolve(pointer))
+            .is_some_and(|id| self.nullable.contains(&id) && self.mapped_type(id).is_some())
+        {
+            return;
+        }
+        let cell = self.cell_deref(expr);
+        match expr {
+            Expr::Path(_) => {
+                self.visit_path_use(expr);
+                return;
+            }
+            Expr::Field(_) => {
+                if let Some((key, _)) = self.mapped_field(expr) {
+                    self.reject_field(key, "is used other than through a dereference");
+                }
+            }
+            Expr::Struct(ExprStruct {
+                path, fields, rest, ..
+            }) => {
+                let name = path.segments.last().map(|segment| segment.ident.clone());
+                for value in fields.iter_mut() {
+                    let (Some(name), Member::Named(field)) = (&name, &value.member) else {
+                        continue;
+                    };
+                    let key = (name.clone(), field.clone());
+                    if let Some(&rust_type) = self.field_types.get(&key) {
+                        self.visit_field_init_mut(key, rust_type, &mut value.expr);
+                    } else {
+                        self.visit_expr_mut(&mut value.expr);
+                    }
+                }
+                if let Some(rest) = rest {
+                    self.visit_expr_mut(rest);
+                }
+                return;
+            }
+            Expr::Return(ExprReturn {
+                expr: Some(value), ..
+            }) => {
+                if let Some((id, _)) = self.mapped(value) {
+                    self.reject(id, "is returned");
+                    return;
+                }
+            }
+            Expr::Call(_) => {
+                self.visit_call_mut(expr);
+                return;
+            }
+            Expr::MethodCall(ExprMethodCall { method, args, .. }) => {
+                for arg in args.iter() {
+                    if let Some((id, _)) = self.mapped(arg) {
+                        self.reject(id, format!("is passed to `{method}`"));
+                    }
+                }
+                if let Some(ids) = self.method_params.get(method).cloned() {
+                    let reason =
+                        format!("is a parameter of `{method}`, whose calls are not rewritten");
+                    ids.into_iter()
+                        .for_each(|id| self.reject(id, reason.clone()));
+                }
+            }
+            Expr::Paren(ExprParen { expr: inner, .. }) => {
+                self.visit_expr_mut(inner);
+                // (*p.add(i)).f, now (p[i]).f
+                if let Expr::Index(_) = inner.as_ref() {
+                    *expr = *inner.clone();
+                }
+                return;
+            }
+            Expr::Assign(ExprAssign { left, right, .. }) => {
+                if let Some(pointer) = self.cell_deref(left) {
+                    self.visit_expr_mut(right);
+                    *expr = parse_quote!(#pointer.set(#right));
+                    return;
+                }
+                // q = p;
+                if let Some(target) = self.mapped(left) {
+                    self.visit_pointer_init_mut(target, right);
+                    return;
+                }
+                // (*s).f = malloc(size_of::<T>()) as *mut T;
+                if let Some((key, rust_type)) = self.mapped_field(left) {
+                    if let Expr::Field(ExprField { base, .. }) = left.as_mut() {
+                        self.visit_expr_mut(base);
+                    }
+                    self.visit_field_init_mut(key, rust_type, right);
+                    return;
+                }
+            }
+            Expr::Binary(ExprBinary {
+                left, op, right, ..
+            }) => {
+                if let (Some(pointer), Some(op)) = (self.cell_deref(left), plain_op(op)) {
+                    self.visit_expr_mut(right);
+                    let mut value = Expr::Binary(ExprBinary {
+                        attrs: Vec::new(),
+                        left: Box::new(parse_quote!(#pointer.get())),
+                        op,
+                        right: right.clone(),
+                    });
+                    Parenthesize.visit_expr_mut(&mut value);
+                    *expr = parse_quote!(#pointer.set(#value));
+                    return;
+                }
+            }
+            Expr::Unary(ExprUnary {
+                op: UnOp::Deref(_),
+                expr: pointer,
+                ..
+            }) => {
+                if let Some(pointer) = cell {
+                    *expr = parse_quote!(#pointer.get());
+                    return;
+                }
+                // *(*s).f, or (*s).f[0] for a slice
+                if let Some((_, rust_type)) = self.mapped_field(pointer) {
+                    if let Expr::Field(ExprField { base, .. }) = strip_parens_mut(pointer) {
+                        self.visit_expr_mut(base);
+                    }
+                    if rust_type.is_slice() {
+                        let pointer = pointer.clone();
+                        *expr = parse_quote!(#pointer[0]);
+                    }
+                    return;
+                }
+                match pointer.as_mut() {
+                    // *p.add(i) -> p[i as usize]
+                    Expr::MethodCall(ExprMethodCall {
+                        receiver,
+                        method,
+                        args,
+                        ..
+                    }) if args.len() == 1 && offset_access(method, args.first()).is_some() => {
+                        // *(*s).f.add(i) -> (*s).f[i as usize]
+                        if let Some((key, _)) = self.mapped_field(receiver) {
+                            if offset_access(method, args.first()) == Some(PointerAccess::OffsetSub)
+                            {
+                                self.reject_field(key, "is offset backwards");
+                                return;
+                            }
+                            if let Expr::Field(ExprField { base, .. }) = strip_parens_mut(receiver)
+                            {
+                                self.visit_expr_mut(base);
+                            }
+                            self.visit_expr_mut(&mut args[0]);
+                            let (pointer, index) = (receiver.clone(), self.index(&args[0]));
+                            *expr = parse_quote!(#pointer[#index]);
+                            return;
+                        }
+                        if let Some((id, _)) = self.mapped(receiver) {
+                            if offset_access(method, args.first()) == Some(PointerAccess::OffsetSub)
+                            {
+                                self.reject(id, "is offset backwards");
+                                return;
+                            }
+                            self.visit_expr_mut(&mut args[0]);
+                            let (pointer, index) = (receiver.clone(), self.index(&args[0]));
+                            *expr = parse_quote!(#pointer[#index]);
+                            return;
+                        }
+                    }
+                    // *p -> p[0], for a slice
+                    pointer => {
+                        if let Some((_, rust_type)) = self.mapped(pointer) {
+                            if rust_type.is_slice() {
+                                let pointer = pointer.clone();
+                                *expr = parse_quote!(#pointer[0]);
+                            }
+                            return;
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        syn::visit_mut::visit_expr_mut(self, expr)
+    }
+
+    /// Replaces the type of a raw pointer local variable declaration with its memory
+    /// safe equivalent, if one was computed.
+    fn visit_local_mut(&mut self, assignment: &mut Local) {
+        let (pat, ty) = local_binding(&assignment.pat);
+        let target = self
+            .scopes
+            .peek(pat, ty)
+            .and_then(|id| Some((id, self.mapped_type(id)?)));
+        if let Some(init) = &mut assignment.init {
+            match target {
+                Some(target) => {
+                    self.visit_pointer_init_mut(target, &mut init.expr);
+                    if let Some((_, diverge)) = &mut init.diverge {
+                        self.visit_expr_mut(diverge);
+                    }
+                }
+                None => self.visit_local_init_mut(init),
+            }
+        }
+        match &mut assignment.pat {
+            Pat::Type(PatType { pat, ty, .. }) => {
+                let id = self.scopes.declare(pat, Some(ty));
+                self.replace_if_mapped(id, ty)
+            }
+            pat => {
+                self.scopes.declare(pat, None);
+            }
+        }
+    }
+}
+
+/// Finds the tracked pointers that might be aliased, and so are not unique: those
+/// assigned from or into another variable, returned, or passed by value to a
+/// function that could retain them. Pointer fields are told apart by name only.
+struct AliasFinder<'a> {
+    /// The variables in scope, numbered as by [RawPointerSanitizer].
+    scopes: Scopes,
+    /// The names of the tracked pointer fields.
+    fields: &'a HashSet<Ident>,
+    aliased: HashSet<PointerId>,
+    aliased_fields: HashSet<Ident>,
 }
 
-/// If the given receiver `p` exists in the pointer map, return a mutable reference
-/// to its access set pointers[p].1
-fn access_set_if_raw_ptr<'expr, 'vis>(
-    receiver: &'expr Box<Expr>,
-    pointers: &'vis mut HashMap<Ident, (TypePtr, HashSet<PointerAccess>)>,
-) -> Option<&'vis mut HashSet<PointerAccess>> {
-    match receiver.as_ref() {
-        Expr::Path(ExprPath { qself, path, .. }) => {
-            if qself.is_some() {
-                return None;
+impl AliasFinder<'_> {
+    /// Marks the pointer the expression evaluates to as aliased, if it is a tracked
+    /// pointer, possibly cast or offset. Returns whether it is one.
+    fn alias(&mut self, expr: &Expr) -> bool {
+        let expr = strip_casts(expr);
+        if let Expr::MethodCall(ExprMethodCall {
+            receiver,
+            method,
+            args,
+            ..
+        }) = expr
+        {
+            if offset_access(method, args.first()).is_some() {
+                return self.alias(receiver);
             }
-            let ident = &path.segments.last().unwrap().ident;
-            pointers.get_mut(ident).map(|(_, access_set)| access_set)
         }
+        if let Expr::Field(ExprField {
+            member: Member::Named(field),
+            ..
+        }) = expr
+        {
+            if self.fields.contains(field) {
+                self.aliased_fields.insert(field.clone());
+                return true;
+            }
+        }
+        match path_ident(expr).and_then(|ident| self.scopes.resolve(ident)) {
+            Some(id) => {
+                self.aliased.insert(id);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Visit<'_> for AliasFinder<'_> {
+    fn visit_item_foreign_mod(&mut self, _: &ItemForeignMod) {}
+
+    fn visit_item_fn(&mut self, item_fn: &'_ ItemFn) {
+        let outer = self.scopes.enter_fn();
+        syn::visit::visit_item_fn(self, item_fn);
+        self.scopes.exit_fn(outer);
+    }
+
+    fn visit_impl_item_fn(&mut self, item_fn: &'_ ImplItemFn) {
+        let outer = self.scopes.enter_fn();
+        syn::visit::visit_impl_item_fn(self, item_fn);
+        self.scopes.exit_fn(outer);
+    }
+
+    fn visit_trait_item_fn(&mut self, item_fn: &'_ TraitItemFn) {
+        let outer = self.scopes.enter_fn();
+        syn::visit::visit_trait_item_fn(self, item_fn);
+        self.scopes.exit_fn(outer);
+    }
+
+    fn visit_fn_arg(&mut self, arg: &'_ FnArg) {
+        if let FnArg::Typed(PatType { pat, ty, .. }) = arg {
+            self.scopes.declare(pat, Some(ty));
+        }
+        syn::visit::visit_fn_arg(self, arg)
+    }
+
+    /// let q = p;
+    fn visit_local(&mut self, local: &'_ Local) {
+        let mut assigned_alias = false;
+        if let Some(init) = &local.init {
+            assigned_alias = self.alias(&init.expr);
+            self.visit_local_init(init);
+        }
+        let (pat, ty) = local_binding(&local.pat);
+        if let Some(target) = self.scopes.declare(pat, ty).filter(|_| assigned_alias) {
+            self.aliased.insert(target);
+        }
+    }
+
+    /// q = p;
+    fn visit_expr_assign(&mut self, assign: &'_ ExprAssign) {
+        if self.alias(&assign.right) {
+            if let Some(target) = path_ident(&assign.left).and_then(|q| self.scopes.resolve(q)) {
+                self.aliased.insert(target);
+            }
+        }
+        syn::visit::visit_expr_assign(self, assign)
+    }
+
+    /// return p;
+    fn visit_expr_return(&mut self, ret: &'_ ExprReturn) {
+        if let Some(expr) = &ret.expr {
+            self.alias(expr);
+        }
+        syn::visit::visit_expr_return(self, ret)
+    }
+
+    /// fn f(p: *mut T) -> *mut T { p }
+    fn visit_block(&mut self, block: &'_ Block) {
+        self.scopes.push();
+        syn::visit::visit_block(self, block);
+        if let Some(Stmt::Expr(expr, None)) = block.stmts.last() {
+            self.alias(expr);
+        }
+        self.scopes.pop();
+    }
+
+    /// f(p), unless `f` frees `p` or only copies through it.
+    fn visit_expr_call(&mut self, call: &'_ ExprCall) {
+        let frees = matches!(
+            call.func.as_ref(),
+            Expr::Path(ExprPath { qself: None, path, .. }) if is_free(path)
+        );
+        if !frees && !is_mem_fn(&call.func) {
+            call.args.iter().for_each(|arg| {
+                self.alias(arg);
+            });
+        }
+        syn::visit::visit_expr_call(self, call)
+    }
+
+    /// x.f(p)
+    fn visit_expr_method_call(&mut self, call: &'_ ExprMethodCall) {
+        call.args.iter().for_each(|arg| {
+            self.alias(arg);
+        });
+        syn::visit::visit_expr_method_call(self, call)
+    }
+
+    /// S { f: p }
+    fn visit_field_value(&mut self, field: &'_ FieldValue) {
+        self.alias(&field.expr);
+        syn::visit::visit_field_value(self, field)
+    }
+}
+
+/// Collects the expressions a function body returns with `return`, outside of any
+/// closure or nested item.
+#[derive(Default)]
+struct ReturnedExprs<'a> {
+    exprs: Vec<&'a Expr>,
+}
+
+impl<'a> Visit<'a> for ReturnedExprs<'a> {
+    fn visit_expr_return(&mut self, ret: &'a ExprReturn) {
+        if let Some(expr) = &ret.expr {
+            self.exprs.push(expr);
+        }
+        syn::visit::visit_expr_return(self, ret)
+    }
+
+    fn visit_expr_closure(&mut self, _: &'a ExprClosure) {}
+
+    fn visit_item(&mut self, _: &'a Item) {}
+}
+
+/// Replaces the allocations a function body returns with `return` by those of the
+/// owning type of its return type, outside of any closure or nested item.
+struct ReturnedAllocations {
+    /// Whether the return type is a boxed slice.
+    indexed: bool,
+}
+
+impl VisitMut for ReturnedAllocations {
+    fn visit_expr_return_mut(&mut self, ret: &mut ExprReturn) {
+        if let Some(expr) = &mut ret.expr {
+            replace_allocation(expr, self.indexed);
+        }
+        syn::visit_mut::visit_expr_return_mut(self, ret)
+    }
+
+    fn visit_expr_closure_mut(&mut self, _: &mut ExprClosure) {}
+
+    fn visit_item_mut(&mut self, _: &mut Item) {}
+}
+
+/// Replaces `malloc(..) as *mut T` with `Box::new(T::default())`, and an allocation
+/// of `n` elements with `vec![T::default(); n].into_boxed_slice()`. A single element
+/// is allocated as a slice of one if `indexed`.
+fn replace_allocation(expr: &mut Expr, indexed: bool) {
+    *expr = match allocation(strip_parens(expr)) {
+        Some(Allocation::Single(elem)) if indexed => {
+            let value = default_of(&elem);
+            parse_quote!(vec![#value; 1].into_boxed_slice())
+        }
+        Some(Allocation::Single(elem)) => {
+            let value = default_of(&elem);
+            parse_quote!(Box::new(#value))
+        }
+        Some(Allocation::Array(elem, count)) => {
+            let value = default_of(&elem);
+            let count = as_usize(&count);
+            parse_quote!(vec![#value; #count].into_boxed_slice())
+        }
+        None => return,
+    };
+}
+
+/// Returns the struct a type names, possibly through a pointer or reference.
+fn struct_name(ty: &Type) -> Option<&Ident> {
+    match ty {
+        Type::Ptr(TypePtr { elem, .. }) | Type::Reference(TypeReference { elem, .. }) => {
+            struct_name(elem)
+        }
+        Type::Path(TypePath { qself: None, path }) => path.segments.last().map(|s| &s.ident),
         _ => None,
     }
 }
 
-/// If input_expr is *(inner), return Some(inner) and None otherwise.
-fn expr_if_unary_deref(input_expr: &Box<Expr>) -> Option<&Box<Expr>> {
-    if let Expr::Unary(ExprUnary { op, expr, .. }) = input_expr.as_ref() {
-        if let UnOp::Deref(_) = op {
-            return Some(expr);
+/// Returns the Rust safe pointer type to the given pointee, or None for
+/// RustPointerType::Undefined.
+fn safe_type(rust_type: RustPointerType, elem: &Type) -> Option<Type> {
+    Some(match rust_type {
+        RustPointerType::ImmutableReference => parse_quote!(&#elem),
+        RustPointerType::MutableReference => parse_quote!(&mut #elem),
+        RustPointerType::CellReference => parse_quote!(&std::cell::Cell<#elem>),
+        RustPointerType::UniquePointer => parse_quote!(Box<#elem>),
+        RustPointerType::ImmutableSlice => parse_quote!(&[#elem]),
+        RustPointerType::MutableSlice => parse_quote!(&mut [#elem]),
+        RustPointerType::UniqueSlicePointer => parse_quote!(Box<[#elem]>),
+        RustPointerType::Undefined => return None,
+    })
+}
+
+/// Returns whether every variable `expr` reads satisfies `allowed`. Paths of more
+/// than one segment, like `libc::EOF`, are not variables.
+fn reads_only(expr: &Expr, allowed: impl Fn(&Ident) -> bool) -> bool {
+    struct Reads<F> {
+        allowed: F,
+        only: bool,
+    }
+    impl<F: Fn(&Ident) -> bool> Visit<'_> for Reads<F> {
+        fn visit_expr_path(&mut self, path: &ExprPath) {
+            if let Some(ident) = path.path.get_ident() {
+                self.only &= (self.allowed)(ident);
+            }
         }
     }
+    let mut reads = Reads {
+        allowed,
+        only: true,
+    };
+    reads.visit_expr(expr);
+    reads.only
+}
+
+/// Returns whether `ty` is an array or a `Vec`.
+fn is_array_or_vec(ty: &Type) -> bool {
+    match ty {
+        Type::Array(_) => true,
+        Type::Path(TypePath { qself: None, path }) => path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Vec"),
+        _ => false,
+    }
+}
+
+/// Returns whether `expr` is known not to be negative: a sum, product, quotient or
+/// remainder of integer literals and variables that are `unsigned`.
+fn non_negative(expr: &Expr, unsigned: &impl Fn(&Ident) -> bool) -> bool {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(_), ..
+        }) => true,
+        Expr::Paren(ExprParen { expr, .. }) | Expr::Cast(ExprCast { expr, .. }) => {
+            non_negative(expr, unsigned)
+        }
+        Expr::Binary(ExprBinary {
+            left, op, right, ..
+        }) => {
+            matches!(
+                op,
+                BinOp::Add(_) | BinOp::Mul(_) | BinOp::Div(_) | BinOp::Rem(_)
+            ) && non_negative(left, unsigned)
+                && non_negative(right, unsigned)
+        }
+        expr => path_ident(expr).is_some_and(unsigned),
+    }
+}
+
+/// Returns the pattern a `let` statement binds, and its declared type if any.
+fn local_binding(pat: &Pat) -> (&Pat, Option<&Type>) {
+    match pat {
+        Pat::Type(PatType { pat, ty, .. }) => (pat, Some(ty)),
+        pat => (pat, None),
+    }
+}
+
+/// Returns the operator a compound assignment operator applies, e.g. `+` for `+=`.
+fn plain_op(op: &BinOp) -> Option<BinOp> {
+    Some(match op {
+        BinOp::AddAssign(_) => BinOp::Add(Default::default()),
+        BinOp::SubAssign(_) => BinOp::Sub(Default::default()),
+        BinOp::MulAssign(_) => BinOp::Mul(Default::default()),
+        BinOp::DivAssign(_) => BinOp::Div(Default::default()),
+        BinOp::RemAssign(_) => BinOp::Rem(Default::default()),
+        BinOp::BitAndAssign(_) => BinOp::BitAnd(Default::default()),
+        BinOp::BitOrAssign(_) => BinOp::BitOr(Default::default()),
+        BinOp::BitXorAssign(_) => BinOp::BitXor(Default::default()),
+        BinOp::ShlAssign(_) => BinOp::Shl(Default::default()),
+        BinOp::ShrAssign(_) => BinOp::Shr(Default::default()),
+        _ => return None,
+    })
+}
+
+/// Returns `expr` without the parentheses around it, for rewriting.
+fn strip_parens_mut(expr: &mut Expr) -> &mut Expr {
+    match expr {
+        Expr::Paren(ExprParen { expr, .. }) => strip_parens_mut(expr),
+        expr => expr,
+    }
+}
+
+/// Returns the pointer `place` dereferences, as `*p` or `*p.add(i)`.
+fn dereferenced(place: &Expr) -> Option<&Expr> {
+    match expr_if_unary_deref(strip_parens(place))? {
+        // *p.add(i)
+        Expr::MethodCall(ExprMethodCall { receiver, .. }) => Some(receiver),
+        // *p
+        expr => Some(expr),
+    }
+}
+
+/// If input_expr is *(inner), return Some(inner) and None otherwise.
+fn expr_if_unary_deref(input_expr: &Expr) -> Option<&Expr> {
+    if let Expr::Unary(ExprUnary {
+        op: UnOp::Deref(_),
+        expr,
+        ..
+    }) = input_expr
+    {
+        return Some(expr);
+    }
     None
 }
 
+/// Returns whether `expr` is `null_mut()` or `null()`, cast or not.
+fn is_null(expr: &Expr) -> bool {
+    matches!(strip_casts(expr), Expr::Call(ExprCall { func, args, .. })
+        if args.is_empty() && matches!(func.as_ref(), Expr::Path(ExprPath { path, .. })
+            if path.segments.last().is_some_and(|segment| segment.ident == "null"
+                || segment.ident == "null_mut")))
+}
+
+/// Returns the pointer compared with null by `p.is_null()`, `p == null_mut()` or
+/// `p == null()`.
+fn null_checked(expr: &Expr) -> Option<&Ident> {
+    match strip_parens(expr) {
+        Expr::MethodCall(ExprMethodCall {
+            receiver,
+            method,
+            args,
+            ..
+        }) if method == "is_null" && args.is_empty() => path_ident(receiver),
+        Expr::Binary(ExprBinary {
+            left,
+            op: BinOp::Eq(_),
+            right,
+            ..
+        }) if is_null(right) => path_ident(strip_casts(left)),
+        Expr::Binary(ExprBinary {
+            left,
+            op: BinOp::Eq(_),
+            right,
+            ..
+        }) if is_null(left) => path_ident(strip_casts(right)),
+        _ => None,
+    }
+}
+
+/// Returns the body of `if p.is_null() { ... }` if the statement is an early return
+/// guard of the form for the pointer `pointer`, i.e. without an `else` and with a
+/// body ending in a `return`, `break` or `continue`.
+fn null_guard<'a>(stmt: &'a Stmt, pointer: &Ident) -> Option<&'a Block> {
+    let Stmt::Expr(
+        Expr::If(ExprIf {
+            cond,
+            then_branch,
+            else_branch: None,
+            ..
+        }),
+        _,
+    ) = stmt
+    else {
+        return None;
+    };
+    let diverges = matches!(
+        then_branch.stmts.last(),
+        Some(Stmt::Expr(
+            Expr::Return(_) | Expr::Break(_) | Expr::Continue(_),
+            _
+        ))
+    );
+    (null_checked(cond) == Some(pointer) && diverges).then_some(then_branch)
+}
+
+/// Functions which copy or set the memory their first argument points to, from
+/// that the second points to for the copies, without retaining either pointer.
+static MEM_FNS: &[&str] = &["memcpy", "memmove", "memset"];
+
+/// Returns whether `func` is one of the [MEM_FNS], as in `memcpy` or
+/// `libc::memcpy`.
+fn is_mem_fn(func: &Expr) -> bool {
+    matches!(func, Expr::Path(ExprPath { qself: None, path, .. })
+        if MEM_FNS.iter().any(|mem_fn| path.segments.last().is_some_and(|s| s.ident == mem_fn)))
+}
+
+/// Returns the access `func` makes through its first argument if it is `ptr::read`
+/// or `ptr::write`, or one of their variants.
+fn ptr_access(func: &Expr) -> Option<PointerAccess> {
+    let Expr::Path(ExprPath {
+        qself: None, path, ..
+    }) = func
+    else {
+        return None;
+    };
+    let in_ptr = path.segments.len() > 1 && path.segments[path.segments.len() - 2].ident == "ptr";
+    match callee_name(func).as_deref().filter(|_| in_ptr) {
+        Some("read" | "read_volatile" | "read_unaligned") => Some(PointerAccess::Read),
+        Some("write" | "write_volatile" | "write_unaligned") => Some(PointerAccess::Write),
+        _ => None,
+    }
+}
+
+/// Returns the operand of the casts `expr` is, through any parentheses.
+fn cast_operand_mut(expr: &mut Expr) -> &mut Expr {
+    match expr {
+        Expr::Cast(ExprCast { expr, .. }) | Expr::Paren(ExprParen { expr, .. }) => {
+            cast_operand_mut(expr)
+        }
+        expr => expr,
+    }
+}
+
+/// Functions which free the memory their first argument points to.
+static FREE_FNS: &[&[&str]] = &[
+    &["free"],
+    &["libc", "free"],
+    &["dealloc"],
+    &["alloc", "dealloc"],
+    &["std", "alloc", "dealloc"],
+    &["drop"],
+    &["mem", "drop"],
+    &["std", "mem", "drop"],
+];
+
 #[inline]
-fn is_offset(ident: &Ident) -> bool {
-    ident.to_string().eq("offset")
+fn is_free(path: &Path) -> bool {
+    let segments: Vec<String> = path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    FREE_FNS.iter().any(|free_fn| segments == *free_fn)
+}
+
+/// Returns the offset permission a pointer method call needs, if `method` moves
+/// the pointer. The direction of `p.offset(n)` is taken from the sign of `n` if
+/// it is an integer literal, and assumed to be forwards otherwise.
+fn offset_access(method: &Ident, arg: Option<&Expr>) -> Option<PointerAccess> {
+    match method.to_string().as_str() {
+        "add" | "wrapping_add" => Some(PointerAccess::OffsetAdd),
+        "sub" | "wrapping_sub" => Some(PointerAccess::OffsetSub),
+        "offset" | "wrapping_offset" => match arg.map(strip_parens) {
+            Some(Expr::Unary(ExprUnary {
+                op: UnOp::Neg(_),
+                expr,
+                ..
+            })) if matches!(strip_parens(expr), Expr::Lit(_)) => Some(PointerAccess::OffsetSub),
+            _ => Some(PointerAccess::OffsetAdd),
+        },
+        _ => None,
+    }
 }
 
 impl Pass for RawPointerSanitizer {
-    fn bind(&mut self, mut monad: MonadicAst) -> MonadicAst {
-        self.identify_raw_pointer_args(&mut monad.ast);
-        self.compute_equivalent_safe_types();
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.identify_raw_pointer_args(&mut monad.ast)?;
+        self.compute_equivalent_safe_types()?;
 
-        // TODO - Replaces the types of the raw pointer variables with their memory safe Rust
-        //      - equivalents, computed from their access permissions. Updates the accesses of
-        //      - the updated variables, as necessary.
-        self.visit_file_mut(&mut monad.ast);
+        // Replaces the types of the raw pointer variables with their memory safe Rust
+        // equivalents, computed from their access permissions. The pointers the
+        // rewrite rejects are kept raw, and the file rewritten again without them.
+        let noted = self.diagnostics.len();
+        monad.ast = loop {
+            let mut ast = monad.ast.clone();
+            let rejected =
+                self.rejected.len() + self.rejected_returns.len() + self.rejected_fields.len();
+            self.scopes = Scopes::default();
+            self.events.clear();
+            self.rewritten = 0;
+            self.diagnostics.truncate(noted);
+            self.visit_file_mut(&mut ast);
+            if self.rejected.len() + self.rejected_returns.len() + self.rejected_fields.len()
+                == rejected
+            {
+                break ast;
+            }
+            for function in self.rejected_returns.keys() {
+                self.return_types.remove(function);
+            }
+            for key in self.rejected_fields.keys() {
+                self.field_types.remove(key);
+            }
+            if let TypeMappingStateMachine::Initialized(map) = &mut self.types {
+                for id in self.rejected.keys() {
+                    map.remove(id);
+                    self.nullable.remove(id);
+                }
+            }
+        };
+        let mut rejected: Vec<_> = self.rejected.iter().collect();
+        rejected.sort();
+        for (id, reason) in rejected {
+            let ident = &self.pointers[id].0;
+            self.diagnostics.push(Diagnostic::new(
+                PASS,
+                self.declared_in.get(id),
+                format!("`{ident}` {reason}; keeping it a raw pointer"),
+            ));
+        }
+        let mut rejected: Vec<_> = self.rejected_returns.iter().collect();
+        rejected.sort();
+        for (function, reason) in rejected {
+            self.diagnostics.push(Diagnostic::new(
+                PASS,
+                Some(function),
+                format!("{reason}; keeping its return type a raw pointer"),
+            ));
+        }
+        let mut rejected: Vec<_> = self.rejected_fields.iter().collect();
+        rejected.sort_by_key(|((name, field), _)| (name.to_string(), field.to_string()));
+        for ((name, field), reason) in rejected {
+            self.diagnostics.push(Diagnostic::new(
+                PASS,
+                None,
+                format!("field `{field}` of `{name}` {reason}; keeping it a raw pointer"),
+            ));
+        }
+
+        monad.stats.raw_pointers_rewritten += self.rewritten;
+        monad.events.append(&mut self.events);
+        monad.diagnostics.append(&mut self.diagnostics);
+        Ok(monad)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use PointerAccess::*;
+    use RustPointerType::*;
 
-        monad
+    /// The rows of the table documented on [PointerAccess::determine_rust_type].
+    const TABLE: &[(&[PointerAccess], RustPointerType)] = &[
+        (&[Read], ImmutableReference),
+        (&[Write, Unique], MutableReference),
+        (&[Write], CellReference),
+        (&[Unique, Free], UniquePointer),
+        (&[OffsetAdd], ImmutableSlice),
+        (&[Write, Unique, OffsetAdd], MutableSlice),
+        (&[Unique, Free, OffsetAdd], UniqueSlicePointer),
+    ];
+
+    #[test]
+    fn determine_rust_type_follows_the_table() {
+        for &(permissions, expected) in TABLE {
+            assert_eq!(
+                PointerAccess::determine_rust_type(permissions, true),
+                expected,
+                "{permissions:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn determine_rust_type_rejects_other_combinations() {
+        for permissions in [&[Write, Free][..], &[Write, Unique, Free], &[Free]] {
+            assert_eq!(
+                PointerAccess::determine_rust_type(permissions, true),
+                Undefined,
+                "{permissions:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn determine_rust_type_ignores_write_through_const_pointers() {
+        assert_eq!(
+            PointerAccess::determine_rust_type(&[Write, Unique], false),
+            ImmutableReference
+        );
+        assert_eq!(
+            PointerAccess::determine_rust_type(&[Write, Unique, OffsetAdd], false),
+            ImmutableSlice
+        );
+    }
+
+    #[test]
+    fn determine_rust_type_needs_write_or_free_for_unique() {
+        assert_eq!(
+            PointerAccess::determine_rust_type(&[Unique], true),
+            ImmutableReference
+        );
+        assert_eq!(
+            PointerAccess::determine_rust_type(&[Unique, OffsetSub], true),
+            ImmutableSlice
+        );
+    }
+
+    /// Returns a sanitizer in the `Computing` state tracking a `*mut i32` per row of
+    /// the table, numbered in order, with the row's permissions. Each is also read,
+    /// as pointers never dereferenced stay raw.
+    fn computing_table() -> RawPointerSanitizer {
+        let mut sanitizer = RawPointerSanitizer::default();
+        for (index, &(permissions, _)) in TABLE.iter().enumerate() {
+            let ident = Ident::new(&format!("p{index}"), proc_macro2::Span::call_site());
+            let pointer: TypePtr = parse_quote!(*mut i32);
+            let mut access_set: HashSet<_> = permissions.iter().copied().collect();
+            access_set.insert(Read);
+            sanitizer
+                .pointers
+                .insert(PointerId(index), (ident, pointer, access_set));
+        }
+        sanitizer.types = TypeMappingStateMachine::Computing(HashMap::new());
+        sanitizer
+    }
+
+    #[test]
+    fn computing_initializes_the_map_from_the_table() {
+        let mut sanitizer = computing_table();
+        sanitizer.compute_equivalent_safe_types().unwrap();
+        let TypeMappingStateMachine::Initialized(map) = &sanitizer.types else {
+            panic!("not initialized");
+        };
+        let expected: HashMap<PointerId, RustPointerType> = TABLE
+            .iter()
+            .enumerate()
+            .map(|(index, &(_, rust_type))| (PointerId(index), rust_type))
+            .collect();
+        assert_eq!(*map, expected);
+    }
+
+    #[test]
+    fn computing_leaves_pointers_never_dereferenced_out_of_the_map() {
+        let mut sanitizer = computing_table();
+        let ident = Ident::new("passed", proc_macro2::Span::call_site());
+        let access_set = HashSet::from([Unique, OffsetAdd]);
+        let unused = PointerId(TABLE.len());
+        sanitizer
+            .pointers
+            .insert(unused, (ident, parse_quote!(*mut i32), access_set));
+        sanitizer.compute_equivalent_safe_types().unwrap();
+        let TypeMappingStateMachine::Initialized(map) = &sanitizer.types else {
+            panic!("not initialized");
+        };
+        assert!(!map.contains_key(&unused));
+        assert_eq!(sanitizer.diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn computing_leaves_undefined_pointers_out_of_the_map() {
+        let mut sanitizer = computing_table();
+        let ident = Ident::new("freed", proc_macro2::Span::call_site());
+        let access_set = HashSet::from([Read, Write, Free]);
+        let undefined = PointerId(TABLE.len());
+        sanitizer
+            .pointers
+            .insert(undefined, (ident, parse_quote!(*mut i32), access_set));
+        sanitizer.compute_equivalent_safe_types().unwrap();
+        let TypeMappingStateMachine::Initialized(map) = &sanitizer.types else {
+            panic!("not initialized");
+        };
+        assert_eq!(map.len(), TABLE.len());
+        assert!(!map.contains_key(&undefined));
+    }
+
+    #[test]
+    fn identifying_advances_from_uninitialized_only() {
+        let mut ast: File = parse_quote!();
+        let mut sanitizer = RawPointerSanitizer::default();
+        sanitizer.identify_raw_pointer_args(&mut ast).unwrap();
+        assert!(matches!(
+            sanitizer.types,
+            TypeMappingStateMachine::Computing(_)
+        ));
+        assert!(matches!(
+            sanitizer.identify_raw_pointer_args(&mut ast),
+            Err(PassError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn computing_requires_the_computing_state() {
+        let mut sanitizer = RawPointerSanitizer::default();
+        assert!(matches!(
+            sanitizer.compute_equivalent_safe_types(),
+            Err(PassError::Unsupported(_))
+        ));
+        assert!(matches!(
+            sanitizer.types,
+            TypeMappingStateMachine::Uninitialized
+        ));
+
+        let mut sanitizer = computing_table();
+        sanitizer.compute_equivalent_safe_types().unwrap();
+        assert!(matches!(
+            sanitizer.compute_equivalent_safe_types(),
+            Err(PassError::Unsupported(_))
+        ));
+        assert!(matches!(
+            sanitizer.types,
+            TypeMappingStateMachine::Initialized(_)
+        ));
+    }
+
+    /// The offset spellings, with the argument each is called with, the permission
+    /// it records, and the rewritten dereference of `p` if it is rewritten.
+    const OFFSETS: &[(&str, &str, PointerAccess, Option<&str>)] = &[
+        ("add", "i", OffsetAdd, Some("p[i]")),
+        ("wrapping_add", "i", OffsetAdd, Some("p[i]")),
+        ("offset", "i as isize", OffsetAdd, Some("p[i]")),
+        ("wrapping_offset", "i as isize", OffsetAdd, Some("p[i]")),
+        ("offset", "-1", OffsetSub, None),
+        ("sub", "i", OffsetSub, None),
+        ("wrapping_sub", "i", OffsetSub, None),
+    ];
+
+    #[test]
+    fn offset_access_follows_the_spelling() {
+        for &(method, arg, access, _) in OFFSETS {
+            let method = Ident::new(method, proc_macro2::Span::call_site());
+            let arg: Expr = syn::parse_str(arg).unwrap();
+            assert_eq!(offset_access(&method, Some(&arg)), Some(access), "{method}");
+        }
+        let method = Ident::new("cast", proc_macro2::Span::call_site());
+        assert_eq!(offset_access(&method, None), None);
+    }
+
+    #[test]
+    fn offsets_are_recorded_and_indexed_per_spelling() {
+        for &(method, arg, access, indexed) in OFFSETS {
+            let source =
+                format!("unsafe fn get(p: *const i32, i: usize) -> i32 {{ *p.{method}({arg}) }}");
+            let mut ast: File = syn::parse_str(&source).unwrap();
+            let mut sanitizer = RawPointerSanitizer::default();
+            sanitizer.identify_raw_pointer_args(&mut ast).unwrap();
+            let (_, _, access_set) = &sanitizer.pointers[&PointerId(0)];
+            assert!(access_set.contains(&access), "{source}: {access_set:?}");
+
+            let output = MonadicAst::new(&source)
+                .unwrap()
+                .replace_raw_pointers()
+                .unwrap()
+                .result();
+            match indexed {
+                Some(index) => {
+                    assert!(output.contains("p: &[i32]"), "{output}");
+                    assert!(output.contains(index), "{output}");
+                }
+                None => assert!(output.contains("p: *const i32"), "{output}"),
+            }
+        }
     }
 }
@@ -0,0 +1,305 @@
+//! Replaces loops walking a slice backwards through a mirrored index with a loop
+//! over the reversed elements, e.g.
+//!
+//! ```ignore
+//! for i in 0..a.len() {
+//!     let j = a.len() - 1 - i;
+//!     total = total * 10 + a[j];
+//! }
+//! ```
+//!
+//! becomes `for &x in a.iter().rev() { total = total * 10 + x; }`. A loop over
+//! `0..n` visits only the first `n` elements, so it iterates over `a[..n]` instead.
+//!
+//! The loop index may only be used to compute the mirrored index, which in turn may
+//! only be used to read elements of the slice. Loops writing to the slice are left
+//! as they are.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{
+    count_ident, expr_type, has_primitive_elements, is_assign_op, is_ident, is_int_lit,
+    len_as_usize, local_types, path_ident, same_tokens, strip_casts, strip_parens, NameGen,
+    Parenthesize,
+};
+use crate::MonadicAst;
+use std::collections::HashMap;
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, BinOp, Expr, ExprAssign, ExprBinary, ExprForLoop, ExprIndex, ExprMethodCall,
+    ExprRange, ExprReference, Ident, ImplItemFn, ItemFn, Local, LocalInit, Pat, PatIdent, PatType,
+    RangeLimits, Stmt, Type,
+};
+
+#[derive(Default)]
+pub struct ReverseIndexToRevIter {
+    /// Declared types of the locals of the function currently being visited.
+    types: HashMap<Ident, Type>,
+    /// Generates names unused in the function currently being visited.
+    names: NameGen,
+}
+
+/// Returns whether `expr` is `end - 1 - index` or `end - index - 1`.
+fn is_mirrored(expr: &Expr, end: &Expr, index: &Ident) -> bool {
+    let Expr::Binary(ExprBinary {
+        left,
+        op: BinOp::Sub(_),
+        right,
+        ..
+    }) = strip_parens(expr)
+    else {
+        return false;
+    };
+    let Expr::Binary(ExprBinary {
+        left: minuend,
+        op: BinOp::Sub(_),
+        right: subtrahend,
+        ..
+    }) = strip_parens(left)
+    else {
+        return false;
+    };
+    same_tokens(strip_parens(minuend), end)
+        && ((is_int_lit(subtrahend, 1) && is_ident(right, index))
+            || (is_ident(subtrahend, index) && is_int_lit(right, 1)))
+}
+
+/// Returns the mirrored index if the statement is `let j = end - 1 - index;`.
+fn mirrored_decl<'a>(stmt: &'a Stmt, end: &Expr, index: &Ident) -> Option<&'a Ident> {
+    let Stmt::Local(Local {
+        pat,
+        init:
+            Some(LocalInit {
+                expr,
+                diverge: None,
+                ..
+            }),
+        ..
+    }) = stmt
+    else {
+        return None;
+    };
+    let pat = match pat {
+        Pat::Type(PatType { pat, .. }) => pat.as_ref(),
+        pat => pat,
+    };
+    match pat {
+        Pat::Ident(PatIdent {
+            by_ref: None,
+            mutability: None,
+            subpat: None,
+            ident,
+            ..
+        }) if is_mirrored(expr, end, index) => Some(ident),
+        _ => None,
+    }
+}
+
+/// Returns whether `expr` is `slice[mirrored]`, with the index possibly cast.
+fn is_element(expr: &Expr, slice: &Ident, mirrored: &Ident) -> bool {
+    matches!(strip_parens(expr), Expr::Index(ExprIndex { expr, index, .. })
+        if is_ident(expr, slice) && is_ident(strip_casts(index), mirrored))
+}
+
+/// Collects the element reads in the loop body, and whether any element is
+/// written to or borrowed mutably.
+struct ElementAccesses<'a> {
+    slice: &'a Ident,
+    mirrored: &'a Ident,
+    reads: usize,
+    /// Whether an element is read through an index cast to `usize`.
+    cast: bool,
+    written: bool,
+}
+
+impl ElementAccesses<'_> {
+    /// Returns whether the place `expr` is, or is part of, an element of the slice.
+    fn is_element_place(&self, expr: &Expr) -> bool {
+        match strip_parens(expr) {
+            Expr::Field(field) => self.is_element_place(&field.base),
+            Expr::Index(ExprIndex { expr, .. }) if is_ident(expr, self.slice) => true,
+            Expr::Index(ExprIndex { expr, .. }) => self.is_element_place(expr),
+            _ => false,
+        }
+    }
+}
+
+impl Visit<'_> for ElementAccesses<'_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        let place = match expr {
+            Expr::Assign(ExprAssign { left, .. }) => Some(left),
+            Expr::Binary(ExprBinary { left, op, .. }) if is_assign_op(op) => Some(left),
+            Expr::Reference(ExprReference {
+                mutability: Some(_),
+                expr,
+                ..
+            }) => Some(expr),
+            _ => None,
+        };
+        self.written |= place.is_some_and(|place| self.is_element_place(place));
+        if is_element(expr, self.slice, self.mirrored) {
+            self.reads += 1;
+            self.cast |= matches!(expr, Expr::Index(ExprIndex { index, .. })
+                if matches!(strip_parens(index), Expr::Cast(_)));
+            return;
+        }
+        syn::visit::visit_expr(self, expr);
+    }
+}
+
+/// Replaces the element reads with the loop's binding.
+struct ElementToBinding<'a> {
+    slice: &'a Ident,
+    mirrored: &'a Ident,
+    binding: Expr,
+}
+
+impl VisitMut for ElementToBinding<'_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if is_element(expr, self.slice, self.mirrored) {
+            *expr = self.binding.clone();
+            return;
+        }
+        syn::visit_mut::visit_expr_mut(self, expr);
+    }
+}
+
+/// Returns the slice `a` of the first element read `a[mirrored]` in the body.
+fn indexed_slice<'a>(stmts: &'a [Stmt], mirrored: &Ident) -> Option<&'a Ident> {
+    struct Finder<'a, 'b> {
+        mirrored: &'b Ident,
+        slice: Option<&'a Ident>,
+    }
+    impl<'a> Visit<'a> for Finder<'a, '_> {
+        fn visit_expr_index(&mut self, expr: &'a ExprIndex) {
+            if self.slice.is_none() && is_ident(strip_casts(&expr.index), self.mirrored) {
+                self.slice = path_ident(&expr.expr);
+            }
+            syn::visit::visit_expr_index(self, expr);
+        }
+    }
+    let mut finder = Finder {
+        mirrored,
+        slice: None,
+    };
+    for stmt in stmts {
+        finder.visit_stmt(stmt);
+    }
+    finder.slice
+}
+
+impl ReverseIndexToRevIter {
+    fn rewrite(&mut self, for_loop: &mut ExprForLoop) {
+        let Pat::Ident(pat) = for_loop.pat.as_ref() else {
+            return;
+        };
+        let index = pat.ident.clone();
+        let Expr::Range(ExprRange {
+            start: Some(start),
+            limits: RangeLimits::HalfOpen(_),
+            end: Some(end),
+            ..
+        }) = for_loop.expr.as_ref()
+        else {
+            return;
+        };
+        let end = strip_parens(end).clone();
+        let Some((decl, body)) = for_loop.body.stmts.split_first() else {
+            return;
+        };
+        let Some(mirrored) = mirrored_decl(decl, &end, &index) else {
+            return;
+        };
+        let mirrored = mirrored.clone();
+        let Some(slice) = indexed_slice(body, &mirrored) else {
+            return;
+        };
+        let slice = slice.clone();
+
+        let mut accesses = ElementAccesses {
+            slice: &slice,
+            mirrored: &mirrored,
+            reads: 0,
+            cast: false,
+            written: false,
+        };
+        for stmt in body {
+            accesses.visit_stmt(stmt);
+        }
+        let uses =
+            |ident: &Ident| -> usize { body.iter().map(|stmt| count_ident(stmt, ident)).sum() };
+        if !is_int_lit(start, 0)
+            || accesses.written
+            || count_ident(&end, &index) > 0
+            || uses(&index) > 0
+            || uses(&mirrored) != accesses.reads
+            || uses(&slice) != accesses.reads
+        {
+            return;
+        }
+
+        // Iterate over the prefix the loop covers, unless it is the whole slice.
+        let covers_all = matches!(strip_casts(&end), Expr::MethodCall(ExprMethodCall {
+            receiver, method, args, ..
+        }) if method == "len" && args.is_empty() && is_ident(receiver, &slice));
+        let receiver: Expr = if covers_all {
+            parse_quote!(#slice)
+        } else if accesses.cast {
+            // A signed bound may be negative, for which the loop runs no iteration.
+            let end = len_as_usize(strip_parens(&end), expr_type(&end, &self.types).as_ref());
+            parse_quote!(#slice[..#end])
+        } else {
+            parse_quote!(#slice[..#end])
+        };
+
+        let binding = self.names.fresh("x");
+        let by_value = self.types.get(&slice).is_some_and(has_primitive_elements);
+        let (pat, element): (Pat, Expr) = if by_value {
+            (parse_quote!(&#binding), parse_quote!(#binding))
+        } else {
+            (parse_quote!(#binding), parse_quote!(*#binding))
+        };
+        let mut body = body.to_vec();
+        let mut rewriter = ElementToBinding {
+            slice: &slice,
+            mirrored: &mirrored,
+            binding: element,
+        };
+        for stmt in &mut body {
+            rewriter.visit_stmt_mut(stmt);
+            Parenthesize.visit_stmt_mut(stmt);
+        }
+        for_loop.body.stmts = body;
+        *for_loop.pat = pat;
+        *for_loop.expr = parse_quote!(#receiver.iter().rev());
+    }
+}
+
+impl VisitMut for ReverseIndexToRevIter {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        self.names = NameGen::new(item_fn);
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        self.names = NameGen::new(item_fn);
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+        self.types = types;
+    }
+
+    fn visit_expr_for_loop_mut(&mut self, for_loop: &mut ExprForLoop) {
+        syn::visit_mut::visit_expr_for_loop_mut(self, for_loop);
+        self.rewrite(for_loop);
+    }
+}
+
+impl Pass for ReverseIndexToRevIter {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
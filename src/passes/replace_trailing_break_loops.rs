@@ -0,0 +1,241 @@
+//! Replaces counter loops written with a trailing exit test, as translated from C's
+//! `do { ... } while (++i < n);`, with a `for` loop:
+//!
+//! ```ignore
+//! let mut i = 0;
+//! loop {
+//!     body(i);
+//!     i += 1;
+//!     if i >= n {
+//!         break;
+//!     }
+//! }
+//! ```
+//!
+//! becomes `for i in 0..n.max(1) { body(i); }`. Unlike a `while` loop, the body runs
+//! at least once, for the start value, even if `n` is not larger; the bound keeps
+//! that first iteration. It is left out when both bounds are literals and the
+//! range is known not to be empty.
+//!
+//! The body may read but not modify the counter or the bound, may not `continue`,
+//! and the counter may not be used after the loop.
+
+use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::passes::utils::{
+    count_ident, is_assign_op, is_ident, is_int_lit, path_ident, strip_parens,
+};
+use crate::MonadicAst;
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, BinOp, Block, Expr, ExprAssign, ExprBinary, ExprBreak, ExprContinue, ExprIf,
+    ExprLit, ExprLoop, ExprReference, Ident, Lit, Local, LocalInit, Pat, Stmt,
+};
+
+#[derive(Default)]
+pub struct TrailingBreakCounterToFor;
+
+/// Finds whether a loop body assigns to or mutably borrows any of `idents`, or
+/// contains a `continue`.
+struct Interference<'a> {
+    idents: &'a [&'a Ident],
+    found: bool,
+}
+
+impl Interference<'_> {
+    fn targets(&self, expr: &Expr) -> bool {
+        self.idents.iter().any(|ident| is_ident(expr, ident))
+    }
+}
+
+impl Visit<'_> for Interference<'_> {
+    fn visit_expr_assign(&mut self, assign: &ExprAssign) {
+        self.found |= self.targets(&assign.left);
+        syn::visit::visit_expr_assign(self, assign);
+    }
+
+    fn visit_expr_binary(&mut self, binary: &ExprBinary) {
+        self.found |= is_assign_op(&binary.op) && self.targets(&binary.left);
+        syn::visit::visit_expr_binary(self, binary);
+    }
+
+    fn visit_expr_reference(&mut self, reference: &ExprReference) {
+        self.found |= reference.mutability.is_some() && self.targets(&reference.expr);
+        syn::visit::visit_expr_reference(self, reference);
+    }
+
+    fn visit_expr_continue(&mut self, _: &ExprContinue) {
+        self.found = true;
+    }
+}
+
+/// Returns whether `stmt` is `counter += 1;` or `counter = counter + 1;`.
+fn is_increment(stmt: &Stmt, counter: &Ident) -> bool {
+    let Stmt::Expr(expr, Some(_)) = stmt else {
+        return false;
+    };
+    match expr {
+        Expr::Binary(ExprBinary {
+            left,
+            op: BinOp::AddAssign(_),
+            right,
+            ..
+        }) => is_ident(left, counter) && is_int_lit(right, 1),
+        Expr::Assign(ExprAssign { left, right, .. }) => {
+            is_ident(left, counter)
+                && matches!(strip_parens(right), Expr::Binary(ExprBinary {
+                    left: operand, op: BinOp::Add(_), right: one, ..
+                }) if is_ident(operand, counter) && is_int_lit(one, 1))
+        }
+        _ => false,
+    }
+}
+
+/// Returns the bound `n` if `stmt` is `if counter >= n { break; }`.
+fn exit_bound<'a>(stmt: &'a Stmt, counter: &Ident) -> Option<&'a Expr> {
+    let Stmt::Expr(
+        Expr::If(ExprIf {
+            cond,
+            then_branch,
+            else_branch: None,
+            ..
+        }),
+        _,
+    ) = stmt
+    else {
+        return None;
+    };
+    let breaks = matches!(
+        then_branch.stmts.as_slice(),
+        [Stmt::Expr(
+            Expr::Break(ExprBreak {
+                label: None,
+                expr: None,
+                ..
+            }),
+            _
+        )]
+    );
+    if !breaks {
+        return None;
+    }
+    match strip_parens(cond) {
+        Expr::Binary(ExprBinary {
+            left,
+            op: BinOp::Ge(_),
+            right,
+            ..
+        }) if is_ident(left, counter) => Some(right),
+        Expr::Binary(ExprBinary {
+            left,
+            op: BinOp::Le(_),
+            right,
+            ..
+        }) if is_ident(right, counter) => Some(left),
+        _ => None,
+    }
+}
+
+/// Returns the value of an integer literal.
+fn int_value(expr: &Expr) -> Option<u128> {
+    match strip_parens(expr) {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(int), ..
+        }) => int.base10_parse().ok(),
+        _ => None,
+    }
+}
+
+impl TrailingBreakCounterToFor {
+    /// Returns the counter and its start value if `stmt` is `let mut i = start;`.
+    fn counter_decl(stmt: &Stmt) -> Option<(&Ident, &Expr)> {
+        let Stmt::Local(Local {
+            pat: Pat::Ident(pat),
+            init:
+                Some(LocalInit {
+                    expr,
+                    diverge: None,
+                    ..
+                }),
+            ..
+        }) = stmt
+        else {
+            return None;
+        };
+        let simple = int_value(expr).is_some() || path_ident(expr).is_some();
+        (pat.mutability.is_some() && pat.by_ref.is_none() && pat.subpat.is_none() && simple)
+            .then_some((&pat.ident, expr.as_ref()))
+    }
+
+    /// Returns the `for` loop replacing the counter declaration and the loop after it.
+    fn replacement(decl: &Stmt, stmt: &Stmt, rest: &[Stmt]) -> Option<Stmt> {
+        let (counter, start) = Self::counter_decl(decl)?;
+        let Stmt::Expr(Expr::Loop(ExprLoop { label, body, .. }), _) = stmt else {
+            return None;
+        };
+        let [body_stmts @ .., increment, exit] = body.stmts.as_slice() else {
+            return None;
+        };
+        if !is_increment(increment, counter) {
+            return None;
+        }
+        let bound = exit_bound(exit, counter)?;
+        let bound_ident = path_ident(bound);
+        if int_value(bound).is_none() && bound_ident.is_none_or(|ident| ident == counter) {
+            return None;
+        }
+        let mut idents = vec![counter];
+        idents.extend(bound_ident);
+        idents.extend(path_ident(start));
+        let mut interference = Interference {
+            idents: &idents,
+            found: false,
+        };
+        body_stmts
+            .iter()
+            .for_each(|stmt| interference.visit_stmt(stmt));
+        if interference.found || rest.iter().any(|stmt| count_ident(stmt, counter) > 0) {
+            return None;
+        }
+
+        let start = strip_parens(start);
+        let bound = strip_parens(bound);
+        let end: Expr = match (int_value(start), int_value(bound)) {
+            (Some(first), Some(last)) if first < last => parse_quote!(#bound),
+            (Some(_), Some(_)) => return None,
+            (Some(first), None) => {
+                let next =
+                    syn::LitInt::new(&(first + 1).to_string(), proc_macro2::Span::call_site());
+                parse_quote!(#bound.max(#next))
+            }
+            (None, _) => parse_quote!((#start + 1).max(#bound)),
+        };
+        let body = Block {
+            brace_token: body.brace_token,
+            stmts: body_stmts.to_vec(),
+        };
+        Some(parse_quote!(#label for #counter in #start..#end #body))
+    }
+}
+
+impl VisitMut for TrailingBreakCounterToFor {
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        syn::visit_mut::visit_block_mut(self, block);
+        let mut index = 0;
+        while index + 1 < block.stmts.len() {
+            let (stmts, rest) = block.stmts.split_at(index + 2);
+            if let Some(for_loop) = Self::replacement(&stmts[index], &stmts[index + 1], rest) {
+                block.stmts.splice(index..index + 2, [for_loop]);
+            }
+            index += 1;
+        }
+    }
+}
+
+impl Pass for TrailingBreakCounterToFor {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        Ok(monad)
+    }
+}
@@ -1,176 +1,602 @@
 use crate::monad::ast::Pass;
+use crate::monad::error::PassError;
+use crate::monad::event::Event;
+use crate::passes::utils::{
+    count_ident, is_reassigned, is_signed_int, is_unsigned_int, local_types, path_ident,
+    primitive_name, same_tokens, strip_casts, strip_parens,
+};
 use crate::MonadicAst;
 use proc_macro2;
 use std::collections::HashMap;
-use syn::{visit::Visit, visit_mut::VisitMut, Expr, ExprLit, ExprWhile, Lit, LitInt, Pat, Stmt};
+use syn::{
+    visit::Visit, visit_mut::VisitMut, BinOp, Expr, ExprBinary, ExprBreak, ExprContinue,
+    ExprForLoop, ExprLit, ExprLoop, ExprWhile, Ident, ImplItemFn, Item, ItemFn, Lifetime, Lit,
+    LitInt, Pat, Stmt, Type,
+};
 
 #[derive(Default)]
 pub struct WhileLoopReplacer {
-    loop_vars: HashMap<String, i32>,
+    /// The integer literals the loop counters are initialized with, as written.
+    loop_vars: HashMap<String, LitInt>,
+    /// Declared types of the locals of the function currently being visited.
+    types: HashMap<Ident, Type>,
+    /// How many loops were converted.
+    converted: usize,
+    events: Vec<Event>,
 }
 
 impl WhileLoopReplacer {
     fn record_if_whileloop(&mut self, wloop: &ExprWhile) {
-        println!("{:?}", wloop);
-        println!("Found a while loop");
-    }
-
-    // Helper function to check if a statement is incrementing a specific variable
-    fn is_increment_stmt(&self, stmt: &Stmt, var_name: &str) -> bool {
-        println!("\n\nFOUND STMT {:?}\n\n", stmt);
-        match stmt {
-            // Check for assignment expressions (i = i + 1)
-            Stmt::Expr(Expr::Assign(assign), _) => {
-                if let Expr::Path(path) = &*assign.left {
-                    let left_var = path.path.segments[0].ident.to_string();
-                    if left_var == var_name {
-                        // Check if right side is an increment
-                        if let Expr::Binary(binary) = &*assign.right {
-                            if let (Expr::Path(left_path), Expr::Lit(right_lit)) =
-                                (&*binary.left, &*binary.right)
-                            {
-                                return left_path.path.segments[0].ident.to_string() == var_name;
-                            }
+        if is_while_let(wloop) {
+            return;
+        }
+        log::trace!("found a while loop: {:?}", wloop);
+    }
+
+    // Helper function to get the step of a statement incrementing a specific variable
+    fn increment_step(&self, stmt: &Stmt, var_name: &str) -> Option<i32> {
+        log::trace!("looking for an increment of `{}` in {:?}", var_name, stmt);
+        step_stmt(
+            stmt,
+            var_name,
+            |op| matches!(op, BinOp::Add(_)),
+            |op| matches!(op, BinOp::AddAssign(_)),
+        )
+    }
+
+    // Helper function to check if a statement is decrementing a specific variable
+    fn is_decrement_stmt(&self, stmt: &Stmt, var_name: &str) -> bool {
+        step_stmt(
+            stmt,
+            var_name,
+            |op| matches!(op, BinOp::Sub(_)),
+            |op| matches!(op, BinOp::SubAssign(_)),
+        ) == Some(1)
+    }
+
+    /// Returns the loop condition with the counter on the left, e.g. `n > i` becomes
+    /// `i < n`, if only its right operand is a counter.
+    fn counter_first(&self, cond: &ExprBinary) -> ExprBinary {
+        let is_counter = |expr: &Expr| match expr {
+            Expr::Path(path) => self
+                .loop_vars
+                .contains_key(&path.path.segments[0].ident.to_string()),
+            _ => false,
+        };
+        if is_counter(&cond.left) || !is_counter(&cond.right) {
+            return cond.clone();
+        }
+        let op = match cond.op {
+            BinOp::Lt(token) => BinOp::Gt(syn::Token![>](token.span)),
+            BinOp::Le(token) => BinOp::Ge(syn::Token![>=](token.spans)),
+            BinOp::Gt(token) => BinOp::Lt(syn::Token![<](token.span)),
+            BinOp::Ge(token) => BinOp::Le(syn::Token![<=](token.spans)),
+            _ => return cond.clone(),
+        };
+        ExprBinary {
+            attrs: cond.attrs.clone(),
+            left: cond.right.clone(),
+            op,
+            right: cond.left.clone(),
+        }
+    }
+
+    /// Returns the bounds of the range replacing a loop over `counter`, typed so that
+    /// the range yields the counter's declared integer type rather than the type the
+    /// literals would infer, e.g. `0usize..10` for a `usize` counter. A variable
+    /// bound of another known integer type is cast to the counter's type, and a
+    /// suffixed literal or a variable of the counter's type is left as it is, as it
+    /// types the range already.
+    fn typed_bounds(&self, counter: &Ident, lower: Expr, upper: Expr) -> (Expr, Expr) {
+        let Some(ty) = self
+            .types
+            .get(counter)
+            .filter(|ty| is_signed_int(ty) || is_unsigned_int(ty))
+        else {
+            return (lower, upper);
+        };
+        if matches!(&upper, Expr::Lit(ExprLit { lit: Lit::Int(int_lit), .. }) if !int_lit.suffix().is_empty())
+        {
+            return (lower, upper);
+        }
+        let bound_type = path_ident(&upper).and_then(|bound| self.types.get(bound));
+        match bound_type {
+            Some(bound_type) if same_tokens(bound_type, ty) => (lower, upper),
+            Some(bound_type) if is_signed_int(bound_type) || is_unsigned_int(bound_type) => {
+                (lower, syn::parse_quote!(#upper as #ty))
+            }
+            _ => (with_suffix(lower, ty), upper),
+        }
+    }
+
+    /// Returns the body of a loop counting `var_name` down, without the decrement,
+    /// and whether the decrement comes first in the body rather than last. Trailing
+    /// `i;` statements after the last statement are ignored. Returns None unless the
+    /// counter is decremented by exactly one such statement, and mutated nowhere
+    /// else in the body.
+    fn strip_decrement(&self, body: &[Stmt], var_name: &str) -> Option<(Vec<Stmt>, bool)> {
+        let positions: Vec<usize> = (0..body.len())
+            .filter(|&index| self.is_decrement_stmt(&body[index], var_name))
+            .collect();
+        let [position] = positions[..] else {
+            return None;
+        };
+        let is_last = body[position + 1..]
+            .iter()
+            .all(|stmt| matches!(stmt, Stmt::Expr(Expr::Path(_), Some(_))));
+        if position != 0 && !is_last {
+            return None;
+        }
+        let mut stmts = body.to_vec();
+        stmts.remove(position);
+        // Only the recognized decrement is removed, so any other mutation of the
+        // counter, e.g. a conditional decrement, would be left writing to the range's
+        // loop variable
+        let counter = Ident::new(var_name, proc_macro2::Span::call_site());
+        if is_reassigned(&stmts, &counter) {
+            return None;
+        }
+        Some((stmts, position == 0 && !is_last))
+    }
+
+    /// Replaces the statement with a for loop if it is a while loop over a counter
+    /// with a known initial value.
+    ///
+    /// Compound conditions, like `while i < n && done == 0`, are not supported: the
+    /// loop may stop before its counter reaches the bound, which a range cannot
+    /// express, so such loops are left as they are. Neither are `while let` loops,
+    /// which match a pattern rather than compare a counter.
+    fn replace_if_counting_loop(&mut self, stmt: &mut Stmt) {
+        let Stmt::Expr(Expr::While(while_loop), _) = stmt else {
+            return;
+        };
+        if is_while_let(while_loop) {
+            return;
+        }
+        if let Expr::Binary(cond) = &*while_loop.cond {
+            if matches!(cond.op, BinOp::And(_) | BinOp::Or(_)) {
+                log::trace!("skipping a loop with a compound condition");
+                return;
+            }
+            let cond = &self.counter_first(cond);
+            // Get the left variable (iterator variable)
+            let l_var = if let Expr::Path(left) = &*cond.left {
+                left.path.segments[0].ident.to_string()
+            } else {
+                return;
+            };
+
+            if self.loop_vars.contains_key(&l_var) && !escapes_loop(while_loop) {
+                // Create the lower bound
+                let lower_bound: syn::Expr = if self.loop_vars.contains_key(&l_var) {
+                    let value = self.loop_vars.get(&l_var).unwrap();
+                    syn::parse_quote!(#value)
+                } else {
+                    let ident = syn::Ident::new(&l_var, proc_macro2::Span::call_site());
+                    syn::parse_quote!(#ident)
+                };
+
+                // Handle both variable and literal upper bounds
+                let upper_bound: syn::Expr = match &*cond.right {
+                    Expr::Path(right) => {
+                        let r_var = right.path.segments[0].ident.to_string();
+                        // The range is computed once, so its bound must not change
+                        if is_reassigned(&while_loop.body.stmts, &counter_ident(&r_var)) {
+                            return;
+                        }
+                        if self.loop_vars.contains_key(&r_var) {
+                            let value: &LitInt = self.loop_vars.get(&r_var).unwrap();
+                            syn::parse_quote!(#value)
+                        } else {
+                            let ident: syn::Ident =
+                                syn::Ident::new(&r_var, proc_macro2::Span::call_site());
+                            syn::parse_quote!(#ident)
                         }
                     }
-                }
-                false
-            }
+                    Expr::Lit(lit) => {
+                        if let Lit::Int(int_lit) = &lit.lit {
+                            syn::parse_quote!(#int_lit)
+                        } else {
+                            return;
+                        }
+                    }
+                    _ => return,
+                };
+
+                let iter_var: syn::Ident = syn::Ident::new(&l_var, proc_macro2::Span::call_site());
+                let (lower_bound, upper_bound) =
+                    self.typed_bounds(&iter_var, lower_bound, upper_bound);
 
-            Stmt::Expr(Expr::Binary(binary), _) => {
-                // check if we have a PlusEq with the target variable
-                if let (Expr::Path(left), Expr::Lit(right)) = (&*binary.left, &*binary.right) {
-                    if left.path.segments[0].ident.to_string() == var_name {
-                        if let Lit::Int(int_lit) = &right.lit {
-                            return int_lit.base10_parse::<i32>().unwrap() == 1;
+                log::debug!("loop counters: {:?}", self.loop_vars);
+
+                // Create the appropriate range expression based on the operator. A
+                // loop counting down starts at the counter's initial value instead.
+                let (range, filtered_stmts): (syn::Expr, Vec<Stmt>) = match &cond.op {
+                    syn::BinOp::Lt(_) | syn::BinOp::Le(_) => {
+                        let range: syn::Expr = if let syn::BinOp::Lt(_) = &cond.op {
+                            syn::parse_quote!(#lower_bound..#upper_bound)
+                        } else {
+                            syn::parse_quote!(#lower_bound..=#upper_bound)
+                        };
+                        // The counter must be stepped by exactly one statement at the top
+                        // level of the body, with no statement after it reading the counter
+                        let steps: Vec<(usize, i32)> = while_loop
+                            .body
+                            .stmts
+                            .iter()
+                            .enumerate()
+                            .filter_map(|(index, stmt)| {
+                                Some((index, self.increment_step(stmt, &l_var)?))
+                            })
+                            .collect();
+                        let [(position, step)] = steps[..] else {
+                            return;
+                        };
+                        let reads_after =
+                            while_loop.body.stmts[position + 1..].iter().any(|stmt| {
+                                !matches!(stmt, Stmt::Expr(Expr::Path(_), Some(_)))
+                                    && count_ident(stmt, &iter_var) > 0
+                            });
+                        if reads_after {
+                            return;
+                        }
+                        let mut filtered_stmts = while_loop.body.stmts.clone();
+                        filtered_stmts.remove(position);
+                        // Counters stepping by more than one visit every step-th value
+                        let range = if step == 1 {
+                            range
+                        } else {
+                            let step = proc_macro2::Literal::i32_unsuffixed(step);
+                            syn::parse_quote!((#range).step_by(#step))
+                        };
+                        // `i += 1; continue;` becomes `continue;`, which steps the counter
+                        let mut body = syn::Block {
+                            brace_token: while_loop.body.brace_token,
+                            stmts: filtered_stmts,
+                        };
+                        let mut continue_steps = ContinueSteps {
+                            var_name: &l_var,
+                            step,
+                            label: while_loop.label.as_ref().map(|label| &label.name),
+                            unstepped: false,
+                        };
+                        continue_steps.visit_block_mut(&mut body);
+                        // Any other mutation of the counter, e.g. a conditional increment,
+                        // changes which values the loop visits
+                        if continue_steps.unstepped || is_reassigned(&body.stmts, &iter_var) {
+                            return;
                         }
+                        (range, body.stmts)
                     }
-                }
-                false
+                    syn::BinOp::Gt(_) | syn::BinOp::Ge(_) => {
+                        let Some((filtered_stmts, decrements_first)) =
+                            self.strip_decrement(&while_loop.body.stmts, &l_var)
+                        else {
+                            return;
+                        };
+                        let range: syn::Expr = match (&cond.op, decrements_first) {
+                            // while i > lo { i -= 1; ... } visits hi - 1 down to lo
+                            (syn::BinOp::Gt(_), true) => {
+                                syn::parse_quote!((#upper_bound..#lower_bound).rev())
+                            }
+                            // while i > lo { ...; i -= 1; } visits hi down to lo + 1
+                            (syn::BinOp::Gt(_), false) => {
+                                let after_bound: syn::Expr = match &upper_bound {
+                                    Expr::Lit(ExprLit {
+                                        lit: Lit::Int(int_lit),
+                                        ..
+                                    }) => {
+                                        // Bounds which cannot be parsed are left alone
+                                        let Some(value) = int_lit
+                                            .base10_parse::<i128>()
+                                            .ok()
+                                            .and_then(|value| value.checked_add(1))
+                                        else {
+                                            return;
+                                        };
+                                        let value = LitInt::new(
+                                            &format!("{}{}", value, int_lit.suffix()),
+                                            int_lit.span(),
+                                        );
+                                        syn::parse_quote!(#value)
+                                    }
+                                    _ => syn::parse_quote!(#upper_bound + 1),
+                                };
+                                syn::parse_quote!((#after_bound..=#lower_bound).rev())
+                            }
+                            // while i >= lo { ...; i -= 1; } visits hi down to lo
+                            (_, false) => {
+                                syn::parse_quote!((#upper_bound..=#lower_bound).rev())
+                            }
+                            _ => return,
+                        };
+                        (range, filtered_stmts)
+                    }
+                    _ => return, // Skip other operators
+                };
+
+                // Create a new block with the filtered statements, keeping their
+                // attributes
+                let new_body = syn::Block {
+                    brace_token: while_loop.body.brace_token,
+                    stmts: filtered_stmts,
+                };
+
+                // Create the for loop with the filtered body, carrying over the
+                // attributes of the while loop
+                let mut for_loop: syn::ExprForLoop = syn::parse_quote! {
+                    for #iter_var in #range {}
+                };
+                for_loop.attrs = while_loop.attrs.clone();
+                for_loop.body = new_body;
+                let for_loop = Expr::ForLoop(for_loop);
+                let event = Event::new("while_to_for", while_loop.while_token.span);
+
+                // Replace the while loop with the for loop
+                *stmt = Stmt::Expr(for_loop, None);
+                self.converted += 1;
+                self.events.push(event);
             }
-            // Check for expressions with semicolons
-            _ => false,
         }
     }
 }
 
+// Returns the positive literal step if a statement is `var = var <op> lit`,
+// `var = lit + var` or `var <op>= lit`, ignoring parentheses and casts of the literal
+fn step_stmt(
+    stmt: &Stmt,
+    var_name: &str,
+    is_op: impl Fn(&BinOp) -> bool,
+    is_assign_op: impl Fn(&BinOp) -> bool,
+) -> Option<i32> {
+    let is_var = |expr: &Expr| path_ident(expr).is_some_and(|ident| ident == var_name);
+    let step = |expr: &Expr| match strip_casts(expr) {
+        Expr::Lit(lit) => positive_int(&lit.lit),
+        _ => None,
+    };
+    match stmt {
+        // Check for assignment expressions (i = i + 1)
+        Stmt::Expr(Expr::Assign(assign), _) if is_var(&assign.left) => {
+            let Expr::Binary(binary) = strip_parens(&assign.right) else {
+                return None;
+            };
+            if !is_op(&binary.op) {
+                None
+            } else if is_var(&binary.left) {
+                step(&binary.right)
+            } else if matches!(binary.op, BinOp::Add(_)) && is_var(&binary.right) {
+                step(&binary.left)
+            } else {
+                None
+            }
+        }
+        // Check for compound assignments (i += 1)
+        Stmt::Expr(Expr::Binary(binary), _) if is_assign_op(&binary.op) && is_var(&binary.left) => {
+            step(&binary.right)
+        }
+        _ => None,
+    }
+}
+
+// Returns the value of a positive integer literal
+fn positive_int(lit: &Lit) -> Option<i32> {
+    if let Lit::Int(int_lit) = lit {
+        return int_lit
+            .base10_parse::<i32>()
+            .ok()
+            .filter(|&value| value > 0);
+    }
+    None
+}
+
+/// Looks for control flow in a while loop body that a `for` loop cannot express:
+/// `break` with a value, `return`, `?`, or a labeled `break` or `continue` to the
+/// loop itself, whose label is dropped by the rewrite.
+struct LoopEscapes<'a> {
+    label: Option<&'a Lifetime>,
+    /// How many loops nested in the body are being visited.
+    depth: usize,
+    found: bool,
+}
+
+impl Visit<'_> for LoopEscapes<'_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        let targets_loop = |label: &Option<Lifetime>| {
+            label
+                .as_ref()
+                .is_some_and(|label| Some(label) == self.label)
+        };
+        match expr {
+            Expr::Break(ExprBreak { label, expr, .. }) => {
+                self.found |= targets_loop(label) || (self.depth == 0 && expr.is_some());
+            }
+            Expr::Continue(ExprContinue { label, .. }) => self.found |= targets_loop(label),
+            Expr::Return(_) | Expr::Try(_) => self.found = true,
+            // A return in a closure leaves the closure only
+            Expr::Closure(_) => return,
+            _ => {}
+        }
+        let is_loop = matches!(expr, Expr::ForLoop(_) | Expr::While(_) | Expr::Loop(_));
+        self.depth += usize::from(is_loop);
+        syn::visit::visit_expr(self, expr);
+        self.depth -= usize::from(is_loop);
+    }
+
+    fn visit_item(&mut self, _: &Item) {}
+}
+
+/// Removes the increments of a counter directly before a `continue` of its loop,
+/// which a `for` loop over the counter's range makes redundant.
+struct ContinueSteps<'a> {
+    var_name: &'a str,
+    step: i32,
+    label: Option<&'a Lifetime>,
+    /// Whether a `continue` of the loop is not directly preceded by an increment, so
+    /// it skips the loop's own increment.
+    unstepped: bool,
+}
+
+impl ContinueSteps<'_> {
+    fn continues(&self, label: &Option<Lifetime>) -> bool {
+        label.is_none() || label.as_ref() == self.label
+    }
+}
+
+impl VisitMut for ContinueSteps<'_> {
+    fn visit_block_mut(&mut self, block: &mut syn::Block) {
+        let mut position = 0;
+        while position < block.stmts.len() {
+            let is_step = step_stmt(
+                &block.stmts[position],
+                self.var_name,
+                |op| matches!(op, BinOp::Add(_)),
+                |op| matches!(op, BinOp::AddAssign(_)),
+            ) == Some(self.step);
+            let stepped = is_step
+                && matches!(block.stmts.get(position + 1),
+                    Some(Stmt::Expr(Expr::Continue(ExprContinue { label, .. }), _))
+                        if self.continues(label));
+            if stepped {
+                // The `continue` now at `position` is accounted for
+                block.stmts.remove(position);
+            } else {
+                self.visit_stmt_mut(&mut block.stmts[position]);
+            }
+            position += 1;
+        }
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Continue(ExprContinue { label, .. }) => self.unstepped |= self.continues(label),
+            // A `continue` in a nested loop or closure does not step this loop's counter
+            Expr::ForLoop(_) | Expr::While(_) | Expr::Loop(_) | Expr::Closure(_) => {}
+            _ => syn::visit_mut::visit_expr_mut(self, expr),
+        }
+    }
+
+    fn visit_item_mut(&mut self, _: &mut Item) {}
+}
+
+/// Gives the integer literal `expr` the suffix of the primitive type `ty`, unless it
+/// has one already.
+fn with_suffix(expr: Expr, ty: &Type) -> Expr {
+    match (&expr, primitive_name(ty)) {
+        (
+            Expr::Lit(ExprLit {
+                lit: Lit::Int(int_lit),
+                ..
+            }),
+            Some(name),
+        ) if int_lit.suffix().is_empty() => {
+            let lit = LitInt::new(&format!("{int_lit}{name}"), int_lit.span());
+            syn::parse_quote!(#lit)
+        }
+        _ => expr,
+    }
+}
+
+/// Returns whether the loop is a `while let`, e.g. `while let Some(x) = it.next()`.
+fn is_while_let(while_loop: &ExprWhile) -> bool {
+    matches!(strip_parens(&while_loop.cond), Expr::Let(_))
+}
+
+// Checks if the body of a while loop leaves it other than by a plain `break` or
+// `continue`
+fn escapes_loop(while_loop: &ExprWhile) -> bool {
+    let mut escapes = LoopEscapes {
+        label: while_loop.label.as_ref().map(|label| &label.name),
+        depth: 0,
+        found: false,
+    };
+    escapes.visit_block(&while_loop.body);
+    escapes.found
+}
+
 impl Visit<'_> for WhileLoopReplacer {
     fn visit_expr_while(&mut self, whileloop: &ExprWhile) {
-        println!("Found a while loop");
         self.record_if_whileloop(whileloop);
     }
 }
 impl VisitMut for WhileLoopReplacer {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        let loop_vars = std::mem::take(&mut self.loop_vars);
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        self.loop_vars = loop_vars;
+        self.types = types;
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item_fn: &mut ImplItemFn) {
+        let types = std::mem::replace(&mut self.types, local_types(&item_fn.sig, &item_fn.block));
+        let loop_vars = std::mem::take(&mut self.loop_vars);
+        syn::visit_mut::visit_impl_item_fn_mut(self, item_fn);
+        self.loop_vars = loop_vars;
+        self.types = types;
+    }
+
+    /// Visits the body of a loop without the counters it reassigns, whose initial
+    /// values only hold in its first iteration.
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        let body = match expr {
+            Expr::While(ExprWhile { body, .. })
+            | Expr::ForLoop(ExprForLoop { body, .. })
+            | Expr::Loop(ExprLoop { body, .. }) => body,
+            _ => return syn::visit_mut::visit_expr_mut(self, expr),
+        };
+        let loop_vars = self.loop_vars.clone();
+        self.loop_vars
+            .retain(|name, _| !is_reassigned(&body.stmts, &counter_ident(name)));
+        syn::visit_mut::visit_expr_mut(self, expr);
+        self.loop_vars = loop_vars;
+    }
+
     fn visit_stmt_mut(&mut self, stmt: &mut Stmt) {
         if let Stmt::Local(local) = stmt {
-            let mut variable_name = String::new();
-            if let Pat::Type(t) = &local.pat {
-                if let Pat::Ident(ident) = &*t.pat {
-                    variable_name = ident.ident.to_string();
-                }
-            }
-            if let Some(local_init) = local.init.as_ref() {
-                if let Expr::Cast(cast_expr) = &*local_init.expr {
-                    if let Expr::Lit(lit) = &*cast_expr.expr {
-                        if let ExprLit {
+            let pat = match &local.pat {
+                Pat::Type(t) => &*t.pat,
+                pat => pat,
+            };
+            if let Pat::Ident(ident) = pat {
+                let variable_name = ident.ident.to_string();
+                // A later declaration shadows the counter, whatever its value
+                self.loop_vars.remove(&variable_name);
+                if let (Pat::Type(_), Some(local_init)) = (&local.pat, local.init.as_ref()) {
+                    if let Expr::Cast(cast_expr) = &*local_init.expr {
+                        if let Expr::Lit(ExprLit {
                             lit: Lit::Int(int_lit),
                             ..
-                        } = lit
+                        }) = &*cast_expr.expr
                         {
-                            let int_lit = int_lit.base10_parse::<i32>().unwrap();
-                            self.loop_vars.insert(variable_name.clone(), int_lit);
+                            self.loop_vars.insert(variable_name, int_lit.clone());
                         }
                     }
                 }
             }
         }
 
-        if let Stmt::Expr(Expr::While(while_loop), _) = stmt {
-            if let Expr::Binary(cond) = &*while_loop.cond {
-                // Get the left variable (iterator variable)
-                let l_var = if let Expr::Path(left) = &*cond.left {
-                    left.path.segments[0].ident.to_string()
-                } else {
-                    return;
-                };
-
-                if self.loop_vars.contains_key(&l_var) {
-                    // Create the lower bound
-                    let lower_bound: syn::Expr = if self.loop_vars.contains_key(&l_var) {
-                        let value = self.loop_vars.get(&l_var).unwrap();
-                        syn::parse_str::<syn::Expr>(&value.to_string()).unwrap()
-                    } else {
-                        let ident = syn::Ident::new(&l_var, proc_macro2::Span::call_site());
-                        syn::parse_quote!(#ident)
-                    };
-
-                    // Handle both variable and literal upper bounds
-                    let upper_bound: syn::Expr = match &*cond.right {
-                        Expr::Path(right) => {
-                            let r_var = right.path.segments[0].ident.to_string();
-                            if self.loop_vars.contains_key(&r_var) {
-                                let value: &i32 = self.loop_vars.get(&r_var).unwrap();
-                                syn::parse_str::<syn::Expr>(&value.to_string()).unwrap()
-                            } else {
-                                let ident: syn::Ident =
-                                    syn::Ident::new(&r_var, proc_macro2::Span::call_site());
-                                syn::parse_quote!(#ident)
-                            }
-                        }
-                        Expr::Lit(lit) => {
-                            if let Lit::Int(int_lit) = &lit.lit {
-                                syn::parse_str::<syn::Expr>(&int_lit.to_string()).unwrap()
-                            } else {
-                                return;
-                            }
-                        }
-                        _ => return,
-                    };
-
-                    let iter_var: syn::Ident =
-                        syn::Ident::new(&l_var, proc_macro2::Span::call_site());
-
-                    println!("HASHMAP: {:?}", self.loop_vars);
-
-                    // Create the appropriate range expression based on the operator
-                    let range: syn::Expr = match &cond.op {
-                        syn::BinOp::Lt(_) => syn::parse_quote!(#lower_bound..#upper_bound),
-                        syn::BinOp::Le(_) => syn::parse_quote!(#lower_bound..=#upper_bound),
-                        _ => return, // Skip other operators
-                    };
-
-                    let filtered_stmts: Vec<Stmt> = while_loop
-                        .body
-                        .stmts
-                        .iter()
-                        .filter(|stmt| !self.is_increment_stmt(stmt, &l_var))
-                        .copied()
-                        .collect();
-
-                    // Create a new block with the filtered statements
-                    let new_body: syn::Block = syn::parse_quote! {{
-                        #(#filtered_stmts)*
-                    }};
-
-                    // Create the for loop with the filtered body
-                    let for_loop: syn::Expr = syn::parse_quote! {
-                        for #iter_var in #range #new_body
-                    };
-
-                    // Replace the while loop with the for loop
-                    *stmt = Stmt::Expr(for_loop, None);
-                }
-            }
-
-            // Continue visiting other statements normally
-            syn::visit_mut::visit_stmt_mut(self, stmt);
-        }
+        // Convert the loops nested in the statement first, so that both levels of
+        // nested counting loops are rewritten
+        syn::visit_mut::visit_stmt_mut(self, stmt);
+        self.replace_if_counting_loop(stmt);
+        // A counter reassigned, other than by a converted loop, no longer holds its
+        // initial value
+        let stmts = std::slice::from_ref(&*stmt);
+        self.loop_vars
+            .retain(|name, _| !is_reassigned(stmts, &counter_ident(name)));
     }
 }
 
+/// Returns the identifier of a recorded loop counter.
+fn counter_ident(name: &str) -> Ident {
+    Ident::new(name, proc_macro2::Span::call_site())
+}
+
 impl Pass for WhileLoopReplacer {
-    fn bind(&mut self, mut monad: MonadicAst) -> MonadicAst {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
         self.visit_file_mut(&mut monad.ast);
-        monad
+        monad.stats.while_loops_converted += self.converted;
+        monad.events.append(&mut self.events);
+        Ok(monad)
     }
 }
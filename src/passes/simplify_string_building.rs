@@ -0,0 +1,323 @@
+//! Replaces byte buffers that are built up one byte at a time and then interpreted
+//! as a string with a `String` built via `String::push`, e.g.
+//!
+//! ```ignore
+//! let mut buf: Vec<u8> = Vec::new();
+//! for _ in 0..n { buf.push(b'*'); }
+//! let s = String::from_utf8(buf).unwrap();
+//! ```
+//!
+//! becomes `let mut buf = String::new(); ... buf.push('*'); ... let s = buf;`.
+//!
+//! The rewrite only fires when every pushed value is an ASCII byte literal, in
+//! which case the buffer is trivially valid UTF-8. Buffers that match the pattern
+//! but push other values are reported as diagnostics instead.
+
+use crate::monad::ast::Pass;
+use crate::monad::diagnostic::Diagnostic;
+use crate::monad::error::PassError;
+use crate::passes::utils::{count_ident, is_ident, path_ident, same_tokens, strip_parens};
+use crate::MonadicAst;
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, Block, Expr, ExprCall, ExprMethodCall, ExprReference, Ident, ItemFn, Lit, LitChar,
+    Local, LocalInit, Pat, PatType, Stmt, Type,
+};
+
+const PASS: &str = "simplify_string_building";
+
+#[derive(Default)]
+pub struct StringBuildSimplify {
+    /// Name of the function currently being visited, for diagnostics.
+    current_fn: Option<Ident>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// The rewritable ways of interpreting the finished buffer as a string.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Conversion {
+    /// `String::from_utf8(buf).unwrap()` and friends, replaced by `buf`.
+    Owned,
+    /// `std::str::from_utf8(&buf).unwrap()`, replaced by `buf.as_str()`.
+    Borrowed,
+}
+
+/// Tallies how a byte buffer is used after its declaration.
+struct BufferUses<'a> {
+    buffer: &'a Ident,
+    /// Occurrences of the buffer accounted for by a recognized use.
+    recognized: usize,
+    /// Whether every pushed value is an ASCII byte literal.
+    ascii_only: bool,
+    /// Whether at least one push happens inside a loop body.
+    pushed_in_loop: bool,
+    /// Number of conversions which can be rewritten.
+    conversions: usize,
+    /// Number of conversions which are recognized but cannot be rewritten.
+    other_conversions: usize,
+    loop_depth: usize,
+}
+
+impl StringBuildSimplify {
+    /// Returns the buffer identifier and initializer of `let mut buf: Vec<u8> = ...;`
+    /// if the initializer creates an empty vector.
+    fn byte_buffer_decl(local: &Local) -> Option<(&Ident, &Expr)> {
+        let Pat::Type(PatType { pat, ty, .. }) = &local.pat else {
+            return None;
+        };
+        let Pat::Ident(pat_ident) = pat.as_ref() else {
+            return None;
+        };
+        let Some(LocalInit {
+            expr,
+            diverge: None,
+            ..
+        }) = &local.init
+        else {
+            return None;
+        };
+        let byte_vec: Type = parse_quote!(Vec<u8>);
+        let is_empty_vec = match strip_parens(expr) {
+            Expr::Call(ExprCall { func, args, .. }) => {
+                let func: &Expr = func;
+                let new: Expr = parse_quote!(Vec::new);
+                let with_capacity: Expr = parse_quote!(Vec::with_capacity);
+                (args.is_empty() && same_tokens(func, &new))
+                    || (args.len() == 1 && same_tokens(func, &with_capacity))
+            }
+            Expr::Macro(mac) => mac.mac.path.is_ident("vec") && mac.mac.tokens.is_empty(),
+            _ => false,
+        };
+        (pat_ident.mutability.is_some() && same_tokens(ty.as_ref(), &byte_vec) && is_empty_vec)
+            .then_some((&pat_ident.ident, expr.as_ref()))
+    }
+
+    /// Rewrites the uses of the buffer declared at `block.stmts[index]`, if the
+    /// declaration starts the byte-buffer-to-string pattern.
+    fn simplify_buffer(&mut self, block: &mut Block, index: usize) {
+        let Stmt::Local(local) = &block.stmts[index] else {
+            return;
+        };
+        let Some((buffer, init)) = Self::byte_buffer_decl(local) else {
+            return;
+        };
+        let buffer = buffer.clone();
+        let rest = &block.stmts[index + 1..];
+
+        let mut uses = BufferUses {
+            buffer: &buffer,
+            recognized: 0,
+            ascii_only: true,
+            pushed_in_loop: false,
+            conversions: 0,
+            other_conversions: 0,
+            loop_depth: 0,
+        };
+        rest.iter().for_each(|stmt| uses.visit_stmt(stmt));
+        let total: usize = rest.iter().map(|stmt| count_ident(stmt, &buffer)).sum();
+        let is_pattern = uses.recognized == total
+            && uses.pushed_in_loop
+            && uses.conversions + uses.other_conversions > 0;
+        if !is_pattern {
+            return;
+        }
+        if !uses.ascii_only || uses.other_conversions > 0 {
+            self.diagnostics.push(Diagnostic::new(
+                PASS,
+                self.current_fn.as_ref(),
+                format!(
+                    "`{}` is built byte-by-byte and interpreted as a string; \
+                     consider building a `String` instead",
+                    buffer
+                ),
+            ));
+            return;
+        }
+
+        let new_init: Expr = match strip_parens(init) {
+            Expr::Call(ExprCall { args, .. }) if args.len() == 1 => {
+                let capacity = &args[0];
+                parse_quote!(String::with_capacity(#capacity))
+            }
+            _ => parse_quote!(String::new()),
+        };
+        block.stmts[index] = parse_quote!(let mut #buffer = #new_init;);
+        let mut rewriter = BufferRewriter { buffer: &buffer };
+        block.stmts[index + 1..]
+            .iter_mut()
+            .for_each(|stmt| rewriter.visit_stmt_mut(stmt));
+    }
+}
+
+/// Returns the ASCII character of a byte literal such as `b'a'`.
+fn ascii_byte_literal(expr: &Expr) -> Option<char> {
+    match strip_parens(expr) {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Byte(byte) if byte.value().is_ascii() => Some(char::from(byte.value())),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns whether `expr` is `buffer` (when `by_ref` is false) or `&buffer`.
+fn is_buffer_arg(expr: &Expr, buffer: &Ident, by_ref: bool) -> bool {
+    match (strip_parens(expr), by_ref) {
+        (
+            Expr::Reference(ExprReference {
+                mutability: None,
+                expr,
+                ..
+            }),
+            true,
+        ) => is_ident(expr, buffer),
+        (expr, false) => is_ident(expr, buffer),
+        _ => false,
+    }
+}
+
+/// Returns whether `expr` is a conversion call such as `String::from_utf8(buf)`,
+/// without any unwrapping of its result.
+fn is_conversion_call(expr: &Expr, buffer: &Ident) -> bool {
+    let Expr::Call(ExprCall { func, args, .. }) = strip_parens(expr) else {
+        return false;
+    };
+    let Expr::Path(func) = func.as_ref() else {
+        return false;
+    };
+    let segments: Vec<String> = func
+        .path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    let by_ref = match segments
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [.., "String", "from_utf8"] => false,
+        [.., "String", "from_utf8_lossy"] | [.., "str", "from_utf8"] => true,
+        _ => return false,
+    };
+    args.len() == 1 && is_buffer_arg(&args[0], buffer, by_ref)
+}
+
+/// Returns the rewritable conversion performed by `expr`, if any.
+fn conversion(expr: &Expr, buffer: &Ident) -> Option<Conversion> {
+    let Expr::MethodCall(ExprMethodCall {
+        receiver, method, ..
+    }) = strip_parens(expr)
+    else {
+        return None;
+    };
+    if !is_conversion_call(receiver, buffer) {
+        return None;
+    }
+    let Expr::Call(ExprCall { func, .. }) = strip_parens(receiver) else {
+        return None;
+    };
+    let Expr::Path(func) = func.as_ref() else {
+        return None;
+    };
+    let function = func.path.segments.last()?.ident.to_string();
+    let is_str = func.path.segments.len() >= 2
+        && func.path.segments[func.path.segments.len() - 2].ident == "str";
+    match (function.as_str(), method.to_string().as_str()) {
+        ("from_utf8", "unwrap" | "expect") if is_str => Some(Conversion::Borrowed),
+        ("from_utf8", "unwrap" | "expect") => Some(Conversion::Owned),
+        ("from_utf8_lossy", "into_owned" | "to_string") => Some(Conversion::Owned),
+        _ => None,
+    }
+}
+
+impl Visit<'_> for BufferUses<'_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if conversion(expr, self.buffer).is_some() {
+            self.conversions += 1;
+            self.recognized += 1;
+            return;
+        }
+        if is_conversion_call(expr, self.buffer) {
+            self.other_conversions += 1;
+            self.recognized += 1;
+            return;
+        }
+        match expr {
+            Expr::MethodCall(ExprMethodCall {
+                receiver,
+                method,
+                args,
+                ..
+            }) if method == "push" && args.len() == 1 && is_ident(receiver, self.buffer) => {
+                self.recognized += 1;
+                self.ascii_only &= ascii_byte_literal(&args[0]).is_some();
+                self.pushed_in_loop |= self.loop_depth > 0;
+                self.visit_expr(&args[0]);
+            }
+            Expr::ForLoop(_) | Expr::While(_) | Expr::Loop(_) => {
+                self.loop_depth += 1;
+                syn::visit::visit_expr(self, expr);
+                self.loop_depth -= 1;
+            }
+            _ => syn::visit::visit_expr(self, expr),
+        }
+    }
+}
+
+/// Rewrites the recognized uses of a byte buffer into their `String` equivalents.
+struct BufferRewriter<'a> {
+    buffer: &'a Ident,
+}
+
+impl VisitMut for BufferRewriter<'_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        let buffer = self.buffer;
+        match conversion(expr, buffer) {
+            Some(Conversion::Owned) => *expr = parse_quote!(#buffer),
+            Some(Conversion::Borrowed) => *expr = parse_quote!(#buffer.as_str()),
+            None => {
+                if let Expr::MethodCall(ExprMethodCall {
+                    receiver,
+                    method,
+                    args,
+                    ..
+                }) = expr
+                {
+                    if method == "push" && path_ident(receiver) == Some(buffer) {
+                        if let Some(c) = args.first().and_then(ascii_byte_literal) {
+                            let c = LitChar::new(c, proc_macro2::Span::call_site());
+                            args[0] = parse_quote!(#c);
+                        }
+                    }
+                }
+                syn::visit_mut::visit_expr_mut(self, expr);
+            }
+        }
+    }
+}
+
+impl VisitMut for StringBuildSimplify {
+    fn visit_item_fn_mut(&mut self, item_fn: &mut ItemFn) {
+        let enclosing = self.current_fn.replace(item_fn.sig.ident.clone());
+        syn::visit_mut::visit_item_fn_mut(self, item_fn);
+        self.current_fn = enclosing;
+    }
+
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        syn::visit_mut::visit_block_mut(self, block);
+        for index in 0..block.stmts.len() {
+            self.simplify_buffer(block, index);
+        }
+    }
+}
+
+impl Pass for StringBuildSimplify {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        self.visit_file_mut(&mut monad.ast);
+        monad.diagnostics.append(&mut self.diagnostics);
+        Ok(monad)
+    }
+}
@@ -0,0 +1,616 @@
+//! Syntactic helpers shared by the AST passes.
+
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::ToTokens;
+use std::collections::{HashMap, HashSet};
+use syn::visit::Visit;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, BinOp, Block, Expr, ExprBinary, ExprCall, ExprCast, ExprLit, ExprMethodCall,
+    ExprParen, ExprPath, GenericArgument, Ident, ItemFn, ItemStruct, Lit, Macro, Pat, PatIdent,
+    PatType, PathArguments, Signature, Stmt, Type, TypeArray, TypePath, TypePtr, TypeReference,
+    TypeSlice, Visibility,
+};
+
+/// Returns the expression wrapped by any number of redundant parentheses.
+pub fn strip_parens(mut expr: &Expr) -> &Expr {
+    while let Expr::Paren(ExprParen { expr: inner, .. }) = expr {
+        expr = inner;
+    }
+    expr
+}
+
+/// Returns the expression with any casts removed, e.g. `n as size_t` yields `n`.
+pub fn strip_casts(mut expr: &Expr) -> &Expr {
+    while let Expr::Cast(ExprCast { expr: inner, .. }) = strip_parens(expr) {
+        expr = inner;
+    }
+    strip_parens(expr)
+}
+
+/// Returns the identifier of a plain single-segment path expression, e.g. `x`.
+pub fn path_ident(expr: &Expr) -> Option<&Ident> {
+    match strip_parens(expr) {
+        Expr::Path(ExprPath {
+            qself: None, path, ..
+        }) => path.get_ident(),
+        _ => None,
+    }
+}
+
+/// Returns whether the expression is the plain path `ident`.
+pub fn is_ident(expr: &Expr, ident: &Ident) -> bool {
+    path_ident(expr).is_some_and(|found| found == ident)
+}
+
+/// Returns whether both nodes print to the same token stream, ignoring spans.
+pub fn same_tokens<A: ToTokens, B: ToTokens>(a: &A, b: &B) -> bool {
+    a.to_token_stream().to_string() == b.to_token_stream().to_string()
+}
+
+/// Counts every occurrence of `ident` in the node's tokens, including occurrences
+/// inside macro invocations and as field names, so callers err on the side of
+/// overcounting uses.
+pub fn count_ident<T: ToTokens>(node: &T, ident: &Ident) -> usize {
+    fn count(tokens: TokenStream, ident: &Ident) -> usize {
+        tokens
+            .into_iter()
+            .map(|token| match token {
+                TokenTree::Ident(found) => usize::from(&found == ident),
+                TokenTree::Group(group) => count(group.stream(), ident),
+                _ => 0,
+            })
+            .sum()
+    }
+    count(node.to_token_stream(), ident)
+}
+
+/// Generates identifiers which do not collide with any identifier already present
+/// in a syntax tree, to avoid capturing names when introducing new bindings.
+#[derive(Default)]
+pub struct NameGen {
+    taken: HashSet<String>,
+}
+
+impl NameGen {
+    pub fn new<T: ToTokens>(node: &T) -> Self {
+        fn collect(tokens: TokenStream, taken: &mut HashSet<String>) {
+            for token in tokens {
+                match token {
+                    TokenTree::Ident(ident) => {
+                        taken.insert(ident.to_string());
+                    }
+                    TokenTree::Group(group) => collect(group.stream(), taken),
+                    _ => {}
+                }
+            }
+        }
+        let mut taken = HashSet::new();
+        collect(node.to_token_stream(), &mut taken);
+        Self { taken }
+    }
+
+    /// Returns `base` if it is unused, and `base_1`, `base_2`, ... otherwise.
+    pub fn fresh(&mut self, base: &str) -> Ident {
+        let name = (0..)
+            .map(|n| match n {
+                0 => base.to_string(),
+                n => format!("{}_{}", base, n),
+            })
+            .find(|name| !self.taken.contains(name))
+            .unwrap();
+        self.taken.insert(name.clone());
+        Ident::new(&name, Span::call_site())
+    }
+}
+
+/// Returns whether the operator is a compound assignment such as `+=`.
+pub fn is_assign_op(op: &BinOp) -> bool {
+    matches!(
+        op,
+        BinOp::AddAssign(_)
+            | BinOp::SubAssign(_)
+            | BinOp::MulAssign(_)
+            | BinOp::DivAssign(_)
+            | BinOp::RemAssign(_)
+            | BinOp::BitXorAssign(_)
+            | BinOp::BitAndAssign(_)
+            | BinOp::BitOrAssign(_)
+            | BinOp::ShlAssign(_)
+            | BinOp::ShrAssign(_)
+    )
+}
+
+/// Returns the binding power of the expression's outermost operator, where a
+/// higher value binds more tightly.
+fn precedence(expr: &Expr) -> u8 {
+    match expr {
+        Expr::Closure(_) | Expr::Return(_) | Expr::Break(_) | Expr::Let(_) | Expr::Yield(_) => 0,
+        Expr::Assign(_) => 1,
+        Expr::Binary(binary) if is_assign_op(&binary.op) => 1,
+        Expr::Range(_) => 2,
+        Expr::Binary(binary) => match binary.op {
+            BinOp::Or(_) => 3,
+            BinOp::And(_) => 4,
+            BinOp::Eq(_)
+            | BinOp::Ne(_)
+            | BinOp::Lt(_)
+            | BinOp::Le(_)
+            | BinOp::Gt(_)
+            | BinOp::Ge(_) => 5,
+            BinOp::BitOr(_) => 6,
+            BinOp::BitXor(_) => 7,
+            BinOp::BitAnd(_) => 8,
+            BinOp::Shl(_) | BinOp::Shr(_) => 9,
+            BinOp::Add(_) | BinOp::Sub(_) => 10,
+            _ => 11,
+        },
+        Expr::Cast(_) => 12,
+        Expr::Unary(_) | Expr::Reference(_) => 13,
+        _ => 14,
+    }
+}
+
+/// Inserts the parentheses required by operator precedence around subexpressions,
+/// e.g. after substituting `x + 1` for `f(x)` in `f(x) * 2`. Passes that splice
+/// expressions into existing ones run this over the nodes they rewrote.
+pub struct Parenthesize;
+
+impl Parenthesize {
+    fn wrap_below(expr: &mut Expr, min: u8) {
+        if precedence(expr) < min {
+            let inner = std::mem::replace(expr, Expr::Verbatim(TokenStream::new()));
+            *expr = Expr::Paren(ExprParen {
+                attrs: Vec::new(),
+                paren_token: Default::default(),
+                expr: Box::new(inner),
+            });
+        }
+    }
+}
+
+impl VisitMut for Parenthesize {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        syn::visit_mut::visit_expr_mut(self, expr);
+        let own = precedence(expr);
+        match expr {
+            Expr::Binary(binary) if own > 1 => {
+                // Comparisons are non-associative; everything else is left associative.
+                let left = if own == 5 { own + 1 } else { own };
+                Self::wrap_below(&mut binary.left, left);
+                Self::wrap_below(&mut binary.right, own + 1);
+            }
+            Expr::Cast(cast) => Self::wrap_below(&mut cast.expr, 12),
+            Expr::Unary(unary) => Self::wrap_below(&mut unary.expr, 13),
+            Expr::Reference(reference) => Self::wrap_below(&mut reference.expr, 13),
+            Expr::MethodCall(call) => Self::wrap_below(&mut call.receiver, 14),
+            Expr::Field(field) => Self::wrap_below(&mut field.base, 14),
+            Expr::Index(index) => Self::wrap_below(&mut index.expr, 14),
+            Expr::Call(call) => Self::wrap_below(&mut call.func, 14),
+            Expr::Try(try_expr) => Self::wrap_below(&mut try_expr.expr, 14),
+            _ => {}
+        }
+    }
+}
+
+/// Returns whether evaluating the expression can have no side effects other than
+/// panicking, i.e. it only reads variables and applies operators to them.
+pub fn is_pure(expr: &Expr) -> bool {
+    match expr {
+        Expr::Path(_) | Expr::Lit(_) => true,
+        Expr::Paren(paren) => is_pure(&paren.expr),
+        Expr::Field(field) => is_pure(&field.base),
+        Expr::Cast(cast) => is_pure(&cast.expr),
+        Expr::Reference(reference) => is_pure(&reference.expr),
+        Expr::Unary(unary) => is_pure(&unary.expr),
+        Expr::Index(index) => is_pure(&index.expr) && is_pure(&index.index),
+        Expr::Binary(binary) => {
+            !is_assign_op(&binary.op) && is_pure(&binary.left) && is_pure(&binary.right)
+        }
+        _ => false,
+    }
+}
+
+/// Macros which only read their arguments.
+const READING_MACROS: &[&str] = &[
+    "print",
+    "println",
+    "eprint",
+    "eprintln",
+    "format",
+    "panic",
+    "assert",
+    "assert_eq",
+    "assert_ne",
+    "debug_assert",
+    "debug_assert_eq",
+    "debug_assert_ne",
+];
+
+/// Returns whether any of the statements assigns to `ident` or borrows it mutably,
+/// so a declaration of `ident` preceding them must stay `mut`. Any mention inside
+/// of a macro other than the formatting and assertion macros counts as a mutation.
+pub fn is_reassigned(stmts: &[Stmt], ident: &Ident) -> bool {
+    struct Finder<'a> {
+        ident: &'a Ident,
+        found: bool,
+    }
+    impl Visit<'_> for Finder<'_> {
+        fn visit_macro(&mut self, mac: &Macro) {
+            let reads = mac
+                .path
+                .get_ident()
+                .is_some_and(|name| READING_MACROS.contains(&name.to_string().as_str()));
+            self.found |= !reads && count_ident(&mac.tokens, self.ident) > 0;
+        }
+
+        fn visit_expr(&mut self, expr: &Expr) {
+            let place = match expr {
+                Expr::Assign(assign) => Some(&assign.left),
+                Expr::Binary(binary) if is_assign_op(&binary.op) => Some(&binary.left),
+                Expr::Reference(reference) if reference.mutability.is_some() => {
+                    Some(&reference.expr)
+                }
+                _ => None,
+            };
+            self.found |= place.is_some_and(|place| is_ident(place, self.ident));
+            syn::visit::visit_expr(self, expr);
+        }
+    }
+    let mut finder = Finder {
+        ident,
+        found: false,
+    };
+    for stmt in stmts {
+        finder.visit_stmt(stmt);
+    }
+    finder.found
+}
+
+/// Returns whether `ident` is the receiver of a method call in the statements,
+/// which may borrow it mutably.
+pub fn is_receiver(stmts: &[Stmt], ident: &Ident) -> bool {
+    struct Finder<'a> {
+        ident: &'a Ident,
+        found: bool,
+    }
+    impl Visit<'_> for Finder<'_> {
+        fn visit_expr_method_call(&mut self, call: &ExprMethodCall) {
+            self.found |= is_ident(&call.receiver, self.ident);
+            syn::visit::visit_expr_method_call(self, call);
+        }
+    }
+    let mut finder = Finder {
+        ident,
+        found: false,
+    };
+    for stmt in stmts {
+        finder.visit_stmt(stmt);
+    }
+    finder.found
+}
+
+/// Returns whether removing the function could break code outside of the file.
+pub fn is_exported(item_fn: &ItemFn) -> bool {
+    !matches!(item_fn.vis, Visibility::Inherited)
+        || item_fn.sig.abi.is_some()
+        || item_fn
+            .attrs
+            .iter()
+            .any(|attr| attr.path().is_ident("no_mangle") || attr.path().is_ident("export_name"))
+}
+
+/// Returns whether the struct derives `Copy`.
+pub fn derives_copy(item: &ItemStruct) -> bool {
+    let mut copy = false;
+    for attr in item
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("derive"))
+    {
+        let _ = attr.parse_nested_meta(|meta| {
+            copy |= meta.path.is_ident("Copy");
+            Ok(())
+        });
+    }
+    copy
+}
+
+/// Returns the declared types of a function's parameters and annotated locals.
+/// Names declared more than once with different types are left out, since this
+/// does not track scopes.
+pub fn local_types(sig: &Signature, block: &Block) -> HashMap<Ident, Type> {
+    struct Collector {
+        types: HashMap<Ident, Type>,
+        ambiguous: HashSet<Ident>,
+    }
+    impl Collector {
+        fn record(&mut self, pat: &Pat, ty: &Type) {
+            if let Pat::Ident(PatIdent { ident, .. }) = pat {
+                match self.types.get(ident) {
+                    Some(existing) if !same_tokens(existing, ty) => {
+                        self.ambiguous.insert(ident.clone());
+                    }
+                    _ => {
+                        self.types.insert(ident.clone(), ty.clone());
+                    }
+                }
+            }
+        }
+    }
+    impl Visit<'_> for Collector {
+        fn visit_pat_type(&mut self, pat_type: &PatType) {
+            self.record(&pat_type.pat, &pat_type.ty);
+            syn::visit::visit_pat_type(self, pat_type);
+        }
+    }
+    let mut collector = Collector {
+        types: HashMap::new(),
+        ambiguous: HashSet::new(),
+    };
+    collector.visit_signature(sig);
+    collector.visit_block(block);
+    let Collector {
+        mut types,
+        ambiguous,
+    } = collector;
+    types.retain(|ident, _| !ambiguous.contains(ident));
+    types
+}
+
+/// Returns the name of a primitive type such as `u32`, if `ty` is one.
+pub fn primitive_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(TypePath { qself: None, path }) => {
+            path.get_ident().map(|ident| ident.to_string())
+        }
+        Type::Paren(paren) => primitive_name(&paren.elem),
+        _ => None,
+    }
+}
+
+pub fn is_unsigned_int(ty: &Type) -> bool {
+    primitive_name(ty).is_some_and(|name| {
+        matches!(
+            name.as_str(),
+            "u8" | "u16" | "u32" | "u64" | "u128" | "usize"
+        )
+    })
+}
+
+pub fn is_signed_int(ty: &Type) -> bool {
+    primitive_name(ty).is_some_and(|name| {
+        matches!(
+            name.as_str(),
+            "i8" | "i16" | "i32" | "i64" | "i128" | "isize"
+        )
+    })
+}
+
+pub fn is_float(ty: &Type) -> bool {
+    primitive_name(ty).is_some_and(|name| matches!(name.as_str(), "f32" | "f64"))
+}
+
+/// Returns whether the comparisons of values of type `ty` form a total order, so
+/// that e.g. `x == x` always holds and `!(a < b)` is `a >= b`. This is the case for
+/// the integer types, `bool` and `char`, but not for floats.
+pub fn is_totally_ordered(ty: &Type) -> bool {
+    primitive_name(ty).is_some_and(|name| {
+        matches!(
+            name.as_str(),
+            "u8" | "u16"
+                | "u32"
+                | "u64"
+                | "u128"
+                | "usize"
+                | "i8"
+                | "i16"
+                | "i32"
+                | "i64"
+                | "i128"
+                | "isize"
+                | "bool"
+                | "char"
+        )
+    })
+}
+
+/// Returns whether `ty` is a primitive number, `bool` or `char`.
+pub fn is_primitive(ty: &Type) -> bool {
+    is_totally_ordered(ty) || is_float(ty)
+}
+
+/// Returns whether the element type of a slice, array or vector of type `ty` is
+/// known to be a primitive, which can be bound by value.
+pub fn has_primitive_elements(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(TypeReference { elem, .. }) => has_primitive_elements(elem),
+        Type::Slice(TypeSlice { elem, .. }) | Type::Array(TypeArray { elem, .. }) => {
+            is_primitive(elem)
+        }
+        Type::Path(TypePath { qself: None, path }) => {
+            let Some(segment) = path.segments.last() else {
+                return false;
+            };
+            let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+                return false;
+            };
+            segment.ident == "Vec"
+                && matches!(args.args.first(), Some(syn::GenericArgument::Type(elem))
+                    if is_primitive(elem))
+        }
+        _ => false,
+    }
+}
+
+/// Returns whether `expr` is the integer literal `value`, with or without a suffix.
+pub fn is_int_lit(expr: &Expr, value: u128) -> bool {
+    match strip_parens(expr) {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(int), ..
+        }) => int
+            .base10_parse::<u128>()
+            .is_ok_and(|parsed| parsed == value),
+        _ => false,
+    }
+}
+
+/// Returns the type of `expr` if it is a variable of known type, a cast, or a
+/// suffixed literal.
+pub fn expr_type(expr: &Expr, types: &HashMap<Ident, Type>) -> Option<Type> {
+    match strip_parens(expr) {
+        Expr::Cast(cast) => Some(cast.ty.as_ref().clone()),
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(int), ..
+        }) if !int.suffix().is_empty() => syn::parse_str(int.suffix()).ok(),
+        Expr::Lit(ExprLit {
+            lit: Lit::Float(float),
+            ..
+        }) if !float.suffix().is_empty() => syn::parse_str(float.suffix()).ok(),
+        expr => path_ident(expr).and_then(|ident| types.get(ident).cloned()),
+    }
+}
+
+/// What an allocation holds.
+pub enum Allocation {
+    /// A single element, which becomes a `Box<T>`.
+    Single(Type),
+    /// The given number of elements, which become a `Vec<T>`.
+    Array(Type, Box<Expr>),
+}
+
+/// Returns the final path segment of a called function, e.g. `malloc`.
+pub fn callee_name(func: &Expr) -> Option<String> {
+    match func {
+        Expr::Path(ExprPath { path, .. }) => path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Returns whether `expr` is `size_of::<T>()` for the type `elem`, ignoring casts.
+pub fn is_size_of(expr: &Expr, elem: &Type) -> bool {
+    let Expr::Call(ExprCall { func, args, .. }) = strip_casts(expr) else {
+        return false;
+    };
+    let Expr::Path(ExprPath { path, .. }) = func.as_ref() else {
+        return false;
+    };
+    let Some(segment) = path.segments.last() else {
+        return false;
+    };
+    let PathArguments::AngleBracketed(generics) = &segment.arguments else {
+        return false;
+    };
+    segment.ident == "size_of"
+        && args.is_empty()
+        && matches!(generics.args.first(), Some(GenericArgument::Type(ty))
+            if generics.args.len() == 1 && same_tokens(ty, elem))
+}
+
+/// Returns the number of `elem`s a `malloc` of `size` bytes holds: None for one
+/// `size_of::<T>()`, and `n` for `n * size_of::<T>()`.
+pub fn element_count(size: &Expr, elem: &Type) -> Option<Option<Expr>> {
+    let size = strip_casts(size);
+    if is_size_of(size, elem) {
+        return Some(None);
+    }
+    let (left, right) = match size {
+        Expr::Binary(ExprBinary {
+            left,
+            op: syn::BinOp::Mul(_),
+            right,
+            ..
+        }) => (left.as_ref(), right.as_ref()),
+        Expr::MethodCall(ExprMethodCall {
+            receiver,
+            method,
+            args,
+            ..
+        }) if method == "wrapping_mul" && args.len() == 1 => (receiver.as_ref(), &args[0]),
+        _ => return None,
+    };
+    if is_size_of(right, elem) {
+        Some(Some(strip_casts(left).clone()))
+    } else if is_size_of(left, elem) {
+        Some(Some(strip_casts(right).clone()))
+    } else {
+        None
+    }
+}
+
+/// Returns the allocation made by `malloc(..) as *mut T` or `calloc(..) as *mut T`.
+pub fn allocation(expr: &Expr) -> Option<Allocation> {
+    let Expr::Cast(ExprCast { expr, ty, .. }) = expr else {
+        return None;
+    };
+    let Type::Ptr(TypePtr { elem, .. }) = ty.as_ref() else {
+        return None;
+    };
+    let elem = elem.as_ref().clone();
+    let Expr::Call(ExprCall { func, args, .. }) = strip_casts(expr) else {
+        return None;
+    };
+    let count = match (callee_name(func)?.as_str(), args.len()) {
+        ("malloc", 1) => element_count(&args[0], &elem)?,
+        ("calloc", 2) if is_size_of(&args[1], &elem) => {
+            let count = strip_casts(&args[0]);
+            (!is_int_lit(count, 1)).then(|| count.clone())
+        }
+        _ => return None,
+    };
+    Some(match count {
+        None => Allocation::Single(elem),
+        Some(count) => Allocation::Array(elem, Box::new(count)),
+    })
+}
+
+/// Returns `expr` cast to a `usize`, unless it is a literal.
+pub fn as_usize(expr: &Expr) -> Expr {
+    if let Expr::Lit(_) = expr {
+        return expr.clone();
+    }
+    let mut cast = Expr::Cast(ExprCast {
+        attrs: Vec::new(),
+        expr: Box::new(expr.clone()),
+        as_token: Default::default(),
+        ty: parse_quote!(usize),
+    });
+    Parenthesize.visit_expr_mut(&mut cast);
+    cast
+}
+
+/// Returns the length `len`, of integer type `ty` if known, as a `usize`. A length
+/// that may be negative is taken as zero, as a loop up to it would not run.
+pub fn len_as_usize(len: &Expr, ty: Option<&Type>) -> Expr {
+    let signed = ty.is_none_or(is_signed_int) && !matches!(strip_parens(len), Expr::Lit(_));
+    if ty.and_then(primitive_name).as_deref() == Some("usize") {
+        len.clone()
+    } else if signed {
+        let len = parenthesized(len);
+        parse_quote!(#len.max(0) as usize)
+    } else {
+        as_usize(len)
+    }
+}
+
+/// Returns `expr` in parentheses, unless it binds tighter than any operator.
+pub fn parenthesized(expr: &Expr) -> Expr {
+    match expr {
+        Expr::Path(_)
+        | Expr::Lit(_)
+        | Expr::Paren(_)
+        | Expr::Call(_)
+        | Expr::MethodCall(_)
+        | Expr::Field(_)
+        | Expr::Index(_) => expr.clone(),
+        expr => parse_quote!((#expr)),
+    }
+}
+
+/// Returns the expression of a default `T`, e.g. `i32::default()`.
+pub fn default_of(elem: &Type) -> Expr {
+    match elem {
+        Type::Path(_) => parse_quote!(#elem::default()),
+        _ => parse_quote!(<#elem>::default()),
+    }
+}
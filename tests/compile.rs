@@ -0,0 +1,89 @@
+//! Tests that the default pipeline keeps the examples compiling: each example
+//! under `examples/` that type-checks must still type-check once transformed.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Returns the `libc` library the examples are checked against, built as a
+/// dev-dependency next to this test.
+fn libc_rlib(deps: &Path) -> PathBuf {
+    fs::read_dir(deps)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| {
+            let name = path.file_name().unwrap().to_string_lossy();
+            name.starts_with("liblibc-") && name.ends_with(".rlib")
+        })
+        .expect("libc is not built")
+}
+
+/// Type-checks `path` as a library crate, returning rustc's errors if it fails.
+fn type_check(path: &Path, deps: &Path, libc: &Path) -> Result<(), String> {
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    let metadata = env::temp_dir().join(format!(
+        "monadicast-compile-{}-{}.rmeta",
+        std::process::id(),
+        path.file_stem().unwrap().to_string_lossy()
+    ));
+    let output = Command::new(rustc)
+        .args([
+            "--edition",
+            "2021",
+            "--crate-type",
+            "lib",
+            "--emit=metadata",
+        ])
+        .args(["-A", "warnings", "-L"])
+        .arg(format!("dependency={}", deps.display()))
+        .arg("--extern")
+        .arg(format!("libc={}", libc.display()))
+        .arg("-o")
+        .arg(&metadata)
+        .arg(path)
+        .output()
+        .unwrap();
+    let _ = fs::remove_file(&metadata);
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).into_owned())
+    }
+}
+
+#[test]
+fn rewritten_examples_type_check() {
+    let deps = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+    let libc = libc_rlib(&deps);
+    let examples = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+    let scratch = env::temp_dir().join(format!("monadicast-compile-{}", std::process::id()));
+    fs::create_dir_all(&scratch).unwrap();
+
+    let mut failed = Vec::new();
+    for entry in walkdir::WalkDir::new(&examples).sort_by_file_name() {
+        let input = entry.unwrap().into_path();
+        if input.extension().is_none_or(|extension| extension != "rs") {
+            continue;
+        }
+        if type_check(&input, &deps, &libc).is_err() {
+            eprintln!("skipping {}, which does not type-check", input.display());
+            continue;
+        }
+        let source = fs::read_to_string(&input).unwrap();
+        let output = monadicast::transform_str(&source)
+            .unwrap_or_else(|error| panic!("{}: {}", input.display(), error));
+        let rewritten = scratch.join(input.file_name().unwrap());
+        fs::write(&rewritten, output).unwrap();
+        if let Err(errors) = type_check(&rewritten, &deps, &libc) {
+            eprintln!("{} no longer type-checks:\n{}", input.display(), errors);
+            failed.push(input.strip_prefix(&examples).unwrap().display().to_string());
+        }
+    }
+    let _ = fs::remove_dir_all(&scratch);
+    assert!(
+        failed.is_empty(),
+        "examples that no longer type-check: {:?}",
+        failed
+    );
+}
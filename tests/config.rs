@@ -0,0 +1,113 @@
+//! Tests of the `monadicast.toml` configuration file: its parsing, and its options
+//! taking effect in the library and the command line tool.
+
+use monadicast::{transform_str_with, Config, PassName};
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn parses_every_option() {
+    let config = Config::from_toml(
+        r#"
+        passes = ["convert_ffi_types", "replace_raw_pointers"]
+        skip = ["replace_raw_pointers"]
+        fixpoint_iterations = 3
+        rewrite_char_buffers = true
+        report_spin_loops = true
+        generate_drop_impls = true
+        rewrite_for_each_loops = true
+        rewrite_max_min_clamps = true
+        max_inlined_statements = 5
+
+        [ffi.types]
+        my_size_t = "usize"
+        "libc::my_flags_t" = "u32"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.passes,
+        Some(vec![
+            PassName::ConvertFfiTypes,
+            PassName::ReplaceRawPointers
+        ])
+    );
+    assert_eq!(config.skip, [PassName::ReplaceRawPointers]);
+    assert_eq!(config.fixpoint_iterations, Some(3));
+    assert!(config.pass_config().rewrite_char_buffers);
+    assert!(config.pass_config().report_spin_loops);
+    assert!(config.pass_config().generate_drop_impls);
+    assert!(config.pass_config().rewrite_for_each_loops);
+    assert!(config.pass_config().rewrite_max_min_clamps);
+    assert_eq!(config.pass_config().max_inlined_statements, 5);
+    assert_eq!(config.ffi.types["my_size_t"], "usize");
+    assert_eq!(config.ffi.types["libc::my_flags_t"], "u32");
+    assert_eq!(Config::from_toml("").unwrap(), Config::default());
+}
+
+#[test]
+fn rejects_invalid_options() {
+    for (source, expected) in [
+        ("skip = [\"no_such_pass\"]", "no_such_pass"),
+        ("fixpoint = 3", "fixpoint"),
+        (
+            "[ffi.types]\nmy_size_t = \"usize usize\"",
+            "is not a Rust type",
+        ),
+    ] {
+        let error = Config::from_toml(source).unwrap_err().to_string();
+        assert!(error.contains(expected), "no `{expected}` in `{error}`");
+    }
+}
+
+#[test]
+fn maps_configured_ffi_types() {
+    let config = Config::from_toml("[ffi.types]\nmy_size_t = \"usize\"").unwrap();
+    let source = "pub unsafe extern \"C\" fn len(n: my_size_t, m: libc::c_int) -> my_size_t { n }";
+    let output = transform_str_with(source, &config.pass_config()).unwrap();
+    assert_eq!(
+        output,
+        "pub unsafe fn len(n: usize, m: i32) -> usize {\n    n\n}\n"
+    );
+}
+
+#[test]
+fn reads_config_file_of_input_directory() {
+    let root = std::env::temp_dir().join(format!("monadicast-config-{}", std::process::id()));
+    let (input, out) = (root.join("input"), root.join("output"));
+    fs::create_dir_all(&input).unwrap();
+    fs::write(
+        input.join("monadicast.toml"),
+        "passes = [\"convert_ffi_types\"]\n[ffi.types]\nmy_size_t = \"usize\"\n",
+    )
+    .unwrap();
+    fs::write(
+        input.join("len.rs"),
+        "fn len(n: my_size_t) -> my_size_t { n + 0 }",
+    )
+    .unwrap();
+
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_monadicast"))
+            .arg(&input)
+            .arg("--out")
+            .arg(&out)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        (
+            fs::read_to_string(out.join("len.rs")).unwrap(),
+            String::from_utf8(output.stderr).unwrap(),
+        )
+    };
+    // Only the configured pass runs, so `n + 0` is kept.
+    let (configured, stderr) = run(&[]);
+    // The command line overrides the configured passes.
+    let (overridden, _) = run(&["--passes", "convert_ffi_types,fold_arithmetic_identities"]);
+    fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(configured, "fn len(n: usize) -> usize {\n    n + 0\n}\n");
+    assert!(stderr.contains("0 skipped"), "{stderr}");
+    assert_eq!(overridden, "fn len(n: usize) -> usize {\n    n\n}\n");
+}
@@ -0,0 +1,49 @@
+//! Tests of composing a [Pipeline] of passes defined outside the crate.
+
+use monadicast::{MonadicAst, Pass, PassError, Pipeline};
+use syn::{parse_quote, Item};
+
+/// Appends an empty function of the given name to the file.
+struct AppendFn(&'static str);
+
+impl Pass for AppendFn {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        let name = quote::format_ident!("{}", self.0);
+        monad.ast.items.push(parse_quote!(fn #name() {}));
+        Ok(monad)
+    }
+}
+
+/// Renames every function of the file by appending a suffix to its name.
+struct SuffixFns(&'static str);
+
+impl Pass for SuffixFns {
+    fn bind(&mut self, mut monad: MonadicAst) -> Result<MonadicAst, PassError> {
+        for item in &mut monad.ast.items {
+            if let Item::Fn(item_fn) = item {
+                let ident = &item_fn.sig.ident;
+                item_fn.sig.ident = quote::format_ident!("{}{}", ident, self.0);
+            }
+        }
+        Ok(monad)
+    }
+}
+
+#[test]
+fn applies_custom_passes_in_the_order_added() {
+    let run = |pipeline: Pipeline| {
+        pipeline
+            .run(MonadicAst::new("fn a() {}").unwrap())
+            .unwrap()
+            .result()
+    };
+    // The renaming only sees the functions appended before it.
+    assert_eq!(
+        run(Pipeline::new().add(AppendFn("b")).add(SuffixFns("_x"))),
+        "fn a_x() {}\nfn b_x() {}\n"
+    );
+    assert_eq!(
+        run(Pipeline::new().add(SuffixFns("_x")).add(AppendFn("b"))),
+        "fn a_x() {}\nfn b() {}\n"
+    );
+}
@@ -0,0 +1,322 @@
+//! Tests of the notes the passes report about code they could not, or did not
+//! fully, rewrite.
+
+use monadicast::{MonadicAst, PassConfig};
+
+#[test]
+fn reports_pointers_never_dereferenced() {
+    let source = r#"
+        pub unsafe fn forward(p: *mut i32) -> i32 {
+            consume(p)
+        }
+        pub unsafe fn get(p: *const i32) -> i32 {
+            *p
+        }
+        pub unsafe fn get_volatile(p: *const i32) -> i32 {
+            std::ptr::read_volatile(p)
+        }
+    "#;
+    let monad = MonadicAst::new(source)
+        .unwrap()
+        .replace_raw_pointers()
+        .unwrap();
+    let notes: Vec<_> = monad
+        .diagnostics()
+        .iter()
+        .filter(|d| d.message.contains("never dereferenced"))
+        .map(|d| (d.function.as_deref(), d.message.as_str()))
+        .collect();
+    assert_eq!(
+        notes,
+        [(
+            Some("forward"),
+            "`p` is never dereferenced, only passed around; keeping it a raw pointer"
+        )]
+    );
+    let output = monad.result();
+    assert!(output.contains("fn forward(p: *mut i32)"), "{output}");
+    assert!(output.contains("fn get(p: &i32)"), "{output}");
+}
+
+#[test]
+fn reports_pointers_that_escape() {
+    let source = r#"
+        pub unsafe fn forward(p: *mut i32) -> *mut i32 {
+            *p = 1;
+            return p;
+        }
+        pub unsafe fn bump(p: *mut i32) -> *mut i32 {
+            *p += 1;
+            p
+        }
+        pub unsafe fn remember(p: *mut i32, seen: &mut Vec<*mut i32>) {
+            *p = 0;
+            seen.push(p);
+        }
+    "#;
+    let monad = MonadicAst::new(source)
+        .unwrap()
+        .replace_raw_pointers()
+        .unwrap();
+    let notes: Vec<_> = monad
+        .diagnostics()
+        .iter()
+        .map(|d| (d.function.as_deref(), d.message.as_str()))
+        .collect();
+    assert_eq!(
+        notes,
+        [
+            (Some("forward"), "`p` is returned; keeping it a raw pointer"),
+            (
+                Some("bump"),
+                "`p` is used other than through a dereference; keeping it a raw pointer"
+            ),
+            (
+                Some("remember"),
+                "`p` is passed to `push`; keeping it a raw pointer"
+            ),
+        ]
+    );
+    let output = monad.result();
+    assert!(!output.contains("Cell"), "{output}");
+    assert!(!output.contains("&mut i32"), "{output}");
+}
+
+#[test]
+fn reports_pointers_a_cell_cannot_replace() {
+    let source = r#"
+        #[derive(Copy, Clone)]
+        pub struct Point {
+            pub x: i32,
+        }
+        pub struct Name {
+            pub len: i32,
+        }
+        pub unsafe fn x_of(p: *const Point) -> i32 {
+            (*p).x
+        }
+        pub unsafe fn len_of(p: *const Name) -> i32 {
+            (*p).len
+        }
+        pub unsafe fn shift(p: *mut Point) -> i32 {
+            (*p).x += 1;
+            x_of(p)
+        }
+        pub unsafe fn rename(p: *mut Name) -> i32 {
+            *p = Name { len: 0 };
+            len_of(p)
+        }
+    "#;
+    let monad = MonadicAst::new(source)
+        .unwrap()
+        .replace_raw_pointers()
+        .unwrap();
+    let notes: Vec<_> = monad
+        .diagnostics()
+        .iter()
+        .map(|d| (d.function.as_deref(), d.message.as_str()))
+        .collect();
+    assert_eq!(
+        notes,
+        [
+            (
+                Some("shift"),
+                "`p` is written in place, which a `Cell` cannot be; keeping it a raw pointer"
+            ),
+            (
+                Some("rename"),
+                "`p` points to a value that is not `Copy`, which a `Cell` cannot get; keeping \
+                 it a raw pointer"
+            ),
+        ]
+    );
+    let output = monad.result();
+    assert!(!output.contains("Cell"), "{output}");
+}
+
+#[test]
+fn reports_returned_allocations_passed_on() {
+    let source = r#"
+        pub unsafe fn new_counter() -> *mut i32 {
+            malloc(::core::mem::size_of::<i32>() as u64) as *mut i32
+        }
+        pub unsafe fn hand_over() {
+            consume(new_counter());
+        }
+    "#;
+    let monad = MonadicAst::new(source)
+        .unwrap()
+        .replace_raw_pointers()
+        .unwrap();
+    let notes: Vec<_> = monad
+        .diagnostics()
+        .iter()
+        .map(|d| (d.function.as_deref(), d.message.as_str()))
+        .collect();
+    assert_eq!(
+        notes,
+        [(
+            Some("new_counter"),
+            "its result is used in `hand_over` other than to initialize a pointer; keeping its \
+             return type a raw pointer"
+        )]
+    );
+    let output = monad.result();
+    assert!(output.contains("fn new_counter() -> *mut i32"), "{output}");
+}
+
+#[test]
+fn reports_const_pointers_written_through() {
+    let source = r#"
+        pub unsafe fn reset(p: *const i32) -> i32 {
+            *p = 0;
+            *p
+        }
+    "#;
+    let monad = MonadicAst::new(source)
+        .unwrap()
+        .replace_raw_pointers()
+        .unwrap();
+    let notes: Vec<_> = monad
+        .diagnostics()
+        .iter()
+        .map(|d| d.message.as_str())
+        .collect();
+    assert_eq!(
+        notes,
+        ["`p` is written through but declared `*const`; keeping it immutable"]
+    );
+    let output = monad.result();
+    assert!(output.contains("fn reset(p: &i32)"), "{output}");
+}
+
+#[test]
+fn reports_exported_free_fns() {
+    let source = r#"
+        pub struct Name {
+            pub text: *mut u8,
+        }
+        pub unsafe fn free_name(n: *mut Name) {
+            free((*n).text as *mut c_void);
+        }
+    "#;
+    let monad = MonadicAst::new(source)
+        .unwrap()
+        .with_config(PassConfig {
+            generate_drop_impls: true,
+            ..PassConfig::default()
+        })
+        .generate_drop_impls()
+        .unwrap();
+    let notes: Vec<_> = monad
+        .diagnostics()
+        .iter()
+        .map(|d| (d.function.as_deref(), d.message.as_str()))
+        .collect();
+    assert_eq!(
+        notes,
+        [(
+            Some("free_name"),
+            "releases the pointer owned by `Name`; consider an `impl Drop`"
+        )]
+    );
+    let output = monad.result();
+    assert!(!output.contains("impl Drop"), "{output}");
+}
+
+#[test]
+fn reports_byte_loops_not_known_to_be_ascii() {
+    let source = r#"
+        pub fn latin1_name() -> String {
+            let bytes: &[u8] = b"caf\xe9";
+            let mut name = String::new();
+            for &b in bytes {
+                name.push(b as char);
+            }
+            name
+        }
+    "#;
+    let monad = MonadicAst::new(source)
+        .unwrap()
+        .replace_byte_string_loops()
+        .unwrap();
+    let notes: Vec<_> = monad
+        .diagnostics()
+        .iter()
+        .map(|d| (d.function.as_deref(), d.message.as_str()))
+        .collect();
+    assert_eq!(
+        notes,
+        [(
+            Some("latin1_name"),
+            "`name` is built from `bytes` a byte per `char`; keeping the loop, as its bytes are \
+             not known to be ASCII, which `String::from_utf8_lossy` would decode differently"
+        )]
+    );
+    let output = monad.result();
+    assert!(!output.contains("from_utf8"), "{output}");
+}
+
+#[test]
+fn reports_free_fns_a_drop_impl_cannot_replace() {
+    let notes = |body: &str| {
+        let source = format!(
+            "pub struct Buffer {{ pub data: *mut u8, pub len: usize }}
+             unsafe fn free_buffer(b: *mut Buffer) {{ free((*b).data as *mut c_void); }}
+             pub unsafe fn f(len: usize) -> *mut u8 {{
+                 let mut b = Buffer {{ data: malloc(len) as *mut u8, len }};
+                 {body}
+             }}"
+        );
+        let monad = MonadicAst::new(&source)
+            .unwrap()
+            .with_config(PassConfig {
+                generate_drop_impls: true,
+                ..PassConfig::default()
+            })
+            .generate_drop_impls()
+            .unwrap();
+        assert!(!monad.result().contains("impl Drop"));
+        monad
+            .diagnostics()
+            .iter()
+            .map(|d| d.message.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        notes("let Buffer { data, .. } = b; data"),
+        [
+            "releases the pointer owned by `Buffer`, but `b` in `f` is not freed exactly once; \
+             keeping it rather than an `impl Drop`"
+        ]
+    );
+    assert_eq!(
+        notes("let data = b.data; free_buffer(&mut b); data"),
+        [
+            "releases the pointer owned by `Buffer`, but `b` in `f` is used other than through \
+             its fields, or its pointer is used other than through a dereference; keeping it \
+             rather than an `impl Drop`"
+        ]
+    );
+    assert_eq!(
+        notes("free_buffer(&mut b); b.data = malloc(len) as *mut u8; free_buffer(&mut b); b.data"),
+        [
+            "releases the pointer owned by `Buffer`, but `b` in `f` is not freed exactly once; \
+             keeping it rather than an `impl Drop`"
+        ]
+    );
+    assert_eq!(
+        notes("free_buffer(&mut b); keep(Buffer { data: b.data, len }); b.data"),
+        [
+            "releases the pointer owned by `Buffer`, but `b` in `f` is used after it is freed, \
+             other than at the end of its scope; keeping it rather than an `impl Drop`"
+        ]
+    );
+    assert_eq!(
+        notes("free_buffer(&mut b); keep(Buffer { data: std::ptr::null_mut(), len }); null()"),
+        [
+            "releases the pointer owned by `Buffer`, but a `Buffer` is created other than as a \
+             local; keeping it rather than an `impl Drop`"
+        ]
+    );
+}
@@ -0,0 +1,40 @@
+//! Tests of applying passes repeatedly until the AST stops changing.
+
+use monadicast::{MonadicAst, PassName};
+
+/// Eliding the wrapper only lets the raw pointer pass, which ran before it, rewrite
+/// `buf` on the next iteration.
+const SOURCE: &str = "
+    fn pass_through(p: *mut u8) -> *mut u8 {
+        return p;
+    }
+    unsafe fn store(buf: *mut u8) {
+        *pass_through(buf) = 1;
+    }
+";
+
+const PASSES: &[PassName] = &[
+    PassName::ReplaceRawPointers,
+    PassName::ElideIdentityWrappers,
+];
+
+#[test]
+fn reapplies_passes_enabled_by_later_ones() {
+    let mut monad = MonadicAst::new(SOURCE).unwrap();
+    // Two iterations rewrite, and a third finds nothing left to do.
+    assert_eq!(monad.run_to_fixpoint(PASSES).unwrap(), 3);
+    assert_eq!(
+        monad.result(),
+        "unsafe fn store(buf: &mut u8) {\n    *buf = 1;\n}\n"
+    );
+}
+
+#[test]
+fn stops_at_the_iteration_cap() {
+    let mut monad = MonadicAst::new(SOURCE).unwrap();
+    assert_eq!(monad.run_to_fixpoint_with(PASSES, 1).unwrap(), 1);
+    assert_eq!(
+        monad.result(),
+        "unsafe fn store(buf: *mut u8) {\n    *buf = 1;\n}\n"
+    );
+}
@@ -0,0 +1,23 @@
+pub fn greeting() -> String {
+    let bytes: &[u8] = b"hello";
+    let s = String::from_utf8_lossy(bytes).into_owned();
+    s
+}
+pub fn digits() -> String {
+    let data = [b'4', 50, 0x7f];
+    let out = String::from_utf8_lossy(&data).into_owned();
+    out
+}
+pub fn shadowed(input: &[u8]) -> String {
+    let bytes: &[u8] = b"id";
+    let mut s = String::new();
+    for &b in bytes {
+        s.push(b as char);
+    }
+    let bytes = input;
+    let mut t = String::new();
+    for &b in bytes {
+        t.push(b as char);
+    }
+    s + &t
+}
@@ -0,0 +1,42 @@
+pub fn drain_queue(queue: &mut [i32; 16]) -> i32 {
+    let mut front: i32 = 0 as i32;
+    let mut rear: i32 = 0 as i32;
+    queue[rear as usize] = 1;
+    rear += 1;
+    let mut sum: i32 = 0 as i32;
+    while front < rear {
+        let value = queue[front as usize];
+        front = front + 1;
+        sum += value;
+        if value < 8 {
+            queue[rear as usize] = value * 2;
+            rear += 1;
+        }
+    }
+    sum
+}
+pub fn local_bound() -> i32 {
+    let mut n: i32 = 0 as i32;
+    n += 4;
+    n
+}
+pub fn parameter_bound(n: i32) -> i32 {
+    let mut total: i32 = 0 as i32;
+    let mut i: i32 = 0 as i32;
+    for i in 0..n {
+        total += i;
+    }
+    total
+}
+pub fn restarted_counter() -> i32 {
+    let mut hits: i32 = 0 as i32;
+    let mut j: i32 = 0 as i32;
+    let mut round: i32 = 0 as i32;
+    for round in 0i32..2 {
+        while j < 3 {
+            hits += 1;
+            j += 1;
+        }
+    }
+    hits
+}
@@ -0,0 +1,31 @@
+use std::ffi::CStr;
+use std::os::raw::c_char;
+extern "C" {
+    fn strcpy(dst: *mut i8, src: *const i8) -> *mut i8;
+    fn strcat(dst: *mut i8, src: *const i8) -> *mut i8;
+    fn strlen(s: *const i8) -> usize;
+}
+pub unsafe fn greeting() -> usize {
+    let mut buf = String::from("hello, ");
+    buf.push_str("world");
+    let s = buf.as_str();
+    s.len()
+}
+pub unsafe fn measured() -> usize {
+    let mut name: [i8; 16] = [0; 16];
+    strcpy(name.as_mut_ptr(), b"id\0".as_ptr() as *const i8);
+    strlen(name.as_ptr())
+}
+pub unsafe fn labelled(flag: bool) -> usize {
+    let width = if flag { 4 } else { 2 };
+    let label = String::from("on");
+    let s = label.as_str();
+    s.len() + width
+}
+pub unsafe fn paired() -> usize {
+    let key = String::from("k");
+    let value = String::from("v");
+    let k = key.as_str();
+    let v = value.as_str();
+    k.len() + v.len()
+}
@@ -0,0 +1,12 @@
+extern "C" {
+    fn observe(p: *mut i32);
+}
+pub unsafe fn bump(mut counter: &mut i32) {
+    *counter += 1 as i32;
+}
+pub unsafe fn bump_observed(mut counter: &std::cell::Cell<i32>, mut step: i32) {
+    counter.set(counter.get() + 1 as i32);
+    observe(counter.as_ptr());
+    counter.set(counter.get() * (step + 1 as i32));
+    counter.set(counter.get() << 2 as i32);
+}
@@ -0,0 +1,78 @@
+pub unsafe fn sum_to(n: i32) -> i32 {
+    let mut sum: i32 = 0 as i32;
+    let mut i: i32 = 0 as i32;
+    for i in 0..n {
+        sum += i;
+    }
+    return sum;
+}
+pub unsafe fn sum_evens(n: i32) -> i32 {
+    let mut sum: i32 = 0 as i32;
+    let mut i: i32 = 0 as i32;
+    for i in (0..n).step_by(2) {
+        sum += i;
+    }
+    let mut j: i32 = 1 as i32;
+    for j in 1..=n {
+        sum -= j;
+    }
+    return sum;
+}
+pub unsafe fn ceil_sqrt(n: i32) -> i32 {
+    let mut done: i32 = 0 as i32;
+    let mut i: i32 = 0 as i32;
+    while i < n && done == 0 as i32 {
+        if i * i >= n {
+            done = 1 as i32;
+        }
+        i += 1;
+    }
+    return i - 1 as i32;
+}
+pub fn count_some(values: &[Option<i32>]) -> i32 {
+    let mut count: i32 = 0 as i32;
+    let mut iter = values.iter();
+    while let Some(value) = iter.next() {
+        if value.is_some() {
+            count += 1;
+        }
+    }
+    return count;
+}
+pub unsafe fn sum_indices(mut n: i32) -> u64 {
+    let mut sum: u64 = 0 as u64;
+    let mut i: usize = 0 as usize;
+    for i in 0usize..10 {
+        sum += i as u64;
+    }
+    let mut j: usize = 0 as usize;
+    for j in 0..n as usize {
+        sum += j as u64;
+    }
+    return sum;
+}
+pub unsafe fn sum_plain(mut n: i32, mut delta: i32) -> i32 {
+    let mut sum: i32 = 0 as i32;
+    let mut i: i32 = 0 as i32;
+    for i in 0..n {
+        sum = sum + i;
+    }
+    let mut j: i32 = 0 as i32;
+    while j < n {
+        sum = sum + j;
+        j = j + delta;
+    }
+    return sum;
+}
+pub unsafe fn sum_down_skipping(mut limit: i32) -> i32 {
+    let mut sum: i32 = 0 as i32;
+    let mut k: i32 = 10 as i32;
+    while k > 0 {
+        k -= 1;
+        sum = sum + k;
+        if sum > limit {
+            k -= 1;
+        }
+    }
+    return sum;
+}
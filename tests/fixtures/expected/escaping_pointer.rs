@@ -0,0 +1,22 @@
+extern "C" {
+    fn retain(p: *mut i32);
+}
+pub unsafe fn forward(p: *mut i32) -> *mut i32 {
+    *p = 1;
+    return p;
+}
+pub unsafe fn bump(p: *mut i32) -> *mut i32 {
+    *p += 1;
+    p
+}
+pub unsafe fn remember(p: *mut i32, seen: &mut Vec<*mut i32>) {
+    *p = 0;
+    seen.push(p);
+}
+pub unsafe fn share(p: &std::cell::Cell<i32>) {
+    p.set(2);
+    retain(p.as_ptr());
+}
+pub unsafe fn store(out: &mut i32) {
+    *out = 3;
+}
@@ -0,0 +1,3 @@
+pub unsafe fn mix(a: i8, b: u16, c: i64, d: f64) -> f64 {
+    (a as f64) + (b as f64) + (c as f64) + d
+}
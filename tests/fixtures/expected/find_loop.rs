@@ -0,0 +1,14 @@
+pub fn first_negative(values: &[i64], n: i32) -> i32 {
+    let pos: i32 = values[..n.max(0) as usize]
+        .iter()
+        .position(|&x| x < 0)
+        .map_or(-1, |i| i as i32);
+    pos
+}
+pub fn first_zero(values: &[u8], n: u32) -> i32 {
+    let pos: i32 = values[..n as usize]
+        .iter()
+        .position(|&x| x == 0)
+        .map_or(-1, |i| i as i32);
+    pos
+}
@@ -0,0 +1,25 @@
+use std::ffi::c_void;
+extern "C" {
+    fn malloc(size: usize) -> *mut c_void;
+    fn free(ptr: *mut c_void);
+}
+pub struct Buffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+impl Drop for Buffer {
+    fn drop(&mut self) {
+        unsafe {
+            free(self.data as *mut c_void);
+        }
+    }
+}
+pub unsafe fn checksum(len: usize) -> u8 {
+    let mut b = Buffer {
+        data: malloc(len) as *mut u8,
+        len,
+    };
+    *b.data = 7;
+    let sum = *b.data;
+    sum
+}
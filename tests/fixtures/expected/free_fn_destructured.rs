@@ -0,0 +1,20 @@
+use std::ffi::c_void;
+extern "C" {
+    fn malloc(size: usize) -> *mut c_void;
+    fn free(ptr: *mut c_void);
+}
+pub struct Buffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+unsafe fn free_buffer(b: &mut Buffer) {
+    free((*b).data as *mut c_void);
+}
+pub unsafe fn take_data(len: usize) -> *mut u8 {
+    let b = Buffer {
+        data: malloc(len) as *mut u8,
+        len,
+    };
+    let Buffer { data, .. } = b;
+    data
+}
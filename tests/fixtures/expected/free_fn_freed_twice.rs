@@ -0,0 +1,24 @@
+use std::ffi::c_void;
+extern "C" {
+    fn malloc(size: usize) -> *mut c_void;
+    fn free(ptr: *mut c_void);
+}
+pub struct Buffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+unsafe fn free_buffer(b: &mut Buffer) {
+    free((*b).data as *mut c_void);
+}
+pub unsafe fn refill(len: usize) -> u8 {
+    let mut b = Buffer {
+        data: malloc(len) as *mut u8,
+        len,
+    };
+    free_buffer(&mut b);
+    b.data = malloc(len) as *mut u8;
+    *b.data = 7;
+    let first = *b.data;
+    free_buffer(&mut b);
+    first
+}
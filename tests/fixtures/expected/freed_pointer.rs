@@ -0,0 +1,14 @@
+extern "C" {
+    fn free(_: *mut ());
+}
+pub unsafe fn take_value(p: Box<i32>) -> i32 {
+    let mut value: i32 = *p;
+    drop(p);
+    return value;
+}
+pub unsafe fn take_aliased(p: *mut i32) -> i32 {
+    let mut q: *mut i32 = p;
+    let mut value: i32 = *p;
+    free(q as *mut ());
+    return value;
+}
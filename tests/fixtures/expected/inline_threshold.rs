@@ -0,0 +1,15 @@
+fn over(x: i32) -> i32 {
+    let y = x * 2;
+    let z = y - x;
+    z + 1
+}
+pub fn combined(x: i32) -> i32 {
+    let a = x * 3;
+    let b = {
+        let x_1: i32 = x;
+        let y = x_1 * 2;
+        y + 1
+    };
+    let c = over(x);
+    a + b + c
+}
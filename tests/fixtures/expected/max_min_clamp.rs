@@ -0,0 +1,12 @@
+pub fn bounded(x: i32) -> i32 {
+    x.clamp(0, 255)
+}
+pub fn bounded_min_first(x: i64) -> i64 {
+    x.clamp(-10, 10)
+}
+pub fn reversed(x: i32) -> i32 {
+    x.max(10).min(0)
+}
+pub fn window(x: u32, lo: u32, hi: u32) -> u32 {
+    x.max(lo).min(hi)
+}
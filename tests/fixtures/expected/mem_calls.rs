@@ -0,0 +1,40 @@
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct point {
+    pub x: i32,
+    pub y: i32,
+}
+pub unsafe fn copy_scores() -> i32 {
+    let mut scores: [i32; 8] = [1 as i32, 2, 3, 4, 5, 6, 7, 8];
+    let mut backup: [i32; 8] = [0; 8];
+    backup.copy_from_slice(&scores);
+    scores.fill(0);
+    return backup[7 as i32 as usize] + scores[0 as i32 as usize];
+}
+pub unsafe fn copy_prefix(mut n: i32) -> i32 {
+    let mut src: [i32; 16] = [7; 16];
+    let mut dst: [i32; 16] = [0; 16];
+    dst[..n as usize].copy_from_slice(&src[..n as usize]);
+    return dst[0 as i32 as usize];
+}
+pub unsafe fn copy_points(mut count: i32) {
+    let mut from: [point; 4] = [point { x: 0, y: 0 }; 4];
+    let mut to: [point; 4] = [point { x: 1, y: 1 }; 4];
+    to[..count as usize].copy_from_slice(&from[..count as usize]);
+    memset(
+        from.as_mut_ptr() as *mut (),
+        0 as i32,
+        ::core::mem::size_of::<[point; 4]>() as u64,
+    );
+}
+pub unsafe fn clear(mut p: *mut (), mut n: u64) {
+    memset(p, 0 as i32, n * ::core::mem::size_of::<i32>() as u64);
+}
+pub unsafe fn add_copy(mut dst: &mut [i32], mut src: &[i32]) {
+    let mut n: i32 = dst.len() as i32;
+    let mut i: i32 = 0 as i32;
+    for i in 0..n {
+        dst[i as usize] = src[i as usize] * 2 as i32;
+    }
+    dst[..n as usize].copy_from_slice(&src[..n as usize]);
+}
@@ -0,0 +1,48 @@
+extern "C" {
+    fn malloc(_: u64) -> *mut ();
+    fn free(_: *mut ());
+    fn consume(_: *mut i32);
+}
+pub struct Reader {
+    pub value: *mut i32,
+}
+impl Reader {
+    pub unsafe fn get(&self) -> i32 {
+        *self.value
+    }
+}
+pub struct Owner {
+    pub data: Box<i32>,
+}
+pub unsafe fn new_owner() -> Owner {
+    let mut owner: Owner = Owner {
+        data: Box::new(i32::default()),
+    };
+    return owner;
+}
+pub unsafe fn reset_owner(owner: &mut Owner) {
+    drop(std::mem::take(&mut (*owner).data));
+    (*owner).data = Box::new(i32::default());
+}
+pub unsafe fn owned_value(owner: &Owner) -> i32 {
+    return *(*owner).data;
+}
+pub unsafe fn release_owner(owner: &mut Owner) {
+    drop(std::mem::take(&mut (*owner).data));
+}
+pub struct Lender {
+    pub loan: *mut i32,
+}
+pub unsafe fn lend(lender: &mut Lender) -> i32 {
+    let mut value: i32 = *(*lender).loan;
+    consume((*lender).loan);
+    free((*lender).loan as *mut ());
+    return value;
+}
+pub struct Slot {
+    pub item: *mut i32,
+}
+pub unsafe fn clear_slot(slot: &mut Slot) {
+    free((*slot).item as *mut ());
+    (*slot).item = 0 as *mut i32;
+}
@@ -0,0 +1,9 @@
+pub unsafe fn store(out: Option<&mut i32>, value: i32) {
+    let Some(out) = out else {
+        return;
+    };
+    *out = value;
+}
+pub unsafe fn load(value: &i32) -> i32 {
+    *value
+}
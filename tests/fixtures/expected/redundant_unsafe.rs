@@ -0,0 +1,17 @@
+pub fn increment(counter: &mut i32) -> i32 {
+    *counter += 1 as i32;
+    return (*counter + 1 as i32) * 2 as i32;
+}
+pub fn float_bits(bits: u64) -> f64 {
+    unsafe { std::mem::transmute::<u64, f64>(bits) }
+}
+pub fn first(values: &i32) -> i32 {
+    *values
+}
+pub unsafe fn square(x: i32) -> i32 {
+    let y: i32 = {
+        let doubled: i32 = x * x;
+        doubled
+    };
+    return y;
+}
@@ -0,0 +1,35 @@
+extern "C" {
+    fn malloc(_: u64) -> *mut ();
+    fn calloc(_: u64, _: u64) -> *mut ();
+}
+pub unsafe fn new_counter() -> Box<i32> {
+    Box::new(i32::default())
+}
+pub unsafe fn new_counts(n: i32) -> Box<[i32]> {
+    if n < 1 as i32 {
+        return vec![i32::default(); 1].into_boxed_slice();
+    }
+    return vec![i32::default(); n as usize].into_boxed_slice();
+}
+pub unsafe fn new_table(n: i32) -> *mut i32 {
+    if n < 1 as i32 {
+        return 0 as *mut i32;
+    }
+    return calloc(n as u64, ::core::mem::size_of::<i32>() as u64) as *mut i32;
+}
+pub unsafe fn new_buffer(n: i32) -> *mut i32 {
+    malloc((n as u64).wrapping_mul(::core::mem::size_of::<i32>() as u64)) as *mut i32
+}
+extern "C" {
+    fn free(_: *mut ());
+    fn consume(_: *mut i32);
+}
+pub unsafe fn read_counter() -> i32 {
+    let mut c: Box<i32> = new_counter();
+    let mut value: i32 = *c;
+    drop(c);
+    return value;
+}
+pub unsafe fn hand_over() {
+    consume(new_buffer(4 as i32));
+}
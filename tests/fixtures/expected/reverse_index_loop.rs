@@ -0,0 +1,14 @@
+pub fn sum_last(values: &[i64], n: i32) -> i64 {
+    let mut total: i64 = 0;
+    for &x in values[..n.max(0) as usize].iter().rev() {
+        total = total * 10 + x;
+    }
+    total
+}
+pub fn sum_last_unsigned(values: &[i64], n: u32) -> i64 {
+    let mut total: i64 = 0;
+    for &x in values[..n as usize].iter().rev() {
+        total = total * 10 + x;
+    }
+    total
+}
@@ -0,0 +1,23 @@
+pub unsafe fn sum_pairs(mut p: &mut [i32]) -> i32 {
+    let n: i32 = p.len() as i32;
+    let mut sum: i32 = 0 as i32;
+    let mut i: i32 = 0 as i32;
+    for i in 0..n {
+        p[i as usize] += 1 as i32;
+        let p: &i32 = &mut sum;
+        sum += *p;
+    }
+    return sum;
+}
+pub unsafe fn bump_each(mut p: &mut [i32]) {
+    let n: i32 = p.len() as i32;
+    let mut i: i32 = 0 as i32;
+    while i < n {
+        {
+            let mut p: &mut i32 = &mut i;
+            *p += 0 as i32;
+        }
+        p[i as usize] += 1 as i32;
+        i += 1;
+    }
+}
@@ -0,0 +1,35 @@
+pub unsafe fn zero_fill(mut p: &mut [i32]) {
+    let mut n: usize = p.len();
+    let mut i: usize = 0 as usize;
+    while i < n {
+        p[i] = 0 as i32;
+        i = i.wrapping_add(1);
+    }
+}
+pub unsafe fn sum_prefix(mut values: &[i32], mut n: i32) -> i32 {
+    let mut sum: i32 = 0 as i32;
+    let mut i: i32 = 0 as i32;
+    while i < n - 1 as i32 {
+        sum += values[i as usize];
+        i += 1;
+    }
+    return sum;
+}
+pub unsafe fn sum_padded(mut values: *const u32, mut n: usize) -> u32 {
+    let values: &[u32] = std::slice::from_raw_parts(values, (n + 1 as usize) as usize);
+    let mut sum: u32 = 0 as u32;
+    let mut i: usize = 0 as usize;
+    while i < n + 1 as usize {
+        sum = sum.wrapping_add(values[i]);
+        i = i.wrapping_add(1);
+    }
+    return sum;
+}
+pub unsafe fn callers(mut out: *mut i32, mut len: i32) -> i32 {
+    let mut buf: [i32; 8] = [0; 8];
+    let mut counts: [u32; 4] = [0; 4];
+    zero_fill(&mut buf[..8 as usize]);
+    zero_fill(std::slice::from_raw_parts_mut(out, len as usize));
+    sum_padded(counts.as_ptr(), 3 as usize);
+    return sum_prefix(&buf, len);
+}
@@ -0,0 +1,31 @@
+pub fn greeting() -> String {
+    let bytes: &[u8] = b"hello";
+    let mut s = String::new();
+    for &b in bytes {
+        s.push(b as char);
+    }
+    s
+}
+
+pub fn digits() -> String {
+    let data = [b'4', 50, 0x7f];
+    let mut out = String::new();
+    for b in data.iter() {
+        out.push(char::from(*b));
+    }
+    out
+}
+
+pub fn shadowed(input: &[u8]) -> String {
+    let bytes: &[u8] = b"id";
+    let mut s = String::new();
+    for &b in bytes {
+        s.push(b as char);
+    }
+    let bytes = input;
+    let mut t = String::new();
+    for &b in bytes {
+        t.push(b as char);
+    }
+    s + &t
+}
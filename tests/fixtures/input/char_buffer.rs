@@ -0,0 +1,40 @@
+use std::ffi::CStr;
+use std::os::raw::c_char;
+
+extern "C" {
+    fn strcpy(dst: *mut c_char, src: *const c_char) -> *mut c_char;
+    fn strcat(dst: *mut c_char, src: *const c_char) -> *mut c_char;
+    fn strlen(s: *const c_char) -> usize;
+}
+
+pub unsafe fn greeting() -> usize {
+    let mut buf: [c_char; 64] = [0; 64];
+    strcpy(buf.as_mut_ptr(), b"hello, \0" as *const u8 as *const c_char);
+    strcat(buf.as_mut_ptr(), b"world\0" as *const u8 as *const c_char);
+    let s = CStr::from_ptr(buf.as_ptr()).to_str().unwrap();
+    s.len()
+}
+
+pub unsafe fn measured() -> usize {
+    let mut name: [c_char; 16] = [0; 16];
+    strcpy(name.as_mut_ptr(), b"id\0".as_ptr() as *const c_char);
+    strlen(name.as_ptr())
+}
+
+pub unsafe fn labelled(flag: bool) -> usize {
+    let mut label: [c_char; 32] = [0; 32];
+    let width = if flag { 4 } else { 2 };
+    strcpy(label.as_mut_ptr(), b"on\0" as *const u8 as *const c_char);
+    let s = CStr::from_ptr(label.as_ptr()).to_str().unwrap();
+    s.len() + width
+}
+
+pub unsafe fn paired() -> usize {
+    let mut key: [c_char; 16] = [0; 16];
+    let mut value: [c_char; 16] = [0; 16];
+    strcpy(key.as_mut_ptr(), b"k\0" as *const u8 as *const c_char);
+    strcpy(value.as_mut_ptr(), b"v\0" as *const u8 as *const c_char);
+    let k = CStr::from_ptr(key.as_ptr()).to_str().unwrap();
+    let v = CStr::from_ptr(value.as_ptr()).to_str().unwrap();
+    k.len() + v.len()
+}
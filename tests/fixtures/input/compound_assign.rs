@@ -0,0 +1,14 @@
+extern "C" {
+    fn observe(p: *mut libc::c_int);
+}
+
+pub unsafe extern "C" fn bump(mut counter: *mut libc::c_int) {
+    *counter += 1 as libc::c_int;
+}
+
+pub unsafe extern "C" fn bump_observed(mut counter: *mut libc::c_int, mut step: libc::c_int) {
+    *counter += 1 as libc::c_int;
+    observe(counter);
+    *counter *= step + 1 as libc::c_int;
+    *counter <<= 2 as libc::c_int;
+}
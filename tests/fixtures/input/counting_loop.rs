@@ -0,0 +1,90 @@
+pub unsafe extern "C" fn sum_to(n: libc::c_int) -> libc::c_int {
+    let mut sum: libc::c_int = 0 as libc::c_int;
+    let mut i: libc::c_int = 0 as libc::c_int;
+    while i < n {
+        sum += i;
+        i += 1;
+    }
+    return sum;
+}
+
+pub unsafe extern "C" fn sum_evens(n: libc::c_int) -> libc::c_int {
+    let mut sum: libc::c_int = 0 as libc::c_int;
+    let mut i: libc::c_int = 0 as libc::c_int;
+    while i < n {
+        sum += i;
+        i = i + 2 as libc::c_int;
+    }
+    let mut j: libc::c_int = 1 as libc::c_int;
+    while j <= n {
+        sum -= j;
+        j = 1 as libc::c_int + j;
+    }
+    return sum;
+}
+
+pub unsafe extern "C" fn ceil_sqrt(n: libc::c_int) -> libc::c_int {
+    let mut done: libc::c_int = 0 as libc::c_int;
+    let mut i: libc::c_int = 0 as libc::c_int;
+    while i < n && done == 0 as libc::c_int {
+        if i * i >= n {
+            done = 1 as libc::c_int;
+        }
+        i += 1;
+    }
+    return i - 1 as libc::c_int;
+}
+
+pub fn count_some(values: &[Option<i32>]) -> i32 {
+    let mut count: libc::c_int = 0 as libc::c_int;
+    let mut iter = values.iter();
+    while let Some(value) = iter.next() {
+        if value.is_some() {
+            count += 1;
+        }
+    }
+    return count;
+}
+
+pub unsafe extern "C" fn sum_indices(mut n: libc::c_int) -> libc::c_ulong {
+    let mut sum: libc::c_ulong = 0 as libc::c_ulong;
+    let mut i: usize = 0 as usize;
+    while i < 10 {
+        sum += i as libc::c_ulong;
+        i += 1;
+    }
+    let mut j: usize = 0 as usize;
+    while j < n {
+        sum += j as libc::c_ulong;
+        j += 1;
+    }
+    return sum;
+}
+
+pub unsafe extern "C" fn sum_plain(mut n: libc::c_int, mut delta: libc::c_int) -> libc::c_int {
+    let mut sum: libc::c_int = 0 as libc::c_int;
+    let mut i: libc::c_int = 0 as libc::c_int;
+    while i < n {
+        sum = sum + i;
+        i += 1;
+    }
+    let mut j: libc::c_int = 0 as libc::c_int;
+    while j < n {
+        sum = sum + j;
+        j = j + delta;
+    }
+    return sum;
+}
+
+pub unsafe extern "C" fn sum_down_skipping(mut limit: libc::c_int) -> libc::c_int {
+    let mut sum: libc::c_int = 0 as libc::c_int;
+    let mut k: libc::c_int = 10 as libc::c_int;
+    while k > 0 {
+        k -= 1;
+        sum = sum + k;
+        if sum > limit {
+            k -= 1;
+        }
+    }
+    return sum;
+}
@@ -0,0 +1,8 @@
+pub unsafe extern "C" fn mix(
+    a: libc::c_char,
+    b: libc::c_ushort,
+    c: std::os::raw::c_long,
+    d: libc::c_double,
+) -> libc::c_double {
+    (a as libc::c_double) + (b as libc::c_double) + (c as libc::c_double) + d
+}
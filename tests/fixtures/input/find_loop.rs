@@ -0,0 +1,21 @@
+pub fn first_negative(values: &[i64], n: libc::c_int) -> libc::c_int {
+    let mut pos: libc::c_int = -1;
+    for i in 0..n {
+        if values[i as usize] < 0 {
+            pos = i;
+            break;
+        }
+    }
+    pos
+}
+
+pub fn first_zero(values: &[u8], n: u32) -> i32 {
+    let mut pos: i32 = -1;
+    for i in 0..n {
+        if values[i as usize] == 0 {
+            pos = i as i32;
+            break;
+        }
+    }
+    pos
+}
@@ -0,0 +1,16 @@
+extern "C" {
+    fn free(_: *mut libc::c_void);
+}
+
+pub unsafe extern "C" fn take_value(p: *mut libc::c_int) -> libc::c_int {
+    let mut value: libc::c_int = *p;
+    free(p as *mut libc::c_void);
+    return value;
+}
+
+pub unsafe extern "C" fn take_aliased(p: *mut libc::c_int) -> libc::c_int {
+    let mut q: *mut libc::c_int = p;
+    let mut value: libc::c_int = *p;
+    free(q as *mut libc::c_void);
+    return value;
+}
@@ -0,0 +1,21 @@
+fn under(x: i32) -> i32 {
+    x * 3
+}
+
+fn at(x: i32) -> i32 {
+    let y = x * 2;
+    y + 1
+}
+
+fn over(x: i32) -> i32 {
+    let y = x * 2;
+    let z = y - x;
+    z + 1
+}
+
+pub fn combined(x: i32) -> i32 {
+    let a = under(x);
+    let b = at(x);
+    let c = over(x);
+    a + b + c
+}
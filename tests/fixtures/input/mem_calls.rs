@@ -0,0 +1,65 @@
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct point {
+    pub x: libc::c_int,
+    pub y: libc::c_int,
+}
+
+pub unsafe extern "C" fn copy_scores() -> libc::c_int {
+    let mut scores: [libc::c_int; 8] = [1 as libc::c_int, 2, 3, 4, 5, 6, 7, 8];
+    let mut backup: [libc::c_int; 8] = [0; 8];
+    memcpy(
+        backup.as_mut_ptr() as *mut libc::c_void,
+        scores.as_mut_ptr() as *const libc::c_void,
+        ::core::mem::size_of::<[libc::c_int; 8]>() as libc::c_ulong,
+    );
+    memset(
+        scores.as_mut_ptr() as *mut libc::c_void,
+        0 as libc::c_int,
+        ::core::mem::size_of::<[libc::c_int; 8]>() as libc::c_ulong,
+    );
+    return backup[7 as libc::c_int as usize] + scores[0 as libc::c_int as usize];
+}
+
+pub unsafe extern "C" fn copy_prefix(mut n: libc::c_int) -> libc::c_int {
+    let mut src: [libc::c_int; 16] = [7; 16];
+    let mut dst: [libc::c_int; 16] = [0; 16];
+    memmove(
+        dst.as_mut_ptr() as *mut libc::c_void,
+        src.as_mut_ptr() as *const libc::c_void,
+        (n as libc::c_ulong).wrapping_mul(::core::mem::size_of::<libc::c_int>() as libc::c_ulong),
+    );
+    return dst[0 as libc::c_int as usize];
+}
+
+pub unsafe extern "C" fn copy_points(mut count: libc::c_int) {
+    let mut from: [point; 4] = [point { x: 0, y: 0 }; 4];
+    let mut to: [point; 4] = [point { x: 1, y: 1 }; 4];
+    memcpy(
+        to.as_mut_ptr() as *mut libc::c_void,
+        from.as_mut_ptr() as *const libc::c_void,
+        (count as libc::c_ulong).wrapping_mul(::core::mem::size_of::<point>() as libc::c_ulong),
+    );
+    memset(
+        from.as_mut_ptr() as *mut libc::c_void,
+        0 as libc::c_int,
+        ::core::mem::size_of::<[point; 4]>() as libc::c_ulong,
+    );
+}
+
+pub unsafe extern "C" fn clear(mut p: *mut libc::c_void, mut n: libc::c_ulong) {
+    memset(p, 0 as libc::c_int, n.wrapping_mul(::core::mem::size_of::<libc::c_int>() as libc::c_ulong));
+}
+
+pub unsafe extern "C" fn add_copy(mut dst: *mut libc::c_int, mut src: *const libc::c_int, mut n: libc::c_int) {
+    let mut i: libc::c_int = 0 as libc::c_int;
+    while i < n {
+        *dst.offset(i as isize) = *src.offset(i as isize) * 2 as libc::c_int;
+        i += 1;
+    }
+    memcpy(
+        dst as *mut libc::c_void,
+        src as *const libc::c_void,
+        (n as libc::c_ulong).wrapping_mul(::core::mem::size_of::<libc::c_int>() as libc::c_ulong),
+    );
+}
@@ -0,0 +1,8 @@
+pub fn latin1_name() -> String {
+    let bytes: &[u8] = b"caf\xe9";
+    let mut name = String::new();
+    for &b in bytes {
+        name.push(b as char);
+    }
+    name
+}
@@ -0,0 +1,60 @@
+extern "C" {
+    fn malloc(_: libc::c_ulong) -> *mut libc::c_void;
+    fn free(_: *mut libc::c_void);
+    fn consume(_: *mut libc::c_int);
+}
+
+pub struct Reader {
+    pub value: *mut libc::c_int,
+}
+
+impl Reader {
+    pub unsafe fn get(&self) -> libc::c_int {
+        *self.value
+    }
+}
+
+pub struct Owner {
+    pub data: *mut libc::c_int,
+}
+
+pub unsafe extern "C" fn new_owner() -> Owner {
+    let mut owner: Owner = Owner {
+        data: malloc(::core::mem::size_of::<libc::c_int>() as libc::c_ulong) as *mut libc::c_int,
+    };
+    return owner;
+}
+
+pub unsafe extern "C" fn reset_owner(owner: *mut Owner) {
+    free((*owner).data as *mut libc::c_void);
+    (*owner).data =
+        malloc(::core::mem::size_of::<libc::c_int>() as libc::c_ulong) as *mut libc::c_int;
+}
+
+pub unsafe extern "C" fn owned_value(owner: *mut Owner) -> libc::c_int {
+    return *(*owner).data;
+}
+
+pub unsafe extern "C" fn release_owner(owner: *mut Owner) {
+    free((*owner).data as *mut libc::c_void);
+}
+
+pub struct Lender {
+    pub loan: *mut libc::c_int,
+}
+
+pub unsafe extern "C" fn lend(lender: *mut Lender) -> libc::c_int {
+    let mut value: libc::c_int = *(*lender).loan;
+    consume((*lender).loan);
+    free((*lender).loan as *mut libc::c_void);
+    return value;
+}
+
+pub struct Slot {
+    pub item: *mut libc::c_int,
+}
+
+pub unsafe extern "C" fn clear_slot(slot: *mut Slot) {
+    free((*slot).item as *mut libc::c_void);
+    (*slot).item = 0 as *mut libc::c_int;
+}
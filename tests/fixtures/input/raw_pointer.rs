@@ -0,0 +1,10 @@
+pub unsafe extern "C" fn store(out: *mut libc::c_int, value: libc::c_int) {
+    if out.is_null() {
+        return;
+    }
+    *out = value;
+}
+
+pub unsafe extern "C" fn load(value: *const libc::c_int) -> libc::c_int {
+    *value
+}
@@ -0,0 +1,22 @@
+pub fn increment(counter: &mut libc::c_int) -> libc::c_int {
+    unsafe {
+        *counter += 1 as libc::c_int;
+    }
+    return unsafe { *counter + 1 as libc::c_int } * 2 as libc::c_int;
+}
+
+pub fn float_bits(bits: u64) -> f64 {
+    unsafe { std::mem::transmute::<u64, f64>(bits) }
+}
+
+pub fn first(values: *const libc::c_int) -> libc::c_int {
+    unsafe { *values }
+}
+
+pub unsafe extern "C" fn square(x: libc::c_int) -> libc::c_int {
+    let y: libc::c_int = unsafe {
+        let doubled: libc::c_int = x * x;
+        doubled
+    };
+    return y;
+}
@@ -0,0 +1,52 @@
+extern "C" {
+    fn malloc(_: libc::c_ulong) -> *mut libc::c_void;
+    fn calloc(_: libc::c_ulong, _: libc::c_ulong) -> *mut libc::c_void;
+}
+
+pub unsafe extern "C" fn new_counter() -> *mut libc::c_int {
+    malloc(::core::mem::size_of::<libc::c_int>() as libc::c_ulong) as *mut libc::c_int
+}
+
+pub unsafe extern "C" fn new_counts(n: libc::c_int) -> *mut libc::c_int {
+    if n < 1 as libc::c_int {
+        return calloc(
+            1 as libc::c_ulong,
+            ::core::mem::size_of::<libc::c_int>() as libc::c_ulong,
+        ) as *mut libc::c_int;
+    }
+    return calloc(
+        n as libc::c_ulong,
+        ::core::mem::size_of::<libc::c_int>() as libc::c_ulong,
+    ) as *mut libc::c_int;
+}
+
+pub unsafe extern "C" fn new_table(n: libc::c_int) -> *mut libc::c_int {
+    if n < 1 as libc::c_int {
+        return 0 as *mut libc::c_int;
+    }
+    return calloc(
+        n as libc::c_ulong,
+        ::core::mem::size_of::<libc::c_int>() as libc::c_ulong,
+    ) as *mut libc::c_int;
+}
+
+pub unsafe extern "C" fn new_buffer(n: libc::c_int) -> *mut libc::c_int {
+    malloc((n as libc::c_ulong).wrapping_mul(::core::mem::size_of::<libc::c_int>() as libc::c_ulong))
+        as *mut libc::c_int
+}
+
+extern "C" {
+    fn free(_: *mut libc::c_void);
+    fn consume(_: *mut libc::c_int);
+}
+
+pub unsafe extern "C" fn read_counter() -> libc::c_int {
+    let mut c: *mut libc::c_int = new_counter();
+    let mut value: libc::c_int = *c;
+    free(c as *mut libc::c_void);
+    return value;
+}
+
+pub unsafe extern "C" fn hand_over() {
+    consume(new_buffer(4 as libc::c_int));
+}
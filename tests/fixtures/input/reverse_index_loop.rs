@@ -0,0 +1,17 @@
+pub fn sum_last(values: &[i64], n: libc::c_int) -> i64 {
+    let mut total: i64 = 0;
+    for i in 0..n {
+        let j = n - i - 1;
+        total = total * 10 + values[j as usize];
+    }
+    total
+}
+
+pub fn sum_last_unsigned(values: &[i64], n: u32) -> i64 {
+    let mut total: i64 = 0;
+    for i in 0..n {
+        let j = n - i - 1;
+        total = total * 10 + values[j as usize];
+    }
+    total
+}
@@ -0,0 +1,23 @@
+pub unsafe extern "C" fn sum_pairs(mut p: *mut libc::c_int, n: libc::c_int) -> libc::c_int {
+    let mut sum: libc::c_int = 0 as libc::c_int;
+    let mut i: libc::c_int = 0 as libc::c_int;
+    while i < n {
+        *p.offset(i as isize) += 1 as libc::c_int;
+        let p: *const libc::c_int = &mut sum;
+        sum += *p;
+        i += 1;
+    }
+    return sum;
+}
+
+pub unsafe extern "C" fn bump_each(mut p: *mut libc::c_int, n: libc::c_int) {
+    let mut i: libc::c_int = 0 as libc::c_int;
+    while i < n {
+        {
+            let mut p: *mut libc::c_int = &mut i;
+            *p += 0 as libc::c_int;
+        }
+        *p.offset(i as isize) += 1 as libc::c_int;
+        i += 1;
+    }
+}
@@ -0,0 +1,36 @@
+pub unsafe extern "C" fn zero_fill(mut p: *mut i32, mut n: usize) {
+    let mut i: usize = 0 as usize;
+    while i < n {
+        *p.add(i) = 0 as i32;
+        i = i.wrapping_add(1);
+    }
+}
+
+pub unsafe extern "C" fn sum_prefix(mut values: *const libc::c_int, mut n: libc::c_int) -> libc::c_int {
+    let mut sum: libc::c_int = 0 as libc::c_int;
+    let mut i: libc::c_int = 0 as libc::c_int;
+    while i < n - 1 as libc::c_int {
+        sum += *values.offset(i as isize);
+        i += 1;
+    }
+    return sum;
+}
+
+pub unsafe extern "C" fn sum_padded(mut values: *const u32, mut n: usize) -> u32 {
+    let mut sum: u32 = 0 as u32;
+    let mut i: usize = 0 as usize;
+    while i < n + 1 as usize {
+        sum = sum.wrapping_add(*values.add(i));
+        i = i.wrapping_add(1);
+    }
+    return sum;
+}
+
+pub unsafe extern "C" fn callers(mut out: *mut i32, mut len: libc::c_int) -> libc::c_int {
+    let mut buf: [i32; 8] = [0; 8];
+    let mut counts: [u32; 4] = [0; 4];
+    zero_fill(buf.as_mut_ptr(), 8 as usize);
+    zero_fill(out, len as usize);
+    sum_padded(counts.as_ptr(), 3 as usize);
+    return sum_prefix(buf.as_ptr(), len);
+}
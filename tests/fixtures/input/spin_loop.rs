@@ -0,0 +1,18 @@
+use std::sync::atomic::{AtomicI32, Ordering};
+
+static READY: AtomicI32 = AtomicI32::new(0);
+
+pub fn wait_ready() {
+    while READY.load(Ordering::Acquire) == 0 {}
+}
+
+pub unsafe fn wait_volatile(flag: *const i32) {
+    while std::ptr::read_volatile(flag) == 0 {}
+}
+
+pub fn drain(mut n: u32) -> u32 {
+    while n > 0 {
+        n -= 1;
+    }
+    n
+}
@@ -0,0 +1,62 @@
+//! Regression tests that the default pipeline is idempotent: transforming its own
+//! output again must leave it unchanged.
+
+use std::fs;
+use std::path::Path;
+
+/// Transforms `source`, then transforms the result again, and asserts that the
+/// second transformation changed nothing.
+fn assert_idempotent(source: &str) {
+    let once = monadicast::transform_str(source).unwrap();
+    let twice = monadicast::transform_str(&once)
+        .unwrap_or_else(|error| panic!("the output does not transform: {error}\n{once}"));
+    assert_eq!(twice, once, "transforming the output again changed it");
+}
+
+#[test]
+fn while_loop_is_idempotent() {
+    assert_idempotent(
+        "pub unsafe extern \"C\" fn sum_to(mut n: libc::c_int) -> libc::c_int {
+            let mut sum: libc::c_int = 0 as libc::c_int;
+            let mut i: libc::c_int = 0 as libc::c_int;
+            while i < n {
+                let mut j: libc::c_int = 0 as libc::c_int;
+                while j < i {
+                    sum += j;
+                    j += 1;
+                }
+                i += 1;
+            }
+            return sum;
+        }",
+    );
+}
+
+#[test]
+fn ffi_function_is_idempotent() {
+    assert_idempotent(
+        "extern \"C\" {
+            fn abs(_: libc::c_int) -> libc::c_int;
+        }
+        pub unsafe extern \"C\" fn widen(
+            mut x: libc::c_short,
+            mut y: libc::c_ulong,
+            mut z: *mut libc::c_char,
+        ) -> libc::c_long {
+            *z = 0 as libc::c_char;
+            return abs(x as libc::c_int) as libc::c_long + y as libc::c_long;
+        }",
+    );
+}
+
+#[test]
+fn golden_fixtures_are_idempotent() {
+    let inputs = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/input");
+    for entry in fs::read_dir(inputs).unwrap() {
+        let input = entry.unwrap().path();
+        if input.extension().is_some_and(|extension| extension == "rs") {
+            eprintln!("{}", input.display());
+            assert_idempotent(&fs::read_to_string(&input).unwrap());
+        }
+    }
+}
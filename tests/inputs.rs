@@ -0,0 +1,113 @@
+//! Tests of the inputs the command line tool accepts: a single file, or a directory
+//! whose Rust files are transformed into the same layout under `--out`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const SOURCE: &str = "pub fn zero(x: i32) -> i32 {\n    x + 0\n}\n";
+const TRANSFORMED: &str = "pub fn zero(x: i32) -> i32 {\n    x\n}\n";
+
+/// Returns a fresh directory for the test `name` to work in.
+fn scratch(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("monadicast-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    root
+}
+
+/// Runs `monadicast` on `input`, writing under `out`.
+fn run(input: &Path, out: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_monadicast"))
+        .arg(input)
+        .arg("--out")
+        .arg(out)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn transforms_a_single_file() {
+    let root = scratch("single-file");
+    let (input, out) = (root.join("zero.rs"), root.join("output"));
+    fs::write(&input, SOURCE).unwrap();
+
+    let output = run(&input, &out);
+    let transformed = fs::read_to_string(out.join("zero.rs"));
+    fs::remove_dir_all(&root).unwrap();
+
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(transformed.unwrap(), TRANSFORMED);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("1 file(s) processed"), "{stderr}");
+}
+
+#[test]
+fn transforms_a_directory_into_the_same_layout() {
+    let root = scratch("directory");
+    let (input, out) = (root.join("input"), root.join("output"));
+    fs::create_dir_all(input.join("nested")).unwrap();
+    fs::write(input.join("zero.rs"), SOURCE).unwrap();
+    fs::write(input.join("nested/zero.rs"), SOURCE).unwrap();
+
+    let output = run(&input, &out);
+    let transformed = [
+        fs::read_to_string(out.join("zero.rs")),
+        fs::read_to_string(out.join("nested/zero.rs")),
+    ];
+    fs::remove_dir_all(&root).unwrap();
+
+    assert!(output.status.success(), "{output:?}");
+    for transformed in transformed {
+        assert_eq!(transformed.unwrap(), TRANSFORMED);
+    }
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("2 file(s) processed"), "{stderr}");
+}
+
+#[test]
+fn skips_files_that_are_not_rust() {
+    let root = scratch("skipped");
+    let (input, out) = (root.join("input"), root.join("output"));
+    fs::create_dir_all(&input).unwrap();
+    fs::write(input.join("zero.rs"), SOURCE).unwrap();
+    fs::write(input.join("notes.txt"), "not Rust").unwrap();
+
+    let output = run(&input, &out);
+    let copied = out.join("notes.txt").exists();
+    fs::remove_dir_all(&root).unwrap();
+
+    assert!(output.status.success(), "{output:?}");
+    assert!(!copied);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("1 file(s) processed, 1 skipped, 0 errored"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn continues_past_unreadable_files() {
+    let root = scratch("unreadable");
+    let (input, out) = (root.join("input"), root.join("output"));
+    fs::create_dir_all(&input).unwrap();
+    // Not UTF-8, so it cannot be read as source.
+    fs::write(input.join("binary.rs"), [0xff, 0xfe, 0x00]).unwrap();
+    fs::write(input.join("zero.rs"), SOURCE).unwrap();
+
+    let output = run(&input, &out);
+    let transformed = fs::read_to_string(out.join("zero.rs"));
+    fs::remove_dir_all(&root).unwrap();
+
+    assert!(!output.status.success(), "{output:?}");
+    assert_eq!(transformed.unwrap(), TRANSFORMED);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("error: ") && stderr.contains("binary.rs"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("1 file(s) processed, 0 skipped, 1 errored"),
+        "{stderr}"
+    );
+}
@@ -0,0 +1,32 @@
+//! Tests of the command line tool reporting the files it cannot parse, with the
+//! location of the failure, and going on with the other files.
+
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn reports_parse_failure_location_and_continues() {
+    let root = std::env::temp_dir().join(format!("monadicast-parse-errors-{}", std::process::id()));
+    let (input, out) = (root.join("input"), root.join("output"));
+    fs::create_dir_all(&input).unwrap();
+    fs::write(input.join("bad.rs"), "fn f() {\n    let x = 1\n}\n").unwrap();
+    fs::write(input.join("good.rs"), "fn g() -> i32 {\n    0\n}\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_monadicast"))
+        .arg(&input)
+        .arg("--out")
+        .arg(&out)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let good_written = out.join("good.rs").exists();
+    fs::remove_dir_all(&root).unwrap();
+
+    assert!(!output.status.success());
+    let location = format!(
+        "error: {}:3:1: expected `;`",
+        input.join("bad.rs").display()
+    );
+    assert!(stderr.contains(&location), "no `{location}` in:\n{stderr}");
+    assert!(good_written, "good.rs was not transformed");
+}
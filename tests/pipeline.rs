@@ -0,0 +1,84 @@
+//! Golden tests of the default pipeline: each `tests/fixtures/input/<name>.rs` must
+//! transform into `tests/fixtures/expected/<name>.rs`, and the notes the passes
+//! report about it must be those in `tests/fixtures/expected/<name>.notes`, which
+//! is left out if there are none. A fixture with a sibling `<name>.toml`, in the
+//! format of `monadicast.toml`, is transformed with the pass options it sets, e.g.
+//! to enable an opt-in rewrite.
+//!
+//! Run with `UPDATE_EXPECTED=1` to rewrite the expected files with the current
+//! output instead, and review the changes with `git diff`. This stands in for
+//! `insta` snapshots, which the crate cannot depend on as it must build offline.
+
+use monadicast::{Config, MonadicAst, PassError, PassName};
+use std::env;
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn fixtures_transform_into_expected() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let update = env::var_os("UPDATE_EXPECTED").is_some();
+    let mut inputs: Vec<_> = fs::read_dir(fixtures.join("input"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "rs"))
+        .collect();
+    inputs.sort();
+    assert!(!inputs.is_empty(), "no fixtures found");
+
+    let mut mismatched = Vec::new();
+    for input in &inputs {
+        let name = input.file_name().unwrap();
+        let source = fs::read_to_string(input).unwrap();
+        let config_path = input.with_extension("toml");
+        let config = if config_path.is_file() {
+            Config::read(&config_path).unwrap_or_else(|error| panic!("{error}"))
+        } else {
+            Config::default()
+        };
+        let monad = MonadicAst::new(&source)
+            .map_err(PassError::from)
+            .and_then(|monad| {
+                monad
+                    .with_config(config.pass_config())
+                    .apply_all(PassName::ALL)
+            })
+            .unwrap_or_else(|error| panic!("{}: {}", input.display(), error));
+        let output = monad
+            .result_formatted()
+            .unwrap_or_else(|error| panic!("{}: {}", input.display(), error));
+        let notes: String = monad
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| format!("{diagnostic}\n"))
+            .collect();
+        let expected_path = fixtures.join("expected").join(name);
+        let notes_path = expected_path.with_extension("notes");
+        if update {
+            fs::write(&expected_path, &output).unwrap();
+            if notes.is_empty() {
+                let _ = fs::remove_file(&notes_path);
+            } else {
+                fs::write(&notes_path, &notes).unwrap();
+            }
+            continue;
+        }
+        let expected = fs::read_to_string(&expected_path).unwrap_or_default();
+        let expected_notes = fs::read_to_string(&notes_path).unwrap_or_default();
+        if output != expected || notes != expected_notes {
+            eprintln!(
+                "{} does not match {}:\n{}{}",
+                input.display(),
+                expected_path.display(),
+                output,
+                notes
+            );
+            mismatched.push(name.to_string_lossy().into_owned());
+        }
+    }
+    assert!(
+        mismatched.is_empty(),
+        "mismatched fixtures: {:?}",
+        mismatched
+    );
+}
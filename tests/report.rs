@@ -0,0 +1,49 @@
+//! Tests of the JSON report of the rewrites made, written by `--report`.
+
+use serde_json::{json, Value};
+use std::fs;
+use std::process::Command;
+
+const SOURCE: &str = "\
+pub unsafe extern \"C\" fn fill(mut p: *mut libc::c_int) {
+    let mut i: libc::c_int = 0 as libc::c_int;
+    while i < 10 {
+        *p = i;
+        i += 1;
+    }
+}
+";
+
+#[test]
+fn reports_rewrites_with_their_spans() {
+    let root = std::env::temp_dir().join(format!("monadicast-report-{}", std::process::id()));
+    fs::create_dir_all(&root).unwrap();
+    let (input, report) = (root.join("fill.rs"), root.join("report.json"));
+    fs::write(&input, SOURCE).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_monadicast"))
+        .arg(&input)
+        .arg("--out")
+        .arg(root.join("output"))
+        .arg("--report")
+        .arg(&report)
+        .output()
+        .unwrap();
+    let json = fs::read_to_string(&report);
+    fs::remove_dir_all(&root).unwrap();
+
+    assert!(output.status.success(), "{output:?}");
+    let report: Value = serde_json::from_str(&json.unwrap()).unwrap();
+    let files = report["files"].as_array().unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0]["path"], json!(input));
+    let events = files[0]["events"].as_array().unwrap();
+    for expected in [
+        json!({"kind": "ffi_type", "line": 1, "column": 43, "before": "libc::c_int", "after": "i32"}),
+        json!({"kind": "ffi_type", "line": 2, "column": 16, "before": "libc::c_int", "after": "i32"}),
+        json!({"kind": "ptr_to_ref", "line": 1, "column": 38, "before": "*mut i32", "after": "&mut i32"}),
+        json!({"kind": "while_to_for", "line": 3, "column": 5}),
+    ] {
+        assert!(events.contains(&expected), "no {expected} in {events:?}");
+    }
+}
@@ -0,0 +1,45 @@
+//! Tests of the counts of rewrites the passes make.
+
+use monadicast::{MonadicAst, Stats};
+
+#[test]
+fn counts_converted_while_loops() {
+    let source = "
+        pub fn sums(n: i32) -> i32 {
+            let mut total = 0;
+            let mut i: i32 = 0 as i32;
+            while i < n {
+                let mut j: i32 = 0 as i32;
+                while j < i {
+                    total += j;
+                    j += 1;
+                }
+                i += 1;
+            }
+            while total > 100 {
+                total /= 2;
+            }
+            total
+        }
+    ";
+    let monad = MonadicAst::new(source)
+        .unwrap()
+        .replace_while_loop()
+        .unwrap();
+    // Both counting loops are converted, and the halving loop is kept.
+    assert_eq!(
+        monad.stats(),
+        &Stats {
+            while_loops_converted: 2,
+            ..Stats::default()
+        },
+        "{}",
+        monad.result()
+    );
+    assert_eq!(
+        monad.result().matches("while").count(),
+        1,
+        "{}",
+        monad.result()
+    );
+}
@@ -0,0 +1,47 @@
+//! Tests of the command line tool reading its input from standard input, as in
+//! `cat foo.rs | monadicast -`.
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+/// Runs `monadicast` with the given arguments, writing `source` to its standard
+/// input.
+fn run_on_stdin(args: &[&str], source: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_monadicast"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(source.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn transforms_stdin_to_stdout() {
+    let source = "pub unsafe extern \"C\" fn add_zero(x: libc::c_int) -> libc::c_int { x + 0 }";
+    for flag in ["-", "--stdin"] {
+        let output = run_on_stdin(&[flag], source);
+        assert!(output.status.success(), "`{flag}` failed");
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            "pub unsafe fn add_zero(x: i32) -> i32 {\n    x\n}\n"
+        );
+    }
+}
+
+#[test]
+fn reports_unparsable_stdin() {
+    let output = run_on_stdin(&["-"], "fn (");
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("error: <stdin>:1:4: "));
+}